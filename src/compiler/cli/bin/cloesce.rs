@@ -5,14 +5,18 @@
 //! The `cloesce` binary provides the following subcommands:
 //!
 //! - `compile`: Compiles `.clo` and `.cloesce` source files into a JSON CIDL file, a Wrangler config file,
-//!   and TypeScript client and backend code. By default, the output files are placed in the `.cloesce` directory,
-//!   but this can be configured in the `cloesce.jsonc` config file.
+//!   an OpenAPI document, a JSON Schema per model, plain old object and API parameter set, and client and backend code (TypeScript, or Rust for workers-rs with `"target": "rust"`), plus a client
+//!   in each extra language listed under `"clients"` (e.g. `["python"]`). By default, the output files are placed in the `.cloesce` directory,
+//!   but this can be configured in the `cloesce.jsonc` config file. A `cache.json` there lets a recompile skip stages whose inputs
+//!   are unchanged, see [cache::CompileCache] (`--no-cache` ignores it).
 //!
 //! - `migrate`: Generates a SQL migration file and a CIDL file containing only the migrated models based on the
-//!   differences between the current CIDL and the last migrated CIDL.
+//!   differences between the current CIDL and the last migrated CIDL, along with a `down/` SQL file reverting it for D1.
 //!
 //! - `fmt`: Formats `.clo` and `.cloesce` source files according to a consistent style.
 //!
+//! - `lsp`: Runs a language server over stdio, providing diagnostics, navigation, hover and formatting to editors.
+//!
 //! - `version`: Displays the current version of the `cloesce` binary and checks for updates.
 //!
 //! # Configuration File
@@ -35,7 +39,7 @@ use serde::Deserialize;
 use tracing_subscriber::FmtSubscriber;
 
 /// Direct values from the <env>.closce.jsonc file
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Hash)]
 #[serde(default)]
 struct ParsedCloesceConfig {
     src_paths: Vec<String>,
@@ -43,6 +47,8 @@ struct ParsedCloesceConfig {
     workers_url: String,
     migrations_path: String,
     wrangler_config_format: WranglerConfigFormat,
+    target: CodegenTarget,
+    clients: Vec<ClientLanguage>,
 }
 
 impl Default for ParsedCloesceConfig {
@@ -53,6 +59,8 @@ impl Default for ParsedCloesceConfig {
             workers_url: "http://localhost:8787".to_string(),
             migrations_path: "./migrations".to_string(),
             wrangler_config_format: WranglerConfigFormat::default(),
+            target: CodegenTarget::default(),
+            clients: vec![],
         }
     }
 }
@@ -167,7 +175,7 @@ impl CloesceConfig {
     }
 }
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, Default, Hash)]
 #[serde(rename_all = "lowercase")]
 enum WranglerConfigFormat {
     #[default]
@@ -184,6 +192,48 @@ impl WranglerConfigFormat {
     }
 }
 
+/// The language the backend, client and service client code is generated in.
+#[derive(Debug, Deserialize, Default, Hash)]
+#[serde(rename_all = "lowercase")]
+enum CodegenTarget {
+    #[default]
+    TypeScript,
+    Rust,
+}
+
+impl CodegenTarget {
+    fn extension(&self) -> &'static str {
+        self.client_language().extension()
+    }
+
+    fn client_language(&self) -> ClientLanguage {
+        match self {
+            CodegenTarget::TypeScript => ClientLanguage::TypeScript,
+            CodegenTarget::Rust => ClientLanguage::Rust,
+        }
+    }
+}
+
+/// A language a client is generated in. The `clients` config key lists any generated
+/// alongside the target's own client.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Hash)]
+#[serde(rename_all = "lowercase")]
+enum ClientLanguage {
+    TypeScript,
+    Rust,
+    Python,
+}
+
+impl ClientLanguage {
+    fn extension(&self) -> &'static str {
+        match self {
+            ClientLanguage::TypeScript => "ts",
+            ClientLanguage::Rust => "rs",
+            ClientLanguage::Python => "py",
+        }
+    }
+}
+
 #[derive(Parser)]
 struct Cli {
     #[command(subcommand)]
@@ -200,6 +250,8 @@ enum Command {
     Migrate(MigrateArgs),
     Fmt(FormatArgs),
     Explain(ExplainArgs),
+    /// Run the language server over stdio
+    Lsp,
     Version,
 }
 
@@ -208,6 +260,10 @@ struct CompileArgs {
     /// Directory to compile. Defaults to the current directory.
     #[arg(default_value = ".")]
     dir: PathBuf,
+
+    /// Regenerate every output, ignoring the compile cache in the output directory.
+    #[arg(long)]
+    no_cache: bool,
 }
 
 #[derive(Args)]
@@ -222,7 +278,9 @@ struct ExplainArgs {
     #[arg(long, default_value = ".")]
     dir: PathBuf,
 
-    /// JSON payload file, required when explaining `save`.
+    /// JSON payload file, required when explaining `save` or `delete`.
+    ///
+    /// For `delete`, the payload is the instance as hydrated by the data source's `get`.
     #[arg(long)]
     payload: Option<PathBuf>,
 }
@@ -233,6 +291,8 @@ enum ExplainOperation {
     Get,
     List,
     Save,
+    Delete,
+    Count,
 }
 
 #[derive(Args)]
@@ -243,7 +303,21 @@ struct MigrateArgs {
     #[arg(long, conflicts_with = "binding")]
     all: bool,
 
-    name: String,
+    /// JSON file answering rename or drop questions, instead of prompting.
+    #[arg(long)]
+    decisions: Option<PathBuf>,
+
+    /// List the rename or drop questions posed by the migration without writing it.
+    #[arg(long)]
+    plan: bool,
+
+    /// SQLite database file to compare against the last migration of `--binding`,
+    /// instead of migrating.
+    #[arg(long, requires = "binding", conflicts_with_all = ["decisions", "plan"])]
+    check: Option<PathBuf>,
+
+    #[arg(required_unless_present = "check")]
+    name: Option<String>,
 }
 
 fn open_file_or_create(path: &Path) -> Result<File, String> {
@@ -269,12 +343,29 @@ struct FormatArgs {
 
 fn main() {
     let start_time = std::time::Instant::now();
+    let cli = Cli::parse();
+
+    // The language server owns stdout for JSON-RPC, so logs go to stderr and
+    // no update notice is printed.
+    if matches!(cli.command, Command::Lsp) {
+        let subscriber = FmtSubscriber::builder()
+            .without_time()
+            .with_writer(std::io::stderr)
+            .finish();
+        tracing::subscriber::set_global_default(subscriber)
+            .expect("Failed to set global default subscriber");
+
+        if let Err(e) = lsp::run_stdio() {
+            tracing::error!("{e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let subscriber = FmtSubscriber::builder().without_time().finish();
     tracing::subscriber::set_global_default(subscriber)
         .expect("Failed to set global default subscriber");
 
-    let cli = Cli::parse();
-
     // Spawn a separate thread as to not impede the compiler.
     // `version` command will always force a fetch
     let update_check = if cfg!(debug_assertions) {
@@ -301,7 +392,7 @@ fn main() {
                     config.parsed.src_paths = vec!["./".to_string()];
                 }
                 let sources = config.collect_sources(&root);
-                compile::compile(config, sources)?;
+                compile::compile(config, sources, args.no_cache)?;
 
                 let elapsed = start_time.elapsed();
                 tracing::info!("Compilation completed in {:.2?}", elapsed);
@@ -339,6 +430,7 @@ fn main() {
                 tracing::info!("Explain completed in {:.2?}", elapsed);
                 Ok(())
             }
+            Command::Lsp => unreachable!("handled before the update check"),
             Command::Version => {
                 println!("cloesce {}", env!("CARGO_PKG_VERSION"));
                 Ok(())
@@ -386,14 +478,24 @@ fn main() {
 
 mod compile {
     use codegen::{
-        backend::BackendGenerator, client::ClientGenerator, wrangler::WranglerDefault,
+        backend::{BackendGenerator, RustBackendGenerator},
+        client::{ClientGenerator, PythonClientGenerator, RustClientGenerator},
+        json_schema::JsonSchemaGenerator,
+        openapi::OpenApiGenerator,
+        wrangler::DevVarsGenerator,
+        wrangler::WranglerDefault,
         wrangler::WranglerGenerator,
     };
     use frontend::{lexer, parser};
 
     use super::*;
+    use crate::cache::{self, CompileCache};
 
-    pub fn compile(config: CloesceConfig, target_paths: Vec<PathBuf>) -> Result<(), String> {
+    pub fn compile(
+        config: CloesceConfig,
+        target_paths: Vec<PathBuf>,
+        no_cache: bool,
+    ) -> Result<(), String> {
         tracing::info!("Starting compilation with config: {:?}", config.parsed);
         if target_paths.is_empty() {
             return Err("No cloesce source files found".into());
@@ -447,6 +549,18 @@ mod compile {
             (generator, spec)
         };
 
+        // Any stage whose inputs match the last compile is skipped, so long as the generated
+        // files haven't been touched since.
+        let cloesce_dir = config.cloesce_dir();
+        let mut cache = if no_cache {
+            CompileCache::default()
+        } else {
+            CompileCache::load(&cloesce_dir)
+        };
+        let config_hash = cache::hash((&config.parsed, &config.env));
+        let up_to_date = cache.config == config_hash && cache.artifacts_intact();
+        cache.config = config_hash;
+
         // Lexing
         let sources = target_paths
             .into_iter()
@@ -462,6 +576,16 @@ mod compile {
                 "Failed to read source files".to_string()
             })?;
 
+        let source_hashes = sources
+            .iter()
+            .map(|(src, path)| (path.clone(), cache::hash(src)))
+            .collect();
+        if up_to_date && cache.services.is_none() && cache.sources == source_hashes {
+            tracing::info!("No source files changed since the last compile.");
+            return Ok(());
+        }
+        cache.sources = source_hashes;
+
         let (lex_results, file_table) = lexer::lex(sources.iter().map(|(src, path)| LexTarget {
             src: src.as_str(),
             path: path.clone(),
@@ -471,101 +595,346 @@ mod compile {
             std::process::exit(1);
         });
 
-        // Parsing
-        let ast = parser::parse(&lex_results, &file_table).unwrap_or_else(|err| {
-            err.display_error(&file_table);
-            std::process::exit(1);
-        });
+        // Parsing, continuing on to report semantic errors in whatever did parse
+        let (ast, parse_errors) = parser::parse_recovering(&lex_results, &file_table);
+        let parsed = parse_errors.is_empty();
+        if !parsed {
+            parse_errors.display_error(&file_table);
+        }
+
+        let token_hashes = lex_results
+            .iter()
+            .map(|lf| {
+                let (src, path) = file_table.resolve(lf.file_id);
+                let tokens = lf
+                    .tokens
+                    .iter()
+                    .map(|t| &src[t.span.start..t.span.end])
+                    .collect::<Vec<_>>();
+                (path.clone(), cache::hash(tokens))
+            })
+            .collect();
+        let services = semantic::read_service_cidls(&ast, &file_table);
+        let services_hash = (!services.is_empty()).then(|| cache::hash(&services));
+        if parsed && up_to_date && cache.tokens == token_hashes && cache.services == services_hash {
+            tracing::info!("Only whitespace or comments changed since the last compile.");
+            cache.save(&cloesce_dir);
+            return Ok(());
+        }
+        cache.tokens = token_hashes;
+        cache.services = services_hash;
 
         // Semantic
-        let idl = match semantic::analyze(&ast) {
-            Ok(idl) => idl,
+        let idl = match semantic::analyze_with_services(&ast, &services) {
+            Ok(idl) if parsed => idl,
+            Ok(_) => return Err("parsing failed".into()),
             Err(errors) => {
+                let analysis_failed = !errors.is_empty();
                 for error in errors {
                     error.display_error(&file_table);
                 }
-                return Err("semantic analysis failed".into());
+                return Err(match (parsed, analysis_failed) {
+                    (true, _) => "semantic analysis failed",
+                    (false, true) => "parsing and semantic analysis failed",
+                    (false, false) => "parsing failed",
+                }
+                .into());
             }
         };
 
+        if matches!(config.parsed.target, CodegenTarget::Rust) {
+            let errors = semantic::check_rust_target(&ast);
+            if !errors.is_empty() {
+                for error in errors {
+                    error.display_error(&file_table);
+                }
+                return Err("semantic analysis failed".into());
+            }
+        }
+
+        let cidl = idl.to_json();
+        let idl_hash = cache::hash(&cidl);
+        if up_to_date && cache.idl == idl_hash {
+            tracing::info!("CIDL unchanged since the last compile, skipping codegen.");
+            cache.save(&cloesce_dir);
+            return Ok(());
+        }
+        cache.idl = idl_hash;
+        cache.artifacts.clear();
+
         // Codegen
+        let title = wrangler_spec
+            .name
+            .clone()
+            .unwrap_or_else(|| "Cloesce API".to_string());
         let wrangler = {
-            WranglerDefault::set_defaults(&mut wrangler_spec, &idl, &config.parsed.migrations_path);
+            let migrations_path = &config.parsed.migrations_path;
+
+            // Every environment declared in the schema gets its own `env.<name>` section
+            for name in idl.envs.keys() {
+                if config.env.as_deref() == Some(*name) {
+                    continue;
+                }
+                let mut spec = wrangler.as_spec(Some(name)).map_err(|e| {
+                    format!("Failed to process wrangler environment {}: {}", name, e)
+                })?;
+                WranglerDefault::set_defaults(&mut spec, &idl, Some(name), migrations_path);
+                wrangler.write_env(&spec, name);
+            }
+
+            if matches!(config.parsed.target, CodegenTarget::Rust) {
+                WranglerDefault::set_rust_defaults(&mut wrangler_spec);
+            }
+            WranglerDefault::set_defaults(
+                &mut wrangler_spec,
+                &idl,
+                config.env.as_deref(),
+                migrations_path,
+            );
             wrangler.generate(wrangler_spec, config.env.as_deref())
         };
 
-        let backend = BackendGenerator::generate(&idl, &config.parsed.workers_url);
-        let client = ClientGenerator::generate(&idl, &config.parsed.workers_url);
-
-        let output_name = |name: &str| name.to_string();
+        let target = &config.parsed.target;
+        let backend = match target {
+            CodegenTarget::TypeScript => {
+                BackendGenerator::generate(&idl, &config.parsed.workers_url)
+            }
+            CodegenTarget::Rust => RustBackendGenerator::generate(&idl, &config.parsed.workers_url),
+        };
+        let mut client_languages = vec![target.client_language()];
+        for lang in &config.parsed.clients {
+            if !client_languages.contains(lang) {
+                client_languages.push(*lang);
+            }
+        }
+        let openapi = OpenApiGenerator::generate(&idl, &title, &config.parsed.workers_url);
+        let schemas = JsonSchemaGenerator::generate(&idl);
 
         // Output CIDL
-        {
-            let cidl_path = config.cloesce_dir().join(output_name("cidl.json"));
-            let mut file = open_file_or_create(&cidl_path)?;
-
-            file.write_all(idl.to_json().as_bytes())
-                .map_err(|e| format!("Failed to write CIDL file {}: {}", cidl_path.display(), e))?;
+        let cidl_path = cloesce_dir.join("cidl.json");
+        if cache.write_artifact(&cidl_path, &cidl, "CIDL file")? {
             tracing::info!("Generated JSON CIDL at {}", cidl_path.display());
-        };
+        }
 
-        // Output Wrangler
-        {
-            let out_wrangler_path = config.wrangler_path();
-            let mut out_wrangler_file = open_file_or_create(&out_wrangler_path)?;
+        // Output OpenAPI
+        let openapi_path = cloesce_dir.join("openapi.json");
+        if cache.write_artifact(&openapi_path, &openapi, "OpenAPI file")? {
+            tracing::info!("Generated OpenAPI document at {}", openapi_path.display());
+        }
 
-            out_wrangler_file
-                .write_all(wrangler.as_bytes())
-                .map_err(|e| {
-                    format!(
-                        "Failed to write wrangler file {}: {}",
-                        out_wrangler_path.display(),
-                        e
-                    )
+        // Output JSON Schemas, removing those of models or methods no longer in the schema
+        let schemas_dir = cloesce_dir.join("schemas");
+        for (name, schema) in &schemas {
+            let schema_path = schemas_dir.join(format!("{name}.json"));
+            if cache.write_artifact(&schema_path, schema, "JSON Schema file")? {
+                tracing::info!("Generated JSON Schema at {}", schema_path.display());
+            }
+        }
+        for entry in std::fs::read_dir(&schemas_dir).into_iter().flatten() {
+            let path = entry.map_err(|e| e.to_string())?.path();
+            let stale = path.extension().is_some_and(|ext| ext == "json")
+                && path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .is_some_and(|stem| !schemas.contains_key(stem));
+            if stale {
+                std::fs::remove_file(&path).map_err(|e| {
+                    format!("Failed to remove stale JSON Schema {}: {e}", path.display())
                 })?;
+                tracing::info!("Removed stale JSON Schema at {}", path.display());
+            }
+        }
+
+        // Output Wrangler
+        let out_wrangler_path = config.wrangler_path();
+        if cache.write_artifact(&out_wrangler_path, &wrangler, "wrangler file")? {
             tracing::info!(
                 "Generated wrangler config at {}",
                 out_wrangler_path.display()
             );
         }
 
-        // Output backend
-        {
-            let backend_path = config.cloesce_dir().join(output_name("backend.ts"));
-            let mut file = open_file_or_create(&backend_path)?;
+        // Output the secrets Wrangler reads from `.dev.vars`, next to the wrangler config
+        if let Some(dev_vars) = DevVarsGenerator::generate(&idl) {
+            let dev_vars_path = config.root.join(".dev.vars.example");
+            if cache.write_artifact(&dev_vars_path, &dev_vars, "dev vars file")? {
+                tracing::info!("Generated secrets example at {}", dev_vars_path.display());
+            }
+        }
 
-            file.write_all(backend.as_bytes()).map_err(|e| {
-                format!(
-                    "Failed to write backend file {}: {}",
-                    backend_path.display(),
-                    e
-                )
-            })?;
+        // Output backend
+        let backend_path = cloesce_dir.join(format!("backend.{}", target.extension()));
+        if cache.write_artifact(&backend_path, &backend, "backend file")? {
             tracing::info!("Generated backend code at {}", backend_path.display());
         }
 
-        // Output client
-        {
-            let client_path = config.cloesce_dir().join(output_name("client.ts"));
-            let mut file = open_file_or_create(&client_path)?;
+        // Output service clients, which only the backend calls
+        for binding in &idl.wrangler_env.service_bindings {
+            let service_path = cloesce_dir.join("services").join(format!(
+                "{}.{}",
+                binding.name,
+                target.extension()
+            ));
+
+            let service = match target {
+                CodegenTarget::TypeScript => ClientGenerator::generate_service(binding),
+                CodegenTarget::Rust => RustClientGenerator::generate_service(binding),
+            };
+            if cache.write_artifact(&service_path, &service, "service client")? {
+                tracing::info!(
+                    "Generated {} service client at {}",
+                    binding.name,
+                    service_path.display()
+                );
+            }
+        }
 
-            file.write_all(client.as_bytes()).map_err(|e| {
-                format!(
-                    "Failed to write client file {}: {}",
-                    client_path.display(),
-                    e
-                )
-            })?;
-            tracing::info!("Generated client code at {}", client_path.display());
+        for lang in client_languages {
+            // Output client
+            let client_path = cloesce_dir.join(format!("client.{}", lang.extension()));
+            let client = match lang {
+                ClientLanguage::TypeScript => {
+                    ClientGenerator::generate(&idl, &config.parsed.workers_url)
+                }
+                ClientLanguage::Rust => {
+                    RustClientGenerator::generate(&idl, &config.parsed.workers_url)
+                }
+                ClientLanguage::Python => {
+                    PythonClientGenerator::generate(&idl, &config.parsed.workers_url)
+                }
+            };
+            if cache.write_artifact(&client_path, &client, "client file")? {
+                tracing::info!("Generated client code at {}", client_path.display());
+            }
         }
 
+        cache.save(&cloesce_dir);
         Ok(())
     }
 }
 
+mod cache {
+    use std::{
+        collections::BTreeMap,
+        hash::{Hash, Hasher},
+    };
+
+    use rustc_hash::FxHasher;
+    use serde::Serialize;
+
+    use super::*;
+
+    const CACHE_FILE_NAME: &str = "cache.json";
+
+    /// The version of the compiler writing the cache: its package version, and a fingerprint
+    /// of the sources and codegen templates it was built from (see `build.rs`).
+    const COMPILER_VERSION: &str = concat!(
+        env!("CARGO_PKG_VERSION"),
+        "+",
+        env!("CLOESCE_COMPILER_HASH")
+    );
+
+    /// Hashes of the inputs and outputs of the last successful `compile`, kept in the output
+    /// directory so that a run with unchanged inputs can skip lexing, analysis or codegen.
+    ///
+    /// Written as JSON; a cache written by any other build of `cloesce` is discarded, so
+    /// a compiler upgrade never serves artifacts generated by the old one.
+    #[derive(Default, Serialize, Deserialize)]
+    #[serde(default)]
+    pub struct CompileCache {
+        version: String,
+
+        /// Hash of the [ParsedCloesceConfig] and environment compiled for
+        pub config: u64,
+
+        /// Hash of each source file's contents
+        pub sources: BTreeMap<PathBuf, u64>,
+
+        /// Hash of each source file's tokens, which is unchanged by whitespace or comment edits
+        pub tokens: BTreeMap<PathBuf, u64>,
+
+        /// Hash of the CIDLs read for `service` blocks, [None] if there were none.
+        ///
+        /// Service CIDLs live outside of the source files, so a schema with services can't
+        /// skip reading them.
+        pub services: Option<u64>,
+
+        /// Hash of the generated `cidl.json`
+        pub idl: u64,
+
+        /// Hash of each generated file as it was written
+        pub artifacts: BTreeMap<PathBuf, u64>,
+    }
+
+    impl CompileCache {
+        fn path(cloesce_dir: &Path) -> PathBuf {
+            cloesce_dir.join(CACHE_FILE_NAME)
+        }
+
+        /// Loads the cache from the output directory, or an empty cache if there is none.
+        pub fn load(cloesce_dir: &Path) -> Self {
+            let Ok(text) = std::fs::read_to_string(Self::path(cloesce_dir)) else {
+                return Self::default();
+            };
+
+            match serde_json::from_str::<Self>(&text) {
+                Ok(cache) if cache.version == COMPILER_VERSION => cache,
+                _ => Self::default(),
+            }
+        }
+
+        pub fn save(&mut self, cloesce_dir: &Path) {
+            self.version = COMPILER_VERSION.to_string();
+
+            let path = Self::path(cloesce_dir);
+            let json = serde_json::to_string_pretty(self).expect("serialize cache to work");
+            if let Err(e) = std::fs::write(&path, json) {
+                tracing::warn!("Failed to write compile cache {}: {}", path.display(), e);
+            }
+        }
+
+        /// True if every generated file is on disk exactly as it was last written.
+        pub fn artifacts_intact(&self) -> bool {
+            !self.artifacts.is_empty()
+                && self.artifacts.iter().all(|(path, h)| {
+                    std::fs::read(path).is_ok_and(|contents| hash(contents.as_slice()) == *h)
+                })
+        }
+
+        /// Writes a generated file, unless it is already on disk with the same contents.
+        ///
+        /// Returns whether the file was written.
+        pub fn write_artifact(
+            &mut self,
+            path: &Path,
+            contents: &str,
+            kind: &str,
+        ) -> Result<bool, String> {
+            let h = hash(contents.as_bytes());
+            self.artifacts.insert(path.to_path_buf(), h);
+
+            if std::fs::read(path).is_ok_and(|existing| hash(existing.as_slice()) == h) {
+                return Ok(false);
+            }
+
+            open_file_or_create(path)?
+                .write_all(contents.as_bytes())
+                .map_err(|e| format!("Failed to write {} {}: {}", kind, path.display(), e))?;
+            Ok(true)
+        }
+    }
+
+    pub fn hash(value: impl Hash) -> u64 {
+        let mut h = FxHasher::default();
+        value.hash(&mut h);
+        h.finish()
+    }
+}
+
 mod explain {
     use frontend::{lexer, parser};
-    use orm::query::explain::explain_save;
+    use orm::query::delete::planner as delete_planner;
+    use orm::query::explain::{explain_delete, explain_save};
     use orm::query::save::planner as save_planner;
 
     use super::*;
@@ -579,7 +948,10 @@ mod explain {
             (ExplainOperation::Save, None) => {
                 return Err("explain save requires --payload <file.json>".into());
             }
-            (ExplainOperation::Save, Some(path)) => {
+            (ExplainOperation::Delete, None) => {
+                return Err("explain delete requires --payload <file.json>".into());
+            }
+            (ExplainOperation::Save | ExplainOperation::Delete, Some(path)) => {
                 let contents = std::fs::read_to_string(path).map_err(|e| {
                     format!("Failed to read payload file {}: {}", path.display(), e)
                 })?;
@@ -622,7 +994,8 @@ mod explain {
         });
 
         // Semantic
-        let idl = match semantic::analyze(&ast) {
+        let services = semantic::read_service_cidls(&ast, &file_table);
+        let idl = match semantic::analyze_with_services(&ast, &services) {
             Ok(idl) => idl,
             Err(errors) => {
                 for error in errors {
@@ -659,12 +1032,19 @@ mod explain {
         let text = match args.operation {
             ExplainOperation::Get => data_source.get_explain.clone(),
             ExplainOperation::List => data_source.list_explain.clone(),
+            ExplainOperation::Count => data_source.count_explain.clone(),
             ExplainOperation::Save => {
                 let payload = payload.expect("checked above");
                 let plan = save_planner::plan(model.name, &idl, &data_source.tree, &payload)
                     .map_err(|e| format!("Failed to plan save: {e}"))?;
                 explain_save(model.name, &data_source.tree, &plan)
             }
+            ExplainOperation::Delete => {
+                let payload = payload.expect("checked above");
+                let plan = delete_planner::plan(model.name, &idl, &data_source.tree, &payload)
+                    .map_err(|e| format!("Failed to plan delete: {e}"))?;
+                explain_delete(model.name, &data_source.tree, &plan)
+            }
         };
 
         println!("{text}");
@@ -677,7 +1057,12 @@ mod migrate {
     use codegen::wrangler::DurableMigrationGenerator;
     use codegen::wrangler::WranglerGenerator;
     use idl::BackingKind;
-    use migrations::{MigrationsDilemma, MigrationsGenerator, MigrationsIdl, MigrationsIntent};
+    use migrations::{
+        DownMigration, MigrationsDecisions, MigrationsDilemma, MigrationsGenerator, MigrationsIdl,
+        MigrationsIntent, MigrationsRecorder,
+    };
+    use migrations::{DriftDetector, SqliteSchema};
+    use sqlx::{SqlitePool, sqlite::SqliteConnectOptions};
 
     use super::*;
 
@@ -744,6 +1129,19 @@ mod migrate {
         let ast_contents = std::fs::read_to_string(&cidl_path)
             .map_err(|e| format!("Failed to read CIDL file {}: {}", cidl_path.display(), e))?;
 
+        let decisions_contents = args
+            .decisions
+            .as_ref()
+            .map(|p| {
+                std::fs::read_to_string(p)
+                    .map_err(|e| format!("Failed to read decisions file {}: {}", p.display(), e))
+            })
+            .transpose()?;
+
+        // Files are only written once every binding has been planned, so an unanswered
+        // question for one binding leaves no other binding half migrated.
+        let mut planned: Vec<PlannedMigration> = Vec::new();
+        let mut unanswered_errs: Vec<String> = Vec::new();
         for current_binding in bindings {
             let d1_database = spec
                 .d1_databases
@@ -772,17 +1170,12 @@ mod migrate {
                     .join(&config.parsed.migrations_path)
                     .join(&current_binding),
             };
-            std::fs::create_dir_all(&migrations_dir).map_err(|e| {
-                format!(
-                    "Failed to create migrations directory {}: {}",
-                    migrations_dir.display(),
-                    e
-                )
-            })?;
 
             // The last migrated CIDL file is the most recent timestamped file
             // within the migrations directory.
-            let last_migrated_cidl_path: Option<PathBuf> = {
+            let last_migrated_cidl_path: Option<PathBuf> = if !migrations_dir.exists() {
+                None
+            } else {
                 let mut dir_entries = std::fs::read_dir(&migrations_dir)
                     .map_err(|e| {
                         format!(
@@ -806,13 +1199,6 @@ mod migrate {
                     .cloned()
             };
 
-            let timestamp: u64 = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .expect("Time went backwards")
-                .as_secs();
-
-            let file_stem = format!("{timestamp}_{}", args.name);
-
             let lm_contents = last_migrated_cidl_path
                 .map(|p: PathBuf| {
                     std::fs::read_to_string(&p).map_err(|e| {
@@ -830,6 +1216,27 @@ mod migrate {
                 .map(MigrationsIdl::from_json)
                 .transpose()?;
 
+            if let Some(db_path) = &args.check {
+                let Some(lm_ast) = lm_ast else {
+                    return Err(format!(
+                        "No migrations found for binding '{current_binding}' in {}.",
+                        migrations_dir.display()
+                    ));
+                };
+                return check(&lm_ast, db_path);
+            }
+
+            let name = args
+                .name
+                .as_deref()
+                .expect("clap should enforce a name unless --check");
+            let timestamp: u64 = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("Time went backwards")
+                .as_secs();
+
+            let file_stem = format!("{timestamp}_{name}");
+
             // Migrate only the models backed by the specified binding.
             let idl = {
                 let mut idl = MigrationsIdl::from_json(&ast_contents)?;
@@ -841,47 +1248,194 @@ mod migrate {
                 idl
             };
 
-            let generated_sql = MigrationsGenerator::migrate(&idl, lm_ast.as_ref(), &MigrationsCli);
+            let (generated_sql, posed) = match (&decisions_contents, args.plan) {
+                (None, false) => {
+                    let recorder = MigrationsRecorder::new(&MigrationsCli);
+                    let generated_sql =
+                        MigrationsGenerator::migrate(&idl, lm_ast.as_ref(), &recorder);
+                    (generated_sql, recorder.posed())
+                }
+                (contents, plan) => {
+                    let decisions = match contents {
+                        Some(contents) => MigrationsDecisions::from_json(contents)
+                            .map_err(|e| format!("Failed to parse decisions file: {e}"))?,
+                        None => MigrationsDecisions::default(),
+                    };
+                    let generated_sql =
+                        MigrationsGenerator::migrate(&idl, lm_ast.as_ref(), &decisions);
+
+                    if plan {
+                        let posed = decisions.posed();
+                        println!("Binding '{current_binding}': {} question(s)", posed.len());
+                        for dilemma in posed {
+                            println!("  {dilemma}");
+                        }
+                        continue;
+                    }
+
+                    let unanswered = decisions.unanswered();
+                    if !unanswered.is_empty() {
+                        unanswered_errs.push(format!(
+                            "Unanswered migration questions for binding '{current_binding}':\n{}",
+                            unanswered
+                                .iter()
+                                .map(|d| format!("  {d}"))
+                                .collect::<Vec<_>>()
+                                .join("\n")
+                        ));
+                        continue;
+                    }
+
+                    (generated_sql, decisions.posed())
+                }
+            };
 
-            match kind {
+            let (migration, down) = match kind {
                 BackingKind::D1 => {
-                    let mut migrated_cidl_file =
-                        open_file_or_create(&migrations_dir.join(format!("{file_stem}.json")))?;
-                    let mut migrated_sql_file =
-                        open_file_or_create(&migrations_dir.join(format!("{file_stem}.sql")))?;
-
-                    migrated_cidl_file
-                        .write_all(idl.to_json().as_bytes())
-                        .map_err(|e| format!("Failed to write migrated CIDL file: {e}"))?;
-                    migrated_sql_file
-                        .write_all(generated_sql.as_bytes())
-                        .map_err(|e| format!("Failed to write migrated SQL file: {e}"))?;
+                    let down = MigrationsGenerator::migrate_down(&idl, lm_ast.as_ref(), &posed);
+                    (generated_sql, Some(down))
                 }
                 BackingKind::DurableObject => {
                     // TODO: Locked to TS for now
                     let migration_ts =
-                        DurableMigrationGenerator::generate(&args.name, timestamp, &generated_sql);
-
-                    let mut migrated_cidl_file =
-                        open_file_or_create(&migrations_dir.join(format!("{file_stem}.json")))?;
-                    let mut migration_file =
-                        open_file_or_create(&migrations_dir.join(format!("{file_stem}.ts")))?;
-
-                    migrated_cidl_file
-                        .write_all(idl.to_json().as_bytes())
-                        .map_err(|e| format!("Failed to write migrated CIDL file: {e}"))?;
-                    migration_file
-                        .write_all(migration_ts.as_bytes())
-                        .map_err(|e| format!("Failed to write migration file: {e}"))?;
+                        DurableMigrationGenerator::generate(name, timestamp, &generated_sql);
+                    (migration_ts, None)
                 }
-            }
+            };
+            planned.push(PlannedMigration {
+                binding: current_binding,
+                kind,
+                migrations_dir,
+                file_stem,
+                cidl: idl.to_json(),
+                migration,
+                down,
+            });
+        }
 
-            tracing::info!("Finished migration for binding '{}'.", current_binding);
+        if !unanswered_errs.is_empty() {
+            return Err(unanswered_errs.join("\n"));
+        }
+
+        for planned in planned {
+            planned.write()?;
         }
 
         Ok(())
     }
 
+    /// A binding's migration, generated and ready to be written.
+    struct PlannedMigration {
+        binding: String,
+        kind: BackingKind,
+        migrations_dir: PathBuf,
+        file_stem: String,
+        cidl: String,
+
+        /// The forward migration: SQL for D1, TypeScript for a Durable Object.
+        migration: String,
+
+        /// The SQL reverting a D1 migration.
+        down: Option<DownMigration>,
+    }
+
+    impl PlannedMigration {
+        fn write(self) -> Result<(), String> {
+            let Self {
+                binding,
+                kind,
+                migrations_dir,
+                file_stem,
+                cidl,
+                migration,
+                down,
+            } = self;
+
+            std::fs::create_dir_all(&migrations_dir).map_err(|e| {
+                format!(
+                    "Failed to create migrations directory {}: {}",
+                    migrations_dir.display(),
+                    e
+                )
+            })?;
+
+            let extension = match kind {
+                BackingKind::D1 => "sql",
+                BackingKind::DurableObject => "ts",
+            };
+            let mut migrated_cidl_file =
+                open_file_or_create(&migrations_dir.join(format!("{file_stem}.json")))?;
+            let mut migration_file =
+                open_file_or_create(&migrations_dir.join(format!("{file_stem}.{extension}")))?;
+
+            migrated_cidl_file
+                .write_all(cidl.as_bytes())
+                .map_err(|e| format!("Failed to write migrated CIDL file: {e}"))?;
+            migration_file
+                .write_all(migration.as_bytes())
+                .map_err(|e| format!("Failed to write migration file: {e}"))?;
+
+            // Down migrations live in a subdirectory, so Wrangler does not apply them.
+            // They are written last, so a failed forward migration leaves none behind.
+            if let Some(down) = down {
+                for loss in &down.losses {
+                    tracing::warn!("Rolling back '{file_stem}': {loss}");
+                }
+                open_file_or_create(
+                    &migrations_dir
+                        .join("down")
+                        .join(format!("{file_stem}.down.sql")),
+                )?
+                .write_all(down.sql.as_bytes())
+                .map_err(|e| format!("Failed to write down migration file: {e}"))?;
+            }
+
+            tracing::info!("Finished migration for binding '{}'.", binding);
+            Ok(())
+        }
+    }
+
+    /// Compares the schema of the SQLite database at `db_path` against the last migrated
+    /// IDL, printing any drift as JSON.
+    fn check(lm_idl: &MigrationsIdl, db_path: &Path) -> Result<(), String> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| format!("Failed to start the async runtime: {e}"))?;
+
+        let schema = runtime.block_on(async {
+            let options = SqliteConnectOptions::new()
+                .filename(db_path)
+                .read_only(true);
+            let db = SqlitePool::connect_with(options).await?;
+            let schema = SqliteSchema::read(&db).await;
+            db.close().await;
+            schema
+        });
+        let schema = schema
+            .map_err(|e| format!("Failed to read SQLite database {}: {e}", db_path.display()))?;
+
+        let drift = DriftDetector::detect(lm_idl, &schema);
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&drift).expect("serialize drift to work")
+        );
+
+        if drift.is_empty() {
+            tracing::info!("No drift found in {}.", db_path.display());
+            return Ok(());
+        }
+
+        for d in &drift {
+            tracing::warn!("{d}");
+        }
+        Err(format!(
+            "Found {} difference(s) between {} and the last migration.",
+            drift.len(),
+            db_path.display()
+        ))
+    }
+
     struct MigrationsCli;
     impl MigrationsIntent for MigrationsCli {
         fn ask(&self, dilemma: MigrationsDilemma) -> Option<usize> {
@@ -0,0 +1,44 @@
+//! Fingerprints the compiler crates that shape `compile`'s output, so the compile cache can
+//! tell a rebuilt compiler from the one that wrote it.
+
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+/// Crates whose sources or templates change what `compile` generates.
+const CRATES: &[&str] = &["idl", "frontend", "semantic", "codegen"];
+
+fn main() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+
+    let mut files = vec![];
+    for krate in CRATES {
+        for dir in ["src", "templates"] {
+            let dir = root.join(krate).join(dir);
+            println!("cargo:rerun-if-changed={}", dir.display());
+            collect(&dir, &mut files);
+        }
+    }
+    files.sort();
+
+    let mut h = DefaultHasher::new();
+    for path in &files {
+        path.strip_prefix(&root).unwrap_or(path).hash(&mut h);
+        std::fs::read(path).unwrap_or_default().hash(&mut h);
+    }
+    println!("cargo:rustc-env=CLOESCE_COMPILER_HASH={:016x}", h.finish());
+}
+
+fn collect(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.filter_map(|e| e.ok().map(|e| e.path())) {
+        if path.is_dir() {
+            collect(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::{fs, path::PathBuf, process::Command};
+
+const CONFIG: &str = r#"{
+    "src_paths": ["./"],
+    "workers_url": "http://localhost:5000/api",
+    "out_path": "."
+}
+"#;
+
+const SRC: &str = r#"d1 { db }
+
+model Person for db {
+    primary {
+        id: int
+    }
+
+    column {
+        name: string
+    }
+}
+"#;
+
+const NO_CHANGES: &str = "No source files changed since the last compile.";
+
+/// A fresh project directory holding the config and [SRC].
+fn project(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+        .join("cache_tests")
+        .join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("cloesce.jsonc"), CONFIG).unwrap();
+    fs::write(dir.join("schema.cloesce"), SRC).unwrap();
+    dir
+}
+
+/// Runs `cloesce compile` in `dir`, returning its log.
+fn compile(dir: &PathBuf) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_cloesce"))
+        .arg("compile")
+        .current_dir(dir)
+        .output()
+        .expect("cloesce to run");
+    let log = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(output.status.success(), "compile failed:\n{log}");
+    log
+}
+
+#[test]
+fn unchanged_sources_hit_the_cache() {
+    // Arrange
+    let dir = project("hit");
+    compile(&dir);
+    let backend = fs::read_to_string(dir.join("backend.ts")).unwrap();
+
+    // Act
+    let log = compile(&dir);
+
+    // Assert
+    assert!(log.contains(NO_CHANGES), "{log}");
+    assert_eq!(fs::read_to_string(dir.join("backend.ts")).unwrap(), backend);
+}
+
+#[test]
+fn changed_sources_miss_the_cache() {
+    // Arrange
+    let dir = project("miss");
+    compile(&dir);
+    fs::write(
+        dir.join("schema.cloesce"),
+        SRC.replace("name: string", "name: string\n        age: int"),
+    )
+    .unwrap();
+
+    // Act
+    let log = compile(&dir);
+
+    // Assert
+    assert!(!log.contains(NO_CHANGES), "{log}");
+    let backend = fs::read_to_string(dir.join("backend.ts")).unwrap();
+    assert!(backend.contains("age"), "backend was not regenerated");
+}
+
+#[test]
+fn tampered_artifact_is_regenerated() {
+    // Arrange
+    let dir = project("tamper");
+    compile(&dir);
+    let backend = fs::read_to_string(dir.join("backend.ts")).unwrap();
+    fs::write(dir.join("backend.ts"), "// edited by hand").unwrap();
+
+    // Act
+    let log = compile(&dir);
+
+    // Assert
+    assert!(!log.contains(NO_CHANGES), "{log}");
+    assert_eq!(fs::read_to_string(dir.join("backend.ts")).unwrap(), backend);
+}
+
+#[test]
+fn cache_from_another_compiler_is_discarded() {
+    // Arrange
+    let dir = project("version");
+    compile(&dir);
+    let cache_path = dir.join("cache.json");
+    let mut cache: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&cache_path).unwrap()).unwrap();
+    cache["version"] = "0.0.0+0000000000000000".into();
+    fs::write(&cache_path, cache.to_string()).unwrap();
+
+    // Act
+    let log = compile(&dir);
+
+    // Assert
+    assert!(!log.contains(NO_CHANGES), "{log}");
+}
@@ -2,12 +2,16 @@ use std::borrow::Cow;
 
 use askama::Template;
 use idl::{
-    ApiMethod, CidlType, CloesceIdl, DEFAULT_DATA_SOURCE_NAME, DataSource, DurableBinding, Model,
-    TemplateSegment,
+    ApiMethod, CidlType, CloesceIdl, CronTrigger, DEFAULT_DATA_SOURCE_NAME, DataSource,
+    DurableBinding, Guard, Model, ServiceBinding, TemplateSegment,
 };
 
 use crate::mappers::{LanguageTypeMapper, TypeScriptMapper};
 
+mod rust;
+
+pub use rust::RustBackendGenerator;
+
 #[derive(Template)]
 #[template(path = "backend.ts.jinja", escape = "none")]
 struct BackendTemplate<'src> {
@@ -70,6 +74,28 @@ impl<'src> BackendTemplate<'src> {
         format!("{}{}", model.name, self.cap_first(&api.name))
     }
 
+    /// The `Env.<Cron>` interface and `Crons.<Cron>` handler name for a Cron Trigger:
+    /// `Cron<n>`, numbered from 1 in schema order.
+    fn cron_name(&self, cron: &CronTrigger<'src>) -> String {
+        let idx = self
+            .idl
+            .crons
+            .iter()
+            .position(|c| c.expression == cron.expression)
+            .unwrap_or_default();
+        format!("Cron{}", idx + 1)
+    }
+
+    /// A Cron Trigger's expression, safe to embed in a doc comment (`*/5` would close it).
+    fn cron_doc(&self, cron: &CronTrigger<'src>) -> String {
+        cron.expression.replace("*/", "*\\/")
+    }
+
+    /// The `Env.<Guard>Guard` interface name for a guard's injected bindings.
+    fn guard_env_name(&self, guard: &Guard<'src>) -> String {
+        format!("{}Guard", guard.name)
+    }
+
     /// The store-handle type prefix for a binding, keyed by its kind.
     fn binding_prefix(&self, name: &str) -> &'static str {
         if self.idl.wrangler_env.d1_bindings.contains(&name) {
@@ -96,9 +122,40 @@ impl<'src> BackendTemplate<'src> {
             return "R2";
         }
 
+        if self
+            .idl
+            .wrangler_env
+            .queue_bindings
+            .iter()
+            .any(|b| b.name == name)
+        {
+            return "Queue";
+        }
+
+        if self
+            .idl
+            .wrangler_env
+            .service_bindings
+            .iter()
+            .any(|b| b.name == name)
+        {
+            return "Service";
+        }
+
         "Do"
     }
 
+    /// Models of a service binding's Worker with API methods, in its schema order.
+    fn service_models<'a>(&self, binding: &'a ServiceBinding<'src>) -> Vec<&'a str> {
+        binding
+            .cidl
+            .models
+            .values()
+            .filter(|m| !m.apis.is_empty())
+            .map(|m| m.name)
+            .collect()
+    }
+
     /// True if any model is backed by this binding.
     fn binding_hosts_models(&self, name: &str) -> bool {
         !self.binding_models(name).is_empty()
@@ -229,7 +286,44 @@ impl<'src> BackendTemplate<'src> {
         out
     }
 
-    /// All injectable names referenced by any route or stubbed data-source verb in the schema.
+    /// Every guard the routes running on a host run, in decl order. `binding` is the Durable
+    /// Object binding hosting the routes, or `None` for the Worker.
+    fn host_guards(&self, binding: Option<&str>) -> Vec<String> {
+        let mut out: Vec<String> = Vec::new();
+        let guards = self
+            .idl
+            .models
+            .values()
+            .flat_map(|m| m.apis.iter())
+            .filter(|api| api.durable_target.as_ref().map(|t| t.binding) == binding)
+            .flat_map(|api| api.guards.iter());
+        for guard in guards {
+            if !out.iter().any(|x| x == guard) {
+                out.push(guard.to_string());
+            }
+        }
+        out
+    }
+
+    /// Every injectable name the named guards require.
+    fn guard_injectables(&self, guards: &[String]) -> Vec<String> {
+        let mut out: Vec<String> = Vec::new();
+        let injects = self
+            .idl
+            .guards
+            .iter()
+            .filter(|g| guards.iter().any(|name| name == g.name))
+            .flat_map(|g| g.injected.iter());
+        for inj in injects {
+            if self.is_injectable(inj) && !out.iter().any(|x| x == inj) {
+                out.push(inj.to_string());
+            }
+        }
+        out
+    }
+
+    /// All injectable names referenced by any route, stubbed data-source verb or Cron Trigger
+    /// in the schema.
     fn injectables_used(&self) -> Vec<String> {
         let mut out: Vec<String> = Vec::new();
         for model in self.idl.models.values() {
@@ -239,6 +333,11 @@ impl<'src> BackendTemplate<'src> {
                 }
             }
         }
+        for inj in self.idl.crons.iter().flat_map(|c| c.injected.iter()) {
+            if self.is_injectable(inj) && !out.iter().any(|x| x == inj) {
+                out.push(inj.to_string());
+            }
+        }
         out
     }
 
@@ -254,10 +353,18 @@ impl<'src> BackendTemplate<'src> {
             .values()
             .map(|m| m.name.to_string())
             .collect();
+        let worker_guards = self.host_guards(None);
+        let mut worker_injectables = self.injectables_used();
+        for inj in self.guard_injectables(&worker_guards) {
+            if !worker_injectables.contains(&inj) {
+                worker_injectables.push(inj);
+            }
+        }
         hosts.push(HostInfo {
             name: "Worker".to_string(),
             models: worker_models,
-            injectables: self.injectables_used(),
+            injectables: worker_injectables,
+            guards: worker_guards,
         });
 
         for binding in &self.idl.wrangler_env.durable_bindings {
@@ -267,6 +374,7 @@ impl<'src> BackendTemplate<'src> {
                 .map(|m| m.name.to_string())
                 .collect();
             let mut injectables: Vec<String> = Vec::new();
+            let guards = self.host_guards(Some(binding.name));
             for model in self.binding_models(binding.name) {
                 for inj in self.model_injectables(model) {
                     if !injectables.iter().any(|x| x == &inj) {
@@ -274,10 +382,16 @@ impl<'src> BackendTemplate<'src> {
                     }
                 }
             }
+            for inj in self.guard_injectables(&guards) {
+                if !injectables.contains(&inj) {
+                    injectables.push(inj);
+                }
+            }
             hosts.push(HostInfo {
                 name: format!("{}Host", binding.name),
                 models,
                 injectables,
+                guards,
             });
         }
 
@@ -303,6 +417,7 @@ struct HostInfo {
     name: String,
     models: Vec<String>,
     injectables: Vec<String>,
+    guards: Vec<String>,
 }
 
 impl HostInfo {
@@ -310,6 +425,7 @@ impl HostInfo {
         self.models
             .iter()
             .chain(self.injectables.iter())
+            .chain(self.guards.iter())
             .cloned()
             .collect()
     }
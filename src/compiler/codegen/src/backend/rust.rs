@@ -0,0 +1,201 @@
+use askama::Template;
+use idl::{
+    ApiMethod, CidlType, CloesceIdl, DataSourceGetMethodParam, HttpVerb, MediaType, Model,
+    ParamSource, TemplateSegment,
+};
+
+use crate::mappers::{LanguageTypeMapper, RustMapper};
+
+#[derive(Template)]
+#[template(path = "backend.rs.jinja", escape = "none")]
+struct RustBackendTemplate<'src> {
+    idl: &'src CloesceIdl<'src>,
+    mapper: RustMapper,
+
+    /// Path segments of the worker URL, which every route is served under.
+    prefix: Vec<String>,
+}
+
+impl<'src> RustBackendTemplate<'src> {
+    /// Models with user-authored routes, in schema order. Generated `$crud` methods run on
+    /// the TypeScript ORM runtime, so they have no Rust handler.
+    fn routed_models(&self) -> Vec<&Model<'src>> {
+        self.idl
+            .models
+            .values()
+            .filter(|m| !self.user_routes(m).is_empty())
+            .collect()
+    }
+
+    fn user_routes<'a>(&self, model: &'a Model<'src>) -> Vec<&'a ApiMethod<'src>> {
+        model
+            .apis
+            .iter()
+            .filter(|a| !a.name.starts_with('$'))
+            .collect()
+    }
+
+    /// Every user-authored route, those with a custom route first so that they take priority
+    /// over derived routes, as in the TypeScript router.
+    fn routes(&self) -> Vec<(&Model<'src>, &ApiMethod<'src>)> {
+        let (custom, derived): (Vec<_>, Vec<_>) = self
+            .routed_models()
+            .into_iter()
+            .flat_map(|m| self.user_routes(m).into_iter().map(move |a| (m, a)))
+            .partition(|(_, a)| a.route.is_some());
+        custom.into_iter().chain(derived).collect()
+    }
+
+    /// Every guard run by a user-authored route, in schema order.
+    fn guards(&self) -> Vec<&'src str> {
+        let mut out: Vec<&'src str> = Vec::new();
+        for (_, api) in self.routes() {
+            for guard in &api.guards {
+                if !out.contains(guard) {
+                    out.push(guard);
+                }
+            }
+        }
+        out
+    }
+
+    /// The traits `route`'s app must implement: [Self::guards]' trait, then each routed
+    /// model's `Api` trait.
+    fn app_bounds(&self) -> Vec<String> {
+        let guards = (!self.guards().is_empty()).then(|| "Guards".to_string());
+        guards
+            .into_iter()
+            .chain(
+                self.routed_models()
+                    .iter()
+                    .map(|m| format!("{}Api", m.name)),
+            )
+            .collect()
+    }
+
+    /// The data source `get` parameters an instance route is keyed by, in path order.
+    fn key_params<'a>(
+        &self,
+        model: &'a Model<'src>,
+        api: &ApiMethod<'src>,
+    ) -> &'a [DataSourceGetMethodParam<'src>] {
+        if api.is_static {
+            return &[];
+        }
+        api.data_source
+            .and_then(|n| model.data_sources.get(n))
+            .map(|ds| ds.get.parameters.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// The handler's parameters after `env`: the route's keys, then its parameters.
+    fn handler_params(&self, model: &Model<'src>, api: &ApiMethod<'src>) -> String {
+        let keys = self
+            .key_params(model, api)
+            .iter()
+            .map(|p| (p.parameter.name.as_ref(), &p.parameter.cidl_type));
+        let params = api
+            .parameters
+            .iter()
+            .map(|p| (p.field.name.as_ref(), &p.field.cidl_type));
+        keys.chain(params)
+            .map(|(name, ty)| {
+                format!(
+                    ", {}: {}",
+                    self.mapper.snake_case(name),
+                    self.mapper.cidl_type(ty)
+                )
+            })
+            .collect()
+    }
+
+    /// The route's path pattern after the prefix, e.g. `"User", key0, "profile"`, or
+    /// `"users", path_id, "posts"` for a custom route.
+    fn path_pattern(&self, model: &Model<'src>, api: &ApiMethod<'src>) -> String {
+        if api.route.is_some() {
+            return api
+                .route_segments()
+                .iter()
+                .map(|seg| match seg {
+                    TemplateSegment::Literal(text) => format!("\"{text}\""),
+                    TemplateSegment::Value(param) => {
+                        format!("path_{}", self.mapper.snake_case(&param.field.name))
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+        }
+
+        let keys = (0..self.key_params(model, api).len()).map(|i| format!("key{i}"));
+        std::iter::once(format!("\"{}\"", model.name))
+            .chain(keys)
+            .chain(std::iter::once(format!("\"{}\"", api.name)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// The route's path for docs, e.g. `/User/{id}/profile`.
+    fn path_doc(&self, model: &Model<'src>, api: &ApiMethod<'src>) -> String {
+        if let Some(route) = api.route {
+            return format!("/{route}");
+        }
+
+        let keys = self
+            .key_params(model, api)
+            .iter()
+            .map(|p| format!("/{{{}}}", p.parameter.name))
+            .collect::<String>();
+        format!("/{}{keys}/{}", model.name, api.name)
+    }
+
+    fn verb(&self, api: &ApiMethod<'src>) -> &'static str {
+        match api.http_verb {
+            HttpVerb::Get => "Get",
+            HttpVerb::Post => "Post",
+            HttpVerb::Put => "Put",
+            HttpVerb::Patch => "Patch",
+            HttpVerb::Delete => "Delete",
+        }
+    }
+
+    /// True if the route's parameters are read from a JSON body.
+    fn has_json_body(&self, api: &ApiMethod<'src>) -> bool {
+        api.http_verb != HttpVerb::Get
+            && api.parameters_media == MediaType::Json
+            && api.parameters.iter().any(|p| p.source == ParamSource::Body)
+    }
+
+    fn is_header(&self, source: &ParamSource) -> bool {
+        matches!(source, ParamSource::Header)
+    }
+
+    fn is_path(&self, source: &ParamSource) -> bool {
+        matches!(source, ParamSource::Path)
+    }
+
+    fn is_stream(&self, ty: &CidlType<'_>) -> bool {
+        matches!(ty, CidlType::Stream)
+    }
+}
+
+pub struct RustBackendGenerator;
+impl RustBackendGenerator {
+    pub fn generate(idl: &CloesceIdl, worker_url: &str) -> String {
+        let path = worker_url
+            .split_once("://")
+            .map_or(worker_url, |(_, rest)| rest)
+            .split_once('/')
+            .map_or("", |(_, path)| path);
+        let tmpl = RustBackendTemplate {
+            idl,
+            mapper: RustMapper,
+            prefix: path
+                .split('/')
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect(),
+        };
+        tmpl.render()
+            .expect("Failed to render Rust backend template")
+    }
+}
@@ -1,18 +1,27 @@
 use askama::Template;
 use idl::{
     ApiMethod, CidlType, CloesceIdl, DataSourceGetMethodParam, HttpVerb, MediaType, Model,
-    NavigationCardinality, NavigationField, ParamSource,
+    NavigationCardinality, NavigationField, ParamSource, ServiceBinding, TemplateSegment,
 };
 
 use crate::mappers::{LanguageTypeMapper, TypeScriptMapper};
 
+mod python;
+mod rust;
+
+pub use python::PythonClientGenerator;
+pub use rust::RustClientGenerator;
+
 macro_rules! cidl_type_contains {
     ($value:expr, $pattern:pat) => {{
         let mut cur = $value;
 
         loop {
             match cur {
-                CidlType::Array(inner) | CidlType::Nullable(inner) | CidlType::KvObject(inner) => {
+                CidlType::Array(inner)
+                | CidlType::Nullable(inner)
+                | CidlType::KvObject(inner)
+                | CidlType::Page(inner) => {
                     if matches!(cur, $pattern) {
                         break true;
                     }
@@ -88,6 +97,10 @@ impl ClientTemplate<'_> {
         cidl_type_contains!(ty, CidlType::Array(_))
     }
 
+    fn is_page(&self, ty: &CidlType<'_>) -> bool {
+        matches!(ty, CidlType::Page(_))
+    }
+
     fn is_blob(&self, ty: &CidlType<'_>) -> bool {
         matches!(ty.root_type(), CidlType::Blob)
     }
@@ -111,6 +124,35 @@ impl ClientTemplate<'_> {
         matches!(source, ParamSource::Header)
     }
 
+    fn is_query(&self, source: &ParamSource) -> bool {
+        matches!(source, ParamSource::Query)
+    }
+
+    fn is_path(&self, source: &ParamSource) -> bool {
+        matches!(source, ParamSource::Path)
+    }
+
+    /// An [ApiMethod::route] as the body of a template literal, each `{param}` replaced by
+    /// the parameter's encoded value.
+    fn route_url(&self, api: &ApiMethod<'_>) -> String {
+        api.route_segments()
+            .iter()
+            .map(|seg| match seg {
+                TemplateSegment::Literal(text) => text.to_string(),
+                TemplateSegment::Value(param) if self.is_date(&param.field.cidl_type) => {
+                    format!(
+                        "${{encodeURIComponent({}.toISOString())}}",
+                        param.field.name
+                    )
+                }
+                TemplateSegment::Value(param) => {
+                    format!("${{encodeURIComponent(String({}))}}", param.field.name)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
     fn is_stream(&self, ty: &CidlType<'_>) -> bool {
         matches!(ty.root_type(), CidlType::Stream)
     }
@@ -145,4 +187,13 @@ impl ClientGenerator {
         };
         tmpl.render().expect("Failed to render client template")
     }
+
+    /// Generates the client for a service binding's Worker. Its requests are routed through
+    /// the binding's `fetch`, so the worker URL only needs a placeholder host.
+    pub fn generate_service(binding: &ServiceBinding) -> String {
+        Self::generate(
+            &binding.cidl,
+            &format!("https://{}", binding.name.to_lowercase()),
+        )
+    }
 }
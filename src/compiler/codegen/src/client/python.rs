@@ -0,0 +1,129 @@
+use askama::Template;
+use idl::{
+    ApiMethod, CidlType, CloesceIdl, DataSourceGetMethodParam, HttpVerb, MediaType, Model,
+    ParamSource, TemplateSegment,
+};
+
+use crate::mappers::{LanguageTypeMapper, PythonMapper};
+
+#[derive(Template)]
+#[template(path = "client.py.jinja", escape = "none")]
+struct PythonClientTemplate<'src> {
+    idl: &'src CloesceIdl<'src>,
+    worker_url: &'src str,
+    mapper: PythonMapper,
+}
+
+impl<'src> PythonClientTemplate<'src> {
+    fn client_models(&self) -> Vec<&Model<'src>> {
+        self.idl
+            .models
+            .values()
+            .filter(|m| !m.apis.is_empty())
+            .collect()
+    }
+
+    /// The client function for an API method; generated `$crud` methods are prefixed with
+    /// `crud_` (`$get` -> `crud_get`).
+    fn method_name(&self, api: &ApiMethod<'src>) -> String {
+        match api.name.strip_prefix('$') {
+            Some(crud) => format!("crud_{}", self.mapper.snake_case(crud)),
+            None => self.mapper.snake_case(&api.name),
+        }
+    }
+
+    /// The data source `get` parameters an instance method is keyed by, in path order.
+    fn key_params<'a>(
+        &self,
+        model: &'a Model<'src>,
+        api: &ApiMethod<'src>,
+    ) -> &'a [DataSourceGetMethodParam<'src>] {
+        if api.is_static {
+            return &[];
+        }
+        api.data_source
+            .and_then(|n| model.data_sources.get(n))
+            .map(|ds| ds.get.parameters.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// The function's parameters after `self`: the instance's keys, then its parameters.
+    fn method_params(&self, model: &Model<'src>, api: &ApiMethod<'src>) -> String {
+        let keys = self
+            .key_params(model, api)
+            .iter()
+            .map(|p| (p.parameter.name.as_ref(), &p.parameter.cidl_type));
+        let params = api
+            .parameters
+            .iter()
+            .map(|p| (p.field.name.as_ref(), &p.field.cidl_type));
+        keys.chain(params)
+            .map(|(name, ty)| {
+                format!(
+                    ", {}: {}",
+                    self.mapper.snake_case(name),
+                    self.mapper.cidl_type(ty)
+                )
+            })
+            .collect()
+    }
+
+    /// The path segments of an [ApiMethod::route], each `{param}` encoded by `_segment`.
+    fn route_path(&self, api: &ApiMethod<'src>) -> String {
+        api.route_segments()
+            .iter()
+            .map(|seg| match seg {
+                TemplateSegment::Literal(text) => format!("\"{text}\""),
+                TemplateSegment::Value(param) => format!(
+                    "_segment({}, {})",
+                    self.mapper.snake_case(&param.field.name),
+                    self.mapper.codec(&param.field.cidl_type)
+                ),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn verb(&self, api: &ApiMethod<'src>) -> &'static str {
+        match api.http_verb {
+            HttpVerb::Get => "GET",
+            HttpVerb::Post => "POST",
+            HttpVerb::Put => "PUT",
+            HttpVerb::Patch => "PATCH",
+            HttpVerb::Delete => "DELETE",
+        }
+    }
+
+    fn is_get_request(&self, api: &ApiMethod<'src>) -> bool {
+        api.http_verb == HttpVerb::Get
+    }
+
+    fn is_header(&self, source: &ParamSource) -> bool {
+        matches!(source, ParamSource::Header)
+    }
+
+    fn is_query(&self, source: &ParamSource) -> bool {
+        matches!(source, ParamSource::Query)
+    }
+
+    fn is_path(&self, source: &ParamSource) -> bool {
+        matches!(source, ParamSource::Path)
+    }
+
+    fn is_stream(&self, ty: &CidlType<'_>) -> bool {
+        matches!(ty, CidlType::Stream)
+    }
+}
+
+pub struct PythonClientGenerator;
+impl PythonClientGenerator {
+    pub fn generate(idl: &CloesceIdl, worker_url: &str) -> String {
+        let tmpl = PythonClientTemplate {
+            idl,
+            worker_url,
+            mapper: PythonMapper,
+        };
+        tmpl.render()
+            .expect("Failed to render Python client template")
+    }
+}
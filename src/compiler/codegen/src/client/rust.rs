@@ -0,0 +1,126 @@
+use askama::Template;
+use idl::{
+    ApiMethod, CidlType, CloesceIdl, DataSourceGetMethodParam, HttpVerb, MediaType, Model,
+    ParamSource, ServiceBinding, TemplateSegment,
+};
+
+use crate::mappers::{LanguageTypeMapper, RustMapper};
+
+#[derive(Template)]
+#[template(path = "client.rs.jinja", escape = "none")]
+struct RustClientTemplate<'src> {
+    idl: &'src CloesceIdl<'src>,
+    worker_url: &'src str,
+    mapper: RustMapper,
+}
+
+impl<'src> RustClientTemplate<'src> {
+    fn client_models(&self) -> Vec<&Model<'src>> {
+        self.idl
+            .models
+            .values()
+            .filter(|m| !m.apis.is_empty())
+            .collect()
+    }
+
+    /// The client method for an API method; generated `$crud` methods are prefixed with
+    /// `crud_` (`$get` -> `crud_get`).
+    fn method_name(&self, api: &ApiMethod<'src>) -> String {
+        match api.name.strip_prefix('$') {
+            Some(crud) => format!("crud_{}", self.mapper.snake_case(crud)),
+            None => self.mapper.snake_case(&api.name),
+        }
+    }
+
+    /// The data source `get` parameters an instance method is keyed by, in path order.
+    fn key_params<'a>(
+        &self,
+        model: &'a Model<'src>,
+        api: &ApiMethod<'src>,
+    ) -> &'a [DataSourceGetMethodParam<'src>] {
+        if api.is_static {
+            return &[];
+        }
+        api.data_source
+            .and_then(|n| model.data_sources.get(n))
+            .map(|ds| ds.get.parameters.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// The method's parameters after `&self`: the instance's keys, then its parameters.
+    fn method_params(&self, model: &Model<'src>, api: &ApiMethod<'src>) -> String {
+        let keys = self
+            .key_params(model, api)
+            .iter()
+            .map(|p| (p.parameter.name.as_ref(), &p.parameter.cidl_type));
+        let params = api
+            .parameters
+            .iter()
+            .map(|p| (p.field.name.as_ref(), &p.field.cidl_type));
+        keys.chain(params)
+            .map(|(name, ty)| {
+                format!(
+                    ", {}: {}",
+                    self.mapper.snake_case(name),
+                    self.mapper.cidl_type(ty)
+                )
+            })
+            .collect()
+    }
+
+    fn verb(&self, api: &ApiMethod<'src>) -> &'static str {
+        match api.http_verb {
+            HttpVerb::Get => "GET",
+            HttpVerb::Post => "POST",
+            HttpVerb::Put => "PUT",
+            HttpVerb::Patch => "PATCH",
+            HttpVerb::Delete => "DELETE",
+        }
+    }
+
+    fn is_get_request(&self, api: &ApiMethod<'src>) -> bool {
+        api.http_verb == HttpVerb::Get
+    }
+
+    fn is_header(&self, source: &ParamSource) -> bool {
+        matches!(source, ParamSource::Header)
+    }
+
+    fn is_query(&self, source: &ParamSource) -> bool {
+        matches!(source, ParamSource::Query)
+    }
+
+    fn is_path(&self, source: &ParamSource) -> bool {
+        matches!(source, ParamSource::Path)
+    }
+
+    fn is_stream(&self, ty: &CidlType<'_>) -> bool {
+        matches!(ty, CidlType::Stream)
+    }
+
+    fn is_void(&self, ty: &CidlType<'_>) -> bool {
+        matches!(ty, CidlType::Void)
+    }
+}
+
+pub struct RustClientGenerator;
+impl RustClientGenerator {
+    pub fn generate(idl: &CloesceIdl, worker_url: &str) -> String {
+        let tmpl = RustClientTemplate {
+            idl,
+            worker_url,
+            mapper: RustMapper,
+        };
+        tmpl.render()
+            .expect("Failed to render Rust client template")
+    }
+
+    /// Generates the client for a service binding's Worker, as
+    /// [crate::client::ClientGenerator::generate_service] does.
+    pub fn generate_service(binding: &ServiceBinding) -> String {
+        Self::generate(
+            &binding.cidl,
+            &format!("https://{}", binding.name.to_lowercase()),
+        )
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+
+use idl::{ApiMethod, CidlType, CloesceIdl, ValidatedField};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::schema::{PARTIAL_SUFFIX, Schema, SchemaBuilder};
+
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+const DEFS_REF_PREFIX: &str = "#/$defs/";
+
+/// A standalone JSON Schema document, carrying every definition its root refers to.
+#[derive(Serialize)]
+struct Document<'a> {
+    #[serde(rename = "$schema")]
+    dialect: &'static str,
+
+    title: &'a str,
+
+    #[serde(flatten)]
+    root: Schema,
+
+    #[serde(rename = "$defs", skip_serializing_if = "BTreeMap::is_empty")]
+    defs: BTreeMap<String, Schema>,
+}
+
+/// Generates JSON Schema (2020-12) documents that accept exactly the values the runtime
+/// validator (`orm::validate::validate_cidl_type`) does, so other systems can check data
+/// against the same rules.
+///
+/// There is one document per model, plain old object, and [ApiMethod] parameter set. The
+/// runtime additionally coerces the string forms query strings and headers carry (`"1"` for an
+/// `int`, `"true"` for a `bool`, `"null"` for null), which the documents leave out.
+pub struct JsonSchemaGenerator;
+impl JsonSchemaGenerator {
+    /// Each document, keyed by name: `<Model>`, `<Poo>` or `<Model>.<method>`.
+    pub fn generate(idl: &CloesceIdl) -> BTreeMap<String, String> {
+        let mut documents = BTreeMap::new();
+
+        for name in idl.models.keys().chain(idl.poos.keys()) {
+            let builder = SchemaBuilder::runtime(DEFS_REF_PREFIX);
+            let root = builder.reference(name);
+            documents.insert(name.to_string(), Self::document(idl, builder, name, root));
+        }
+
+        for model in idl.models.values() {
+            for api in &model.apis {
+                let fields = Self::parameters(api);
+                if fields.is_empty() {
+                    continue;
+                }
+                let title = format!("{}.{}", model.name, api.name);
+                let mut builder = SchemaBuilder::runtime(DEFS_REF_PREFIX);
+                let root = builder.fields(&fields, false);
+                documents.insert(title.clone(), Self::document(idl, builder, &title, root));
+            }
+        }
+
+        documents
+    }
+
+    /// The parameters of an [ApiMethod] the router validates, wherever they are read from.
+    /// A stream is sent as the raw body, so has no JSON form.
+    fn parameters<'a>(api: &ApiMethod<'a>) -> Vec<ValidatedField<'a>> {
+        api.parameters
+            .iter()
+            .filter(|p| !matches!(p.field.cidl_type, CidlType::Stream))
+            .map(|p| p.field.clone())
+            .collect()
+    }
+
+    /// Builds a document around `root`, defining every component it transitively references.
+    fn document<'a>(
+        idl: &CloesceIdl<'a>,
+        mut builder: SchemaBuilder<'a>,
+        title: &str,
+        root: Schema,
+    ) -> String {
+        let mut defs = BTreeMap::new();
+        let mut pending = references(&root);
+        while let Some(name) = pending.pop() {
+            if defs.contains_key(&name) {
+                continue;
+            }
+
+            let schema = match Self::component(idl, &mut builder, &name) {
+                Some(schema) => schema,
+                None => continue,
+            };
+            pending.extend(references(&schema));
+            defs.insert(name, schema);
+        }
+
+        let document = Document {
+            dialect: JSON_SCHEMA_DIALECT,
+            title,
+            root,
+            defs,
+        };
+        serde_json::to_string_pretty(&document).expect("JSON Schema document to serialize")
+    }
+
+    /// The component schema of a model, plain old object or enum, or of a partial.
+    fn component<'a>(
+        idl: &CloesceIdl<'a>,
+        builder: &mut SchemaBuilder<'a>,
+        name: &str,
+    ) -> Option<Schema> {
+        let is_object = |name| idl.models.contains_key(name) || idl.poos.contains_key(name);
+        let (name, partial) = match name.strip_suffix(PARTIAL_SUFFIX) {
+            Some(object) if !is_object(name) => (object, true),
+            _ => (name, false),
+        };
+
+        if let Some(model) = idl.models.get(name) {
+            return Some(builder.model(model, partial));
+        }
+        if let Some(poo) = idl.poos.get(name) {
+            return Some(builder.fields(&poo.fields, partial));
+        }
+        idl.enums.get(name).map(|e| builder.enumeration(e))
+    }
+}
+
+/// The names of the components a schema references, in document order.
+fn references(schema: &Schema) -> Vec<String> {
+    fn walk(value: &Value, out: &mut Vec<String>) {
+        match value {
+            Value::Object(obj) => {
+                for (key, value) in obj {
+                    match (key.as_str(), value) {
+                        ("$ref", Value::String(r)) => {
+                            if let Some(name) = r.strip_prefix(DEFS_REF_PREFIX) {
+                                out.push(name.to_string());
+                            }
+                        }
+                        _ => walk(value, out),
+                    }
+                }
+            }
+            Value::Array(items) => items.iter().for_each(|v| walk(v, out)),
+            _ => {}
+        }
+    }
+
+    let mut out = vec![];
+    walk(
+        &serde_json::to_value(schema).expect("schema to serialize"),
+        &mut out,
+    );
+    out
+}
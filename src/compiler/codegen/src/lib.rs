@@ -2,6 +2,9 @@
 
 pub mod backend;
 pub mod client;
+pub mod json_schema;
+pub mod openapi;
 pub mod wrangler;
 
 mod mappers;
+mod schema;
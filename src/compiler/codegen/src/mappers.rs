@@ -1,4 +1,4 @@
-use idl::{CidlType, MediaType, TemplateSegment};
+use idl::{CidlType, MediaType, NavigationCardinality, NavigationField, TemplateSegment};
 
 pub trait LanguageTypeMapper {
     /// Maps a [CidlType] to a type in the target language
@@ -48,7 +48,7 @@ impl LanguageTypeMapper for TypeScriptMapper {
             CidlType::Boolean => "boolean".to_string(),
             CidlType::DateIso => "Date".to_string(),
             CidlType::Blob => "Uint8Array".to_string(),
-            CidlType::Object { name, .. } => name.to_string(),
+            CidlType::Object { name, .. } | CidlType::Enum { name } => name.to_string(),
             CidlType::Nullable(inner) => format!("{} | null", self.cidl_type(inner)),
             CidlType::Array(inner) => format!("{}[]", self.cidl_type(inner)),
             CidlType::Void => "void".to_string(),
@@ -60,6 +60,7 @@ impl LanguageTypeMapper for TypeScriptMapper {
                 TypeScriptMapperKind::ClientApi => "Uint8Array".to_string(),
             },
             CidlType::KvObject(inner) => format!("KValue<{}>", self.cidl_type(inner)),
+            CidlType::Page(inner) => format!("Page<{}>", self.cidl_type(inner)),
             CidlType::R2Object => match self.kind {
                 TypeScriptMapperKind::BackendTypes => "R2ObjectBody".to_string(),
                 TypeScriptMapperKind::ClientApi => "R2Object".to_string(),
@@ -99,3 +100,252 @@ impl LanguageTypeMapper for TypeScriptMapper {
             .replace("${", "\\${")
     }
 }
+
+/// Maps to Rust types for a `workers-rs` backend and client, serialized with `serde`.
+///
+/// Rust has no mapped types, so a [CidlType::Partial] is left as untyped JSON.
+pub struct RustMapper;
+
+impl RustMapper {
+    /// Strict and reserved keywords, which must be raw identifiers (`r#type`) as a field
+    /// or parameter name.
+    const KEYWORDS: [&'static str; 50] = [
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait",
+        "true", "type", "unsafe", "use", "where", "while", "abstract", "become", "box", "do",
+        "final", "macro", "override", "priv", "typeof", "unsized", "virtual", "yield",
+    ];
+
+    /// A schema name as a Rust identifier, raw if it is a keyword.
+    pub fn ident(&self, name: &str) -> String {
+        if Self::KEYWORDS.contains(&name) {
+            format!("r#{name}")
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// A schema name in `snake_case` (`getUser` -> `get_user`), as a Rust identifier.
+    pub fn snake_case(&self, name: &str) -> String {
+        let mut out = String::with_capacity(name.len());
+        let mut prev_lower = false;
+        for c in name.chars() {
+            if c.is_uppercase() {
+                if prev_lower {
+                    out.push('_');
+                }
+                out.extend(c.to_lowercase());
+                prev_lower = false;
+            } else {
+                out.push(c);
+                prev_lower = c.is_lowercase() || c.is_ascii_digit();
+            }
+        }
+        self.ident(out.trim_start_matches('$'))
+    }
+
+    /// A navigation field's type. A one-to-one navigation is boxed, as models may navigate
+    /// to each other.
+    pub fn navigation_type(&self, nav: &NavigationField) -> String {
+        match nav.cardinality {
+            NavigationCardinality::One => format!("Option<Box<{}>>", nav.model_reference),
+            NavigationCardinality::Many => format!("Vec<{}>", nav.model_reference),
+        }
+    }
+}
+
+impl LanguageTypeMapper for RustMapper {
+    fn cidl_type(&self, ty: &CidlType) -> String {
+        match ty {
+            CidlType::Json | CidlType::Partial { .. } => "serde_json::Value".to_string(),
+            CidlType::Int => "i64".to_string(),
+            CidlType::Real => "f64".to_string(),
+            CidlType::String | CidlType::DateIso => "String".to_string(),
+            CidlType::Boolean => "bool".to_string(),
+            CidlType::Blob => "Blob".to_string(),
+            CidlType::Stream => "Vec<u8>".to_string(),
+            CidlType::Object { name, .. } | CidlType::Enum { name } => name.to_string(),
+            CidlType::Nullable(inner) => format!("Option<{}>", self.cidl_type(inner)),
+            CidlType::Array(inner) => format!("Vec<{}>", self.cidl_type(inner)),
+            CidlType::Void => "()".to_string(),
+            CidlType::KvObject(inner) if matches!(**inner, CidlType::Stream) => {
+                "KValue<()>".to_string()
+            }
+            CidlType::KvObject(inner) => format!("KValue<{}>", self.cidl_type(inner)),
+            CidlType::Page(inner) => format!("Page<{}>", self.cidl_type(inner)),
+            CidlType::R2Object => "R2Object".to_string(),
+        }
+    }
+
+    fn media_type(&self, ty: &MediaType) -> String {
+        match ty {
+            MediaType::Json => "MediaType::Json".to_string(),
+            MediaType::Octet => "MediaType::Octet".to_string(),
+        }
+    }
+
+    fn interpolate_segments(&self, segments: &[TemplateSegment<&str>]) -> String {
+        let body = segments
+            .iter()
+            .map(|segment| match segment {
+                TemplateSegment::Literal(text) => self
+                    .escape_string(text.as_ref())
+                    .replace('{', "{{")
+                    .replace('}', "}}"),
+                TemplateSegment::Value(name) => format!("{{{}}}", self.ident(name)),
+            })
+            .collect::<String>();
+        format!("format!(\"{body}\")")
+    }
+
+    fn doc_block(&self, text: &str, indent: &str) -> String {
+        text.lines()
+            .map(|line| format!("{indent}/// {line}").trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn escape_string(&self, s: &str) -> String {
+        s.replace('\\', "\\\\").replace('"', "\\\"")
+    }
+}
+
+/// Maps to Python type hints for a standard library only client, where models and plain
+/// objects become dataclasses.
+///
+/// JSON values are converted to and from these types by codecs (see [PythonMapper::codec]),
+/// as dates and blobs have no JSON representation of their own.
+pub struct PythonMapper;
+
+impl PythonMapper {
+    /// Keywords and soft keywords that can't (or shouldn't) name a field or parameter.
+    const KEYWORDS: [&'static str; 38] = [
+        "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class",
+        "continue", "def", "del", "elif", "else", "except", "finally", "for", "from", "global",
+        "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return",
+        "try", "while", "with", "yield", "match", "case", "type",
+    ];
+
+    /// A schema name as a Python identifier, suffixed with `_` if it is a keyword.
+    pub fn ident(&self, name: &str) -> String {
+        if Self::KEYWORDS.contains(&name) {
+            format!("{name}_")
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// A schema name in `snake_case` (`getUser` -> `get_user`), as a Python identifier.
+    pub fn snake_case(&self, name: &str) -> String {
+        let mut out = String::with_capacity(name.len());
+        let mut prev_lower = false;
+        for c in name.chars() {
+            if c.is_uppercase() {
+                if prev_lower {
+                    out.push('_');
+                }
+                out.extend(c.to_lowercase());
+                prev_lower = false;
+            } else {
+                out.push(c);
+                prev_lower = c.is_lowercase() || c.is_ascii_digit();
+            }
+        }
+        self.ident(out.trim_start_matches('$'))
+    }
+
+    /// The codec converting a value of `ty` between its JSON form and its Python type.
+    pub fn codec(&self, ty: &CidlType) -> String {
+        match ty {
+            CidlType::DateIso => "_DATE".to_string(),
+            CidlType::Blob => "_BLOB".to_string(),
+            CidlType::Object { name, .. } => format!("_obj(\"{name}\")"),
+            CidlType::Nullable(inner) => format!("_opt({})", self.codec(inner)),
+            CidlType::Array(inner) => format!("_list({})", self.codec(inner)),
+            CidlType::KvObject(inner) if matches!(**inner, CidlType::Stream) => {
+                "_kv(_ANY)".to_string()
+            }
+            CidlType::KvObject(inner) => format!("_kv({})", self.codec(inner)),
+            CidlType::Page(inner) => format!("_page({})", self.codec(inner)),
+            CidlType::R2Object => "_obj(\"R2Object\")".to_string(),
+            CidlType::Void => "_VOID".to_string(),
+            _ => "_ANY".to_string(),
+        }
+    }
+
+    /// A navigation field's type hint and codec.
+    pub fn navigation_type(&self, nav: &NavigationField) -> (String, String) {
+        let object = CidlType::Object {
+            name: nav.model_reference,
+        };
+        match nav.cardinality {
+            NavigationCardinality::One => (
+                format!("Optional[{}]", self.cidl_type(&object)),
+                format!("_opt({})", self.codec(&object)),
+            ),
+            NavigationCardinality::Many => (
+                format!("list[{}]", self.cidl_type(&object)),
+                format!("_list({})", self.codec(&object)),
+            ),
+        }
+    }
+}
+
+impl LanguageTypeMapper for PythonMapper {
+    fn cidl_type(&self, ty: &CidlType) -> String {
+        match ty {
+            CidlType::Json => "Any".to_string(),
+            CidlType::Partial { .. } => "dict[str, Any]".to_string(),
+            CidlType::Int => "int".to_string(),
+            CidlType::Real => "float".to_string(),
+            CidlType::String => "str".to_string(),
+            CidlType::DateIso => "datetime".to_string(),
+            CidlType::Boolean => "bool".to_string(),
+            CidlType::Blob | CidlType::Stream => "bytes".to_string(),
+            CidlType::Object { name, .. } | CidlType::Enum { name } => name.to_string(),
+            CidlType::Nullable(inner) => format!("Optional[{}]", self.cidl_type(inner)),
+            CidlType::Array(inner) => format!("list[{}]", self.cidl_type(inner)),
+            CidlType::Void => "None".to_string(),
+            CidlType::KvObject(inner) if matches!(**inner, CidlType::Stream) => {
+                "KValue[None]".to_string()
+            }
+            CidlType::KvObject(inner) => format!("KValue[{}]", self.cidl_type(inner)),
+            CidlType::Page(inner) => format!("Page[{}]", self.cidl_type(inner)),
+            CidlType::R2Object => "R2Object".to_string(),
+        }
+    }
+
+    fn media_type(&self, ty: &MediaType) -> String {
+        match ty {
+            MediaType::Json => "\"json\"".to_string(),
+            MediaType::Octet => "\"octet\"".to_string(),
+        }
+    }
+
+    fn interpolate_segments(&self, segments: &[TemplateSegment<&str>]) -> String {
+        let body = segments
+            .iter()
+            .map(|segment| match segment {
+                TemplateSegment::Literal(text) => self
+                    .escape_string(text.as_ref())
+                    .replace('{', "{{")
+                    .replace('}', "}}"),
+                TemplateSegment::Value(name) => format!("{{{}}}", self.ident(name)),
+            })
+            .collect::<String>();
+        format!("f\"{body}\"")
+    }
+
+    fn doc_block(&self, text: &str, indent: &str) -> String {
+        text.replace("\"\"\"", "\\\"\\\"\\\"")
+            .lines()
+            .map(|line| format!("{indent}{line}").trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn escape_string(&self, s: &str) -> String {
+        s.replace('\\', "\\\\").replace('"', "\\\"")
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::BTreeMap;
+
+use idl::{ApiMethod, CidlType, CloesceIdl, HttpVerb, MediaType, Model, ParamSource};
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::schema::{Schema, SchemaBuilder, partial_name};
+
+const OPENAPI_VERSION: &str = "3.1.0";
+const SCHEMA_REF_PREFIX: &str = "#/components/schemas/";
+
+#[derive(Serialize)]
+struct OpenApi<'a> {
+    openapi: &'static str,
+    info: Info<'a>,
+    servers: Vec<Server<'a>>,
+    paths: IndexMap<String, IndexMap<&'static str, Operation>>,
+    components: Components,
+}
+
+#[derive(Serialize)]
+struct Info<'a> {
+    title: &'a str,
+    version: &'static str,
+}
+
+#[derive(Serialize)]
+struct Server<'a> {
+    url: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Operation {
+    operation_id: String,
+    tags: Vec<String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    parameters: Vec<Parameter>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_body: Option<Content>,
+
+    responses: IndexMap<&'static str, Response>,
+}
+
+#[derive(Serialize)]
+struct Parameter {
+    name: String,
+
+    #[serde(rename = "in")]
+    location: &'static str,
+
+    required: bool,
+    schema: Schema,
+}
+
+#[derive(Serialize)]
+struct Content {
+    required: bool,
+    content: IndexMap<&'static str, MediaTypeObject>,
+}
+
+#[derive(Serialize)]
+struct Response {
+    description: &'static str,
+
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    content: IndexMap<&'static str, MediaTypeObject>,
+}
+
+#[derive(Serialize)]
+struct MediaTypeObject {
+    schema: Schema,
+}
+
+#[derive(Serialize)]
+struct Components {
+    schemas: BTreeMap<String, Schema>,
+}
+
+fn media_type(media: &MediaType) -> &'static str {
+    match media {
+        MediaType::Json => "application/json",
+        MediaType::Octet => "application/octet-stream",
+    }
+}
+
+fn http_verb(verb: &HttpVerb) -> &'static str {
+    match verb {
+        HttpVerb::Get => "get",
+        HttpVerb::Post => "post",
+        HttpVerb::Put => "put",
+        HttpVerb::Patch => "patch",
+        HttpVerb::Delete => "delete",
+    }
+}
+
+/// Generates an OpenAPI 3.1 document describing every route the Cloesce router serves.
+///
+/// Models, plain old objects and enums become component schemas, and each [ApiMethod]
+/// (including generated CRUD methods) becomes a path.
+pub struct OpenApiGenerator;
+impl OpenApiGenerator {
+    pub fn generate(idl: &CloesceIdl, title: &str, worker_url: &str) -> String {
+        let mut builder = SchemaBuilder::new(SCHEMA_REF_PREFIX);
+
+        let mut paths = IndexMap::new();
+        for model in idl.models.values() {
+            for api in &model.apis {
+                // Methods with a custom route may share it under different verbs
+                let (path, operation) = Self::operation(&mut builder, model, api);
+                paths
+                    .entry(path)
+                    .or_insert_with(IndexMap::new)
+                    .insert(http_verb(&api.http_verb), operation);
+            }
+        }
+
+        let mut schemas = BTreeMap::new();
+        for model in idl.models.values() {
+            schemas.insert(model.name.to_string(), builder.model(model, false));
+        }
+        for poo in idl.poos.values() {
+            schemas.insert(poo.name.to_string(), builder.fields(&poo.fields, false));
+        }
+        for e in idl.enums.values() {
+            schemas.insert(e.name.to_string(), builder.enumeration(e));
+        }
+
+        // Partial components may reference further partials, so drain until settled.
+        let mut done = 0;
+        while let Some(&name) = builder.partials.get(done) {
+            done += 1;
+            let schema = match (idl.models.get(name), idl.poos.get(name)) {
+                (Some(model), _) => builder.model(model, true),
+                (_, Some(poo)) => builder.fields(&poo.fields, true),
+                _ => continue,
+            };
+            schemas.insert(partial_name(name), schema);
+        }
+
+        let document = OpenApi {
+            openapi: OPENAPI_VERSION,
+            info: Info {
+                title,
+                version: "1.0.0",
+            },
+            servers: vec![Server { url: worker_url }],
+            paths,
+            components: Components { schemas },
+        };
+        serde_json::to_string_pretty(&document).expect("OpenAPI document to serialize")
+    }
+
+    /// Maps an [ApiMethod] to its route and [Operation], mirroring how the router
+    /// reads each parameter: instance methods take their data source's `get` parameters
+    /// as path segments, GET requests take parameters from the query string, and every
+    /// other verb from the request body, unless tagged otherwise. A custom route replaces
+    /// the derived one, its `{variables}` already in OpenAPI's path template syntax.
+    fn operation<'a>(
+        builder: &mut SchemaBuilder<'a>,
+        model: &Model<'a>,
+        api: &ApiMethod<'a>,
+    ) -> (String, Operation) {
+        let mut path = match api.route {
+            Some(route) => format!("/{route}"),
+            None => format!("/{}", model.name),
+        };
+        let mut parameters = vec![];
+
+        let get_params = api
+            .data_source
+            .filter(|_| !api.is_static)
+            .and_then(|ds| model.data_sources.get(ds))
+            .map(|ds| ds.get.parameters.as_slice())
+            .unwrap_or_default();
+        for p in get_params {
+            path.push_str(&format!("/{{{}}}", p.parameter.name));
+            parameters.push(Parameter {
+                name: p.parameter.name.to_string(),
+                location: "path",
+                required: true,
+                schema: builder.field(&p.parameter, false),
+            });
+        }
+        if api.route.is_none() {
+            path.push_str(&format!("/{}", api.name));
+        }
+
+        let mut body = IndexMap::new();
+        let mut body_required = vec![];
+        for param in &api.parameters {
+            let location = match (param.source, api.http_verb) {
+                (ParamSource::Header, _) => "header",
+                (ParamSource::Query, _) => "query",
+                (ParamSource::Path, _) => "path",
+                (ParamSource::Body, HttpVerb::Get) => "query",
+                (ParamSource::Body, _) => {
+                    let name = param.field.name.to_string();
+                    if !param.field.cidl_type.is_nullable() {
+                        body_required.push(name.clone());
+                    }
+                    body.insert(name, builder.field(&param.field, false));
+                    continue;
+                }
+            };
+            parameters.push(Parameter {
+                name: param.field.name.to_string(),
+                location,
+                required: !param.field.cidl_type.is_nullable(),
+                schema: builder.field(&param.field, false),
+            });
+        }
+
+        let request_body = match api.parameters_media {
+            // A stream is always the only parameter, sent as the raw body
+            MediaType::Octet => Some(Content {
+                required: true,
+                content: IndexMap::from([(
+                    media_type(&MediaType::Octet),
+                    MediaTypeObject {
+                        schema: builder.schema(&CidlType::Stream, &[], false),
+                    },
+                )]),
+            }),
+            MediaType::Json if !body.is_empty() => Some(Content {
+                required: true,
+                content: IndexMap::from([(
+                    media_type(&MediaType::Json),
+                    MediaTypeObject {
+                        schema: Schema::object(body, body_required),
+                    },
+                )]),
+            }),
+            MediaType::Json => None,
+        };
+
+        let ok = Response {
+            description: "Success",
+            content: match api.return_type {
+                CidlType::Void => IndexMap::new(),
+                _ => IndexMap::from([(
+                    media_type(&api.return_media),
+                    MediaTypeObject {
+                        schema: builder.schema(&api.return_type, &[], false),
+                    },
+                )]),
+            },
+        };
+
+        // The router responds to any failure with a plain text message
+        let error = Response {
+            description: "Invalid request, unknown route, or server error",
+            content: IndexMap::from([(
+                "text/plain",
+                MediaTypeObject {
+                    schema: Schema::of("string"),
+                },
+            )]),
+        };
+
+        let operation = Operation {
+            operation_id: format!("{}.{}", model.name, api.name),
+            tags: vec![model.name.to_string()],
+            parameters,
+            request_body,
+            responses: IndexMap::from([("200", ok), ("default", error)]),
+        };
+        (path, operation)
+    }
+}
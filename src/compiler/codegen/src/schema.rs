@@ -0,0 +1,418 @@
+//! JSON Schema (2020-12) for [CidlType]s, shared by the OpenAPI and JSON Schema generators.
+
+use idl::{CidlType, Enum, Model, NavigationCardinality, Number, ValidatedField, Validator};
+use indexmap::IndexMap;
+use serde::Serialize;
+use serde_json::Value;
+
+/// The shape of an RFC 3339 date-time, as the runtime parses a `date`.
+const DATE_TIME_PATTERN: &str =
+    r"^\d{4}-\d{2}-\d{2}[Tt ]\d{2}:\d{2}:\d{2}(\.\d+)?([Zz]|[+-]\d{2}:\d{2})$";
+
+/// The shape of a standard, padded base64 string.
+const BASE64_PATTERN: &str = r"^([A-Za-z0-9+/]{4})*([A-Za-z0-9+/]{2}==|[A-Za-z0-9+/]{3}=)?$";
+
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(untagged)]
+pub(crate) enum SchemaType {
+    Single(&'static str),
+    Nullable([&'static str; 2]),
+}
+
+/// The subset of JSON Schema (2020-12) needed to describe a [CidlType].
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Schema {
+    #[serde(rename = "$ref", skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub ty: Option<SchemaType>,
+
+    #[serde(rename = "enum", skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<&'static str>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_encoding: Option<&'static str>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_media_type: Option<&'static str>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Box<Schema>>,
+
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    pub properties: IndexMap<String, Schema>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub additional_properties: Option<Box<Schema>>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub required: Vec<String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub any_of: Vec<Schema>,
+
+    // Validators
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<Value>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclusive_minimum: Option<Value>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<Value>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclusive_maximum: Option<Value>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multiple_of: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<usize>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+}
+
+impl Schema {
+    pub fn of(ty: &'static str) -> Self {
+        Self {
+            ty: Some(SchemaType::Single(ty)),
+            ..Default::default()
+        }
+    }
+
+    pub fn object(properties: IndexMap<String, Schema>, required: Vec<String>) -> Self {
+        Self {
+            properties,
+            required,
+            ..Self::of("object")
+        }
+    }
+
+    fn nullable(self) -> Self {
+        match self.ty {
+            Some(SchemaType::Single(ty)) => Self {
+                ty: Some(SchemaType::Nullable([ty, "null"])),
+                ..self
+            },
+            Some(SchemaType::Nullable(_)) => self,
+            _ => Self {
+                any_of: vec![self, Schema::of("null")],
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Maps each [Validator] to its JSON Schema keyword
+    fn with_validators(mut self, validators: &[Validator]) -> Self {
+        let number = |n: &Number| match n {
+            Number::Int(i) => Value::from(*i),
+            Number::Float(f) => Value::from(*f),
+        };
+
+        for validator in validators {
+            match validator {
+                Validator::GreaterThan(n) => self.exclusive_minimum = Some(number(n)),
+                Validator::GreaterThanOrEqual(n) => self.minimum = Some(number(n)),
+                Validator::LessThan(n) => self.exclusive_maximum = Some(number(n)),
+                Validator::LessThanOrEqual(n) => self.maximum = Some(number(n)),
+                Validator::Step(step) => self.multiple_of = Some(*step),
+                Validator::Length(len) => {
+                    self.min_length = Some(*len);
+                    self.max_length = Some(*len);
+                }
+                Validator::MinLength(len) => self.min_length = Some(*len),
+                Validator::MaxLength(len) => self.max_length = Some(*len),
+                Validator::Regex(pattern) => self.pattern = Some(pattern.to_string()),
+            }
+        }
+        self
+    }
+}
+
+/// Builds component schemas, tracking which objects are referenced as a `Partial`
+/// so their partial component can be generated after the fact.
+pub(crate) struct SchemaBuilder<'a> {
+    pub partials: Vec<&'a str>,
+
+    /// Prefix of a component reference, e.g. `#/components/schemas/`.
+    ref_prefix: &'static str,
+
+    /// Mirror `orm::validate::validate_cidl_type` exactly, rather than describing the canonical
+    /// shape of a value:
+    /// - array, `json` and partial fields may be missing
+    /// - every value of a partial may be null
+    /// - a KV object's `metadata` may be missing
+    /// - dates and blobs are checked by pattern (and a blob may be a byte array)
+    runtime: bool,
+}
+
+impl<'a> SchemaBuilder<'a> {
+    /// A builder describing the canonical shape of each value.
+    pub fn new(ref_prefix: &'static str) -> Self {
+        Self {
+            partials: vec![],
+            ref_prefix,
+            runtime: false,
+        }
+    }
+
+    /// A builder accepting exactly what the runtime validator accepts.
+    pub fn runtime(ref_prefix: &'static str) -> Self {
+        Self {
+            runtime: true,
+            ..Self::new(ref_prefix)
+        }
+    }
+
+    pub fn reference(&self, name: &str) -> Schema {
+        Schema {
+            reference: Some(format!("{}{name}", self.ref_prefix)),
+            ..Default::default()
+        }
+    }
+
+    /// Maps a [CidlType] to a [Schema], applying `validators` to the innermost value
+    /// (the runtime validates each element of an array, never the array itself).
+    ///
+    /// When `partial`, object references point to their partial component.
+    pub fn schema(&mut self, ty: &CidlType<'a>, validators: &[Validator], partial: bool) -> Schema {
+        let schema = match ty {
+            CidlType::Void | CidlType::Json => return Schema::default(),
+            CidlType::Int => Schema {
+                format: Some("int64"),
+                ..Schema::of("integer")
+            }
+            .with_validators(validators),
+            CidlType::Real => Schema {
+                format: Some("double"),
+                ..Schema::of("number")
+            }
+            .with_validators(validators),
+            CidlType::String => Schema::of("string").with_validators(validators),
+            CidlType::Boolean => Schema::of("boolean"),
+            CidlType::DateIso => Schema {
+                format: Some("date-time"),
+                pattern: self.runtime.then(|| DATE_TIME_PATTERN.to_string()),
+                ..Schema::of("string")
+            },
+            CidlType::Blob if self.runtime => Schema {
+                any_of: vec![
+                    Schema {
+                        content_encoding: Some("base64"),
+                        pattern: Some(BASE64_PATTERN.to_string()),
+                        ..Schema::of("string")
+                    },
+                    Schema {
+                        items: Some(Box::new(Schema {
+                            minimum: Some(0.into()),
+                            maximum: Some(255.into()),
+                            ..Schema::of("integer")
+                        })),
+                        ..Schema::of("array")
+                    },
+                ],
+                ..Default::default()
+            },
+            CidlType::Blob => Schema {
+                content_encoding: Some("base64"),
+                ..Schema::of("string")
+            },
+            CidlType::Stream => Schema {
+                content_media_type: Some("application/octet-stream"),
+                ..Schema::of("string")
+            },
+            CidlType::R2Object => r2_object(),
+            CidlType::Enum { name } => self.reference(name),
+            CidlType::Object { name } if partial => self.partial(name),
+            CidlType::Object { name } => self.reference(name),
+            CidlType::Partial { object_name } => self.partial(object_name),
+            CidlType::Array(inner) => Schema {
+                items: Some(Box::new(self.schema(inner, validators, partial))),
+                ..Schema::of("array")
+            },
+            CidlType::Nullable(inner) => self.schema(inner, validators, partial).nullable(),
+            CidlType::KvObject(inner) if self.runtime => {
+                let raw = self.schema(inner, validators, partial);
+                let metadata = match partial {
+                    true => Schema::default(),
+                    false => Schema {
+                        ty: Some(SchemaType::Nullable(["object", "null"])),
+                        ..Default::default()
+                    },
+                };
+                let required = match partial || !self.is_required(inner) {
+                    true => vec![],
+                    false => vec!["raw".into()],
+                };
+                Schema::object(
+                    IndexMap::from([("raw".into(), raw), ("metadata".into(), metadata)]),
+                    required,
+                )
+            }
+            CidlType::KvObject(inner) => {
+                let raw = self.schema(inner, validators, partial).nullable();
+                Schema::object(
+                    IndexMap::from([("raw".into(), raw), ("metadata".into(), Schema::default())]),
+                    vec!["raw".into(), "metadata".into()],
+                )
+            }
+            CidlType::Page(inner) => {
+                let results = Schema {
+                    items: Some(Box::new(self.schema(inner, validators, partial))),
+                    ..Schema::of("array")
+                };
+                let next_cursor = Schema::of("string").nullable();
+                Schema::object(
+                    IndexMap::from([
+                        ("results".into(), results),
+                        ("nextCursor".into(), next_cursor),
+                    ]),
+                    vec!["results".into(), "nextCursor".into()],
+                )
+            }
+        };
+
+        // The runtime accepts null anywhere within a partial
+        let partial = partial || matches!(ty, CidlType::Partial { .. });
+        match self.runtime && partial {
+            true => schema.nullable(),
+            false => schema,
+        }
+    }
+
+    /// True if a value of the type must be present.
+    fn is_required(&self, ty: &CidlType<'a>) -> bool {
+        !self.runtime
+            || !matches!(
+                ty,
+                CidlType::Array(_) | CidlType::Json | CidlType::Partial { .. }
+            )
+    }
+
+    pub fn field(&mut self, field: &ValidatedField<'a>, partial: bool) -> Schema {
+        self.schema(&field.cidl_type, &field.validators, partial)
+    }
+
+    fn partial(&mut self, name: &'a str) -> Schema {
+        if !self.partials.contains(&name) {
+            self.partials.push(name);
+        }
+        self.reference(&partial_name(name))
+    }
+
+    /// The component schema of a model. Every column and route field is present on an instance,
+    /// while navigation, KV and R2 fields are only present when hydrated.
+    pub fn model(&mut self, model: &Model<'a>, partial: bool) -> Schema {
+        let mut properties = IndexMap::new();
+        let mut required = vec![];
+
+        let columns = model.primary_columns.iter().chain(&model.columns);
+        for field in columns.map(|c| &c.field).chain(&model.route_fields) {
+            properties.insert(field.name.to_string(), self.field(field, partial));
+            if self.is_required(&field.cidl_type) {
+                required.push(field.name.to_string());
+            }
+        }
+
+        for nav in &model.navigation_fields {
+            let target = CidlType::Object {
+                name: nav.model_reference,
+            };
+            let ty = match nav.cardinality {
+                NavigationCardinality::One => target,
+                NavigationCardinality::Many => CidlType::array(target),
+            };
+            properties.insert(nav.field.name.to_string(), self.schema(&ty, &[], partial));
+        }
+
+        for kv in &model.kv_fields {
+            properties.insert(kv.field.name.to_string(), self.field(&kv.field, partial));
+        }
+
+        for r2 in &model.r2_fields {
+            let schema = self.schema(&r2.field.cidl_type, &[], partial);
+            properties.insert(r2.field.name.to_string(), schema);
+        }
+
+        if partial {
+            required.clear();
+        }
+        Schema::object(properties, required)
+    }
+
+    pub fn fields(&mut self, fields: &[ValidatedField<'a>], partial: bool) -> Schema {
+        let properties = fields
+            .iter()
+            .map(|f| (f.name.to_string(), self.field(f, partial)))
+            .collect();
+        let required = match partial {
+            true => vec![],
+            false => fields
+                .iter()
+                .filter(|f| self.is_required(&f.cidl_type))
+                .map(|f| f.name.to_string())
+                .collect(),
+        };
+        Schema::object(properties, required)
+    }
+
+    pub fn enumeration(&self, e: &Enum<'a>) -> Schema {
+        Schema {
+            members: e.members.iter().map(|m| m.to_string()).collect(),
+            ..Schema::of("string")
+        }
+    }
+}
+
+/// Suffix of the component name of an object's partial, e.g. `UserPartial`.
+pub(crate) const PARTIAL_SUFFIX: &str = "Partial";
+
+pub(crate) fn partial_name(name: &str) -> String {
+    format!("{name}{PARTIAL_SUFFIX}")
+}
+
+/// The client representation of an R2 object, as serialized by the router
+fn r2_object() -> Schema {
+    let string = || Schema::of("string");
+    let properties = IndexMap::from([
+        ("key".into(), string()),
+        ("version".into(), string()),
+        (
+            "size".into(),
+            Schema {
+                format: Some("int64"),
+                ..Schema::of("integer")
+            },
+        ),
+        ("etag".into(), string()),
+        ("httpEtag".into(), string()),
+        (
+            "uploaded".into(),
+            Schema {
+                format: Some("date-time"),
+                ..string()
+            },
+        ),
+        (
+            "customMetadata".into(),
+            Schema {
+                additional_properties: Some(Box::new(string())),
+                ..Schema::of("object")
+            },
+        ),
+    ]);
+    let required = ["key", "version", "size", "etag", "httpEtag", "uploaded"]
+        .map(String::from)
+        .to_vec();
+    Schema::object(properties, required)
+}
@@ -0,0 +1,25 @@
+use idl::CloesceIdl;
+
+pub struct DevVarsGenerator;
+impl DevVarsGenerator {
+    /// Lists every secret declared in the schema as a `.dev.vars.example` file, or `None`
+    /// when there are no secrets.
+    ///
+    /// Secrets are never written to the Wrangler config; Wrangler reads their local values
+    /// from `.dev.vars`, and deployed values are set with `wrangler secret put`.
+    pub fn generate(idl: &CloesceIdl) -> Option<String> {
+        if idl.wrangler_env.secrets.is_empty() {
+            return None;
+        }
+
+        let mut out = String::from(
+            "# Secrets declared in the Cloesce schema.\n\
+             # Copy to .dev.vars and fill in values for local development.\n",
+        );
+        for secret in &idl.wrangler_env.secrets {
+            out.push_str(&secret.name);
+            out.push_str("=\n");
+        }
+        Some(out)
+    }
+}
@@ -3,9 +3,11 @@ use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+mod dev_vars;
 mod migrations;
 mod spec;
 
+pub use dev_vars::DevVarsGenerator;
 pub use migrations::DurableMigrationGenerator;
 pub use spec::{WranglerDefault, WranglerGenerator};
 
@@ -41,6 +43,44 @@ pub struct DurableObjects {
     pub bindings: Vec<DurableObjectBinding>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct QueueProducer {
+    pub binding: Option<String>,
+    pub queue: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct QueueConsumer {
+    pub queue: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Queues {
+    #[serde(default)]
+    pub producers: Vec<QueueProducer>,
+
+    #[serde(default)]
+    pub consumers: Vec<QueueConsumer>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Service {
+    pub binding: Option<String>,
+    pub service: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Triggers {
+    #[serde(default)]
+    pub crons: Vec<String>,
+}
+
+/// A custom build, run by wrangler before bundling `main`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Build {
+    pub command: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct RenamedClass {
     pub from: String,
@@ -67,6 +107,9 @@ pub struct WranglerSpec {
     pub compatibility_date: Option<String>,
     pub main: Option<String>,
 
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build: Option<Build>,
+
     #[serde(default)]
     pub d1_databases: Vec<D1Database>,
 
@@ -82,6 +125,15 @@ pub struct WranglerSpec {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub migrations: Vec<DurableObjectMigration>,
 
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queues: Option<Queues>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub services: Vec<Service>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub triggers: Option<Triggers>,
+
     #[serde(default)]
     pub vars: HashMap<String, Value>,
 }
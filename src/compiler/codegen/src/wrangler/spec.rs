@@ -4,8 +4,8 @@ use idl::{CidlType, CloesceIdl};
 use serde_json::{Map, Value as JsonValue};
 
 use crate::wrangler::{
-    D1Database, DurableObjectBinding, DurableObjectMigration, DurableObjects, KVNamespace,
-    R2Bucket, WranglerSpec,
+    Build, D1Database, DurableObjectBinding, DurableObjectMigration, DurableObjects, KVNamespace,
+    QueueConsumer, QueueProducer, Queues, R2Bucket, Service, Triggers, WranglerSpec,
 };
 
 #[derive(Clone, Copy)]
@@ -115,6 +115,13 @@ impl WranglerGenerator {
             self.insert("main", main.clone());
         }
 
+        if let Some(build) = &spec.build {
+            self.insert(
+                "build",
+                serde_json::to_value(build).expect("JSON to serialize"),
+            );
+        }
+
         Self::write_bindings(self.root_object(), &spec, env);
 
         match self.format {
@@ -210,6 +217,63 @@ impl WranglerGenerator {
             );
         }
 
+        if let Some(queues) = &spec.queues {
+            let target = entry_object(target, "queues", "queues must be an object");
+            if !queues.producers.is_empty() {
+                merge_into_array(
+                    target,
+                    "producers",
+                    "binding",
+                    &queues.producers,
+                    |producer| producer.binding.as_deref(),
+                    |producer, existing| {
+                        if let Some(queue) = &producer.queue {
+                            existing.insert("queue".into(), queue.clone().into());
+                        }
+                    },
+                );
+            }
+            if !queues.consumers.is_empty() {
+                merge_into_array(
+                    target,
+                    "consumers",
+                    "queue",
+                    &queues.consumers,
+                    |consumer| consumer.queue.as_deref(),
+                    |_, _| {},
+                );
+            }
+        }
+
+        if !spec.services.is_empty() {
+            merge_into_array(
+                target,
+                "services",
+                "binding",
+                &spec.services,
+                |service| service.binding.as_deref(),
+                |service, existing| {
+                    if let Some(name) = &service.service {
+                        existing.insert("service".into(), name.clone().into());
+                    }
+                },
+            );
+        }
+
+        // Crons are replaced rather than merged, so one dropped from the schema is removed.
+        let has_crons = target
+            .get("triggers")
+            .is_some_and(|t| t.get("crons").is_some());
+        if let Some(triggers) = &spec.triggers
+            && (has_crons || !triggers.crons.is_empty())
+        {
+            let target = entry_object(target, "triggers", "triggers must be an object");
+            target.insert(
+                "crons".into(),
+                JsonValue::Array(triggers.crons.iter().cloned().map(Into::into).collect()),
+            );
+        }
+
         if !spec.migrations.is_empty() {
             target.insert(
                 "migrations".into(),
@@ -258,6 +322,12 @@ impl WranglerGenerator {
         Ok(serde_json::from_value(JsonValue::Object(merged))?)
     }
 
+    /// Writes the bindings of `spec` into the `env.<name>` section, leaving the rest of the
+    /// config as is.
+    pub fn write_env(&mut self, spec: &WranglerSpec, env: &str) {
+        Self::write_bindings(self.root_object(), spec, Some(env));
+    }
+
     pub fn insert(&mut self, key: &str, value: impl Into<JsonValue>) {
         self.root_object().insert(key.to_string(), value.into());
     }
@@ -265,9 +335,34 @@ impl WranglerGenerator {
 
 pub struct WranglerDefault;
 impl WranglerDefault {
+    /// Points wrangler at the worker `worker-build` bundles from the generated Rust backend,
+    /// unless the config already sets `main` or a build command. Call before [Self::set_defaults].
+    pub fn set_rust_defaults(spec: &mut WranglerSpec) {
+        if spec.main.is_none() {
+            spec.main = Some("build/worker/shim.mjs".to_string());
+        }
+
+        let build = spec.build.get_or_insert_with(Build::default);
+        if build.command.is_none() {
+            build.command = Some("cargo install -q worker-build && worker-build --release".into());
+        }
+    }
+
     /// Ensures that all required values exist or places a default
     /// for them
-    pub fn set_defaults(spec: &mut WranglerSpec, idl: &CloesceIdl, default_migrations_path: &str) {
+    ///
+    /// When `env` names an environment declared in the schema, its d1 bindings and vars are
+    /// used in place of the top level ones.
+    pub fn set_defaults(
+        spec: &mut WranglerSpec,
+        idl: &CloesceIdl,
+        env: Option<&str>,
+        default_migrations_path: &str,
+    ) {
+        let overlay = env
+            .and_then(|env| idl.envs.get(env))
+            .unwrap_or(&idl.wrangler_env);
+
         let default_migrations_path = default_migrations_path
             .trim_end_matches('/')
             .trim_end_matches('\\');
@@ -295,7 +390,8 @@ impl WranglerDefault {
         );
 
         // Ensure all bindings referenced in the WranglerEnv exist in the spec
-        for d1 in &idl.wrangler_env.d1_bindings {
+        for d1 in &overlay.d1_bindings {
+            let declared_name = overlay.d1_databases.get(d1).map(|name| name.to_string());
             let db = spec
                 .d1_databases
                 .iter_mut()
@@ -303,6 +399,9 @@ impl WranglerDefault {
 
             match db {
                 Some(db) => {
+                    if declared_name.is_some() {
+                        db.database_name = declared_name;
+                    }
                     if db.database_id.is_none() {
                         db.database_id = Some(format!("replace_with_{}_id", d1));
                         tracing::warn!(
@@ -330,7 +429,9 @@ impl WranglerDefault {
                 None => {
                     spec.d1_databases.push(D1Database {
                         binding: Some(d1.to_string()),
-                        database_name: Some(format!("replace_with_{}_name", d1)),
+                        database_name: Some(
+                            declared_name.unwrap_or_else(|| format!("replace_with_{}_name", d1)),
+                        ),
                         database_id: Some(format!("replace_with_{}_id", d1)),
                         migrations_dir: Some(format!("{}/{}", default_migrations_path, d1)),
                     });
@@ -432,6 +533,96 @@ impl WranglerDefault {
             }
         }
 
+        for queue_binding in &idl.wrangler_env.queue_bindings {
+            let name = queue_binding.name;
+            let queues = spec.queues.get_or_insert_with(Queues::default);
+
+            let existing = queues
+                .producers
+                .iter_mut()
+                .find(|p| p.binding.as_deref() == Some(name));
+
+            let queue = match existing {
+                Some(producer) => producer
+                    .queue
+                    .get_or_insert_with(|| {
+                        tracing::warn!(
+                            "Queue producer with binding {} is missing a queue name. See https://developers.cloudflare.com/queues/get-started/",
+                            name
+                        );
+                        format!("replace-with-{}-queue", name).to_lowercase()
+                    })
+                    .clone(),
+                None => {
+                    let queue = format!("replace-with-{}-queue", name).to_lowercase();
+                    queues.producers.push(QueueProducer {
+                        binding: Some(name.to_string()),
+                        queue: Some(queue.clone()),
+                    });
+
+                    tracing::warn!(
+                        "Queue producer with binding {} was missing, added a default. See https://developers.cloudflare.com/queues/get-started/",
+                        name
+                    );
+                    queue
+                }
+            };
+
+            if !queues
+                .consumers
+                .iter()
+                .any(|c| c.queue.as_deref() == Some(queue.as_str()))
+            {
+                queues.consumers.push(QueueConsumer { queue: Some(queue) });
+            }
+        }
+
+        for service_binding in &idl.wrangler_env.service_bindings {
+            let name = service_binding.name;
+            let existing = spec
+                .services
+                .iter_mut()
+                .find(|s| s.binding.as_deref() == Some(name));
+
+            match existing {
+                Some(service) => {
+                    if service.service.is_none() {
+                        service.service =
+                            Some(format!("replace-with-{}-service", name).to_lowercase());
+                        tracing::warn!(
+                            "Service binding {} is missing a service name. See https://developers.cloudflare.com/workers/runtime-apis/bindings/service-bindings/",
+                            name
+                        );
+                    }
+                }
+                None => {
+                    spec.services.push(Service {
+                        binding: Some(name.to_string()),
+                        service: Some(format!("replace-with-{}-service", name).to_lowercase()),
+                    });
+
+                    tracing::warn!(
+                        "Service binding {} was missing, added a default. See https://developers.cloudflare.com/workers/runtime-apis/bindings/service-bindings/",
+                        name
+                    );
+                }
+            }
+        }
+
+        // Cron Triggers mirror the schema: a cron no longer declared would fire into a
+        // Worker with no handler for it, so it is dropped.
+        if let Some(triggers) = &mut spec.triggers {
+            triggers
+                .crons
+                .retain(|c| idl.crons.iter().any(|cron| cron.expression == c));
+        }
+        for cron in &idl.crons {
+            let triggers = spec.triggers.get_or_insert_with(Triggers::default);
+            if !triggers.crons.iter().any(|c| c == cron.expression) {
+                triggers.crons.push(cron.expression.to_string());
+            }
+        }
+
         // Wrangler DO class migrations: fold the existing `[[migrations]]` entries into
         // the set of live classes, then register any schema DO binding not yet covered
         // under a new tag. Renames and deletions are destructive and left to the
@@ -480,7 +671,7 @@ impl WranglerDefault {
         }
 
         // Generate default vars from the IDL's WranglerEnv
-        for var in &idl.wrangler_env.vars {
+        for var in &overlay.vars {
             spec.vars.entry(var.name.to_string()).or_insert_with(|| {
                 let default = match var.cidl_type {
                     CidlType::String => "default_string",
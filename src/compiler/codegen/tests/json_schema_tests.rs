@@ -0,0 +1,349 @@
+use std::sync::LazyLock;
+
+use base64::{Engine, prelude::BASE64_STANDARD};
+use codegen::json_schema::JsonSchemaGenerator;
+use compiler_test::{COMPREHENSIVE_SRC, src_to_idl};
+use idl::{CidlType, CloesceIdl, ValidatedField};
+use orm::validate::validate_cidl_type;
+use proptest::{collection::vec, option, prelude::*, sample::select};
+use serde_json::{Map, Value, json};
+
+const SRC: &str = r#"
+d1 { db }
+
+enum Color {
+    Red,
+    Green,
+}
+
+poo Address {
+    [minlen 2]
+    [maxlen 8]
+    city: string
+
+    [regex /^[0-9]+$/]
+    zip: option<string>
+
+    floors: array<int>
+    extra: json
+    color: Color
+}
+
+model Person for db {
+    primary {
+        [gt 0]
+        id: int
+    }
+
+    column {
+        [gt 0.5]
+        [lte 9.5]
+        ratio: real
+
+        [len 3]
+        code: string
+
+        [gte 2]
+        [step 2]
+        rank: option<int>
+
+        born: date
+        photo: blob
+        active: bool
+    }
+}
+
+api Person {
+    post update -> bool {
+        person: partial<Person>
+        address: Address
+        tags: array<option<string>>
+        note: kvobject<Address>
+        home: option<Address>
+    }
+}
+"#;
+
+#[test]
+fn json_schema_generation_snapshot() {
+    let idl = src_to_idl(SRC);
+    let documents = JsonSchemaGenerator::generate(&idl);
+
+    assert_eq!(
+        documents.keys().collect::<Vec<_>>(),
+        ["Address", "Person", "Person.update"]
+    );
+    insta::assert_snapshot!(documents["Person.update"]);
+}
+
+#[test]
+fn generates_a_document_per_model_poo_and_parameter_set() {
+    let idl = src_to_idl(COMPREHENSIVE_SRC);
+    let documents = JsonSchemaGenerator::generate(&idl);
+
+    for name in idl.models.keys().chain(idl.poos.keys()) {
+        assert!(documents.contains_key(*name), "missing document for {name}");
+    }
+
+    // A stream is the raw body, so a method taking only a stream has no document
+    assert!(documents.contains_key("BasicService.instanceMethod"));
+    assert!(!documents.contains_key("BasicService.uploadData"));
+    assert!(!documents.contains_key("BasicService.downloadData"));
+
+    for (name, document) in &documents {
+        let document: Value = serde_json::from_str(document).unwrap();
+        assert_eq!(document["title"], json!(name));
+        assert_refs_resolve(&document, &document);
+        if let Err(e) = jsonschema::draft202012::meta::validate(&document) {
+            panic!("{name} is not a valid JSON Schema 2020-12 document: {e}");
+        }
+    }
+}
+
+static IDL: LazyLock<CloesceIdl<'static>> = LazyLock::new(|| src_to_idl(SRC));
+
+proptest! {
+    #[test]
+    fn schema_agrees_with_runtime_validation(
+        (address, person, params) in any::<bool>().prop_flat_map(|anomalies| (
+            value(&CidlType::Object { name: "Address" }, anomalies, 0),
+            value(&CidlType::Object { name: "Person" }, anomalies, 0),
+            parameters("Person", "update", anomalies),
+        ))
+    ) {
+        let idl = &*IDL;
+        let documents = JsonSchemaGenerator::generate(idl);
+
+        // Models and plain old objects, validated as a value of their type
+        for (name, value) in [("Address", address), ("Person", person)] {
+            let document: Value = serde_json::from_str(&documents[name]).unwrap();
+            let ty = CidlType::Object { name };
+
+            let field = field("value", ty);
+            let runtime = validate_cidl_type(&field, value.clone(), idl, false).is_ok();
+            let schema = value.as_ref().is_some_and(|v| accepts(&document, &document, v));
+            prop_assert_eq!(schema, runtime, "{}: {:?}", name, value);
+
+            // Field by field too, so a rejection elsewhere can't mask a disagreement
+            if let Some(Value::Object(obj)) = &value {
+                let component = &document["$defs"][name];
+                for f in fields_of(idl, name) {
+                    agrees_on_field(idl, &document, component, f, obj)?;
+                }
+            }
+        }
+
+        // An API method's parameters, each validated separately
+        let document: Value = serde_json::from_str(&documents["Person.update"]).unwrap();
+        let api = idl.models["Person"].apis.iter().find(|a| a.name == "update").unwrap();
+        for p in &api.parameters {
+            agrees_on_field(idl, &document, &document, &p.field, &params)?;
+        }
+        let runtime = api.parameters.iter().all(|p| {
+            let value = params.get(p.field.name.as_ref()).cloned();
+            validate_cidl_type(&p.field, value, idl, false).is_ok()
+        });
+        let schema = accepts(&document, &document, &Value::Object(params.clone()));
+        prop_assert_eq!(schema, runtime, "{:?}", Value::Object(params));
+    }
+}
+
+/// Checks the runtime and the object schema `schema` agree on one field of `obj`.
+fn agrees_on_field(
+    idl: &CloesceIdl,
+    document: &Value,
+    schema: &Value,
+    field: &ValidatedField,
+    obj: &Map<String, Value>,
+) -> Result<(), TestCaseError> {
+    let name = field.name.as_ref();
+    let value = obj.get(name);
+    let runtime = validate_cidl_type(field, value.cloned(), idl, false).is_ok();
+    let schema = match value {
+        Some(v) => accepts(document, &schema["properties"][name], v),
+        None => !schema["required"]
+            .as_array()
+            .is_some_and(|r| r.contains(&json!(name))),
+    };
+    prop_assert_eq!(schema, runtime, "{}: {:?}", name, value);
+    Ok(())
+}
+
+/// Validates `value` against `schema` with a JSON Schema 2020-12 validator, resolving
+/// `$ref`s against the `$defs` of `document`. Annotations (`format`, `contentEncoding`, ...)
+/// are not asserted.
+fn accepts(document: &Value, schema: &Value, value: &Value) -> bool {
+    let mut schema = schema.clone();
+    if let (Value::Object(obj), Some(defs)) = (&mut schema, document.get("$defs")) {
+        obj.insert("$defs".into(), defs.clone());
+    }
+    jsonschema::draft202012::new(&schema)
+        .expect("schema to compile")
+        .is_valid(value)
+}
+
+fn fields_of<'a>(idl: &'a CloesceIdl<'a>, name: &str) -> Vec<&'a ValidatedField<'a>> {
+    match (idl.models.get(name), idl.poos.get(name)) {
+        (Some(model), _) => model.all_columns().map(|(c, _)| &c.field).collect(),
+        (_, Some(poo)) => poo.fields.iter().collect(),
+        _ => unreachable!(),
+    }
+}
+
+fn field<'a>(name: &'a str, cidl_type: CidlType<'a>) -> ValidatedField<'a> {
+    ValidatedField {
+        name: name.into(),
+        cidl_type,
+        validators: vec![],
+    }
+}
+
+fn assert_refs_resolve(document: &Value, value: &Value) {
+    match value {
+        Value::Object(obj) => {
+            if let Some(Value::String(r)) = obj.get("$ref") {
+                let name = r.strip_prefix("#/$defs/").unwrap();
+                assert!(document["$defs"].get(name).is_some(), "unresolved {r}");
+            }
+            obj.values().for_each(|v| assert_refs_resolve(document, v));
+        }
+        Value::Array(items) => items.iter().for_each(|v| assert_refs_resolve(document, v)),
+        _ => {}
+    }
+}
+
+/// The parameters of an API method, each from [value].
+fn parameters(
+    model: &str,
+    method: &str,
+    anomalies: bool,
+) -> impl Strategy<Value = Map<String, Value>> {
+    let api = IDL.models[model]
+        .apis
+        .iter()
+        .find(|a| a.name == method)
+        .unwrap();
+    object(
+        api.parameters.iter().map(|p| &p.field).collect(),
+        anomalies,
+        0,
+    )
+}
+
+/// An object of `fields`, each from [value], leaving out those it gives none for.
+fn object(
+    fields: Vec<&ValidatedField<'static>>,
+    anomalies: bool,
+    depth: u32,
+) -> impl Strategy<Value = Map<String, Value>> + use<> {
+    let names: Vec<String> = fields.iter().map(|f| f.name.to_string()).collect();
+    let values: Vec<_> = fields
+        .iter()
+        .map(|f| value(&f.cidl_type, anomalies, depth))
+        .collect();
+    values.prop_map(move |values| {
+        names
+            .iter()
+            .zip(values)
+            .filter_map(|(name, v)| Some((name.clone(), v?)))
+            .collect()
+    })
+}
+
+/// Values around a [CidlType]: well-typed, or with `anomalies` sometimes missing (None),
+/// null, or of the wrong type. Numbers and strings stay small, around the bounds the
+/// fixture's validators set.
+///
+/// It leaves out the string forms the runtime coerces (`"1"`, `"true"`, `"null"`), which the
+/// schema deliberately doesn't describe.
+fn value(ty: &CidlType<'static>, anomalies: bool, depth: u32) -> BoxedStrategy<Option<Value>> {
+    let typed = typed_value(ty, anomalies, depth);
+    if !anomalies {
+        return typed;
+    }
+
+    let wrong = select(vec![
+        json!(true),
+        json!(0.5),
+        json!("x-y"),
+        json!({}),
+        json!(7),
+    ]);
+    prop_oneof![
+        17 => typed,
+        1 => Just(None),
+        1 => Just(Some(Value::Null)),
+        1 => wrong.prop_map(Some),
+    ]
+    .boxed()
+}
+
+fn typed_value(
+    ty: &CidlType<'static>,
+    anomalies: bool,
+    depth: u32,
+) -> BoxedStrategy<Option<Value>> {
+    let small_int = || -10i64..=10;
+    let strategy: BoxedStrategy<Value> = match ty {
+        CidlType::Int => small_int().prop_map(|n| json!(n)).boxed(),
+        CidlType::Real => prop_oneof![
+            small_int().prop_map(|n| json!(n)),
+            small_int().prop_map(|n| json!(n as f64 - 0.25)),
+        ]
+        .boxed(),
+        CidlType::String => vec(select(vec!['a', 'b', '1', '9', ' ']), 0..6)
+            .prop_map(|chars| json!(String::from_iter(chars)))
+            .boxed(),
+        CidlType::Boolean => any::<bool>().prop_map(|b| json!(b)).boxed(),
+        CidlType::DateIso => select(vec![
+            "2024-01-15T10:30:00Z",
+            "2024-01-15 10:30:00.123+05:30",
+            "not-a-date",
+        ])
+        .prop_map(|d| json!(d))
+        .boxed(),
+        CidlType::Blob => prop_oneof![
+            vec(any::<u8>(), 0..6).prop_map(|bytes| json!(BASE64_STANDARD.encode(bytes))),
+            vec(0u16..300, 0..4).prop_map(|bytes| json!(bytes)),
+        ]
+        .boxed(),
+        CidlType::Enum { .. } => select(vec!["Red", "Green", "Blue"])
+            .prop_map(|m| json!(m))
+            .boxed(),
+        CidlType::Json => select(vec![json!(null), json!(1), json!("s"), json!({"a": []})]).boxed(),
+        CidlType::Nullable(inner) => {
+            return prop_oneof![
+                1 => Just(Some(Value::Null)),
+                3 => value(inner, anomalies, depth),
+            ]
+            .boxed();
+        }
+        CidlType::Array(inner) => vec(value(inner, anomalies, depth), 0..4)
+            .prop_map(|items| Value::Array(items.into_iter().flatten().collect()))
+            .boxed(),
+        CidlType::KvObject(inner) => (
+            value(inner, anomalies, depth),
+            option::of(select(vec![json!(null), json!({}), json!(5)])),
+        )
+            .prop_map(|(raw, metadata)| {
+                let mut obj = Map::new();
+                if let Some(raw) = raw {
+                    obj.insert("raw".into(), raw);
+                }
+                if let Some(metadata) = metadata {
+                    obj.insert("metadata".into(), metadata);
+                }
+                Value::Object(obj)
+            })
+            .boxed(),
+        // Keep nesting shallow
+        CidlType::Object { .. } | CidlType::Partial { .. } if depth > 2 => Just(json!({})).boxed(),
+        CidlType::Object { name } | CidlType::Partial { object_name: name } => {
+            object(fields_of(&IDL, name), anomalies, depth + 1)
+                .prop_map(Value::Object)
+                .boxed()
+        }
+        other => unreachable!("no values generated for {other:?}"),
+    };
+    strategy.prop_map(Some).boxed()
+}
@@ -0,0 +1,162 @@
+use codegen::openapi::OpenApiGenerator;
+use compiler_test::src_to_idl;
+use serde_json::{Value, json};
+
+fn generate(src: &str) -> Value {
+    let idl = src_to_idl(src);
+    serde_json::from_str(&OpenApiGenerator::generate(
+        &idl,
+        "Test",
+        "http://localhost/api",
+    ))
+    .unwrap()
+}
+
+#[test]
+fn validators_map_to_json_schema_keywords() {
+    let doc = generate(
+        r#"
+        poo Signup {
+            [minlen 3]
+            [maxlen 20]
+            [regex /^[a-z]+$/]
+            username: string
+
+            [len 2]
+            country: string
+
+            [gt 0]
+            [lte 120]
+            age: int
+
+            [gte 0.5]
+            [lt 10.5]
+            [step 2]
+            tags: array<int>
+
+            nickname: option<string>
+        }
+
+        model Account {}
+        api Account {
+            post signup -> Signup {
+                form: Signup
+            }
+        }
+        "#,
+    );
+
+    let signup = &doc["components"]["schemas"]["Signup"];
+    assert_eq!(
+        signup["properties"]["username"],
+        json!({ "type": "string", "minLength": 3, "maxLength": 20, "pattern": "^[a-z]+$" })
+    );
+    assert_eq!(
+        signup["properties"]["country"],
+        json!({ "type": "string", "minLength": 2, "maxLength": 2 })
+    );
+    assert_eq!(
+        signup["properties"]["age"],
+        json!({ "type": "integer", "format": "int64", "exclusiveMinimum": 0, "maximum": 120 })
+    );
+
+    // Validators apply to each element of an array
+    assert_eq!(
+        signup["properties"]["tags"],
+        json!({
+            "type": "array",
+            "items": {
+                "type": "integer",
+                "format": "int64",
+                "minimum": 0.5,
+                "exclusiveMaximum": 10.5,
+                "multipleOf": 2
+            }
+        })
+    );
+    assert_eq!(
+        signup["properties"]["nickname"],
+        json!({ "type": ["string", "null"] })
+    );
+
+    let operation = &doc["paths"]["/Account/signup"]["post"];
+    assert_eq!(
+        operation["requestBody"]["content"]["application/json"]["schema"],
+        json!({
+            "type": "object",
+            "properties": { "form": { "$ref": "#/components/schemas/Signup" } },
+            "required": ["form"]
+        })
+    );
+}
+
+#[test]
+fn crud_routes_map_to_paths() {
+    let doc = generate(
+        r#"
+        d1 { db }
+
+        [crud get, list, save, delete]
+        model Dog for db {
+            primary {
+                id: int
+            }
+
+            column {
+                name: string
+            }
+        }
+        "#,
+    );
+
+    let paths = doc["paths"].as_object().unwrap();
+    let mut routes = paths
+        .iter()
+        .flat_map(|(path, item)| {
+            item.as_object()
+                .unwrap()
+                .keys()
+                .map(move |v| format!("{v} {path}"))
+        })
+        .collect::<Vec<_>>();
+    routes.sort();
+    assert_eq!(
+        routes,
+        vec![
+            "delete /Dog/$delete",
+            "get /Dog/$get",
+            "get /Dog/$list",
+            "post /Dog/$save",
+        ]
+    );
+
+    // GET parameters are read from the query string
+    let get = &paths["/Dog/$get"]["get"];
+    assert_eq!(
+        get["parameters"],
+        json!([{ "name": "id", "in": "query", "required": true, "schema": { "type": "integer", "format": "int64" } }])
+    );
+    assert_eq!(
+        get["responses"]["200"]["content"]["application/json"]["schema"],
+        json!({ "$ref": "#/components/schemas/Dog" })
+    );
+
+    // Save accepts a deep partial of the model
+    let save = &paths["/Dog/$save"]["post"];
+    assert_eq!(
+        save["requestBody"]["content"]["application/json"]["schema"]["properties"]["model"],
+        json!({ "$ref": "#/components/schemas/DogPartial" })
+    );
+    let partial = &doc["components"]["schemas"]["DogPartial"];
+    assert_eq!(partial["required"], Value::Null);
+    assert_eq!(partial["properties"]["name"], json!({ "type": "string" }));
+
+    // Delete returns no content
+    let delete = &paths["/Dog/$delete"]["delete"];
+    assert_eq!(delete["responses"]["200"].get("content"), None);
+
+    assert_eq!(
+        doc["components"]["schemas"]["Dog"]["required"],
+        json!(["id", "name"])
+    );
+}
@@ -0,0 +1,57 @@
+use std::{fs, path::Path, process::Command};
+
+use codegen::{backend::RustBackendGenerator, client::RustClientGenerator};
+use compiler_test::{COMPREHENSIVE_SRC, src_to_idl};
+
+const WORKERS_URL: &str = "http://example.com/path/to/api";
+
+const MANIFEST: &str = r#"
+[package]
+name = "generated"
+version = "0.0.0"
+edition = "2024"
+publish = false
+
+[workspace]
+
+[dependencies]
+serde = { version = "1", features = ["derive"] }
+serde_json = "1"
+worker = "0.6"
+"#;
+
+#[test]
+fn generated_rust_compiles() {
+    // Arrange
+    let idl = src_to_idl(COMPREHENSIVE_SRC);
+    let backend = RustBackendGenerator::generate(&idl, WORKERS_URL);
+    let client = RustClientGenerator::generate(&idl, WORKERS_URL);
+
+    // The crate lives in the target dir so its dependencies stay built between runs
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("generated_rust");
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join("Cargo.toml"), MANIFEST).unwrap();
+    fs::write(
+        dir.join("src/lib.rs"),
+        "pub mod backend;\npub mod client;\n",
+    )
+    .unwrap();
+    fs::write(dir.join("src/backend.rs"), backend).unwrap();
+    fs::write(dir.join("src/client.rs"), client).unwrap();
+
+    // Act
+    let output = Command::new(env!("CARGO"))
+        .arg("check")
+        .arg("--quiet")
+        .current_dir(&dir)
+        .env_remove("RUSTFLAGS")
+        .output()
+        .expect("cargo to run");
+
+    // Assert
+    assert!(
+        output.status.success(),
+        "generated Rust failed to compile:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -1,7 +1,11 @@
 use codegen::{
-    backend::BackendGenerator, client::ClientGenerator, wrangler::DurableMigrationGenerator,
+    backend::{BackendGenerator, RustBackendGenerator},
+    client::{ClientGenerator, PythonClientGenerator, RustClientGenerator},
+    openapi::OpenApiGenerator,
+    wrangler::DurableMigrationGenerator,
 };
 use compiler_test::{COMPREHENSIVE_SRC, src_to_idl};
+use idl::ServiceBinding;
 
 #[test]
 fn backend_code_generation_snapshot() {
@@ -12,6 +16,40 @@ fn backend_code_generation_snapshot() {
     insta::assert_snapshot!(backend_code);
 }
 
+#[test]
+fn service_binding_code_generation_snapshot() {
+    const WORKERS_URL: &str = "http://example.com/path/to/api";
+    let remote = src_to_idl(
+        r#"
+        model Session {}
+        api Session {
+            post login -> string {
+                name: string
+            }
+        }
+        "#,
+    );
+    let mut idl = src_to_idl(
+        r#"
+        model Account {}
+        api Account {
+            post signIn -> string {}
+        }
+        "#,
+    );
+
+    // `inject { Auth }` needs the remote CIDL on disk, so the binding is wired up by hand.
+    idl.models["Account"].apis[0].injected.push("Auth");
+    idl.wrangler_env.service_bindings.push(ServiceBinding {
+        name: "Auth",
+        cidl: Box::new(remote),
+    });
+
+    let backend_code = BackendGenerator::generate(&idl, WORKERS_URL);
+    let service_code = ClientGenerator::generate_service(&idl.wrangler_env.service_bindings[0]);
+    insta::assert_snapshot!(format!("{backend_code}\n\n{service_code}"));
+}
+
 #[test]
 fn durable_migration_generation_snapshot() {
     const NAME: &str = "create_users_table";
@@ -36,3 +74,68 @@ fn client_code_generation_snapshot() {
     let client_code = ClientGenerator::generate(&idl, WORKERS_URL);
     insta::assert_snapshot!(client_code);
 }
+
+#[test]
+fn rust_backend_code_generation_snapshot() {
+    const WORKERS_URL: &str = "http://example.com/path/to/api";
+    let idl = src_to_idl(COMPREHENSIVE_SRC);
+
+    let backend_code = RustBackendGenerator::generate(&idl, WORKERS_URL);
+    insta::assert_snapshot!(backend_code);
+}
+
+#[test]
+fn rust_client_code_generation_snapshot() {
+    const WORKERS_URL: &str = "http://example.com/path/to/api";
+    let idl = src_to_idl(COMPREHENSIVE_SRC);
+
+    let client_code = RustClientGenerator::generate(&idl, WORKERS_URL);
+    insta::assert_snapshot!(client_code);
+}
+
+#[test]
+fn python_client_code_generation_snapshot() {
+    const WORKERS_URL: &str = "http://example.com/path/to/api";
+    let idl = src_to_idl(COMPREHENSIVE_SRC);
+
+    let client_code = PythonClientGenerator::generate(&idl, WORKERS_URL);
+    insta::assert_snapshot!(client_code);
+}
+
+#[test]
+fn python_client_reserved_names_snapshot() {
+    const WORKERS_URL: &str = "http://example.com/path/to/api";
+    let idl = src_to_idl(
+        r#"
+        model Search {}
+        api Search {
+            get find "search/{path}" -> string {
+                [path]
+                path: string
+
+                query: string
+            }
+
+            post search -> string {
+                [query]
+                path: string
+
+                query: string
+                json: string
+            }
+        }
+        "#,
+    );
+
+    let client_code = PythonClientGenerator::generate(&idl, WORKERS_URL);
+    insta::assert_snapshot!(client_code);
+}
+
+#[test]
+fn openapi_generation_snapshot() {
+    const WORKERS_URL: &str = "http://example.com/path/to/api";
+    let idl = src_to_idl(COMPREHENSIVE_SRC);
+
+    let openapi = OpenApiGenerator::generate(&idl, "Comprehensive", WORKERS_URL);
+    insta::assert_snapshot!(openapi);
+}
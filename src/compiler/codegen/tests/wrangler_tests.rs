@@ -1,7 +1,8 @@
 use std::path::Path;
 
-use codegen::wrangler::{WranglerDefault, WranglerGenerator};
+use codegen::wrangler::{DevVarsGenerator, WranglerDefault, WranglerGenerator};
 use compiler_test::src_to_idl;
+use idl::ServiceBinding;
 
 fn from_toml_str(src: &str) -> WranglerGenerator {
     WranglerGenerator::from_contents(src.to_string(), Path::new("wrangler.toml")).unwrap()
@@ -46,12 +47,12 @@ fn generates_default_wrangler_value() {
     let specs = vec![
         {
             let mut spec = from_toml_str("").as_spec(None).unwrap();
-            WranglerDefault::set_defaults(&mut spec, &idl, "migrations");
+            WranglerDefault::set_defaults(&mut spec, &idl, None, "migrations");
             spec
         },
         {
             let mut spec = from_json_str("{}").as_spec(None).unwrap();
-            WranglerDefault::set_defaults(&mut spec, &idl, "migrations");
+            WranglerDefault::set_defaults(&mut spec, &idl, None, "migrations");
             spec
         },
     ];
@@ -68,6 +69,68 @@ fn generates_default_wrangler_value() {
     }
 }
 
+#[test]
+fn rust_target_builds_with_worker_build() {
+    // Arrange
+    let idl = src_to_idl("d1 { db }");
+    let mut generator = from_toml_str(
+        r#"
+        [build]
+        command = "make worker"
+        "#,
+    );
+
+    // Act
+    let mut spec = from_toml_str("").as_spec(None).unwrap();
+    WranglerDefault::set_rust_defaults(&mut spec);
+    WranglerDefault::set_defaults(&mut spec, &idl, None, "migrations");
+
+    let mut custom = generator.as_spec(None).unwrap();
+    WranglerDefault::set_rust_defaults(&mut custom);
+    let custom_toml = generator.generate(custom, None);
+
+    // Assert
+    assert_eq!(spec.main.as_deref(), Some("build/worker/shim.mjs"));
+    assert_eq!(
+        spec.build.and_then(|b| b.command).as_deref(),
+        Some("cargo install -q worker-build && worker-build --release")
+    );
+    assert!(custom_toml.contains(r#"command = "make worker""#));
+}
+
+#[test]
+fn secrets_are_listed_in_dev_vars_not_wrangler_vars() {
+    // Arrange
+    let src = r#"
+        var {
+            API_URL: string
+        }
+        secret {
+            STRIPE_KEY: string
+            WEBHOOK_SECRET: string
+        }
+    "#;
+    let idl = src_to_idl(src);
+
+    // Act
+    let mut spec = from_toml_str("").as_spec(None).unwrap();
+    WranglerDefault::set_defaults(&mut spec, &idl, None, "migrations");
+    let dev_vars = DevVarsGenerator::generate(&idl);
+
+    // Assert
+    assert_eq!(spec.vars.len(), 1);
+    assert!(spec.vars.contains_key("API_URL"));
+
+    let dev_vars = dev_vars.expect("secrets to produce a .dev.vars.example");
+    let entries = dev_vars
+        .lines()
+        .filter(|l| !l.starts_with('#'))
+        .collect::<Vec<_>>();
+    assert_eq!(entries, vec!["STRIPE_KEY=", "WEBHOOK_SECRET="]);
+
+    assert!(DevVarsGenerator::generate(&src_to_idl("var { API_URL: string }")).is_none());
+}
+
 #[test]
 fn generates_default_d1_wrangler_values() {
     // Arrange
@@ -86,12 +149,12 @@ fn generates_default_d1_wrangler_values() {
     let specs = vec![
         {
             let mut spec = from_toml_str("").as_spec(None).unwrap();
-            WranglerDefault::set_defaults(&mut spec, &idl, "my-migrations");
+            WranglerDefault::set_defaults(&mut spec, &idl, None, "my-migrations");
             spec
         },
         {
             let mut spec = from_json_str("{}").as_spec(None).unwrap();
-            WranglerDefault::set_defaults(&mut spec, &idl, "my-migrations");
+            WranglerDefault::set_defaults(&mut spec, &idl, None, "my-migrations");
             spec
         },
     ];
@@ -129,12 +192,12 @@ fn generates_default_kv_wrangler_values() {
     let specs = vec![
         {
             let mut spec = from_toml_str("").as_spec(None).unwrap();
-            WranglerDefault::set_defaults(&mut spec, &idl, "migrations");
+            WranglerDefault::set_defaults(&mut spec, &idl, None, "migrations");
             spec
         },
         {
             let mut spec = from_json_str("{}").as_spec(None).unwrap();
-            WranglerDefault::set_defaults(&mut spec, &idl, "migrations");
+            WranglerDefault::set_defaults(&mut spec, &idl, None, "migrations");
             spec
         },
     ];
@@ -172,12 +235,12 @@ fn generates_default_durable_object_wrangler_values() {
     let specs = vec![
         {
             let mut spec = from_toml_str("").as_spec(None).unwrap();
-            WranglerDefault::set_defaults(&mut spec, &idl, "migrations");
+            WranglerDefault::set_defaults(&mut spec, &idl, None, "migrations");
             spec
         },
         {
             let mut spec = from_json_str("{}").as_spec(None).unwrap();
-            WranglerDefault::set_defaults(&mut spec, &idl, "migrations");
+            WranglerDefault::set_defaults(&mut spec, &idl, None, "migrations");
             spec
         },
     ];
@@ -222,13 +285,13 @@ fn durable_objects_serialize_to_correct_wrangler_format() {
     // Act: TOML
     let mut toml_gen = from_toml_str("");
     let mut toml_spec = toml_gen.as_spec(None).unwrap();
-    WranglerDefault::set_defaults(&mut toml_spec, &idl, "migrations");
+    WranglerDefault::set_defaults(&mut toml_spec, &idl, None, "migrations");
     let toml_out = toml_gen.generate(toml_spec, None);
 
     // Act: JSON
     let mut json_gen = from_json_str("{}");
     let mut json_spec = json_gen.as_spec(None).unwrap();
-    WranglerDefault::set_defaults(&mut json_spec, &idl, "migrations");
+    WranglerDefault::set_defaults(&mut json_spec, &idl, None, "migrations");
     let json_out = json_gen.generate(json_spec, None);
 
     // Assert: TOML uses [[durable_objects.bindings]]
@@ -251,6 +314,187 @@ fn durable_objects_serialize_to_correct_wrangler_format() {
     );
 }
 
+#[test]
+fn generates_default_queue_wrangler_values() {
+    // Arrange
+    let idl = src_to_idl(
+        r#"
+        queue Emails {
+            message: string
+        }
+
+        queue Pings {
+            message: int
+        }
+    "#,
+    );
+
+    // Act: the existing producer keeps its queue, which gains a consumer
+    let mut json_gen = from_json_str(
+        r#"{
+            "queues": {
+                "producers": [{ "binding": "Emails", "queue": "outbound-emails" }]
+            }
+        }"#,
+    );
+    let mut spec = json_gen.as_spec(None).unwrap();
+    WranglerDefault::set_defaults(&mut spec, &idl, None, "migrations");
+
+    // Assert
+    let queues = spec.queues.as_ref().expect("queues should be populated");
+    assert_eq!(queues.producers.len(), 2);
+    assert_eq!(queues.producers[0].binding.as_deref(), Some("Emails"));
+    assert_eq!(
+        queues.producers[0].queue.as_deref(),
+        Some("outbound-emails")
+    );
+    assert_eq!(queues.producers[1].binding.as_deref(), Some("Pings"));
+    assert_eq!(
+        queues.producers[1].queue.as_deref(),
+        Some("replace-with-pings-queue")
+    );
+
+    let consumers = queues
+        .consumers
+        .iter()
+        .map(|c| c.queue.as_deref().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        consumers,
+        vec!["outbound-emails", "replace-with-pings-queue"]
+    );
+
+    // Act: TOML
+    let mut toml_gen = from_toml_str("");
+    let mut toml_spec = toml_gen.as_spec(None).unwrap();
+    WranglerDefault::set_defaults(&mut toml_spec, &idl, None, "migrations");
+    let toml_out = toml_gen.generate(toml_spec, None);
+
+    // Assert: TOML uses [[queues.producers]] and [[queues.consumers]]
+    assert!(
+        toml_out.contains("[[queues.producers]]"),
+        "expected queues.producers table in TOML output:\n{toml_out}"
+    );
+    assert!(toml_out.contains("[[queues.consumers]]"));
+    assert!(toml_out.contains("queue = \"replace-with-emails-queue\""));
+
+    // Assert: JSON merges into the existing producer
+    let json_out = json_gen.generate(spec, None);
+    let json_val: serde_json::Value = serde_json::from_str(&json_out).unwrap();
+    assert_eq!(json_val["queues"]["producers"].as_array().unwrap().len(), 2);
+    assert_eq!(
+        json_val["queues"]["consumers"][0]["queue"],
+        "outbound-emails"
+    );
+}
+
+#[test]
+fn generates_cron_triggers() {
+    // Arrange
+    let idl = src_to_idl(
+        r#"
+        cron "*/5 * * * *" {}
+        cron "0 0 * * SUN" {}
+    "#,
+    );
+
+    // Act: an existing trigger is kept, not duplicated, and one no longer declared is removed
+    let mut json_gen = from_json_str(
+        r#"{
+            "triggers": { "crons": ["0 0 * * SUN", "0 12 * * *"] }
+        }"#,
+    );
+    let mut spec = json_gen.as_spec(None).unwrap();
+    WranglerDefault::set_defaults(&mut spec, &idl, None, "migrations");
+
+    // Assert
+    let crons = &spec
+        .triggers
+        .as_ref()
+        .expect("triggers should be populated")
+        .crons;
+    assert_eq!(crons, &vec!["0 0 * * SUN", "*/5 * * * *"]);
+
+    let json_out = json_gen.generate(spec, None);
+    let json_val: serde_json::Value = serde_json::from_str(&json_out).unwrap();
+    assert_eq!(
+        json_val["triggers"]["crons"],
+        serde_json::json!(["0 0 * * SUN", "*/5 * * * *"])
+    );
+
+    // Act: TOML
+    let mut toml_gen = from_toml_str("");
+    let mut toml_spec = toml_gen.as_spec(None).unwrap();
+    WranglerDefault::set_defaults(&mut toml_spec, &idl, None, "migrations");
+    let toml_out = toml_gen.generate(toml_spec, None);
+
+    // Assert
+    assert!(
+        toml_out.contains("[triggers]"),
+        "expected triggers table in TOML output:\n{toml_out}"
+    );
+    assert!(toml_out.contains("\"*/5 * * * *\""));
+
+    // Act: a schema without crons clears the existing ones
+    let mut cleared_gen = from_json_str(
+        r#"{
+            "triggers": { "crons": ["0 12 * * *"] }
+        }"#,
+    );
+    let mut cleared_spec = cleared_gen.as_spec(None).unwrap();
+    WranglerDefault::set_defaults(&mut cleared_spec, &src_to_idl(""), None, "migrations");
+    let cleared_out = cleared_gen.generate(cleared_spec, None);
+
+    // Assert
+    let cleared_val: serde_json::Value = serde_json::from_str(&cleared_out).unwrap();
+    assert_eq!(cleared_val["triggers"]["crons"], serde_json::json!([]));
+}
+
+#[test]
+fn generates_service_bindings() {
+    // Arrange
+    let mut idl = src_to_idl("");
+    for name in ["Auth", "Billing"] {
+        idl.wrangler_env.service_bindings.push(ServiceBinding {
+            name,
+            cidl: Box::default(),
+        });
+    }
+
+    // Act: an existing service name is kept, a missing one gets a default
+    let mut json_gen = from_json_str(
+        r#"{
+            "services": [{ "binding": "Auth", "service": "auth-worker" }]
+        }"#,
+    );
+    let mut spec = json_gen.as_spec(None).unwrap();
+    WranglerDefault::set_defaults(&mut spec, &idl, None, "migrations");
+
+    // Assert
+    let services = spec
+        .services
+        .iter()
+        .map(|s| (s.binding.as_deref(), s.service.as_deref()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        services,
+        vec![
+            (Some("Auth"), Some("auth-worker")),
+            (Some("Billing"), Some("replace-with-billing-service")),
+        ]
+    );
+
+    let json_out = json_gen.generate(spec, None);
+    let json_val: serde_json::Value = serde_json::from_str(&json_out).unwrap();
+    assert_eq!(
+        json_val["services"],
+        serde_json::json!([
+            { "binding": "Auth", "service": "auth-worker" },
+            { "binding": "Billing", "service": "replace-with-billing-service" },
+        ])
+    );
+}
+
 #[test]
 fn handles_d1_database_with_missing_values() {
     // Arrange
@@ -274,7 +518,7 @@ fn handles_d1_database_with_missing_values() {
     let mut spec = from_toml_str(toml_with_incomplete_d1)
         .as_spec(None)
         .unwrap();
-    WranglerDefault::set_defaults(&mut spec, &idl, "default-migrations");
+    WranglerDefault::set_defaults(&mut spec, &idl, None, "default-migrations");
 
     // Assert
     assert_eq!(spec.d1_databases.len(), 1);
@@ -408,7 +652,7 @@ fn env_generate_writes_into_env_block() {
 
         let mut generator = from_toml_str(toml_src);
         let mut spec = generator.as_spec(Some("staging")).unwrap();
-        WranglerDefault::set_defaults(&mut spec, &idl, "migrations");
+        WranglerDefault::set_defaults(&mut spec, &idl, Some("staging"), "migrations");
         let output = generator.generate(spec, Some("staging"));
 
         assert!(
@@ -446,7 +690,7 @@ fn env_generate_writes_into_env_block() {
 
         let mut generator = from_json_str(json_src);
         let mut spec = generator.as_spec(Some("staging")).unwrap();
-        WranglerDefault::set_defaults(&mut spec, &idl, "migrations");
+        WranglerDefault::set_defaults(&mut spec, &idl, Some("staging"), "migrations");
         let output = generator.generate(spec, Some("staging"));
 
         let output_val: serde_json::Value = serde_json::from_str(&output).unwrap();
@@ -465,6 +709,62 @@ fn env_generate_writes_into_env_block() {
     }
 }
 
+#[test]
+fn schema_envs_write_their_overlays() {
+    // Arrange
+    let idl = src_to_idl(
+        r#"
+        d1 { DB: "prod-db" }
+
+        var {
+            API_URL: string
+        }
+
+        env staging {
+            d1 { DB: "staging-db" }
+            var { DEBUG: bool }
+        }
+
+        model User for DB {
+            primary { id: int }
+        }
+    "#,
+    );
+
+    let mut generator = from_json_str(
+        r#"{
+            "name": "my-worker",
+            "d1_databases": [
+                { "binding": "DB", "database_id": "prod-id" }
+            ]
+        }"#,
+    );
+
+    // Act
+    let mut staging = generator.as_spec(Some("staging")).unwrap();
+    WranglerDefault::set_defaults(&mut staging, &idl, Some("staging"), "migrations");
+    generator.write_env(&staging, "staging");
+
+    let mut spec = generator.as_spec(None).unwrap();
+    WranglerDefault::set_defaults(&mut spec, &idl, None, "migrations");
+    let output: serde_json::Value = serde_json::from_str(&generator.generate(spec, None)).unwrap();
+
+    // Assert
+    assert_eq!(output["d1_databases"][0]["database_id"], "prod-id");
+    assert_eq!(output["d1_databases"][0]["database_name"], "prod-db");
+    assert!(output["vars"].get("DEBUG").is_none());
+
+    let staging = &output["env"]["staging"];
+    assert_eq!(staging["d1_databases"][0]["binding"], "DB");
+    assert_eq!(staging["d1_databases"][0]["database_name"], "staging-db");
+    assert_eq!(
+        staging["d1_databases"][0]["database_id"],
+        "replace_with_DB_id"
+    );
+    assert_eq!(staging["vars"]["API_URL"], "default_string");
+    assert_eq!(staging["vars"]["DEBUG"], "false");
+}
+
 #[test]
 fn durable_object_migrations_diff_against_existing_entries() {
     // Arrange
@@ -498,7 +798,7 @@ fn durable_object_migrations_diff_against_existing_entries() {
     // Act
     let mut generator = from_toml_str(existing);
     let mut spec = generator.as_spec(None).unwrap();
-    WranglerDefault::set_defaults(&mut spec, &idl, "migrations");
+    WranglerDefault::set_defaults(&mut spec, &idl, None, "migrations");
     let out = generator.generate(spec.clone(), None);
 
     // Assert: a single new entry registering only GlobalDo
@@ -538,8 +838,8 @@ fn durable_object_migrations_idempotent() {
 
     // Act: two consecutive compiles over the same config
     let mut spec = from_toml_str("").as_spec(None).unwrap();
-    WranglerDefault::set_defaults(&mut spec, &idl, "migrations");
-    WranglerDefault::set_defaults(&mut spec, &idl, "migrations");
+    WranglerDefault::set_defaults(&mut spec, &idl, None, "migrations");
+    WranglerDefault::set_defaults(&mut spec, &idl, None, "migrations");
 
     // Assert: the class is registered exactly once
     assert_eq!(spec.migrations.len(), 1);
@@ -72,7 +72,9 @@ pub fn src_to_idl(src: &str) -> CloesceIdl<'_> {
         }
     };
 
-    match semantic::analyze(&parsed) {
+    // The IDL borrows from the AST (namespaced names are generated), so keep it alive
+    let parsed = Box::leak(Box::new(parsed));
+    match semantic::analyze(parsed) {
         Ok(idl) => idl,
         Err(errors) => {
             for error in &errors {
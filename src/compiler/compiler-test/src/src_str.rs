@@ -58,11 +58,33 @@ durable GlobalDo {
     }
 }
 
+queue Emails {
+    message: BasicPoo
+}
+
+cron "*/15 * * * *" {
+    inject { db YouTubeApi }
+}
+
+cron "0 0 * * SUN" {}
+
+guard RequireAdmin {
+    inject { YouTubeApi }
+}
+
+guard RequireUser {}
+
+guard RequireTenant {}
+
 var {
     // Comment again
     MY_VAR: string // More comments
 }
 
+secret {
+    MY_SECRET: string
+}
+
 inject { YouTubeApi }
 
 model BasicModel for db {
@@ -91,6 +113,23 @@ model HasSqlColumnTypes for db {
         dubNull: option<real>
         booNull: option<bool>
         dateNull: option<date>
+        enm: BasicEnum
+        enmNull: option<BasicEnum>
+    }
+}
+
+model HasColumnDefaults for db {
+    primary {
+        id: int
+    }
+
+    column {
+        score: int = 100
+        ratio: real = 0.5
+        label: string = "none" // trailing comment
+        active: bool = true
+        created: date = now
+        enm: BasicEnum = Second
     }
 }
 
@@ -116,6 +155,7 @@ model OneToManyModel for db {
     }
 }
 
+[crud count]
 model ModelWithCompositePk for db {
     primary {
         tenantId: string
@@ -220,6 +260,7 @@ source WithR2 for ToyotaPrius {
 }
 
 [crud get, save, list]
+[guard RequireUser]
 model ModelWithCruds for db {
     primary {
         id: int
@@ -234,6 +275,7 @@ model ModelWithCruds for db {
     }
 }
 
+[guard RequireAdmin]
 source ByName for ModelWithCruds {
     include {}
 
@@ -319,6 +361,7 @@ model BasicService {}
 api BasicService {
     get downloadData -> stream {}
 
+    [guard RequireUser]
     post instanceMethod -> int {
         input: int
     }
@@ -347,6 +390,44 @@ api BasicService {
             GlobalDo::{}
         }
     }
+
+    post sendEmail {
+        inject { Emails }
+    }
+
+    get searchTenant "tenants/{tenantId}/search" -> string {
+        [path]
+        tenantId: int
+
+        [query]
+        term: string
+
+        [query]
+        limit: option<int>
+    }
+
+    post archiveTenant "tenants/{tenantId}/archive" {
+        [path]
+        tenantId: int
+
+        [query]
+        reason: string
+
+        note: string
+    }
+
+    post shadowedNames -> string {
+        [query]
+        query: string
+
+        url: string
+        headers: string
+        payload: string
+        body: string
+        app: string
+        env: string
+        req: string
+    }
 }
 
 [crud get, save]
@@ -362,7 +443,8 @@ model GlobalSettings for GlobalDo {
     }
 }
 
-[crud get, list, save]
+[crud get, list, save, count]
+[guard RequireTenant]
 model LeaderboardEntry for LeaderboardDo(tenantId) {
     primary {
         id: int
@@ -378,9 +460,15 @@ model LeaderboardEntry for LeaderboardDo(tenantId) {
     }
 }
 
+enum BasicEnum {
+    First,
+    Second, // trailing comment
+}
+
 poo BasicPoo {
     field1: string
     field2: int
+    field3: array<BasicEnum>
 }
 
 poo PooWithComposition {
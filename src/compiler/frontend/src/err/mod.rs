@@ -1,9 +1,12 @@
 //! Error displays for the lexer and parser.
 
 use ariadne::{Color, Config, IndexType, Label, Report};
-use chumsky::error::RichReason;
+use std::ops::Range;
+
+use chumsky::error::{Rich, RichReason};
 
 use crate::{
+    Span,
     lexer::{FileTable, LexError, Token},
     parser::ParserError,
 };
@@ -24,6 +27,8 @@ impl std::fmt::Display for Token<'_> {
             Token::Dot => write!(f, "."),
             Token::DoubleColon => write!(f, "::"),
             Token::Arrow => write!(f, "->"),
+            Token::EqEq => write!(f, "=="),
+            Token::Eq => write!(f, "="),
             Token::StringLit(s) => write!(f, "\"{s}\""),
             Token::IntLit(s) => write!(f, "{s}"),
             Token::RealLit(s) => write!(f, "{s}"),
@@ -35,8 +40,23 @@ impl std::fmt::Display for Token<'_> {
     }
 }
 
+/// An error flattened to its primary label, for consumers that do not render
+/// [ariadne] reports (e.g. an editor).
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    /// The displayed path of the file, as given by [crate::lexer::LexTarget::path]
+    pub path: String,
+
+    /// Byte range of the primary label
+    pub range: Range<usize>,
+
+    pub message: String,
+}
+
 pub trait DisplayError {
     fn display_error(&self, file_table: &FileTable);
+
+    fn diagnostics(&self, file_table: &FileTable) -> Vec<Diagnostic>;
 }
 
 impl DisplayError for Vec<LexError> {
@@ -63,6 +83,19 @@ impl DisplayError for Vec<LexError> {
             }
         }
     }
+
+    fn diagnostics(&self, file_table: &FileTable) -> Vec<Diagnostic> {
+        self.iter()
+            .flat_map(|error| {
+                let (_, path) = file_table.resolve(error.file_id);
+                error.error_spans.iter().map(move |span| Diagnostic {
+                    path: path.display().to_string(),
+                    range: span.clone(),
+                    message: "unexpected token".into(),
+                })
+            })
+            .collect()
+    }
 }
 
 impl DisplayError for ParserError<'_, '_> {
@@ -76,31 +109,7 @@ impl DisplayError for ParserError<'_, '_> {
             let path_str = path.display().to_string();
             let ariadne_span = span.start..span.end;
 
-            let (message, label_msg) = match error.reason() {
-                RichReason::ExpectedFound { expected, found } => {
-                    let found_str = match found {
-                        Some(tok) => format!("found '{}'", **tok),
-                        None => "found end of input".to_string(),
-                    };
-                    let expected_str = if expected.is_empty() {
-                        "something else".to_string()
-                    } else {
-                        let parts: Vec<String> = expected.iter().map(|p| format!("{p}")).collect();
-                        if parts.len() == 1 {
-                            format!("expected {}", parts[0])
-                        } else {
-                            format!(
-                                "expected one of {}",
-                                parts[..parts.len() - 1].join(", ")
-                                    + ", or "
-                                    + &parts[parts.len() - 1]
-                            )
-                        }
-                    };
-                    (format!("{found_str}, {expected_str}"), found_str)
-                }
-                RichReason::Custom(msg) => (msg.clone(), msg.clone()),
-            };
+            let (message, label_msg) = messages(error);
 
             let mut builder = Report::build(
                 ariadne::ReportKind::Error,
@@ -125,4 +134,45 @@ impl DisplayError for ParserError<'_, '_> {
             builder.finish().write(&mut cache, std::io::stderr()).ok();
         }
     }
+
+    fn diagnostics(&self, file_table: &FileTable) -> Vec<Diagnostic> {
+        self.iter()
+            .map(|error| {
+                let span = error.span();
+                let (_, path) = file_table.resolve(span.context);
+                Diagnostic {
+                    path: path.display().to_string(),
+                    range: span.start..span.end,
+                    message: messages(error).0,
+                }
+            })
+            .collect()
+    }
+}
+
+/// The report message and primary label message of a parser error
+fn messages(error: &Rich<'_, Token<'_>, Span>) -> (String, String) {
+    match error.reason() {
+        RichReason::ExpectedFound { expected, found } => {
+            let found_str = match found {
+                Some(tok) => format!("found '{}'", **tok),
+                None => "found end of input".to_string(),
+            };
+            let expected_str = if expected.is_empty() {
+                "something else".to_string()
+            } else {
+                let parts: Vec<String> = expected.iter().map(|p| format!("{p}")).collect();
+                if parts.len() == 1 {
+                    format!("expected {}", parts[0])
+                } else {
+                    format!(
+                        "expected one of {}",
+                        parts[..parts.len() - 1].join(", ") + ", or " + &parts[parts.len() - 1]
+                    )
+                }
+            };
+            (format!("{found_str}, {expected_str}"), found_str)
+        }
+        RichReason::Custom(msg) => (msg.clone(), msg.clone()),
+    }
 }
@@ -15,6 +15,12 @@ pub enum Doc<'src> {
     },
 
     Seq(Vec<Doc<'src>>),
+
+    /// Indents every line of `doc` by `depth` more levels, e.g. the blocks of a namespace.
+    Nest {
+        depth: usize,
+        doc: Box<Doc<'src>>,
+    },
 }
 
 impl<'src> Doc<'src> {
@@ -43,6 +49,14 @@ impl<'src> Doc<'src> {
         Doc::HardLine { depth }
     }
 
+    #[inline(always)]
+    pub fn nest(depth: usize, doc: Doc<'src>) -> Self {
+        Doc::Nest {
+            depth,
+            doc: Box::new(doc),
+        }
+    }
+
     // TODO: revisit this if it ends up being a noticeable bottleneck.
     pub fn then(self, other: Doc<'src>) -> Doc<'src> {
         match (self, other) {
@@ -71,25 +85,26 @@ impl<'src> Doc<'src> {
 
 pub fn render(doc: &Doc<'_>) -> String {
     let mut out = String::new();
-    render_into(doc, &mut out);
+    render_into(doc, 0, &mut out);
     out
 }
 
-fn render_into(doc: &Doc<'_>, out: &mut String) {
+fn render_into(doc: &Doc<'_>, base: usize, out: &mut String) {
     match doc {
         Doc::Nil => {}
         Doc::Text(s) => out.push_str(s),
         Doc::OwnedText(s) => out.push_str(s),
         Doc::HardLine { depth } => {
             out.push('\n');
-            for _ in 0..*depth {
+            for _ in 0..base + depth {
                 out.push_str("    ");
             }
         }
         Doc::Seq(docs) => {
             for doc in docs {
-                render_into(doc, out);
+                render_into(doc, base, out);
             }
         }
+        Doc::Nest { depth, doc } => render_into(doc, base + depth, out),
     }
 }
@@ -13,15 +13,17 @@ mod doc;
 use std::cell::{Cell, RefCell};
 
 use doc::Doc;
-use idl::{CidlType, CrudKind, HttpVerb};
+use idl::{CidlType, CrudKind, HttpVerb, SortDirection};
 
 use crate::{
-    ApiBlock, ApiBlockMethod, ArgumentLiteral, Ast, AstBlockKind, Cardinality, D1BindingBlock,
-    DataSourceBlock, DataSourceBlockMethod, DurableBindingBlock, DurableShardBlock, ForeignBlock,
-    InjectBlock, InjectEntry, InjectInitializer, Keyword, KvBindingBlock, KvBindingTemplate,
-    KvFieldArgument, KvFieldBlock, MethodInjectBlock, ModelBlock, ModelBlockKind, NavigationBlock,
-    NavigationKey, ParsedIncludeTree, PlainOldObjectBlock, R2BindingBlock, R2BindingTemplate,
-    R2FieldBlock, Spd, SqlBlockKind, Symbol, Tag, VarBlock, fmt_cidl_type, lexer::CommentMap,
+    ApiBlock, ApiBlockMethod, ArgumentLiteral, Ast, AstBlockKind, Cardinality, CronBlock,
+    D1BindingBlock, DataSourceBlock, DataSourceBlockMethod, DefaultValue, DurableBindingBlock,
+    DurableShardBlock, EnumBlock, EnvBlock, EnvBlockKind, ForeignBlock, GuardBlock, InjectBlock,
+    InjectEntry, InjectInitializer, Keyword, KvBindingBlock, KvBindingTemplate, KvFieldArgument,
+    KvFieldBlock, ListClause, MethodInjectBlock, ModelBlock, ModelBlockKind, NamespaceBlock,
+    NavigationBlock, NavigationKey, ParsedIncludeTree, PlainOldObjectBlock, QueueBindingBlock,
+    R2BindingBlock, R2BindingTemplate, R2FieldBlock, SecretBlock, ServiceBindingBlock, Spd,
+    SqlBlockKind, Symbol, Tag, VarBlock, fmt_cidl_type, lexer::CommentMap,
 };
 
 /// Formats an [Ast] into a string, preserving comments and blank lines.
@@ -223,11 +225,22 @@ impl<'src> FmtCtx<'src> {
     }
 
     fn spd_doc<T: ToDoc<'src>>(&self, spd: &'src Spd<T>, indent: usize, inline: bool) -> Doc<'src> {
+        self.spd_doc_with(spd, indent, inline, |inner| inner.to_doc(self))
+    }
+
+    /// As [FmtCtx::spd_doc], rendering the node's content with `content` instead of its [ToDoc].
+    fn spd_doc_with<T>(
+        &self,
+        spd: &'src Spd<T>,
+        indent: usize,
+        inline: bool,
+        content: impl FnOnce(&'src T) -> Doc<'src>,
+    ) -> Doc<'src> {
         let gap = self.gap(self.cursor.get(), spd.span.start);
         let (leading, has_leading_comments) = self.leading_comments(spd.span.start, indent);
 
         self.node_ends.borrow_mut().push(spd.span.end);
-        let content = spd.inner.to_doc(self);
+        let content = content(&spd.inner);
         self.node_ends.borrow_mut().pop();
 
         let trailing = self.trailing_comment(spd.span.end);
@@ -324,9 +337,18 @@ impl<'src> FmtCtx<'src> {
                 .then(Doc::text(": "))
                 .then(Doc::owned(fmt_cidl_type(&sym.cidl_type)))
         };
+        let (content, sym_end) = match &sym.default {
+            Some(default) => (
+                content
+                    .then(Doc::text(" = "))
+                    .then(default.inner.to_doc(self)),
+                default.span.end,
+            ),
+            None => (content, sym.span.end),
+        };
 
-        let trailing = self.trailing_comment(sym.span.end);
-        self.advance(sym.span.end);
+        let trailing = self.trailing_comment(sym_end);
+        self.advance(sym_end);
 
         let content_sep = if has_leading_comments {
             Doc::hardline(indent)
@@ -404,12 +426,23 @@ impl<'src> ToDoc<'src> for AstBlockKind<'src> {
             AstBlockKind::Api(b) => b.to_doc(ctx),
             AstBlockKind::DataSource(b) => b.to_doc(ctx),
             AstBlockKind::PlainOldObject(b) => b.to_doc(ctx),
+            AstBlockKind::Enum(b) => b.to_doc(ctx),
             AstBlockKind::D1Binding(b) => b.to_doc(ctx),
             AstBlockKind::KvBinding(b) => b.to_doc(ctx),
             AstBlockKind::R2Binding(b) => b.to_doc(ctx),
             AstBlockKind::DurableBinding(b) => b.to_doc(ctx),
+            AstBlockKind::QueueBinding(b) => b.to_doc(ctx),
+            AstBlockKind::ServiceBinding(b) => b.to_doc(ctx),
+            AstBlockKind::Cron(b) => b.to_doc(ctx),
+            AstBlockKind::Guard(b) => b.to_doc(ctx),
+            AstBlockKind::Env(b) => b.to_doc(ctx),
             AstBlockKind::Var(b) => b.to_doc(ctx),
+            AstBlockKind::Secret(b) => b.to_doc(ctx),
+            AstBlockKind::Namespace(b) => b.to_doc(ctx),
             AstBlockKind::Inject(b) => b.to_doc(ctx),
+            AstBlockKind::Error { .. } => {
+                unreachable!("only an Ast parsed without errors is formatted")
+            }
         }
     }
 }
@@ -446,31 +479,49 @@ impl<'src> ToDoc<'src> for ModelBlock<'src> {
     }
 }
 
+impl<'src> ToDoc<'src> for ArgumentLiteral<'src> {
+    fn to_doc(&'src self, _ctx: &FmtCtx<'src>) -> Doc<'src> {
+        match self {
+            ArgumentLiteral::Int(s) | ArgumentLiteral::Real(s) => Doc::text(s),
+            ArgumentLiteral::Str(s) => Doc::text("\"").then(Doc::text(s)).then(Doc::text("\"")),
+            ArgumentLiteral::Regex(s) => Doc::text("/").then(Doc::text(s)).then(Doc::text("/")),
+        }
+    }
+}
+
+impl<'src> ToDoc<'src> for DefaultValue<'src> {
+    fn to_doc(&'src self, ctx: &FmtCtx<'src>) -> Doc<'src> {
+        match self {
+            DefaultValue::Literal(lit) => lit.to_doc(ctx),
+            DefaultValue::Ident(s) => Doc::text(s),
+        }
+    }
+}
+
 impl<'src> ToDoc<'src> for Tag<'src> {
     fn to_doc(&'src self, ctx: &FmtCtx<'src>) -> Doc<'src> {
         let inner = match self {
-            Tag::Validator { name, argument } => {
-                let arg = match argument {
-                    ArgumentLiteral::Int(s) | ArgumentLiteral::Real(s) => Doc::text(s),
-                    ArgumentLiteral::Str(s) => {
-                        Doc::text("\"").then(Doc::text(s)).then(Doc::text("\""))
-                    }
-                    ArgumentLiteral::Regex(s) => {
-                        Doc::text("/").then(Doc::text(s)).then(Doc::text("/"))
-                    }
-                };
-
-                Doc::text(name.as_str()).then(Doc::text(" ")).then(arg)
-            }
+            Tag::Validator { name, argument } => Doc::text(name.as_str())
+                .then(Doc::text(" "))
+                .then(argument.to_doc(ctx)),
 
             Tag::Internal => Doc::kw(Keyword::Internal),
             Tag::Instance => Doc::kw(Keyword::Instance),
             Tag::Header => Doc::kw(Keyword::Header),
+            Tag::Query => Doc::kw(Keyword::Query),
+            Tag::Path => Doc::kw(Keyword::Path),
+            Tag::Guard { guard } => Doc::kw(Keyword::Guard)
+                .then(Doc::text(" "))
+                .then(Doc::text(guard.name)),
 
             Tag::Unique { fields: symbols } => Doc::kw(Keyword::Unique)
                 .then(Doc::text(" "))
                 .then(comma_separated(symbols, |sym| ctx.sym_doc(sym, 0, true))),
 
+            Tag::Index { fields: symbols } => Doc::kw(Keyword::Index)
+                .then(Doc::text(" "))
+                .then(comma_separated(symbols, |sym| ctx.sym_doc(sym, 0, true))),
+
             Tag::Crud { kinds } => Doc::kw(Keyword::Crud)
                 .then(Doc::text(" "))
                 .then(comma_separated(kinds, |kind| ctx.spd_doc(kind, 0, true))),
@@ -486,6 +537,8 @@ impl<'src> ToDoc<'src> for CrudKind {
             CrudKind::Get => Keyword::Get,
             CrudKind::List => Keyword::List,
             CrudKind::Save => Keyword::Save,
+            CrudKind::Delete => Keyword::Delete,
+            CrudKind::Count => Keyword::Count,
         };
         Doc::text(kw.as_str())
     }
@@ -682,15 +735,24 @@ impl<'src> ToDoc<'src> for ApiBlock<'src> {
 
 impl<'src> ToDoc<'src> for MethodInjectBlock<'src> {
     fn to_doc(&'src self, ctx: &FmtCtx<'src>) -> Doc<'src> {
-        if self.entries.is_empty() {
-            return Doc::kw(Keyword::Inject).then(Doc::text(" {}"));
-        }
-        let mut inner = Doc::nil();
-        for entry in &self.entries {
-            inner = inner.then(ctx.spd_doc(entry, 3, false));
-        }
-        Doc::kw(Keyword::Inject).then(ctx.block(inner, 3))
+        inject_block_doc(ctx, self, 3)
+    }
+}
+
+/// `inject { entry* }`, with its entries at `depth`.
+fn inject_block_doc<'src>(
+    ctx: &FmtCtx<'src>,
+    block: &'src MethodInjectBlock<'src>,
+    depth: usize,
+) -> Doc<'src> {
+    if block.entries.is_empty() {
+        return Doc::kw(Keyword::Inject).then(Doc::text(" {}"));
     }
+    let mut inner = Doc::nil();
+    for entry in &block.entries {
+        inner = inner.then(ctx.spd_doc(entry, depth, false));
+    }
+    Doc::kw(Keyword::Inject).then(ctx.block(inner, depth))
 }
 
 impl<'src> ToDoc<'src> for InjectEntry<'src> {
@@ -746,11 +808,25 @@ impl<'src> ToDoc<'src> for ApiBlockMethod<'src> {
             }
         };
 
-        let signature = source
+        let mut tags = Doc::nil();
+        for tag in &self.symbol.tags {
+            tags = tags.then(tag.inner.to_doc(ctx)).then(Doc::hardline(1));
+        }
+
+        let signature = tags
+            .then(source)
             .then(verb)
             .then(Doc::text(" "))
             .then(Doc::text(self.symbol.name));
 
+        let signature = match self.route {
+            Some(route) => signature
+                .then(Doc::text(" \""))
+                .then(Doc::text(route))
+                .then(Doc::text("\"")),
+            None => signature,
+        };
+
         let signature = if matches!(self.symbol.cidl_type, CidlType::Void) {
             signature
         } else {
@@ -759,7 +835,13 @@ impl<'src> ToDoc<'src> for ApiBlockMethod<'src> {
                 .then(Doc::owned(fmt_cidl_type(&self.symbol.cidl_type)))
         };
 
-        signature.then(method_body_doc(ctx, &self.parameters, &self.injects, 2))
+        signature.then(method_body_doc(
+            ctx,
+            &self.parameters,
+            &self.injects,
+            &[],
+            2,
+        ))
     }
 }
 
@@ -769,11 +851,39 @@ impl<'src> ToDoc<'src> for DataSourceBlockMethod<'src> {
             ctx,
             &self.parameters,
             &self.injects,
+            &self.clauses,
             2,
         ))
     }
 }
 
+impl<'src> ToDoc<'src> for ListClause<'src> {
+    fn to_doc(&'src self, ctx: &FmtCtx<'src>) -> Doc<'src> {
+        match self {
+            ListClause::OrderBy { terms } => {
+                let terms = comma_separated(terms, |(column, direction)| {
+                    let column = ctx.sym_doc(column, 0, true);
+                    match direction {
+                        Some(SortDirection::Asc) => {
+                            column.then(Doc::text(" ")).then(Doc::kw(Keyword::Asc))
+                        }
+                        Some(SortDirection::Desc) => {
+                            column.then(Doc::text(" ")).then(Doc::kw(Keyword::Desc))
+                        }
+                        None => column,
+                    }
+                });
+                Doc::kw(Keyword::OrderBy).then(Doc::text(" ")).then(terms)
+            }
+            ListClause::Where { column, param } => Doc::kw(Keyword::Where)
+                .then(Doc::text(" "))
+                .then(ctx.sym_doc(column, 0, true))
+                .then(Doc::text(" == "))
+                .then(ctx.sym_doc(param, 0, true)),
+        }
+    }
+}
+
 impl<'src> ToDoc<'src> for DataSourceBlock<'src> {
     fn to_doc(&'src self, ctx: &FmtCtx<'src>) -> Doc<'src> {
         let source_doc = ctx
@@ -838,29 +948,137 @@ impl<'src> ToDoc<'src> for PlainOldObjectBlock<'src> {
     }
 }
 
-impl<'src> ToDoc<'src> for D1BindingBlock<'src> {
+impl<'src> ToDoc<'src> for EnumBlock<'src> {
     fn to_doc(&'src self, ctx: &FmtCtx<'src>) -> Doc<'src> {
-        if self.bindings.is_empty() {
-            return Doc::kw(Keyword::D1).then(Doc::text(" {}"));
+        let doc = ctx.top_decl_doc(&self.symbol, Keyword::Enum);
+
+        if self.members.is_empty() {
+            return doc.then(Doc::text(" {}"));
         }
+
+        // Members are written one per line, each with a trailing comma placed
+        // before any trailing comment.
         let mut inner = Doc::nil();
-        for sym in &self.bindings {
-            inner = inner.then(ctx.sym_doc(sym, 1, false));
+        for member in &self.members {
+            let (leading, has_leading_comments) = ctx.leading_comments(member.span.start, 1);
+            let content_sep = if has_leading_comments {
+                Doc::hardline(1)
+            } else {
+                Doc::nil()
+            };
+
+            // A trailing comment follows the member's comma in the source, if it has one
+            let rest = ctx.src.get(member.span.end..).unwrap_or("");
+            let end = match rest.trim_start().strip_prefix(',') {
+                Some(after) => ctx.src.len() - after.len(),
+                None => member.span.end,
+            };
+            let trailing = ctx.trailing_comment(end);
+            ctx.advance(end);
+
+            inner = inner
+                .then(Doc::hardline(1))
+                .then(leading)
+                .then(content_sep)
+                .then(Doc::text(member.name))
+                .then(Doc::text(","))
+                .then(trailing);
+        }
+        doc.then(ctx.block(inner, 1))
+    }
+}
+
+impl<'src> ToDoc<'src> for D1BindingBlock<'src> {
+    fn to_doc(&'src self, ctx: &FmtCtx<'src>) -> Doc<'src> {
+        d1_block_doc(ctx, self, 1)
+    }
+}
+
+/// `d1 { binding* }`, with its bindings at `depth`.
+fn d1_block_doc<'src>(
+    ctx: &FmtCtx<'src>,
+    block: &'src D1BindingBlock<'src>,
+    depth: usize,
+) -> Doc<'src> {
+    if block.bindings.is_empty() {
+        return Doc::kw(Keyword::D1).then(Doc::text(" {}"));
+    }
+    let mut inner = Doc::nil();
+    for binding in &block.bindings {
+        inner = inner.then(ctx.sym_doc(&binding.symbol, depth, false));
+        if let Some(name) = &binding.database_name {
+            inner = inner
+                .then(Doc::text(": \""))
+                .then(Doc::text(name.inner))
+                .then(Doc::text("\""))
+                .then(ctx.trailing_comment(name.span.end));
+            ctx.advance(name.span.end);
         }
-        Doc::kw(Keyword::D1).then(ctx.block(inner, 1))
     }
+    Doc::kw(Keyword::D1).then(ctx.block(inner, depth))
 }
 
 impl<'src> ToDoc<'src> for VarBlock<'src> {
     fn to_doc(&'src self, ctx: &FmtCtx<'src>) -> Doc<'src> {
-        if self.vars.is_empty() {
-            return Doc::kw(Keyword::Var).then(Doc::text(" {}"));
+        var_block_doc(ctx, self, 1)
+    }
+}
+
+/// `var { symbol* }`, with its vars at `depth`.
+fn var_block_doc<'src>(ctx: &FmtCtx<'src>, block: &'src VarBlock<'src>, depth: usize) -> Doc<'src> {
+    if block.vars.is_empty() {
+        return Doc::kw(Keyword::Var).then(Doc::text(" {}"));
+    }
+    let mut inner = Doc::nil();
+    for sym in &block.vars {
+        inner = inner.then(ctx.sym_doc(sym, depth, false));
+    }
+    Doc::kw(Keyword::Var).then(ctx.block(inner, depth))
+}
+
+impl<'src> ToDoc<'src> for SecretBlock<'src> {
+    fn to_doc(&'src self, ctx: &FmtCtx<'src>) -> Doc<'src> {
+        if self.secrets.is_empty() {
+            return Doc::kw(Keyword::Secret).then(Doc::text(" {}"));
         }
         let mut inner = Doc::nil();
-        for sym in &self.vars {
+        for sym in &self.secrets {
             inner = inner.then(ctx.sym_doc(sym, 1, false));
         }
-        Doc::kw(Keyword::Var).then(ctx.block(inner, 1))
+        Doc::kw(Keyword::Secret).then(ctx.block(inner, 1))
+    }
+}
+
+impl<'src> ToDoc<'src> for NamespaceBlock<'src> {
+    fn to_doc(&'src self, ctx: &FmtCtx<'src>) -> Doc<'src> {
+        let doc = ctx.top_decl_doc(&self.symbol, Keyword::Namespace);
+        if self.blocks.is_empty() {
+            return doc.then(Doc::text(" {}"));
+        }
+
+        // Members are formatted as top level blocks, one level deeper
+        let mut inner = Doc::nil();
+        for spd in &self.blocks {
+            inner = inner.then(ctx.spd_doc(spd, 0, false));
+        }
+        doc.then(ctx.block(Doc::nest(1, inner), 1))
+    }
+}
+
+impl<'src> ToDoc<'src> for EnvBlock<'src> {
+    fn to_doc(&'src self, ctx: &FmtCtx<'src>) -> Doc<'src> {
+        let doc = ctx.top_decl_doc(&self.symbol, Keyword::Env);
+        if self.blocks.is_empty() {
+            return doc.then(Doc::text(" {}"));
+        }
+        let mut inner = Doc::nil();
+        for spd in &self.blocks {
+            inner = inner.then(ctx.spd_doc_with(spd, 1, false, |b| match b {
+                EnvBlockKind::D1Binding(b) => d1_block_doc(ctx, b, 2),
+                EnvBlockKind::Var(b) => var_block_doc(ctx, b, 2),
+            }));
+        }
+        doc.then(ctx.block(inner, 1))
     }
 }
 
@@ -932,6 +1150,61 @@ impl<'src> ToDoc<'src> for R2BindingBlock<'src> {
     }
 }
 
+impl<'src> ToDoc<'src> for QueueBindingBlock<'src> {
+    fn to_doc(&'src self, ctx: &FmtCtx<'src>) -> Doc<'src> {
+        ctx.top_decl_doc(&self.symbol, Keyword::Queue)
+            .then(ctx.block(ctx.sym_doc(&self.message, 1, false), 1))
+    }
+}
+
+impl<'src> ToDoc<'src> for ServiceBindingBlock<'src> {
+    fn to_doc(&'src self, ctx: &FmtCtx<'src>) -> Doc<'src> {
+        let doc = ctx
+            .top_decl_doc(&self.symbol, Keyword::Service)
+            .then(Doc::text(" "))
+            .then(Doc::kw(Keyword::From))
+            .then(Doc::text(" \""))
+            .then(Doc::text(self.cidl_path.inner))
+            .then(Doc::text("\""));
+        ctx.advance(self.cidl_path.span.end);
+        doc
+    }
+}
+
+impl<'src> ToDoc<'src> for CronBlock<'src> {
+    fn to_doc(&'src self, ctx: &FmtCtx<'src>) -> Doc<'src> {
+        let doc = Doc::kw(Keyword::Cron)
+            .then(Doc::text(" \""))
+            .then(Doc::text(self.expression.inner))
+            .then(Doc::text("\""));
+        ctx.advance(self.expression.span.end);
+
+        if self.injects.is_empty() {
+            return doc.then(Doc::text(" {}"));
+        }
+        let mut inner = Doc::nil();
+        for spd in &self.injects {
+            inner = inner.then(ctx.spd_doc_with(spd, 1, false, |b| inject_block_doc(ctx, b, 2)));
+        }
+        doc.then(ctx.block(inner, 1))
+    }
+}
+
+impl<'src> ToDoc<'src> for GuardBlock<'src> {
+    fn to_doc(&'src self, ctx: &FmtCtx<'src>) -> Doc<'src> {
+        let doc = ctx.top_decl_doc(&self.symbol, Keyword::Guard);
+
+        if self.injects.is_empty() {
+            return doc.then(Doc::text(" {}"));
+        }
+        let mut inner = Doc::nil();
+        for spd in &self.injects {
+            inner = inner.then(ctx.spd_doc_with(spd, 1, false, |b| inject_block_doc(ctx, b, 2)));
+        }
+        doc.then(ctx.block(inner, 1))
+    }
+}
+
 impl<'src> ToDoc<'src> for DurableBindingBlock<'src> {
     fn to_doc(&'src self, ctx: &FmtCtx<'src>) -> Doc<'src> {
         let doc = ctx.top_decl_doc(&self.symbol, Keyword::Durable);
@@ -997,19 +1270,23 @@ fn method_body_doc<'src>(
     ctx: &FmtCtx<'src>,
     params: &'src [Symbol<'src>],
     injects: &'src [Spd<MethodInjectBlock<'src>>],
+    clauses: &'src [Spd<ListClause<'src>>],
     depth: usize,
 ) -> Doc<'src> {
     enum Item<'a, 'src> {
         Param(&'a Symbol<'src>),
         Inject(&'a Spd<MethodInjectBlock<'src>>),
+        Clause(&'a Spd<ListClause<'src>>),
     }
 
     let mut items: Vec<Item<'src, 'src>> = Vec::new();
     items.extend(params.iter().map(Item::Param));
     items.extend(injects.iter().map(Item::Inject));
+    items.extend(clauses.iter().map(Item::Clause));
     items.sort_by_key(|item| match item {
         Item::Param(p) => p.span.start,
         Item::Inject(i) => i.span.start,
+        Item::Clause(c) => c.span.start,
     });
 
     if items.is_empty() {
@@ -1021,6 +1298,7 @@ fn method_body_doc<'src>(
         inner = inner.then(match item {
             Item::Param(p) => ctx.sym_doc(p, depth, false),
             Item::Inject(i) => ctx.spd_doc(i, depth, false),
+            Item::Clause(c) => ctx.spd_doc(c, depth, false),
         });
     }
     ctx.block(inner, depth)
@@ -41,6 +41,10 @@ pub enum Token<'src> {
     DoubleColon,
     #[token("->")]
     Arrow,
+    #[token("==")]
+    EqEq,
+    #[token("=")]
+    Eq,
 
     // Literals
     #[regex(r#""[^"]*""#, |lex| {
@@ -112,7 +116,7 @@ pub struct FileTable<'src> {
 
 impl<'src> FileTable<'src> {
     /// Panics if the ID is not found
-    pub fn resolve(&self, file_id: FileId) -> (&str, &PathBuf) {
+    pub fn resolve(&self, file_id: FileId) -> (&'src str, &PathBuf) {
         let (src, path) = self.table.get(&file_id).expect("invalid file ID");
         (src, path)
     }
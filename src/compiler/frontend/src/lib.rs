@@ -9,7 +9,9 @@ pub mod formatter;
 pub mod lexer;
 pub mod parser;
 
-use idl::{CidlType, CrudKind, HttpVerb};
+use std::collections::BTreeMap;
+
+use idl::{CidlType, CrudKind, HttpVerb, SortDirection};
 use indexmap::IndexMap;
 
 use crate::lexer::Token;
@@ -50,10 +52,13 @@ contextual_keywords! {
     Route => "route",
     For => "for",
     Include => "include",
+    OrderBy => "orderBy",
+    Where => "where",
 
     // Block type
     Model => "model",
     Poo => "poo",
+    Enum => "enum",
     Source => "source",
     SelfKw => "self",
     Inject => "inject",
@@ -64,11 +69,25 @@ contextual_keywords! {
     Kv => "kv",
     Durable => "durable",
     Shard => "shard",
+    Queue => "queue",
+    Message => "message",
+    Cron => "cron",
+    Guard => "guard",
+    Service => "service",
+    From => "from",
+    Env => "env",
+    Secret => "secret",
+    Namespace => "namespace",
 
     // CRUD / SQL method
     Get => "get",
     List => "list",
     Save => "save",
+    Count => "count",
+
+    // List clause
+    Asc => "asc",
+    Desc => "desc",
 
     // HTTP verb
     Post => "post",
@@ -81,8 +100,10 @@ contextual_keywords! {
     Internal => "internal",
     Instance => "instance",
     Header => "header",
+    Query => "query",
+    Path => "path",
     Unique => "unique",
-
+    Index => "index",
 
     // Validator tag (numeric)
     LessThan => "lt",
@@ -127,7 +148,7 @@ pub fn fmt_cidl_type(ty: &CidlType) -> String {
         CidlType::Stream => Keyword::TStream.as_str().into(),
         CidlType::Json => Keyword::TJson.as_str().into(),
         CidlType::R2Object => Keyword::TR2Object.as_str().into(),
-        CidlType::Object { name } => name.to_string(),
+        CidlType::Object { name } | CidlType::Enum { name } => name.to_string(),
         CidlType::Partial { object_name } => {
             format!("{}<{}>", Keyword::GPartial.as_str(), object_name)
         }
@@ -140,6 +161,8 @@ pub fn fmt_cidl_type(ty: &CidlType) -> String {
         CidlType::KvObject(inner) => {
             format!("{}<{}>", Keyword::GKvObject.as_str(), fmt_cidl_type(inner))
         }
+        // Not syntactic, but shown for generated `list` methods.
+        CidlType::Page(inner) => format!("page<{}>", fmt_cidl_type(inner)),
         CidlType::Void => panic!("void type should not appear in CidlType formatting"),
     }
 }
@@ -193,6 +216,16 @@ pub enum ArgumentLiteral<'src> {
     Regex(&'src str),
 }
 
+/// A default value assigned to a column, e.g. `100` in `score: int = 100`
+#[derive(Debug, Clone, PartialEq)]
+pub enum DefaultValue<'src> {
+    /// An int, real or string literal
+    Literal(ArgumentLiteral<'src>),
+
+    /// `true`, `false`, `now` or an enum member
+    Ident(&'src str),
+}
+
 #[derive(Debug, Clone)]
 pub struct InjectInitializer<'src> {
     pub target: Symbol<'src>,
@@ -225,15 +258,27 @@ pub enum Tag<'src> {
     /// [Keyword::Header]
     Header,
 
+    /// [Keyword::Query]
+    Query,
+
+    /// [Keyword::Path]
+    Path,
+
     /// [Keyword::Unique]
     Unique { fields: Vec<Symbol<'src>> },
 
+    /// [Keyword::Index]
+    Index { fields: Vec<Symbol<'src>> },
+
     /// [Keyword::Instance]
     Instance,
 
     /// [Keyword::Crud]
     Crud { kinds: Vec<Spd<CrudKind>> },
 
+    /// [Keyword::Guard]
+    Guard { guard: Symbol<'src> },
+
     /// `[Keyword argument]` where [Keyword] _should_ be a validator keyword (e.g [Keyword::LessThan])
     Validator {
         name: Keyword,
@@ -253,6 +298,9 @@ pub struct Symbol<'src> {
     pub span: Span,
 
     pub tags: Vec<Spd<Tag<'src>>>,
+
+    /// `= value` following a column's type, e.g. `score: int = 100`
+    pub default: Option<Box<Spd<DefaultValue<'src>>>>,
 }
 
 impl PartialEq for Symbol<'_> {
@@ -288,11 +336,16 @@ pub struct MethodSource<'src> {
 pub struct ApiBlockMethod<'src> {
     /// The symbol for the method name, e.g. `getUser` in `post getUser(...) { ... }`
     ///
-    /// The [CidlType] of this symbol represents the return type of the API method.
+    /// The [CidlType] of this symbol represents the return type of the API method, and its tags
+    /// are the method's leading tags, e.g. `[guard RequireAdmin]`.
     pub symbol: Symbol<'src>,
 
     pub source: Option<Spd<MethodSource<'src>>>,
     pub http_verb: HttpVerb,
+
+    /// A custom route template, e.g. `"users/{id}/posts"` in `get posts "users/{id}/posts" { ... }`
+    pub route: Option<&'src str>,
+
     pub parameters: Vec<Symbol<'src>>,
     pub injects: Vec<Spd<MethodInjectBlock<'src>>>,
 }
@@ -301,6 +354,23 @@ pub struct DataSourceBlockMethod<'src> {
     pub method: Symbol<'src>,
     pub parameters: Vec<Symbol<'src>>,
     pub injects: Vec<Spd<MethodInjectBlock<'src>>>,
+
+    /// Only ever populated on a `list` method.
+    pub clauses: Vec<Spd<ListClause<'src>>>,
+}
+
+/// A clause shaping a generated `list`.
+pub enum ListClause<'src> {
+    /// [Keyword::OrderBy], e.g. `orderBy createdAt desc, title`
+    OrderBy {
+        terms: Vec<(Symbol<'src>, Option<SortDirection>)>,
+    },
+
+    /// [Keyword::Where], e.g. `where status == status`
+    Where {
+        column: Symbol<'src>,
+        param: Symbol<'src>,
+    },
 }
 
 pub struct ParsedIncludeTree<'src>(
@@ -512,9 +582,26 @@ pub struct PlainOldObjectBlock<'src> {
     pub fields: Vec<Symbol<'src>>,
 }
 
+/// [Keyword::Enum]
+pub struct EnumBlock<'src> {
+    /// The symbol for the enum name, e.g. `Status` in `enum Status { ... }`
+    pub symbol: Symbol<'src>,
+
+    /// The untyped member symbols, e.g. `Open` and `Closed` in `enum Status { Open, Closed }`
+    pub members: Vec<Symbol<'src>>,
+}
+
+/// A binding in a [D1BindingBlock], e.g. `Db` or `Db: "staging-db"`.
+pub struct D1Binding<'src> {
+    pub symbol: Symbol<'src>,
+
+    /// The name of the D1 database the binding uses, spanning the whole string literal.
+    pub database_name: Option<Spd<&'src str>>,
+}
+
 /// [Keyword::D1]
 pub struct D1BindingBlock<'src> {
-    pub bindings: Vec<Symbol<'src>>,
+    pub bindings: Vec<D1Binding<'src>>,
 }
 
 /// [Keyword::Var]
@@ -522,6 +609,11 @@ pub struct VarBlock<'src> {
     pub vars: Vec<Symbol<'src>>,
 }
 
+/// [Keyword::Secret]
+pub struct SecretBlock<'src> {
+    pub secrets: Vec<Symbol<'src>>,
+}
+
 pub struct KvBindingTemplate<'src> {
     /// The symbol naming the field
     ///
@@ -581,6 +673,86 @@ pub struct DurableBindingBlock<'src> {
     pub templates: Vec<Spd<KvBindingTemplate<'src>>>,
 }
 
+/// [Keyword::Queue]
+pub struct QueueBindingBlock<'src> {
+    /// The binding name, e.g. `Emails`.
+    pub symbol: Symbol<'src>,
+
+    /// The [Keyword::Message] symbol, whose [CidlType] is the type of every message on the queue.
+    pub message: Symbol<'src>,
+}
+
+/// [Keyword::Service]
+pub struct ServiceBindingBlock<'src> {
+    /// The binding name, e.g. `Auth`.
+    pub symbol: Symbol<'src>,
+
+    /// The path to the service's CIDL (e.g. `"../auth/.cloesce/cidl.json"`), relative to the
+    /// declaring file, spanning the whole string literal.
+    pub cidl_path: Spd<&'src str>,
+}
+
+/// [Keyword::Cron]
+pub struct CronBlock<'src> {
+    /// The cron expression, e.g. `"0 * * * *"`, spanning the whole string literal (quotes included).
+    pub expression: Spd<&'src str>,
+
+    pub injects: Vec<Spd<MethodInjectBlock<'src>>>,
+}
+
+/// [Keyword::Guard]
+pub struct GuardBlock<'src> {
+    pub symbol: Symbol<'src>,
+    pub injects: Vec<Spd<MethodInjectBlock<'src>>>,
+}
+
+pub enum EnvBlockKind<'src> {
+    D1Binding(D1BindingBlock<'src>),
+    Var(VarBlock<'src>),
+}
+
+/// [Keyword::Env]
+pub struct EnvBlock<'src> {
+    /// The environment name, e.g. `staging` in `env staging { ... }`
+    pub symbol: Symbol<'src>,
+
+    pub blocks: Vec<Spd<EnvBlockKind<'src>>>,
+}
+
+/// [Keyword::Namespace]
+pub struct NamespaceBlock<'src> {
+    /// The namespace name, e.g. `billing` in `namespace billing { ... }`
+    pub symbol: Symbol<'src>,
+
+    /// Models, plain old objects, enums, APIs and data sources declared in the namespace.
+    pub blocks: Vec<Spd<AstBlockKind<'src>>>,
+
+    /// The name each model, plain old object and enum in the namespace is generated as,
+    /// e.g. `billing_Invoice` for `Invoice`.
+    pub qualified: BTreeMap<&'src str, String>,
+}
+
+impl<'src> NamespaceBlock<'src> {
+    pub fn new(symbol: Symbol<'src>, blocks: Vec<Spd<AstBlockKind<'src>>>) -> Self {
+        let qualified = blocks
+            .inners()
+            .filter_map(|b| match b {
+                AstBlockKind::Model(b) => Some(&b.symbol),
+                AstBlockKind::PlainOldObject(b) => Some(&b.symbol),
+                AstBlockKind::Enum(b) => Some(&b.symbol),
+                _ => None,
+            })
+            .map(|s| (s.name, format!("{}_{}", symbol.name, s.name)))
+            .collect();
+
+        Self {
+            symbol,
+            blocks,
+            qualified,
+        }
+    }
+}
+
 /// [Keyword::Inject]
 pub struct InjectBlock<'src> {
     pub symbols: Vec<Symbol<'src>>,
@@ -592,12 +764,27 @@ pub enum AstBlockKind<'src> {
     DataSource(DataSourceBlock<'src>),
     Model(ModelBlock<'src>),
     PlainOldObject(PlainOldObjectBlock<'src>),
+    Enum(EnumBlock<'src>),
     D1Binding(D1BindingBlock<'src>),
     KvBinding(KvBindingBlock<'src>),
     R2Binding(R2BindingBlock<'src>),
     DurableBinding(DurableBindingBlock<'src>),
+    QueueBinding(QueueBindingBlock<'src>),
+    ServiceBinding(ServiceBindingBlock<'src>),
+    Cron(CronBlock<'src>),
+    Guard(GuardBlock<'src>),
+    Env(EnvBlock<'src>),
     Var(VarBlock<'src>),
+    Secret(SecretBlock<'src>),
+    Namespace(NamespaceBlock<'src>),
     Inject(InjectBlock<'src>),
+
+    /// A block that failed to parse, skipped over by [parser::parse_recovering].
+    Error {
+        /// The name the block's header declares, if it got that far, e.g. `Invoice` in
+        /// `model Invoice for db { ... }`
+        symbol: Option<Symbol<'src>>,
+    },
 }
 
 /// The raw parsed structure of a Cloesce source file
@@ -610,8 +797,9 @@ pub struct Ast<'src> {
 impl<'src> Ast<'src> {
     /// Merges another [Ast] into this one by appending all of the blocks from the other into this one's blocks.
     ///
-    /// NOTE: Cloesce does not have any import constructs, so this naive "merge blocks together" strategy
-    /// is sufficient for creating an AST representing the entirety of a multi-file project.
+    /// NOTE: Names are shared across files, with [NamespaceBlock]s scoping their own declarations,
+    /// so this naive "merge blocks together" strategy is sufficient for creating an AST representing
+    /// the entirety of a multi-file project.
     fn merge(&mut self, mut other: Ast<'src>) {
         self.blocks.append(&mut other.blocks);
     }
@@ -3,14 +3,18 @@ use chumsky::prelude::*;
 use idl::HttpVerb;
 
 use crate::{
-    ApiBlock, ApiBlockMethod, AstBlockKind, MethodSource, Symbol,
+    ApiBlock, ApiBlockMethod, AstBlockKind, GuardBlock, MethodSource, Symbol,
     lexer::Token,
-    parser::{Extra, MapSpanned, TokenInput, cidl_type, kw, method_body, symbol},
+    parser::{
+        Extra, MapSpanned, TokenInput, cidl_type, kw, method_body, method_inject_block,
+        qualified_symbol, skip_braced, symbol, tags,
+    },
 };
 
 /// ```cloesce
 /// api Namespace {
-///     self(SourceName) http_verb methodName -> cidl_type {
+///     [tag]*
+///     self(SourceName) http_verb methodName "route/{param}" -> cidl_type {
 ///         [tag]* ident: cidl_type
 ///
 ///         inject {
@@ -24,11 +28,15 @@ use crate::{
 pub fn api_block<'tokens, 'src: 'tokens>()
 -> impl Parser<'tokens, TokenInput<'tokens, 'src>, AstBlockKind<'src>, Extra<'tokens, 'src>> {
     kw!(Api)
-        .ignore_then(symbol())
+        .ignore_then(qualified_symbol())
         .then(
+            // A method that fails to parse is skipped, leaving the rest of the API intact
             method()
+                .map(Some)
+                .recover_with(via_parser(skip_braced().map(|_| None)))
                 .repeated()
                 .collect::<Vec<_>>()
+                .map(|methods| methods.into_iter().flatten().collect())
                 .delimited_by(just(Token::LBrace), just(Token::RBrace)),
         )
         .map(|(symbol, methods)| AstBlockKind::Api(ApiBlock { symbol, methods }))
@@ -36,7 +44,8 @@ pub fn api_block<'tokens, 'src: 'tokens>()
 }
 
 /// ```cloesce
-/// self(sourceName) verb methodName -> returnType {
+/// [tag]*
+/// self(sourceName) verb methodName "route/{param}" -> returnType {
 ///     [tag]* param: cidl_type
 ///
 ///     inject {
@@ -69,21 +78,27 @@ fn method<'tokens, 'src: 'tokens>() -> impl Parser<
         )
         .map_spanned(|source| MethodSource { source });
 
-    source
-        .or_not()
+    tags()
+        .then(source.or_not())
         .then(verb)
         .then(symbol())
+        .then(select! { Token::StringLit(s) => s }.or_not())
         .then(just(Token::Arrow).ignore_then(cidl_type()).or_not())
         .then(method_body())
         .map_spanned(
-            |((((source, http_verb), symbol), return_type), (parameters, injects))| {
+            |(
+                (((((tags, source), http_verb), symbol), route), return_type),
+                (parameters, injects),
+            )| {
                 ApiBlockMethod {
                     symbol: Symbol {
                         cidl_type: return_type.unwrap_or_default(),
+                        tags,
                         ..symbol
                     },
                     source,
                     http_verb,
+                    route,
                     parameters,
                     injects,
                 }
@@ -91,3 +106,25 @@ fn method<'tokens, 'src: 'tokens>() -> impl Parser<
         )
         .boxed()
 }
+
+/// ```cloesce
+/// guard GuardName {
+///     inject {
+///         ident1
+///         ident2
+///     }
+/// }
+/// ```
+pub fn guard_block<'tokens, 'src: 'tokens>()
+-> impl Parser<'tokens, TokenInput<'tokens, 'src>, AstBlockKind<'src>, Extra<'tokens, 'src>> {
+    kw!(Guard)
+        .ignore_then(symbol())
+        .then(
+            method_inject_block()
+                .repeated()
+                .collect::<Vec<_>>()
+                .delimited_by(just(Token::LBrace), just(Token::RBrace)),
+        )
+        .map(|(symbol, injects)| AstBlockKind::Guard(GuardBlock { symbol, injects }))
+        .boxed()
+}
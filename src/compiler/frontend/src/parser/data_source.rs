@@ -1,10 +1,16 @@
 use chumsky::prelude::*;
 use indexmap::IndexMap;
 
+use idl::SortDirection;
+
 use crate::{
-    AstBlockKind, DataSourceBlock, DataSourceBlockMethod, Keyword, ParsedIncludeTree, Spd, Symbol,
+    AstBlockKind, DataSourceBlock, DataSourceBlockMethod, Keyword, ListClause, MethodInjectBlock,
+    ParsedIncludeTree, Spd, Symbol,
     lexer::Token,
-    parser::{Extra, MapSpanned, TokenInput, kw, method_body, symbol, tags},
+    parser::{
+        Extra, MapSpanned, TokenInput, kw, method_body, method_inject_block, qualified_symbol,
+        symbol, tagged_typed_symbol, tags,
+    },
 };
 
 /// ```cloesce
@@ -20,6 +26,11 @@ use crate::{
 ///         ident: cidl_type
 ///     }
 ///
+///     list {
+///         orderBy ident [asc|desc], ...
+///         where ident == ident
+///     }
+///
 ///     save {
 ///         user: partial<User>
 ///     }
@@ -71,21 +82,40 @@ pub fn data_source_block<'tokens, 'src: 'tokens>()
                     },
                     parameters,
                     injects,
+                    clauses: Vec::new(),
                 }
             })
             .boxed()
     };
 
     let get_method = stub("get", Keyword::Get.into());
-    let list_method = stub("list", Keyword::List.into());
     let save_method = stub("save", Keyword::Save.into());
 
+    // [tags]* list { (param | inject | clause)* }
+    let list_method = tags()
+        .then(kw!(List).map_with(|_, e| e.span()))
+        .then(list_body())
+        .map_spanned(
+            |((leading_tags, name_span), (parameters, injects, clauses))| DataSourceBlockMethod {
+                method: Symbol {
+                    name: "list",
+                    span: name_span,
+                    tags: leading_tags,
+                    ..Default::default()
+                },
+                parameters,
+                injects,
+                clauses,
+            },
+        )
+        .boxed();
+
     // [tags]* source SourceName for ModelName { include { ... } get? list? save? }
     let source_block = tags()
         .then_ignore(kw!(Source))
         .then(symbol())
         .then_ignore(kw!(For))
-        .then(symbol())
+        .then(qualified_symbol())
         .then(
             include_tree
                 .or_not()
@@ -112,3 +142,76 @@ pub fn data_source_block<'tokens, 'src: 'tokens>()
 
     source_block.map_spanned(AstBlockKind::DataSource).boxed()
 }
+
+/// A method body which may additionally hold [ListClause]s:
+/// ```cloesce
+/// {
+///     orderBy createdAt desc, title
+///     where status == status
+/// }
+/// ```
+#[allow(clippy::type_complexity)]
+fn list_body<'tokens, 'src: 'tokens>() -> impl Parser<
+    'tokens,
+    TokenInput<'tokens, 'src>,
+    (
+        Vec<Symbol<'src>>,
+        Vec<Spd<MethodInjectBlock<'src>>>,
+        Vec<Spd<ListClause<'src>>>,
+    ),
+    Extra<'tokens, 'src>,
+> {
+    enum Item<'src> {
+        Param(Symbol<'src>),
+        Inject(Spd<MethodInjectBlock<'src>>),
+        Clause(Spd<ListClause<'src>>),
+    }
+
+    let direction = choice((
+        kw!(Asc).to(SortDirection::Asc),
+        kw!(Desc).to(SortDirection::Desc),
+    ));
+
+    // orderBy ident [asc|desc], ...
+    let order_by = kw!(OrderBy)
+        .ignore_then(
+            symbol()
+                .then(direction.or_not())
+                .separated_by(just(Token::Comma))
+                .at_least(1)
+                .collect::<Vec<_>>(),
+        )
+        .map(|terms| ListClause::OrderBy { terms });
+
+    // where ident == ident
+    let filter = kw!(Where)
+        .ignore_then(symbol())
+        .then_ignore(just(Token::EqEq))
+        .then(symbol())
+        .map(|(column, param)| ListClause::Where { column, param });
+
+    let clause = choice((order_by, filter))
+        .map_spanned(|c| c)
+        .map(Item::Clause);
+    let inject = method_inject_block().map(Item::Inject);
+    let param = tagged_typed_symbol().map(Item::Param);
+
+    choice((clause, inject, param))
+        .repeated()
+        .collect::<Vec<_>>()
+        .delimited_by(just(Token::LBrace), just(Token::RBrace))
+        .map(|items| {
+            let mut parameters = Vec::new();
+            let mut injects = Vec::new();
+            let mut clauses = Vec::new();
+            for item in items {
+                match item {
+                    Item::Param(p) => parameters.push(p),
+                    Item::Inject(i) => injects.push(i),
+                    Item::Clause(c) => clauses.push(c),
+                }
+            }
+            (parameters, injects, clauses)
+        })
+        .boxed()
+}
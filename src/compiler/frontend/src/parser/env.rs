@@ -1,31 +1,51 @@
-//! Parses for Cloudflare Environment bindings: D1, KV, R2, Durable Objects, and Variables
+//! Parses for Cloudflare Environment bindings: D1, KV, R2, Durable Objects, Queues, Services,
+//! Cron Triggers, Variables, Secrets, and the named environments overlaying them
 
 use chumsky::prelude::*;
 
 use crate::{
-    AstBlockKind, D1BindingBlock, DurableBindingBlock, DurableShardBlock, KvBindingBlock,
-    KvBindingTemplate, R2BindingBlock, R2BindingTemplate, Spd, Symbol, VarBlock,
+    AstBlockKind, CronBlock, D1Binding, D1BindingBlock, DurableBindingBlock, DurableShardBlock,
+    EnvBlock, EnvBlockKind, Keyword, KvBindingBlock, KvBindingTemplate, QueueBindingBlock,
+    R2BindingBlock, R2BindingTemplate, SecretBlock, ServiceBindingBlock, Spd, Symbol, VarBlock,
     lexer::Token,
-    parser::{Extra, MapSpanned, TokenInput, cidl_type, kw, symbol, tagged_typed_symbol, tags},
+    parser::{
+        Extra, MapSpanned, TokenInput, cidl_type, kw, method_inject_block, symbol,
+        tagged_typed_symbol, tags,
+    },
 };
 
 /// ```cloesce
 /// d1 {
 ///     db
-///     db2
+///     db2: "database-name"
 /// }
 /// ```
 pub fn d1_binding_block<'tokens, 'src: 'tokens>()
 -> impl Parser<'tokens, TokenInput<'tokens, 'src>, AstBlockKind<'src>, Extra<'tokens, 'src>> {
+    d1_bindings().map(AstBlockKind::D1Binding).boxed()
+}
+
+fn d1_bindings<'tokens, 'src: 'tokens>()
+-> impl Parser<'tokens, TokenInput<'tokens, 'src>, D1BindingBlock<'src>, Extra<'tokens, 'src>> {
+    let binding = symbol()
+        .then(
+            just(Token::Colon)
+                .ignore_then(select! { Token::StringLit(value) => value }.map_spanned(|n| n))
+                .or_not(),
+        )
+        .map(|(symbol, database_name)| D1Binding {
+            symbol,
+            database_name,
+        });
+
     kw!(D1)
         .ignore_then(
-            symbol()
+            binding
                 .repeated()
                 .collect::<Vec<_>>()
                 .delimited_by(just(Token::LBrace), just(Token::RBrace)),
         )
-        .map(|bindings| AstBlockKind::D1Binding(D1BindingBlock { bindings }))
-        .boxed()
+        .map(|bindings| D1BindingBlock { bindings })
 }
 
 /// ```cloesce
@@ -36,6 +56,11 @@ pub fn d1_binding_block<'tokens, 'src: 'tokens>()
 /// ```
 pub fn var_block<'tokens, 'src: 'tokens>()
 -> impl Parser<'tokens, TokenInput<'tokens, 'src>, AstBlockKind<'src>, Extra<'tokens, 'src>> {
+    vars().map(AstBlockKind::Var).boxed()
+}
+
+fn vars<'tokens, 'src: 'tokens>()
+-> impl Parser<'tokens, TokenInput<'tokens, 'src>, VarBlock<'src>, Extra<'tokens, 'src>> {
     kw!(Var)
         .ignore_then(
             tagged_typed_symbol()
@@ -43,7 +68,55 @@ pub fn var_block<'tokens, 'src: 'tokens>()
                 .collect::<Vec<_>>()
                 .delimited_by(just(Token::LBrace), just(Token::RBrace)),
         )
-        .map(|vars| AstBlockKind::Var(VarBlock { vars }))
+        .map(|vars| VarBlock { vars })
+}
+
+/// ```cloesce
+/// secret {
+///     STRIPE_KEY: string
+/// }
+/// ```
+pub fn secret_block<'tokens, 'src: 'tokens>()
+-> impl Parser<'tokens, TokenInput<'tokens, 'src>, AstBlockKind<'src>, Extra<'tokens, 'src>> {
+    kw!(Secret)
+        .ignore_then(
+            tagged_typed_symbol()
+                .repeated()
+                .collect::<Vec<_>>()
+                .delimited_by(just(Token::LBrace), just(Token::RBrace)),
+        )
+        .map(|secrets| AstBlockKind::Secret(SecretBlock { secrets }))
+        .boxed()
+}
+
+/// ```cloesce
+/// env staging {
+///     d1 {
+///         db: "staging-db"
+///     }
+///
+///     var {
+///         DEBUG: bool
+///     }
+/// }
+/// ```
+pub fn env_block<'tokens, 'src: 'tokens>()
+-> impl Parser<'tokens, TokenInput<'tokens, 'src>, AstBlockKind<'src>, Extra<'tokens, 'src>> {
+    let block = choice((
+        d1_bindings().map(EnvBlockKind::D1Binding),
+        vars().map(EnvBlockKind::Var),
+    ))
+    .map_spanned(|b| b);
+
+    kw!(Env)
+        .ignore_then(symbol())
+        .then(
+            block
+                .repeated()
+                .collect::<Vec<_>>()
+                .delimited_by(just(Token::LBrace), just(Token::RBrace)),
+        )
+        .map(|(symbol, blocks)| AstBlockKind::Env(EnvBlock { symbol, blocks }))
         .boxed()
 }
 
@@ -176,3 +249,68 @@ pub fn durable_binding_block<'tokens, 'src: 'tokens>()
         })
         .boxed()
 }
+
+/// ```cloesce
+/// queue Emails {
+///     message: EmailJob
+/// }
+/// ```
+pub fn queue_binding_block<'tokens, 'src: 'tokens>()
+-> impl Parser<'tokens, TokenInput<'tokens, 'src>, AstBlockKind<'src>, Extra<'tokens, 'src>> {
+    let message = kw!(Message)
+        .ignore_then(just(Token::Colon))
+        .ignore_then(cidl_type())
+        .map_with(|cidl_type, e| Symbol {
+            name: Keyword::Message.as_str(),
+            cidl_type,
+            span: e.span(),
+            ..Default::default()
+        });
+
+    kw!(Queue)
+        .ignore_then(symbol())
+        .then(message.delimited_by(just(Token::LBrace), just(Token::RBrace)))
+        .map(|(symbol, message)| AstBlockKind::QueueBinding(QueueBindingBlock { symbol, message }))
+        .boxed()
+}
+
+/// ```cloesce
+/// service Auth from "../auth/.cloesce/cidl.json"
+/// ```
+pub fn service_binding_block<'tokens, 'src: 'tokens>()
+-> impl Parser<'tokens, TokenInput<'tokens, 'src>, AstBlockKind<'src>, Extra<'tokens, 'src>> {
+    kw!(Service)
+        .ignore_then(symbol())
+        .then_ignore(kw!(From))
+        .then(select! { Token::StringLit(value) => value }.map_spanned(|p| p))
+        .map(|(symbol, cidl_path)| {
+            AstBlockKind::ServiceBinding(ServiceBindingBlock { symbol, cidl_path })
+        })
+        .boxed()
+}
+
+/// ```cloesce
+/// cron "0 * * * *" {
+///     inject {
+///         Db
+///     }
+/// }
+/// ```
+pub fn cron_block<'tokens, 'src: 'tokens>()
+-> impl Parser<'tokens, TokenInput<'tokens, 'src>, AstBlockKind<'src>, Extra<'tokens, 'src>> {
+    kw!(Cron)
+        .ignore_then(select! { Token::StringLit(value) => value }.map_spanned(|e| e))
+        .then(
+            method_inject_block()
+                .repeated()
+                .collect::<Vec<_>>()
+                .delimited_by(just(Token::LBrace), just(Token::RBrace)),
+        )
+        .map(|(expression, injects)| {
+            AstBlockKind::Cron(CronBlock {
+                expression,
+                injects,
+            })
+        })
+        .boxed()
+}
@@ -10,8 +10,8 @@ use idl::{CidlType, CrudKind};
 
 use crate::lexer::{FileTable, LexedFile, SpannedToken, Token};
 use crate::{
-    ArgumentLiteral, Ast, AstBlockKind, InjectBlock, InjectEntry, InjectInitializer, Keyword,
-    MethodInjectBlock, PlainOldObjectBlock, Span, Spd, Symbol, Tag,
+    ArgumentLiteral, Ast, AstBlockKind, EnumBlock, InjectBlock, InjectEntry, InjectInitializer,
+    Keyword, MethodInjectBlock, NamespaceBlock, PlainOldObjectBlock, Span, Spd, Symbol, Tag,
 };
 
 pub type ParserError<'tokens, 'src> = Vec<Rich<'tokens, Token<'src>, Span>>;
@@ -21,6 +21,19 @@ pub fn parse<'tokens, 'src: 'tokens>(
     lexed: &'tokens [LexedFile<'src>],
     file_table: &'tokens FileTable<'src>,
 ) -> Result<Ast<'src>, ParserError<'tokens, 'src>> {
+    let (ast, errors) = parse_recovering(lexed, file_table);
+    errors.is_empty().then_some(ast).ok_or(errors)
+}
+
+/// As [parse], but returns the [Ast] even if errors were encountered, so that later phases can
+/// still report on everything that did parse.
+///
+/// A block that fails to parse is left as an [AstBlockKind::Error] (see [skip_block]), while a
+/// model member or API method that fails to parse is dropped from its block (see [skip_braced]).
+pub fn parse_recovering<'tokens, 'src: 'tokens>(
+    lexed: &'tokens [LexedFile<'src>],
+    file_table: &'tokens FileTable<'src>,
+) -> (Ast<'src>, ParserError<'tokens, 'src>) {
     let mut ast = Ast::default();
     let mut errors = Vec::new();
 
@@ -33,15 +46,27 @@ pub fn parse<'tokens, 'src: 'tokens>(
             context: lf.file_id,
         });
 
-        let res = parser().parse(input).into_result();
-
-        match res {
-            Ok(res) => ast.merge(res),
-            Err(errs) => errors.extend(errs),
+        let (res, errs) = file_parser(src).parse(input).into_output_errors();
+        if let Some(res) = res {
+            ast.merge(res);
         }
+        errors.extend(errs);
     }
 
-    errors.is_empty().then_some(ast).ok_or(errors)
+    (ast, errors)
+}
+
+/// Runs [parser] over a single file, with the file's source as the parser context (qualified names,
+/// e.g. `billing::Invoice`, are sliced from the source, see [qualified_symbol]).
+fn file_parser<'tokens, 'src: 'tokens>(
+    src: &'src str,
+) -> impl Parser<
+    'tokens,
+    TokenInput<'tokens, 'src>,
+    Ast<'src>,
+    extra::Err<Rich<'tokens, Token<'src>, Span>>,
+> {
+    parser().with_ctx(src)
 }
 
 fn parser<'tokens, 'src: 'tokens>()
@@ -53,14 +78,142 @@ fn parser<'tokens, 'src: 'tokens>()
         env::kv_binding_block().map_spanned(|b| b),
         env::r2_binding_block().map_spanned(|b| b),
         env::durable_binding_block().map_spanned(|b| b),
+        env::queue_binding_block().map_spanned(|b| b),
+        env::service_binding_block().map_spanned(|b| b),
+        env::cron_block().map_spanned(|b| b),
+        api::guard_block().map_spanned(|b| b),
+        env::env_block().map_spanned(|b| b),
         env::var_block().map_spanned(|b| b),
+        env::secret_block().map_spanned(|b| b),
         api::api_block().map_spanned(|b| b),
         poo_block().map_spanned(|b| b),
+        enum_block().map_spanned(|b| b),
         inject_block().map_spanned(|b| b),
+        namespace_block().map_spanned(|b| b),
     ))
+    .map(Some)
+    .recover_with(via_parser(skip_block().map_spanned(|b| b).map(Some)))
+    // Unbalanced braces can't be skipped over, so resume at the next token starting a block,
+    // dropping the rest of the file if there is none
+    .recover_with(skip_then_retry_until(any().ignored(), end()))
+    .recover_with(via_parser(any().repeated().at_least(1).map(|_| None)))
     .repeated()
     .collect::<Vec<_>>()
-    .map(|blocks| Ast { blocks })
+    .map(|blocks| Ast {
+        blocks: blocks.into_iter().flatten().collect(),
+    })
+}
+
+/// Recovers from an error in a block (or a member of one, such as an API method) by skipping the
+/// rest of its header and its balanced `{ ... }` body, resuming at whatever follows.
+///
+/// Fails without consuming anything if the braces are unbalanced.
+fn skip_braced<'tokens, 'src: 'tokens>()
+-> impl Parser<'tokens, TokenInput<'tokens, 'src>, (), Extra<'tokens, 'src>> + Clone {
+    none_of([Token::LBrace, Token::RBrace])
+        .repeated()
+        .then(nested_delimiters(
+            Token::LBrace,
+            Token::RBrace,
+            [
+                (Token::LParen, Token::RParen),
+                (Token::LBracket, Token::RBracket),
+            ],
+            |_| (),
+        ))
+        .ignored()
+}
+
+/// As [skip_braced], keeping the name the skipped block's header declares (e.g. `Invoice` in
+/// `model Invoice for db { ... }`) so references to it aren't reported as unresolved.
+fn skip_block<'tokens, 'src: 'tokens>()
+-> impl Parser<'tokens, TokenInput<'tokens, 'src>, AstBlockKind<'src>, Extra<'tokens, 'src>> + Clone
+{
+    none_of([Token::LBrace, Token::RBrace])
+        .map_with(|token, e| (token, e.span()))
+        .repeated()
+        .collect::<Vec<_>>()
+        .then_ignore(nested_delimiters(
+            Token::LBrace,
+            Token::RBrace,
+            [
+                (Token::LParen, Token::RParen),
+                (Token::LBracket, Token::RBracket),
+            ],
+            |_| (),
+        ))
+        .map(|header| AstBlockKind::Error {
+            symbol: declared_name(&header),
+        })
+}
+
+/// The name following the first declaring keyword of a block header, outside of its tags.
+fn declared_name<'src>(header: &[(Token<'src>, Span)]) -> Option<Symbol<'src>> {
+    const DECLARING: [Keyword; 11] = [
+        Keyword::Model,
+        Keyword::Poo,
+        Keyword::Enum,
+        Keyword::Source,
+        Keyword::Guard,
+        Keyword::Durable,
+        Keyword::Kv,
+        Keyword::R2,
+        Keyword::Queue,
+        Keyword::Service,
+        Keyword::Env,
+    ];
+
+    let mut depth = 0usize;
+    for (i, (token, _)) in header.iter().enumerate() {
+        match token {
+            Token::LBracket => depth += 1,
+            Token::RBracket => depth = depth.saturating_sub(1),
+            _ if depth == 0 && DECLARING.into_iter().any(|kw| Token::from(kw) == *token) => {
+                let (Token::Ident(name), span) = header.get(i + 1)? else {
+                    return None;
+                };
+                return Some(Symbol {
+                    name,
+                    span: *span,
+                    ..Default::default()
+                });
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// ```cloesce
+/// namespace billing {
+///     model Invoice { ... }
+///     poo LineItem { ... }
+/// }
+/// ```
+///
+/// Only models, plain old objects, enums, APIs and data sources can be namespaced; bindings
+/// are shared by the whole Worker.
+fn namespace_block<'tokens, 'src: 'tokens>()
+-> impl Parser<'tokens, TokenInput<'tokens, 'src>, AstBlockKind<'src>, Extra<'tokens, 'src>> {
+    let member = choice((
+        model::model_block(),
+        data_source::data_source_block(),
+        api::api_block().map_spanned(|b| b),
+        poo_block().map_spanned(|b| b),
+        enum_block().map_spanned(|b| b),
+    ));
+
+    kw!(Namespace)
+        .ignore_then(symbol())
+        .then(
+            member
+                .recover_with(via_parser(skip_block().map_spanned(|b| b)))
+                .repeated()
+                .collect::<Vec<_>>()
+                .delimited_by(just(Token::LBrace), just(Token::RBrace)),
+        )
+        .map(|(symbol, blocks)| AstBlockKind::Namespace(NamespaceBlock::new(symbol, blocks)))
+        .boxed()
 }
 
 /// ```cloesce
@@ -86,6 +239,28 @@ fn poo_block<'tokens, 'src: 'tokens>()
         .boxed()
 }
 
+/// ```cloesce
+/// enum MyEnum {
+///     Member1,
+///     Member2,
+///     ...
+/// }
+/// ```
+fn enum_block<'tokens, 'src: 'tokens>()
+-> impl Parser<'tokens, TokenInput<'tokens, 'src>, AstBlockKind<'src>, Extra<'tokens, 'src>> {
+    kw!(Enum)
+        .ignore_then(symbol())
+        .then(
+            symbol()
+                .separated_by(just(Token::Comma))
+                .allow_trailing()
+                .collect::<Vec<_>>()
+                .delimited_by(just(Token::LBrace), just(Token::RBrace)),
+        )
+        .map(|(symbol, members)| AstBlockKind::Enum(EnumBlock { symbol, members }))
+        .boxed()
+}
+
 /// ```cloesce
 /// inject {
 ///     ident1
@@ -131,7 +306,7 @@ fn tags<'tokens, 'src: 'tokens>()
         .then_ignore(just(Token::RBracket))
         .map(|(name, argument)| Tag::Validator { name, argument });
 
-    // [crud get|list|save, get|list|save, ...]
+    // [crud get|list|save|delete|count, get|list|save|delete|count, ...]
     let crud_tag = just(Token::LBracket)
         .then(kw!(Crud))
         .ignore_then(
@@ -139,6 +314,8 @@ fn tags<'tokens, 'src: 'tokens>()
                 kw!(Get).to(CrudKind::Get),
                 kw!(List).to(CrudKind::List),
                 kw!(Save).to(CrudKind::Save),
+                kw!(Delete).to(CrudKind::Delete),
+                kw!(Count).to(CrudKind::Count),
             ))
             .map_spanned(|b| b)
             .separated_by(just(Token::Comma))
@@ -160,6 +337,25 @@ fn tags<'tokens, 'src: 'tokens>()
         .then_ignore(just(Token::RBracket))
         .map(|_| Tag::Header);
 
+    // [query]
+    let query_tag = just(Token::LBracket)
+        .then(kw!(Query))
+        .then_ignore(just(Token::RBracket))
+        .map(|_| Tag::Query);
+
+    // [path]
+    let path_tag = just(Token::LBracket)
+        .then(kw!(Path))
+        .then_ignore(just(Token::RBracket))
+        .map(|_| Tag::Path);
+
+    // [guard ident]
+    let guard_tag = just(Token::LBracket)
+        .then(kw!(Guard))
+        .ignore_then(symbol())
+        .then_ignore(just(Token::RBracket))
+        .map(|guard| Tag::Guard { guard });
+
     // [instance]
     let instance_tag = just(Token::LBracket)
         .then(kw!(Instance))
@@ -178,13 +374,29 @@ fn tags<'tokens, 'src: 'tokens>()
         .then_ignore(just(Token::RBracket))
         .map(|fields| Tag::Unique { fields });
 
+    // [index a1, a2, ...]
+    let index_tag = just(Token::LBracket)
+        .then(kw!(Index))
+        .ignore_then(
+            symbol()
+                .separated_by(just(Token::Comma))
+                .allow_trailing()
+                .collect::<Vec<_>>(),
+        )
+        .then_ignore(just(Token::RBracket))
+        .map(|fields| Tag::Index { fields });
+
     choice((
         validator,
         crud_tag,
         internal_tag,
         header_tag,
+        query_tag,
+        path_tag,
+        guard_tag,
         instance_tag,
         unique_tag,
+        index_tag,
     ))
     .map_spanned(|tag| tag)
     .repeated()
@@ -206,6 +418,49 @@ fn symbol<'tokens, 'src: 'tokens>()
     })
 }
 
+/// ```cloesce
+/// ident
+/// namespace::ident
+/// ```
+///
+/// A qualified name is kept as written in the source (e.g. `billing::Invoice`), to be resolved
+/// during semantic analysis.
+fn qualified_symbol<'tokens, 'src: 'tokens>()
+-> impl Parser<'tokens, TokenInput<'tokens, 'src>, Symbol<'src>, Extra<'tokens, 'src>> {
+    symbol()
+        .then(just(Token::DoubleColon).ignore_then(symbol()).or_not())
+        .try_map_with(|(first, last), e| qualify(first, last, e.ctx()))
+}
+
+/// Joins `namespace` and `name` into one [Symbol] spanning both, e.g. `billing::Invoice`.
+fn qualify<'tokens, 'src>(
+    namespace: Symbol<'src>,
+    name: Option<Symbol<'src>>,
+    src: &&'src str,
+) -> Result<Symbol<'src>, Rich<'tokens, Token<'src>, Span>> {
+    let Some(name) = name else {
+        return Ok(namespace);
+    };
+
+    let span = Span::new(
+        namespace.span.context(),
+        namespace.span.start..name.span.end,
+    );
+    let qualified = &src[span.start..span.end];
+    if qualified.contains(char::is_whitespace) {
+        return Err(Rich::custom(
+            span,
+            "qualified names cannot contain whitespace",
+        ));
+    }
+
+    Ok(Symbol {
+        name: qualified,
+        span,
+        ..Default::default()
+    })
+}
+
 /// ```cloesce
 /// ident: cidl_type
 /// ```
@@ -257,6 +512,37 @@ fn method_body<'tokens, 'src: 'tokens>() -> impl Parser<
         Inject(Spd<MethodInjectBlock<'src>>),
     }
 
+    let inject = method_inject_block().map(Item::Inject).boxed();
+    let param = tagged_typed_symbol().map(Item::Param).boxed();
+    let item = choice((inject, param)).boxed();
+
+    item.repeated()
+        .collect::<Vec<_>>()
+        .delimited_by(just(Token::LBrace), just(Token::RBrace))
+        .map(|items| {
+            let mut parameters = Vec::new();
+            let mut injects = Vec::new();
+            for item in items {
+                match item {
+                    Item::Param(p) => parameters.push(p),
+                    Item::Inject(i) => injects.push(i),
+                }
+            }
+            (parameters, injects)
+        })
+        .boxed()
+}
+
+/// ```cloesce
+/// inject {
+///     ident1
+///     ident2::target(arg)
+///     ident3::{ target1(arg1), target2(arg2) }
+/// }
+/// ```
+fn method_inject_block<'tokens, 'src: 'tokens>()
+-> impl Parser<'tokens, TokenInput<'tokens, 'src>, Spd<MethodInjectBlock<'src>>, Extra<'tokens, 'src>>
+{
     // `target(arg)`
     let initializer = || {
         symbol()
@@ -286,7 +572,7 @@ fn method_body<'tokens, 'src: 'tokens>() -> impl Parser<
         });
 
     // `inject { entry* }`
-    let inject_block = kw!(Inject)
+    kw!(Inject)
         .ignore_then(
             inject_entry
                 .map_spanned(|e| e)
@@ -294,26 +580,7 @@ fn method_body<'tokens, 'src: 'tokens>() -> impl Parser<
                 .collect::<Vec<_>>()
                 .delimited_by(just(Token::LBrace), just(Token::RBrace)),
         )
-        .map_spanned(|entries| MethodInjectBlock { entries });
-
-    let inject = inject_block.map(Item::Inject).boxed();
-    let param = tagged_typed_symbol().map(Item::Param).boxed();
-    let item = choice((inject, param)).boxed();
-
-    item.repeated()
-        .collect::<Vec<_>>()
-        .delimited_by(just(Token::LBrace), just(Token::RBrace))
-        .map(|items| {
-            let mut parameters = Vec::new();
-            let mut injects = Vec::new();
-            for item in items {
-                match item {
-                    Item::Param(p) => parameters.push(p),
-                    Item::Inject(i) => injects.push(i),
-                }
-            }
-            (parameters, injects)
-        })
+        .map_spanned(|entries| MethodInjectBlock { entries })
         .boxed()
 }
 
@@ -357,8 +624,7 @@ fn cidl_type<'tokens, 'src: 'tokens>()
         });
 
         // If unresolved, assume its an object
-        let unresolved_type =
-            select! { Token::Ident(name) => name }.map(|name: &str| CidlType::Object { name });
+        let unresolved_type = qualified_symbol().map(|s| CidlType::Object { name: s.name });
 
         choice((generic, primitive_keyword, unresolved_type)).boxed()
     })
@@ -375,7 +641,8 @@ pub(crate) use kw;
 type TokenInput<'tokens, 'src> =
     MappedInput<'tokens, Token<'src>, Span, &'tokens [SpannedToken<'src>]>;
 
-type Extra<'tokens, 'src> = extra::Err<Rich<'tokens, Token<'src>, Span>>;
+/// Parsers run with the source being parsed as their context, see [qualified_symbol].
+type Extra<'tokens, 'src> = extra::Full<Rich<'tokens, Token<'src>, Span>, (), &'src str>;
 
 trait MapSpanned<'tokens, 'src: 'tokens, O>:
     Parser<'tokens, TokenInput<'tokens, 'src>, O, Extra<'tokens, 'src>> + Sized
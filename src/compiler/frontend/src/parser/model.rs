@@ -1,12 +1,30 @@
 use chumsky::prelude::*;
 
 use crate::{
-    AstBlockKind, Cardinality, ForeignBlock, KvFieldArgument, KvFieldBlock, ModelBlock,
-    ModelBlockKind, NavigationBlock, NavigationKey, R2FieldBlock, Spd, SqlBlockKind, Symbol,
+    ArgumentLiteral, AstBlockKind, Cardinality, DefaultValue, ForeignBlock, KvFieldArgument,
+    KvFieldBlock, ModelBlock, ModelBlockKind, NavigationBlock, NavigationKey, R2FieldBlock, Spd,
+    SqlBlockKind, Symbol,
     lexer::Token,
-    parser::{Extra, MapSpanned, TokenInput, kw, symbol, tagged_typed_symbol, tags},
+    parser::{
+        Extra, MapSpanned, TokenInput, kw, qualify, skip_braced, symbol, tagged_typed_symbol, tags,
+    },
 };
 
+/// `Model` or `namespace::Model`, where a qualified name must be followed by `::`, as in
+/// `billing::Invoice::id`.
+///
+/// Without that lookahead, `Model::target` could not be told apart from `namespace::Model`.
+fn model_reference<'tokens, 'src: 'tokens>()
+-> impl Parser<'tokens, TokenInput<'tokens, 'src>, Symbol<'src>, Extra<'tokens, 'src>> {
+    let qualified = symbol()
+        .then_ignore(just(Token::DoubleColon))
+        .then(symbol())
+        .then_ignore(just(Token::DoubleColon).rewind())
+        .try_map_with(|(namespace, name), e| qualify(namespace, Some(name), e.ctx()));
+
+    choice((qualified, symbol()))
+}
+
 /// `foreign AdjModel::field [optional] { localField ... }`
 /// or `foreign AdjModel::{ field1, field2 } [option] { localField ... }`.
 fn foreign_block<'tokens, 'src: 'tokens>()
@@ -23,7 +41,7 @@ fn foreign_block<'tokens, 'src: 'tokens>()
     )));
 
     kw!(Foreign)
-        .ignore_then(symbol())
+        .ignore_then(model_reference())
         .then(targets)
         .then(kw!(GOption).or_not())
         .then(
@@ -41,6 +59,20 @@ fn foreign_block<'tokens, 'src: 'tokens>()
         .boxed()
 }
 
+/// `= 100` | `= 1.5` | `= "text"` | `= true` | `= now` | `= EnumMember`
+fn column_default<'tokens, 'src: 'tokens>()
+-> impl Parser<'tokens, TokenInput<'tokens, 'src>, Spd<DefaultValue<'src>>, Extra<'tokens, 'src>> {
+    just(Token::Eq).ignore_then(
+        choice((
+            select! { Token::IntLit(s) => DefaultValue::Literal(ArgumentLiteral::Int(s)) },
+            select! { Token::RealLit(s) => DefaultValue::Literal(ArgumentLiteral::Real(s)) },
+            select! { Token::StringLit(s) => DefaultValue::Literal(ArgumentLiteral::Str(s)) },
+            select! { Token::Ident(s) => DefaultValue::Ident(s) },
+        ))
+        .map_spanned(|d| d),
+    )
+}
+
 /// `kv Binding::target(local1, ...) { localField }`
 /// | `kv Binding::target { localField }`
 /// | `kv Binding::{ template(args), shardField(local), ... } { localField }`.
@@ -119,9 +151,14 @@ fn r2_field_block<'tokens, 'src: 'tokens>()
 
 pub fn model_block<'tokens, 'src: 'tokens>()
 -> impl Parser<'tokens, TokenInput<'tokens, 'src>, Spd<AstBlockKind<'src>>, Extra<'tokens, 'src>> {
-    // `column { ([tag]* ident: cidl_type)* }`
+    // `column { ([tag]* ident: cidl_type (= default)?)* }`
     let column_block = kw!(Column).ignore_then(
         tagged_typed_symbol()
+            .then(column_default().or_not())
+            .map(|(sym, default)| Symbol {
+                default: default.map(Box::new),
+                ..sym
+            })
             .repeated()
             .collect::<Vec<_>>()
             .delimited_by(just(Token::LBrace), just(Token::RBrace))
@@ -186,7 +223,7 @@ pub fn model_block<'tokens, 'src: 'tokens>()
         ));
 
         cardinality
-            .then(symbol())
+            .then(model_reference())
             .then(keys)
             .then(
                 symbol()
@@ -230,10 +267,14 @@ pub fn model_block<'tokens, 'src: 'tokens>()
         .then(symbol())
         .then(backing.or_not())
         .then(
+            // A member that fails to parse is skipped, leaving the rest of the model intact
             sub_blocks
                 .map_spanned(|k| k)
+                .map(Some)
+                .recover_with(via_parser(skip_braced().map(|_| None)))
                 .repeated()
                 .collect::<Vec<_>>()
+                .map(|blocks| blocks.into_iter().flatten().collect())
                 .delimited_by(just(Token::LBrace), just(Token::RBrace)),
         )
         .map(|(((tags, symbol), backing), blocks)| {
@@ -138,3 +138,177 @@ fn comments_retained() {
 
     insta::assert_snapshot!(formatted);
 }
+
+#[test]
+fn format_list_clauses() {
+    // Arrange
+    let src = r#"
+    source Recent for Post {
+        include {}
+        list {
+            // newest first
+            orderBy   createdAt desc,title
+            where status==status
+        }
+    }
+    "#;
+
+    let (parse_ast, lex_results, _) = lex_parse(src);
+
+    // Act
+    let formatted = formatter::format(&parse_ast, &lex_results[0].comment_map, src);
+
+    // Assert
+    insta::assert_snapshot!(formatted);
+}
+
+#[test]
+fn format_column_defaults() {
+    // Arrange
+    let src = r#"
+    model Player for db {
+        primary { id: int }
+        column {
+            [gte 0]
+            score: int=100 // starting score
+            name: string   =   "anon"
+            joined: date = now
+        }
+    }
+    "#;
+
+    let (parse_ast, lex_results, _) = lex_parse(src);
+
+    // Act
+    let formatted = formatter::format(&parse_ast, &lex_results[0].comment_map, src);
+
+    // Assert
+    insta::assert_snapshot!(formatted);
+}
+
+#[test]
+fn format_service_bindings() {
+    // Arrange
+    let src = r#"
+    // the auth worker
+    service   Auth   from    "../auth/.cloesce/cidl.json"
+    service Billing from "../billing/.cloesce/cidl.json" // trailing
+    "#;
+
+    let (parse_ast, lex_results, _) = lex_parse(src);
+
+    // Act
+    let formatted = formatter::format(&parse_ast, &lex_results[0].comment_map, src);
+
+    // Assert
+    insta::assert_snapshot!(formatted);
+}
+
+#[test]
+fn format_cron_blocks() {
+    // Arrange
+    let src = r#"
+    cron   "*/5 * * * *"   {
+        // every five minutes
+        inject { Db   Auth }
+    }
+    cron "0 0 * * SUN" {   }
+    "#;
+
+    let (parse_ast, lex_results, _) = lex_parse(src);
+
+    // Act
+    let formatted = formatter::format(&parse_ast, &lex_results[0].comment_map, src);
+
+    // Assert
+    insta::assert_snapshot!(formatted);
+}
+
+#[test]
+fn format_guards() {
+    // Arrange
+    let src = r#"
+    guard   RequireAdmin {
+        inject { Db   Auth }
+    }
+    guard RequireUser {   }
+    api User {
+        [guard RequireAdmin]   [guard RequireUser]   get ping -> string {}
+    }
+    "#;
+
+    let (parse_ast, lex_results, _) = lex_parse(src);
+
+    // Act
+    let formatted = formatter::format(&parse_ast, &lex_results[0].comment_map, src);
+
+    // Assert
+    insta::assert_snapshot!(formatted);
+}
+
+#[test]
+fn format_env_blocks() {
+    // Arrange
+    let src = r#"
+    d1 {   db :   "prod-db"   audit }
+    env   staging {
+        // staging has its own database
+        d1 { db: "staging-db" // trailing
+        }
+        var { DEBUG:   bool }
+    }
+    env preview {   }
+    "#;
+
+    let (parse_ast, lex_results, _) = lex_parse(src);
+
+    // Act
+    let formatted = formatter::format(&parse_ast, &lex_results[0].comment_map, src);
+
+    // Assert
+    insta::assert_snapshot!(formatted);
+}
+
+#[test]
+fn format_secret_block() {
+    // Arrange
+    let src = r#"
+    var { API_URL:string }
+    secret {   STRIPE_KEY :   string // live key
+    WEBHOOK_SECRET: string }
+    secret {}
+    "#;
+
+    let (parse_ast, lex_results, _) = lex_parse(src);
+
+    // Act
+    let formatted = formatter::format(&parse_ast, &lex_results[0].comment_map, src);
+
+    // Assert
+    insta::assert_snapshot!(formatted);
+}
+
+#[test]
+fn format_namespace_block() {
+    // Arrange
+    let src = r#"
+    namespace   billing {
+    // Invoice lifecycle
+    enum Status { Open, Paid }
+    model Invoice for db { primary { id: int }
+    foreign shared::Customer::id { customerId }
+    many LineItem::invoiceId(id) { items } }
+    api Invoice { self post pay -> Status {} }
+    }
+    namespace empty {}
+    api billing::Invoice { get latest -> billing::Invoice {} }
+    "#;
+
+    let (parse_ast, lex_results, _) = lex_parse(src);
+
+    // Act
+    let formatted = formatter::format(&parse_ast, &lex_results[0].comment_map, src);
+
+    // Assert
+    insta::assert_snapshot!(formatted);
+}
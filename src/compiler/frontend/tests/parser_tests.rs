@@ -1,9 +1,14 @@
+use std::path::PathBuf;
+
 use compiler_test::lex_and_ast;
 use frontend::{
-    ArgumentLiteral, Ast, AstBlockKind, Cardinality, ForeignBlock, InjectEntry, Keyword,
-    ModelBlock, ModelBlockKind, NavigationKey, Spd, SqlBlockKind, Tag,
+    ArgumentLiteral, Ast, AstBlockKind, Cardinality, DefaultValue, EnvBlockKind, ForeignBlock,
+    InjectEntry, Keyword, ListClause, ModelBlock, ModelBlockKind, NavigationKey, Spd, SqlBlockKind,
+    Tag,
+    lexer::{self, LexTarget},
+    parser,
 };
-use idl::{CidlType, CrudKind, HttpVerb};
+use idl::{CidlType, CrudKind, HttpVerb, SortDirection};
 
 /// Matches a foreign block against its referenced `model` and `targets`.
 fn foreign_matches(fb: &ForeignBlock, model: &str, targets: &[&str]) -> bool {
@@ -62,7 +67,7 @@ fn top_level_bindings() {
         .iter()
         .find_map(|spd| match &spd.inner {
             AstBlockKind::D1Binding(b) => {
-                Some(b.bindings.iter().map(|s| s.name).collect::<Vec<_>>())
+                Some(b.bindings.iter().map(|b| b.symbol.name).collect::<Vec<_>>())
             }
             _ => None,
         })
@@ -220,6 +225,391 @@ fn durable_binding_block() {
     assert_eq!(global.templates[0].inner.symbol.name, "config");
 }
 
+#[test]
+fn env_block() {
+    // Act
+    let ast = lex_and_ast(
+        r#"
+        d1 {
+            db: "prod-db"
+            audit
+        }
+
+        env staging {
+            d1 {
+                db: "staging-db"
+            }
+
+            var {
+                DEBUG: bool
+            }
+        }
+
+        env preview {}
+        "#,
+    );
+
+    // Assert
+    let d1 = ast
+        .blocks
+        .iter()
+        .find_map(|spd| match &spd.inner {
+            AstBlockKind::D1Binding(b) => Some(b),
+            _ => None,
+        })
+        .expect("d1 binding block to be present");
+    assert_eq!(
+        d1.bindings[0].database_name.as_ref().map(|n| n.inner),
+        Some("prod-db")
+    );
+    assert!(d1.bindings[1].database_name.is_none());
+
+    let envs = ast
+        .blocks
+        .iter()
+        .filter_map(|spd| match &spd.inner {
+            AstBlockKind::Env(b) => Some(b),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(envs.len(), 2);
+    assert_eq!(envs[0].symbol.name, "staging");
+    assert_eq!(envs[0].blocks.len(), 2);
+
+    let EnvBlockKind::D1Binding(d1) = &envs[0].blocks[0].inner else {
+        panic!("expected a d1 block");
+    };
+    assert_eq!(d1.bindings[0].symbol.name, "db");
+    assert_eq!(
+        d1.bindings[0].database_name.as_ref().map(|n| n.inner),
+        Some("staging-db")
+    );
+
+    let EnvBlockKind::Var(vars) = &envs[0].blocks[1].inner else {
+        panic!("expected a var block");
+    };
+    assert_eq!(vars.vars[0].name, "DEBUG");
+    assert_eq!(vars.vars[0].cidl_type, CidlType::Boolean);
+
+    assert_eq!(envs[1].symbol.name, "preview");
+    assert!(envs[1].blocks.is_empty());
+}
+
+#[test]
+fn secret_block() {
+    // Act
+    let ast = lex_and_ast(
+        r#"
+        secret {
+            STRIPE_KEY: string
+            SIGNING_SEED: int
+        }
+
+        secret {}
+        "#,
+    );
+
+    // Assert
+    let secrets = ast
+        .blocks
+        .iter()
+        .filter_map(|spd| match &spd.inner {
+            AstBlockKind::Secret(b) => Some(
+                b.secrets
+                    .iter()
+                    .map(|s| (s.name, &s.cidl_type))
+                    .collect::<Vec<_>>(),
+            ),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        secrets,
+        vec![
+            vec![
+                ("STRIPE_KEY", &CidlType::String),
+                ("SIGNING_SEED", &CidlType::Int)
+            ],
+            vec![]
+        ]
+    );
+}
+
+#[test]
+fn namespace_block() {
+    // Act
+    let ast = lex_and_ast(
+        r#"
+        namespace billing {
+            enum Status { Open, Paid }
+
+            model Invoice for db {
+                primary { id: int }
+                column { status: Status }
+                foreign shared::Customer::id { customerId }
+                many LineItem::invoiceId(id) { items }
+            }
+
+            api Invoice {}
+        }
+
+        api billing::Invoice {
+            get latest -> billing::Invoice {}
+        }
+
+        source Recent for billing::Invoice {
+            include {}
+        }
+        "#,
+    );
+
+    // Assert
+    let AstBlockKind::Namespace(ns) = &ast.blocks[0].inner else {
+        panic!("expected a namespace block");
+    };
+    assert_eq!(ns.symbol.name, "billing");
+    assert_eq!(ns.blocks.len(), 3);
+    assert_eq!(
+        ns.qualified
+            .iter()
+            .map(|(k, v)| (*k, v.as_str()))
+            .collect::<Vec<_>>(),
+        vec![("Invoice", "billing_Invoice"), ("Status", "billing_Status")]
+    );
+
+    let AstBlockKind::Model(invoice) = &ns.blocks[1].inner else {
+        panic!("expected a model block");
+    };
+    let fb = invoice
+        .blocks
+        .iter()
+        .find_map(|spd| match &spd.inner {
+            ModelBlockKind::Foreign(fb) => Some(fb),
+            _ => None,
+        })
+        .unwrap();
+    assert!(foreign_matches(fb, "shared::Customer", &["id"]));
+
+    let nav = invoice
+        .blocks
+        .iter()
+        .find_map(|spd| match &spd.inner {
+            ModelBlockKind::Navigation(nav) => Some(nav),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(nav.model.name, "LineItem");
+
+    let AstBlockKind::Api(api) = &ast.blocks[1].inner else {
+        panic!("expected an api block");
+    };
+    assert_eq!(api.symbol.name, "billing::Invoice");
+    assert_eq!(
+        api.methods[0].inner.symbol.cidl_type,
+        CidlType::Object {
+            name: "billing::Invoice"
+        }
+    );
+
+    let AstBlockKind::DataSource(ds) = &ast.blocks[2].inner else {
+        panic!("expected a data source block");
+    };
+    assert_eq!(ds.model.name, "billing::Invoice");
+}
+
+#[test]
+fn parse_recovering_keeps_valid_blocks() {
+    // Arrange
+    let src = r#"
+        model A for db {
+            primary { id: int }
+            column { x int }
+            column { y: string }
+        }
+
+        api A {
+            get broken -> -> int {}
+            get fine -> string {}
+        }
+
+        poo Broken { x: }
+
+        poo Fine { x: int }
+        "#;
+    let (lexed, file_table) = lexer::lex(vec![LexTarget {
+        src,
+        path: PathBuf::from("<test>"),
+    }])
+    .unwrap_or_else(|_| panic!("lexing should succeed"));
+
+    // Act
+    let (ast, errors) = parser::parse_recovering(&lexed, &file_table);
+
+    // Assert
+    assert_eq!(errors.len(), 3);
+    assert_eq!(ast.blocks.len(), 4);
+
+    let model = find_model(&ast, "A");
+    assert_eq!(model.blocks.len(), 2);
+
+    let AstBlockKind::Api(api) = &ast.blocks[1].inner else {
+        panic!("expected an api block");
+    };
+    assert_eq!(api.methods.len(), 1);
+    assert_eq!(api.methods[0].inner.symbol.name, "fine");
+
+    let AstBlockKind::Error {
+        symbol: Some(broken),
+    } = &ast.blocks[2].inner
+    else {
+        panic!("expected an error block with its name");
+    };
+    assert_eq!(broken.name, "Broken");
+    let AstBlockKind::PlainOldObject(poo) = &ast.blocks[3].inner else {
+        panic!("expected a poo block");
+    };
+    assert_eq!(poo.symbol.name, "Fine");
+}
+
+#[test]
+fn queue_binding_block() {
+    // Act
+    let ast = lex_and_ast(
+        r#"
+        queue Emails {
+            message: EmailJob
+        }
+
+        queue Pings {
+            message: array<int>
+        }
+        "#,
+    );
+
+    // Assert
+    let queues = ast
+        .blocks
+        .iter()
+        .filter_map(|spd| match &spd.inner {
+            AstBlockKind::QueueBinding(b) => Some(b),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(queues.len(), 2);
+
+    assert_eq!(queues[0].symbol.name, "Emails");
+    assert_eq!(queues[0].message.name, "message");
+    assert_eq!(
+        queues[0].message.cidl_type,
+        CidlType::Object { name: "EmailJob" }
+    );
+
+    assert_eq!(queues[1].symbol.name, "Pings");
+    assert_eq!(queues[1].message.cidl_type, CidlType::array(CidlType::Int));
+}
+
+#[test]
+fn service_binding_block() {
+    // Act
+    let ast = lex_and_ast(
+        r#"
+        service Auth from "../auth/.cloesce/cidl.json"
+        "#,
+    );
+
+    // Assert
+    let services = ast
+        .blocks
+        .iter()
+        .filter_map(|spd| match &spd.inner {
+            AstBlockKind::ServiceBinding(b) => Some(b),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(services.len(), 1);
+
+    assert_eq!(services[0].symbol.name, "Auth");
+    assert_eq!(services[0].cidl_path.inner, "../auth/.cloesce/cidl.json");
+}
+
+#[test]
+fn cron_block() {
+    // Act
+    let ast = lex_and_ast(
+        r#"
+        cron "*/5 * * * *" {
+            inject { Db Auth }
+        }
+
+        cron "0 0 * * SUN" {}
+        "#,
+    );
+
+    // Assert
+    let crons = ast
+        .blocks
+        .iter()
+        .filter_map(|spd| match &spd.inner {
+            AstBlockKind::Cron(b) => Some(b),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(crons.len(), 2);
+
+    assert_eq!(crons[0].expression.inner, "*/5 * * * *");
+    assert_eq!(crons[0].injects.len(), 1);
+    let entries = &crons[0].injects[0].inner.entries;
+    assert!(matches!(&entries[0].inner, InjectEntry::Binding(s) if s.name == "Db"));
+    assert!(matches!(&entries[1].inner, InjectEntry::Binding(s) if s.name == "Auth"));
+
+    assert_eq!(crons[1].expression.inner, "0 0 * * SUN");
+    assert!(crons[1].injects.is_empty());
+}
+
+#[test]
+fn guard_block_and_tags() {
+    // Act
+    let ast = lex_and_ast(
+        r#"
+        guard RequireAdmin {
+            inject { Db Auth }
+        }
+
+        api User {
+            [guard RequireAdmin]
+            [guard RequireUser]
+            get ping -> string {}
+        }
+        "#,
+    );
+
+    // Assert
+    let AstBlockKind::Guard(guard) = &ast.blocks[0].inner else {
+        panic!("expected a guard block");
+    };
+    assert_eq!(guard.symbol.name, "RequireAdmin");
+    assert_eq!(guard.injects.len(), 1);
+    let entries = &guard.injects[0].inner.entries;
+    assert!(matches!(&entries[0].inner, InjectEntry::Binding(s) if s.name == "Db"));
+    assert!(matches!(&entries[1].inner, InjectEntry::Binding(s) if s.name == "Auth"));
+
+    let AstBlockKind::Api(api) = &ast.blocks[1].inner else {
+        panic!("expected an api block");
+    };
+    let guards = api.methods[0]
+        .inner
+        .symbol
+        .tags
+        .iter()
+        .map(|t| match &t.inner {
+            Tag::Guard { guard } => guard.name,
+            other => panic!("unexpected tag {other:?}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(guards, vec!["RequireAdmin", "RequireUser"]);
+}
+
 #[test]
 fn model_durable_backing() {
     let ast = lex_and_ast(
@@ -398,6 +788,60 @@ fn poo_block() {
     );
 }
 
+#[test]
+fn enum_block() {
+    // Act
+    let ast = lex_and_ast(
+        r#"
+        enum Status {
+            Open,
+            Closed,
+            Archived,
+        }
+
+        enum Single { Only }
+
+        poo Ticket {
+            status: Status
+        }
+        "#,
+    );
+
+    // Assert
+    let enums = ast
+        .blocks
+        .iter()
+        .filter_map(|spd| match &spd.inner {
+            AstBlockKind::Enum(e) => Some((
+                e.symbol.name,
+                e.members.iter().map(|m| m.name).collect::<Vec<_>>(),
+            )),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        enums,
+        vec![
+            ("Status", vec!["Open", "Closed", "Archived"]),
+            ("Single", vec!["Only"]),
+        ]
+    );
+
+    // Enum references are resolved during semantic analysis
+    let ticket = ast
+        .blocks
+        .iter()
+        .find_map(|spd| match &spd.inner {
+            AstBlockKind::PlainOldObject(p) => Some(p),
+            _ => None,
+        })
+        .expect("Ticket poo to be present");
+    assert_eq!(
+        ticket.fields[0].cidl_type,
+        CidlType::Object { name: "Status" }
+    );
+}
+
 #[test]
 fn inject_block() {
     // Act
@@ -513,6 +957,89 @@ fn api_block() {
     assert!(matches!(list.inner.http_verb, HttpVerb::Get));
 }
 
+#[test]
+fn api_route_and_param_tags() {
+    // Act
+    let ast = lex_and_ast(
+        r#"
+        api User {
+            get posts "users/{id}/posts" -> array<string> {
+                [path]
+                id: int
+
+                [query]
+                limit: int
+            }
+
+            get plain {}
+        }
+        "#,
+    );
+
+    // Assert
+    let AstBlockKind::Api(api) = &ast.blocks[0].inner else {
+        panic!("expected an api block");
+    };
+    let posts = &api.methods[0].inner;
+    assert_eq!(posts.route, Some("users/{id}/posts"));
+    assert_eq!(posts.symbol.cidl_type, CidlType::array(CidlType::String));
+    assert!(matches!(posts.parameters[0].tags[0].inner, Tag::Path));
+    assert!(matches!(posts.parameters[1].tags[0].inner, Tag::Query));
+
+    assert_eq!(api.methods[1].inner.route, None);
+}
+
+#[test]
+fn data_source_list_clauses() {
+    // Act
+    let ast = lex_and_ast(
+        r#"
+        source Recent for Post {
+            include {}
+
+            list {
+                orderBy createdAt desc, title
+                where status == status
+            }
+        }
+        "#,
+    );
+
+    // Assert
+    let ds = ast
+        .blocks
+        .iter()
+        .find_map(|spd| match &spd.inner {
+            AstBlockKind::DataSource(ds) => Some(ds),
+            _ => None,
+        })
+        .expect("data source to be present");
+    let list = &ds.list.as_ref().expect("list method").inner;
+    assert!(list.parameters.is_empty());
+    assert_eq!(list.clauses.len(), 2);
+
+    match &list.clauses[0].inner {
+        ListClause::OrderBy { terms } => {
+            let terms = terms
+                .iter()
+                .map(|(sym, direction)| (sym.name, *direction))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                terms,
+                vec![("createdAt", Some(SortDirection::Desc)), ("title", None)]
+            );
+        }
+        _ => panic!("expected an orderBy clause"),
+    }
+
+    match &list.clauses[1].inner {
+        ListClause::Where { column, param } => {
+            assert_eq!((column.name, param.name), ("status", "status"));
+        }
+        _ => panic!("expected a where clause"),
+    }
+}
+
 #[test]
 fn api_context_tag() {
     // Act
@@ -635,10 +1162,12 @@ fn api_self_receiver() {
 fn model_primary_unique_optional_foreign() {
     let ast = lex_and_ast(
         r#"
-        [crud get, save, list]
+        [crud get, save, list, delete]
         [unique a, b]
         [unique orgId2]
         [unique deptId, role]
+        [index tagId]
+        [index a, role]
         model M for d1_db {
             column {
                 score: real
@@ -679,6 +1208,7 @@ fn model_primary_unique_optional_foreign() {
         cruds.iter().any(|c| matches!(c, CrudKind::Get))
             && cruds.iter().any(|c| matches!(c, CrudKind::Save))
             && cruds.iter().any(|c| matches!(c, CrudKind::List))
+            && cruds.iter().any(|c| matches!(c, CrudKind::Delete))
     );
 
     let uniques = m
@@ -692,6 +1222,17 @@ fn model_primary_unique_optional_foreign() {
         .collect::<Vec<_>>();
     assert_eq!(uniques.len(), 3);
 
+    let indexes = m
+        .symbol
+        .tags
+        .iter()
+        .flat_map(|t| match &t.inner {
+            Tag::Index { fields } => Some(fields.iter().map(|s| s.name).collect::<Vec<_>>()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(indexes, vec![vec!["tagId"], vec!["a", "role"]]);
+
     let columns = m
         .blocks
         .iter()
@@ -765,6 +1306,52 @@ fn model_primary_unique_optional_foreign() {
     assert_eq!(draft_fb.fields[0].name, "draftId");
 }
 
+#[test]
+fn model_column_defaults() {
+    // Act
+    let ast = lex_and_ast(
+        r#"
+        model Player {
+            column {
+                score: int = 100
+                ratio: real = 0.5
+                name: string = "anon"
+                active: bool = true
+                joined: date = now
+                plain: int
+            }
+        }
+        "#,
+    );
+
+    // Assert
+    let model = find_model(&ast, "Player");
+    let defaults = model
+        .sql_symbols()
+        .map(|s| (s.name, s.default.as_ref().map(|d| d.inner.clone())))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        defaults,
+        vec![
+            (
+                "score",
+                Some(DefaultValue::Literal(ArgumentLiteral::Int("100")))
+            ),
+            (
+                "ratio",
+                Some(DefaultValue::Literal(ArgumentLiteral::Real("0.5")))
+            ),
+            (
+                "name",
+                Some(DefaultValue::Literal(ArgumentLiteral::Str("anon")))
+            ),
+            ("active", Some(DefaultValue::Ident("true"))),
+            ("joined", Some(DefaultValue::Ident("now"))),
+            ("plain", None),
+        ]
+    );
+}
+
 #[test]
 fn model_navigation() {
     let ast = lex_and_ast(
@@ -51,6 +51,13 @@ pub enum CidlType<'src> {
         name: &'src str,
     },
 
+    /// An enumeration, containing the name of the enum.
+    /// Values are the member names as strings.
+    Enum {
+        #[serde(borrow)]
+        name: &'src str,
+    },
+
     /// A part of a model or plain object, containing the name of the class.
     ///
     /// Only valid as a method argument.
@@ -71,6 +78,12 @@ pub enum CidlType<'src> {
     /// A Cloudflare Workers KV object (GET value response)
     #[serde(borrow)]
     KvObject(Box<CidlType<'src>>),
+
+    /// One page of a generated `list`: the rows, and an opaque cursor for the next page
+    /// (null on the last page).
+    /// Not representable syntactically, only used internally.
+    #[serde(borrow)]
+    Page(Box<CidlType<'src>>),
 }
 
 impl<'src> CidlType<'src> {
@@ -79,6 +92,7 @@ impl<'src> CidlType<'src> {
             CidlType::Array(inner) => inner.root_type(),
             CidlType::Nullable(inner) => inner.root_type(),
             CidlType::KvObject(inner) => inner.root_type(),
+            CidlType::Page(inner) => inner.root_type(),
             t => t,
         }
     }
@@ -105,6 +119,10 @@ impl<'src> CidlType<'src> {
     pub fn nullable(cidl_type: CidlType<'src>) -> CidlType<'src> {
         CidlType::Nullable(Box::new(cidl_type))
     }
+
+    pub fn page(cidl_type: CidlType<'src>) -> CidlType<'src> {
+        CidlType::Page(Box::new(cidl_type))
+    }
 }
 
 #[derive(Deserialize, Serialize, Clone, Copy, PartialEq)]
@@ -245,6 +263,64 @@ pub struct Column<'src> {
     /// identified as a key by being in the primary_key_columns list. Thus, a column
     /// that makes up a primary key can be a part of a composite foreign key.
     pub composite_id: Option<usize>,
+
+    /// The value written when a row is inserted without this column.
+    #[serde(borrow, default)]
+    pub default: Option<ColumnDefault<'src>>,
+}
+
+/// A user specified default value of a [Column], e.g. `100` in `score: int = 100`.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub enum ColumnDefault<'src> {
+    Number(Number),
+    Boolean(bool),
+
+    /// A string, date or enum member
+    #[serde(borrow)]
+    String(Cow<'src, str>),
+
+    /// The current time as an ISO 8601 date, e.g. `createdAt: date = now`
+    Now,
+}
+
+impl Hash for ColumnDefault<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            ColumnDefault::Number(Number::Int(i)) => i.hash(state),
+            ColumnDefault::Number(Number::Float(f)) => f.to_bits().hash(state),
+            ColumnDefault::Boolean(b) => b.hash(state),
+            ColumnDefault::String(s) => s.hash(state),
+            ColumnDefault::Now => {}
+        }
+    }
+}
+
+impl PartialEq for ColumnDefault<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ColumnDefault::Number(Number::Int(a)), ColumnDefault::Number(Number::Int(b))) => {
+                a == b
+            }
+            (ColumnDefault::Number(Number::Float(a)), ColumnDefault::Number(Number::Float(b))) => {
+                a.to_bits() == b.to_bits()
+            }
+            (ColumnDefault::Boolean(a), ColumnDefault::Boolean(b)) => a == b,
+            (ColumnDefault::String(a), ColumnDefault::String(b)) => a == b,
+            (ColumnDefault::Now, ColumnDefault::Now) => true,
+            _ => false,
+        }
+    }
+}
+
+/// A non-unique secondary index over one or more columns of a SQLite backed model.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ModelIndex<'src> {
+    /// The SQL index name, `idx$<Model>$<col1>$<col2>...`
+    pub name: String,
+
+    #[serde(borrow)]
+    pub columns: Vec<&'src str>,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Hash)]
@@ -252,6 +328,80 @@ pub enum CrudKind {
     Get,
     List,
     Save,
+    Delete,
+    Count,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+/// An `orderBy` term on a generated `list`.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct ListOrder<'src> {
+    pub column: &'src str,
+    pub direction: SortDirection,
+}
+
+/// A `where column == param` filter on a generated `list`.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct ListFilter<'src> {
+    pub column: &'src str,
+
+    /// The runtime parameter the column is compared against.
+    pub param: &'src str,
+}
+
+/// The ordering and filtering a data source declares for its generated `list`.
+///
+/// Empty for a `list` ordered by primary key alone, and for a stub `list`.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Default)]
+pub struct ListQuery<'src> {
+    #[serde(borrow)]
+    pub order_by: Vec<ListOrder<'src>>,
+
+    #[serde(borrow)]
+    pub filters: Vec<ListFilter<'src>>,
+}
+
+impl<'src> ListQuery<'src> {
+    pub fn is_empty(&self) -> bool {
+        self.order_by.is_empty() && self.filters.is_empty()
+    }
+
+    /// The columns a `list` pages over, in sort order: each `orderBy` column, then every
+    /// primary key column not already ordered on as a tie-breaker, following the direction
+    /// of the last `orderBy` term.
+    ///
+    /// With no `orderBy`, just the primary key, ascending.
+    pub fn seek_columns<'m>(
+        &self,
+        model: &'m Model<'src>,
+    ) -> Vec<(&'m Column<'src>, SortDirection)> {
+        let find = |name: &str| {
+            model
+                .primary_columns
+                .iter()
+                .chain(&model.columns)
+                .find(|c| c.field.name == name)
+        };
+
+        let mut seek = self
+            .order_by
+            .iter()
+            .filter_map(|o| find(o.column).map(|c| (c, o.direction)))
+            .collect::<Vec<_>>();
+
+        let tie_break = seek.last().map_or(SortDirection::Asc, |(_, d)| *d);
+        for pk in &model.primary_columns {
+            if !seek.iter().any(|(c, _)| c.field.name == pk.field.name) {
+                seek.push((pk, tie_break));
+            }
+        }
+        seek
+    }
 }
 
 #[derive(Deserialize, Serialize, Default)]
@@ -310,19 +460,34 @@ pub struct DataSource<'src> {
     #[serde(borrow)]
     pub list: DataSourceMethod<'src>,
 
+    /// Ordering and filtering applied by a generated `list`.
+    #[serde(borrow, default)]
+    pub list_query: ListQuery<'src>,
+
     #[serde(borrow)]
     pub get: DataSourceGetMethod<'src>,
 
     #[serde(borrow)]
     pub save: DataSourceMethod<'src>,
 
+    /// Counts the rows a generated `list` pages over, taking its shard and `where` parameters.
+    /// Never a stub, and empty for a model without a SQLite backing. Unused if `list` is a stub.
+    #[serde(borrow)]
+    pub count: DataSourceMethod<'src>,
+
     /// True if the data source should not be exposed to the client
     pub is_internal: bool,
 
+    /// [Guard]s run by every route that loads through this data source.
+    #[serde(borrow, default)]
+    pub guards: Vec<&'src str>,
+
     pub get_plan: Option<serde_json::Value>,
     pub list_plan: Option<serde_json::Value>,
+    pub count_plan: Option<serde_json::Value>,
     pub get_explain: String,
     pub list_explain: String,
+    pub count_explain: String,
 }
 
 impl DataSource<'_> {
@@ -410,6 +575,12 @@ pub enum ParamSource {
     #[default]
     Body,
     Header,
+
+    /// Read from the URL's query string.
+    Query,
+
+    /// Read from a `{param}` segment of [ApiMethod::route].
+    Path,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
@@ -434,6 +605,11 @@ pub struct ApiMethod<'src> {
 
     pub http_verb: HttpVerb,
 
+    /// A custom route relative to the Worker URL, e.g. `users/{id}/posts`, served in place of
+    /// the derived `Model/method` route. Only static methods have one.
+    #[serde(borrow)]
+    pub route: Option<&'src str>,
+
     /// The media format the client should use to read the response body.
     pub return_media: MediaType,
 
@@ -452,6 +628,35 @@ pub struct ApiMethod<'src> {
     /// If present, the method executes inside this Durable Object's context.
     #[serde(borrow)]
     pub durable_target: Option<DurableTarget<'src>>,
+
+    /// The [Guard]s run, in order, before the method's `self` is hydrated. Includes those
+    /// inherited from its data source (and, for a CRUD method, its model).
+    #[serde(borrow, default)]
+    pub guards: Vec<&'src str>,
+}
+
+impl<'src> ApiMethod<'src> {
+    /// Splits [ApiMethod::route] into its `/` separated segments, each either literal text or
+    /// the [ParamSource::Path] parameter filling it. Empty if the method has no route.
+    pub fn route_segments(&self) -> Vec<TemplateSegment<'src, &ApiMethodParam<'src>>> {
+        let Some(route) = self.route else {
+            return Vec::new();
+        };
+
+        route
+            .split('/')
+            .map(|seg| {
+                let param = seg
+                    .strip_prefix('{')
+                    .and_then(|s| s.strip_suffix('}'))
+                    .and_then(|name| self.parameters.iter().find(|p| p.field.name == name));
+                match param {
+                    Some(param) => TemplateSegment::Value(param),
+                    None => TemplateSegment::Literal(Cow::Borrowed(seg)),
+                }
+            })
+            .collect()
+    }
 }
 
 #[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
@@ -491,6 +696,9 @@ pub struct Model<'src> {
     #[serde(borrow)]
     pub columns: Vec<Column<'src>>,
 
+    #[serde(borrow, default)]
+    pub indexes: Vec<ModelIndex<'src>>,
+
     /// Contains both Workers KV and Durable Object KV
     #[serde(borrow)]
     pub kv_fields: Vec<KvField<'src>>,
@@ -511,6 +719,10 @@ pub struct Model<'src> {
     pub data_sources: BTreeMap<&'src str, DataSource<'src>>,
 
     pub cruds: Vec<CrudKind>,
+
+    /// [Guard]s run by every generated CRUD method.
+    #[serde(borrow, default)]
+    pub crud_guards: Vec<&'src str>,
 }
 
 impl Model<'_> {
@@ -578,6 +790,17 @@ pub struct PlainOldObject<'src> {
     pub fields: Vec<ValidatedField<'src>>,
 }
 
+/// A closed set of string members, e.g. `enum Status { Open, Closed }`.
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Enum<'src> {
+    #[serde(borrow)]
+    pub name: &'src str,
+
+    /// Members in declaration order.
+    #[serde(borrow)]
+    pub members: Vec<&'src str>,
+}
+
 /// Some field within a KV or R2 binding
 /// which represents a function to create keys for the binding
 #[derive(Deserialize, Serialize)]
@@ -609,12 +832,36 @@ pub struct DurableBinding<'src> {
     pub templates: Vec<BindingTemplate<'src>>,
 }
 
+/// A Cloudflare Queue, bound for both producing and consuming messages.
+#[derive(Deserialize, Serialize)]
+pub struct QueueBinding<'src> {
+    pub name: &'src str,
+
+    /// The type of every message sent to or consumed from the queue.
+    #[serde(borrow)]
+    pub message: ValidatedField<'src>,
+}
+
+/// A Service binding to another Cloesce Worker, typed by that Worker's CIDL.
+#[derive(Deserialize, Serialize)]
+pub struct ServiceBinding<'src> {
+    pub name: &'src str,
+
+    /// The CIDL of the bound Worker, whose APIs are reachable through the binding.
+    #[serde(borrow)]
+    pub cidl: Box<CloesceIdl<'src>>,
+}
+
 #[derive(Deserialize, Serialize, Default)]
 pub struct WranglerEnv<'src> {
     /// Contains each d1 binding name
     #[serde(borrow)]
     pub d1_bindings: Vec<&'src str>,
 
+    /// The database name of each d1 binding that declares one, e.g. `db: "staging-db"`
+    #[serde(borrow, default)]
+    pub d1_databases: BTreeMap<&'src str, &'src str>,
+
     #[serde(borrow)]
     pub kv_bindings: Vec<Binding<'src>>,
 
@@ -624,8 +871,39 @@ pub struct WranglerEnv<'src> {
     #[serde(borrow)]
     pub durable_bindings: Vec<DurableBinding<'src>>,
 
+    #[serde(borrow, default)]
+    pub queue_bindings: Vec<QueueBinding<'src>>,
+
+    #[serde(borrow, default)]
+    pub service_bindings: Vec<ServiceBinding<'src>>,
+
     #[serde(borrow)]
     pub vars: Vec<Field<'src>>,
+
+    /// Typed like vars, but never written to the Wrangler config
+    #[serde(borrow, default)]
+    pub secrets: Vec<Field<'src>>,
+}
+
+/// A Cron Trigger, invoking the Worker's `scheduled` handler on its schedule.
+#[derive(Deserialize, Serialize)]
+pub struct CronTrigger<'src> {
+    /// The cron expression, e.g. `0 * * * *`.
+    pub expression: &'src str,
+
+    /// Bindings (env bindings, vars or injectables) handed to the handler.
+    #[serde(borrow)]
+    pub injected: Vec<&'src str>,
+}
+
+/// A named check run before an API method, able to reject the request.
+#[derive(Deserialize, Serialize)]
+pub struct Guard<'src> {
+    pub name: &'src str,
+
+    /// Bindings (env bindings, vars or injectables) handed to the guard.
+    #[serde(borrow)]
+    pub injected: Vec<&'src str>,
 }
 
 /// The Cloesce Interface Definition Language (CIDL), describing a full stack app.
@@ -645,11 +923,27 @@ pub struct CloesceIdl<'src> {
     #[serde(borrow)]
     pub poos: BTreeMap<&'src str, PlainOldObject<'src>>,
 
+    #[serde(borrow, default)]
+    pub enums: BTreeMap<&'src str, Enum<'src>>,
+
     #[serde(borrow)]
     pub injects: Vec<&'src str>,
+
+    #[serde(borrow, default)]
+    pub crons: Vec<CronTrigger<'src>>,
+
+    #[serde(borrow, default)]
+    pub guards: Vec<Guard<'src>>,
+
+    /// Each named environment (e.g. `staging`), resolved against the top level [WranglerEnv].
+    ///
+    /// Environments only overlay d1 bindings and vars, so every other binding is left empty
+    /// here and shared with [CloesceIdl::wrangler_env].
+    #[serde(borrow, default)]
+    pub envs: BTreeMap<&'src str, WranglerEnv<'src>>,
 }
 
-impl CloesceIdl<'_> {
+impl<'src> CloesceIdl<'src> {
     pub fn to_json(&self) -> String {
         serde_json::to_string_pretty(self).expect("serialize self to work")
     }
@@ -662,6 +956,16 @@ impl CloesceIdl<'_> {
             return;
         }
 
+        // An enum column's members are part of its SQL `CHECK` constraint
+        let enums = &self.enums;
+        let hash_enum = |field: &ValidatedField<'src>, h: &mut FxHasher| {
+            if let CidlType::Enum { name } = field.cidl_type.root_type()
+                && let Some(e) = enums.get(name)
+            {
+                e.members.hash(h);
+            }
+        };
+
         let mut root_h = FxHasher::default();
         for model in self.models.values_mut() {
             let binding = match &model.backing {
@@ -682,8 +986,12 @@ impl CloesceIdl<'_> {
                     let mut h = FxHasher::default();
                     h.write(b"ModelPrimaryKeyColumn");
                     pk.field.hash(&mut h);
+                    hash_enum(&pk.field, &mut h);
                     pk.foreign_key_reference.hash(&mut h);
                     pk.unique_ids.hash(&mut h);
+                    if let Some(default) = &pk.default {
+                        default.hash(&mut h);
+                    }
                     h.finish()
                 };
 
@@ -696,8 +1004,12 @@ impl CloesceIdl<'_> {
                     let mut h = FxHasher::default();
                     h.write(b"ModelColumn");
                     col.field.hash(&mut h);
+                    hash_enum(&col.field, &mut h);
                     col.foreign_key_reference.hash(&mut h);
                     col.unique_ids.hash(&mut h);
+                    if let Some(default) = &col.default {
+                        default.hash(&mut h);
+                    }
                     h.finish()
                 };
 
@@ -705,6 +1017,10 @@ impl CloesceIdl<'_> {
                 model_h.write_u64(col_h);
             }
 
+            // Indexes are hashed into the model alone, so an index change never
+            // looks like a column change.
+            model.indexes.hash(&mut model_h);
+
             let model_h_finished = model_h.finish();
             model.hash = model_h_finished;
             root_h.write_u64(model_h_finished);
@@ -0,0 +1,196 @@
+//! Cloesce Language Server
+//!
+//! Speaks the Language Server Protocol, reusing the compiler [frontend] and [semantic] crates to
+//! provide diagnostics, go-to-definition, find-references, hover and formatting for Cloesce sources.
+//!
+//! The server is synchronous: each message is handled to completion before the next is read.
+//! Every query re-runs the compiler over the entire [Workspace], which is cheap at the size
+//! of a Cloesce schema.
+
+use lsp_server::{Connection, Message, Notification, Request, Response};
+use lsp_types::{
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    HoverProviderCapability, InitializeParams, OneOf, PublishDiagnosticsParams, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind,
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+        Notification as NotificationTrait, PublishDiagnostics,
+    },
+    request::{Formatting, GotoDefinition, HoverRequest, References, Request as RequestTrait},
+};
+use serde::de::DeserializeOwned;
+
+use crate::workspace::Workspace;
+
+mod line_index;
+mod workspace;
+
+/// Runs the language server over stdin/stdout until the client exits.
+pub fn run_stdio() -> Result<(), String> {
+    let (connection, io_threads) = Connection::stdio();
+    run(connection)?;
+    io_threads.join().map_err(|e| e.to_string())
+}
+
+/// Runs the language server over `connection` until the client exits.
+pub fn run(connection: Connection) -> Result<(), String> {
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    };
+
+    let params = connection
+        .initialize(serde_json::to_value(capabilities).expect("capabilities to serialize"))
+        .map_err(|e| e.to_string())?;
+    let params: InitializeParams = serde_json::from_value(params).map_err(|e| e.to_string())?;
+
+    let mut server = Server {
+        connection: &connection,
+        workspace: Workspace::default(),
+    };
+
+    #[allow(deprecated)]
+    let roots = match params.workspace_folders {
+        Some(folders) => folders.into_iter().map(|f| f.uri).collect(),
+        None => params.root_uri.into_iter().collect::<Vec<_>>(),
+    };
+    for root in roots {
+        if let Some(path) = workspace::file_path(&root) {
+            server.workspace.load_dir(&path);
+        }
+    }
+    server.publish_diagnostics()?;
+
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection
+                    .handle_shutdown(&request)
+                    .map_err(|e| e.to_string())?
+                {
+                    return Ok(());
+                }
+                server.request(request)?;
+            }
+            Message::Notification(notification) => server.notification(notification)?,
+            Message::Response(_) => {
+                // The server never sends requests
+            }
+        }
+    }
+
+    Ok(())
+}
+
+struct Server<'c> {
+    connection: &'c Connection,
+    workspace: Workspace,
+}
+
+impl Server<'_> {
+    fn request(&mut self, request: Request) -> Result<(), String> {
+        let workspace = &self.workspace;
+        let response = match request.method.as_str() {
+            GotoDefinition::METHOD => respond::<GotoDefinition>(request, |p| {
+                let p = p.text_document_position_params;
+                workspace
+                    .definition(&p.text_document.uri, p.position)
+                    .map(Into::into)
+            }),
+            References::METHOD => respond::<References>(request, |p| {
+                Some(workspace.references(
+                    &p.text_document_position.text_document.uri,
+                    p.text_document_position.position,
+                    p.context.include_declaration,
+                ))
+            }),
+            HoverRequest::METHOD => respond::<HoverRequest>(request, |p| {
+                let p = p.text_document_position_params;
+                workspace.hover(&p.text_document.uri, p.position)
+            }),
+            Formatting::METHOD => {
+                respond::<Formatting>(request, |p| workspace.format(&p.text_document.uri))
+            }
+            _ => Response::new_err(
+                request.id,
+                lsp_server::ErrorCode::MethodNotFound as i32,
+                format!("unsupported request '{}'", request.method),
+            ),
+        };
+
+        self.send(response)
+    }
+
+    fn notification(&mut self, notification: Notification) -> Result<(), String> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let p: DidOpenTextDocumentParams = extract(notification)?;
+                self.workspace
+                    .open(p.text_document.uri, p.text_document.text);
+            }
+            DidChangeTextDocument::METHOD => {
+                let p: DidChangeTextDocumentParams = extract(notification)?;
+
+                // Only full document sync is advertised, so the last change is the whole text.
+                let Some(change) = p.content_changes.into_iter().last() else {
+                    return Ok(());
+                };
+                self.workspace.open(p.text_document.uri, change.text);
+            }
+            DidCloseTextDocument::METHOD => {
+                let p: DidCloseTextDocumentParams = extract(notification)?;
+                self.workspace.close(&p.text_document.uri);
+            }
+            _ => return Ok(()),
+        }
+
+        self.publish_diagnostics()
+    }
+
+    fn publish_diagnostics(&self) -> Result<(), String> {
+        for (uri, diagnostics) in self.workspace.diagnostics() {
+            let params = PublishDiagnosticsParams {
+                uri,
+                diagnostics,
+                version: None,
+            };
+            self.send(Notification::new(PublishDiagnostics::METHOD.into(), params))?;
+        }
+        Ok(())
+    }
+
+    fn send(&self, message: impl Into<Message>) -> Result<(), String> {
+        self.connection
+            .sender
+            .send(message.into())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Responds to a request of kind `R` with the result of `handler`, or an
+/// invalid params error if the request's params do not deserialize.
+fn respond<R: RequestTrait>(
+    request: Request,
+    handler: impl FnOnce(R::Params) -> R::Result,
+) -> Response {
+    let id = request.id.clone();
+    match request.extract::<R::Params>(R::METHOD) {
+        Ok((id, params)) => Response::new_ok(id, handler(params)),
+        Err(e) => Response::new_err(
+            id,
+            lsp_server::ErrorCode::InvalidParams as i32,
+            format!("{e:?}"),
+        ),
+    }
+}
+
+fn extract<P: DeserializeOwned>(notification: Notification) -> Result<P, String> {
+    let method = notification.method.clone();
+    notification
+        .extract(&method)
+        .map_err(|e| format!("invalid '{method}' notification: {e:?}"))
+}
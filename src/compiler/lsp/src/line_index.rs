@@ -0,0 +1,57 @@
+use lsp_types::{Position, Range};
+
+/// Converts between the byte offsets used by the compiler and the UTF-16 line/column
+/// [Position]s used by LSP.
+pub struct LineIndex<'a> {
+    text: &'a str,
+
+    /// Byte offset of the start of each line
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { text, line_starts }
+    }
+
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let start = self.line_starts[line];
+        Position {
+            line: line as u32,
+            character: self.text[start..offset].encode_utf16().count() as u32,
+        }
+    }
+
+    pub fn range(&self, range: std::ops::Range<usize>) -> Range {
+        Range {
+            start: self.position(range.start),
+            end: self.position(range.end),
+        }
+    }
+
+    /// Clamps positions past the end of a line to the end of that line.
+    pub fn offset(&self, position: Position) -> usize {
+        let Some(&start) = self.line_starts.get(position.line as usize) else {
+            return self.text.len();
+        };
+
+        let mut utf16 = 0;
+        for (i, c) in self.text[start..].char_indices() {
+            if c == '\n' || utf16 >= position.character {
+                return start + i;
+            }
+            utf16 += c.len_utf16() as u32;
+        }
+        self.text.len()
+    }
+
+    /// The range spanning the entire text
+    pub fn full_range(&self) -> Range {
+        self.range(0..self.text.len())
+    }
+}
@@ -0,0 +1,552 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    path::{Path, PathBuf},
+};
+
+use frontend::{
+    Ast, AstBlockKind, FileTable, Symbol,
+    err::{Diagnostic, DisplayError},
+    fmt_cidl_type, formatter,
+    lexer::{self, LexTarget, LexedFile, Token},
+    parser,
+};
+use idl::{CidlType, CloesceIdl, Model};
+use lsp_types::{
+    DiagnosticSeverity, Hover, HoverContents, Location, MarkupContent, MarkupKind, Position,
+    TextEdit, Uri,
+};
+use semantic::SymbolTable;
+
+use crate::line_index::LineIndex;
+
+pub struct Document {
+    /// The URI the editor knows this document by
+    pub uri: Uri,
+    pub text: String,
+}
+
+/// Every Cloesce source the server knows about: those open in the editor, and those
+/// loaded from disk so cross file references resolve.
+///
+/// The compiler has no imports, so every query runs over the entire workspace.
+#[derive(Default)]
+pub struct Workspace {
+    /// Keyed by [document_key]
+    documents: BTreeMap<String, Document>,
+}
+
+impl Workspace {
+    pub fn open(&mut self, uri: Uri, text: String) {
+        self.documents
+            .insert(document_key(&uri), Document { uri, text });
+    }
+
+    /// Reverts a closed document to its contents on disk, forgetting it if there are none.
+    pub fn close(&mut self, uri: &Uri) {
+        let key = document_key(uri);
+        match std::fs::read_to_string(&key) {
+            Ok(text) => self.open(uri.clone(), text),
+            Err(_) => {
+                self.documents.remove(&key);
+            }
+        }
+    }
+
+    /// Loads every `.clo` and `.cloesce` file under `root`, skipping hidden directories
+    /// and `node_modules`. Documents already open are left untouched.
+    pub fn load_dir(&mut self, root: &Path) {
+        fn is_source(path: &Path) -> bool {
+            matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("cloesce") | Some("clo")
+            )
+        }
+
+        let mut queue = VecDeque::from([root.to_path_buf()]);
+        while let Some(dir) = queue.pop_front() {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                if path.is_dir() {
+                    if !name.starts_with('.') && name != "node_modules" {
+                        queue.push_back(path);
+                    }
+                    continue;
+                }
+
+                if !is_source(&path) {
+                    continue;
+                }
+                let Some(uri) = path_to_uri(&path) else {
+                    continue;
+                };
+                if self.documents.contains_key(&document_key(&uri)) {
+                    continue;
+                }
+                if let Ok(text) = std::fs::read_to_string(&path) {
+                    self.open(uri, text);
+                }
+            }
+        }
+    }
+
+    /// The diagnostics of every document, including documents with none so that
+    /// stale diagnostics are cleared.
+    pub fn diagnostics(&self) -> Vec<(Uri, Vec<lsp_types::Diagnostic>)> {
+        self.analyze(|analysis| {
+            let mut by_key = self
+                .documents
+                .keys()
+                .map(|key| (key.as_str(), Vec::new()))
+                .collect::<BTreeMap<_, _>>();
+
+            for diagnostic in &analysis.diagnostics {
+                let Some((key, doc)) = self.documents.get_key_value(&diagnostic.path) else {
+                    continue;
+                };
+                by_key
+                    .get_mut(key.as_str())
+                    .unwrap()
+                    .push(lsp_types::Diagnostic {
+                        range: LineIndex::new(&doc.text).range(diagnostic.range.clone()),
+                        severity: Some(DiagnosticSeverity::ERROR),
+                        source: Some("cloesce".into()),
+                        message: diagnostic.message.clone(),
+                        ..Default::default()
+                    });
+            }
+
+            by_key
+                .into_iter()
+                .map(|(key, diagnostics)| (self.documents[key].uri.clone(), diagnostics))
+                .collect()
+        })
+    }
+
+    pub fn definition(&self, uri: &Uri, position: Position) -> Option<Location> {
+        self.analyze(|analysis| {
+            let (lf, i) = analysis.ident_at(&document_key(uri), self.offset(uri, position)?)?;
+            let table = SymbolTable::new(analysis.ast?);
+            let decl = analysis.resolve(&table, lf, i)?;
+            self.location(analysis.file_table, decl.span.context, name_range(decl))
+        })
+    }
+
+    pub fn references(
+        &self,
+        uri: &Uri,
+        position: Position,
+        include_declaration: bool,
+    ) -> Vec<Location> {
+        self.analyze(|analysis| {
+            let Some(offset) = self.offset(uri, position) else {
+                return Vec::new();
+            };
+            let Some((lf, i)) = analysis.ident_at(&document_key(uri), offset) else {
+                return Vec::new();
+            };
+            let Some(ast) = analysis.ast else {
+                return Vec::new();
+            };
+            let table = SymbolTable::new(ast);
+            let Some(decl) = analysis.resolve(&table, lf, i) else {
+                return Vec::new();
+            };
+
+            let mut locations = Vec::new();
+            for lf in analysis.lexed {
+                for (i, token) in lf.tokens.iter().enumerate() {
+                    if !matches!(token.inner, Token::Ident(name) if name == decl.name) {
+                        continue;
+                    }
+                    if analysis.resolve(&table, lf, i) != Some(decl) {
+                        continue;
+                    }
+
+                    let is_declaration =
+                        lf.file_id == decl.span.context && token.span.start == decl.span.start;
+                    if is_declaration && !include_declaration {
+                        continue;
+                    }
+
+                    locations.extend(self.location(
+                        analysis.file_table,
+                        lf.file_id,
+                        token.span.start..token.span.end,
+                    ));
+                }
+            }
+            locations
+        })
+    }
+
+    pub fn hover(&self, uri: &Uri, position: Position) -> Option<Hover> {
+        let doc = self.documents.get(&document_key(uri))?;
+        self.analyze(|analysis| {
+            let (lf, i) = analysis.ident_at(&document_key(uri), self.offset(uri, position)?)?;
+            let table = SymbolTable::new(analysis.ast?);
+            let decl = analysis.resolve(&table, lf, i)?;
+
+            let span = lf.tokens[i].span;
+            Some(Hover {
+                contents: HoverContents::Markup(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: format!("```cloesce\n{}\n```", analysis.describe(&table, decl)),
+                }),
+                range: Some(LineIndex::new(&doc.text).range(span.start..span.end)),
+            })
+        })
+    }
+
+    /// A single edit replacing the document with its formatted self, or [None]
+    /// if the document does not parse.
+    pub fn format(&self, uri: &Uri) -> Option<Vec<TextEdit>> {
+        let key = document_key(uri);
+        let doc = self.documents.get(&key)?;
+
+        let (lexed, file_table) = lexer::lex([LexTarget {
+            src: &doc.text,
+            path: PathBuf::from(&key),
+        }])
+        .ok()?;
+        let ast = parser::parse(&lexed, &file_table).ok()?;
+        let formatted = formatter::format(&ast, &lexed[0].comment_map, &doc.text);
+
+        if formatted == doc.text {
+            return Some(Vec::new());
+        }
+        Some(vec![TextEdit {
+            range: LineIndex::new(&doc.text).full_range(),
+            new_text: formatted,
+        }])
+    }
+
+    fn offset(&self, uri: &Uri, position: Position) -> Option<usize> {
+        let doc = self.documents.get(&document_key(uri))?;
+        Some(LineIndex::new(&doc.text).offset(position))
+    }
+
+    fn location(
+        &self,
+        file_table: &FileTable,
+        file_id: frontend::lexer::FileId,
+        range: std::ops::Range<usize>,
+    ) -> Option<Location> {
+        let (src, path) = file_table.resolve(file_id);
+        let doc = self.documents.get(path.to_str()?)?;
+        Some(Location {
+            uri: doc.uri.clone(),
+            range: LineIndex::new(src).range(range),
+        })
+    }
+
+    /// Runs the workspace through as much of the compiler as it can get through.
+    fn analyze<R>(&self, f: impl FnOnce(Analysis<'_, '_>) -> R) -> R {
+        let targets = self.documents.iter().map(|(key, doc)| LexTarget {
+            src: &doc.text,
+            path: PathBuf::from(key),
+        });
+
+        let (lexed, file_table) = match lexer::lex(targets) {
+            Ok(res) => res,
+            Err((errors, file_table)) => {
+                return f(Analysis {
+                    diagnostics: errors.diagnostics(&file_table),
+                    file_table: &file_table,
+                    lexed: &[],
+                    ast: None,
+                    idl: None,
+                });
+            }
+        };
+
+        // Whatever parsed is still analyzed, so a typo doesn't hide every other diagnostic
+        let (ast, parse_errors) = parser::parse_recovering(&lexed, &file_table);
+        let mut diagnostics = parse_errors.diagnostics(&file_table);
+
+        let services = semantic::read_service_cidls(&ast, &file_table);
+        match semantic::analyze_with_services(&ast, &services) {
+            Ok(idl) => f(Analysis {
+                diagnostics,
+                file_table: &file_table,
+                lexed: &lexed,
+                ast: Some(&ast),
+                idl: parse_errors.is_empty().then_some(&idl),
+            }),
+            Err(errors) => {
+                diagnostics.extend(errors.iter().flat_map(|e| e.diagnostics(&file_table)));
+                f(Analysis {
+                    diagnostics,
+                    file_table: &file_table,
+                    lexed: &lexed,
+                    ast: Some(&ast),
+                    idl: None,
+                })
+            }
+        }
+    }
+}
+
+/// The workspace run through the compiler, as far as it got.
+struct Analysis<'a, 'src> {
+    diagnostics: Vec<Diagnostic>,
+    file_table: &'a FileTable<'src>,
+    lexed: &'a [LexedFile<'src>],
+
+    /// [None] if lexing failed, otherwise whatever parsed
+    ast: Option<&'a Ast<'src>>,
+
+    /// [None] if parsing or semantic analysis failed
+    idl: Option<&'a CloesceIdl<'src>>,
+}
+
+impl<'a, 'src> Analysis<'a, 'src> {
+    /// The file and token index of the identifier touching `offset` in the document `key`
+    fn ident_at(&self, key: &str, offset: usize) -> Option<(&'a LexedFile<'src>, usize)> {
+        let lf = self
+            .lexed
+            .iter()
+            .find(|lf| self.file_table.resolve(lf.file_id).1 == Path::new(key))?;
+
+        let i = lf.tokens.iter().position(|t| {
+            matches!(t.inner, Token::Ident(_)) && t.span.start <= offset && offset <= t.span.end
+        })?;
+        Some((lf, i))
+    }
+
+    /// The declaration the identifier at `tokens[i]` refers to.
+    fn resolve(
+        &self,
+        table: &SymbolTable<'src, 'a>,
+        lf: &LexedFile<'src>,
+        i: usize,
+    ) -> Option<&'a Symbol<'src>> {
+        let Token::Ident(name) = lf.tokens[i].inner else {
+            return None;
+        };
+        let span = &lf.tokens[i].span;
+
+        // A namespaced name, `billing::Invoice`
+        let written = self.written_name(lf, i);
+        if written != name
+            && let Some(global) = table.global(table.resolve_at(span, written))
+        {
+            return Some(global);
+        }
+
+        // `Model::field`, or `billing::Model::field`
+        if i >= 2
+            && matches!(lf.tokens[i - 1].inner, Token::DoubleColon)
+            && matches!(lf.tokens[i - 2].inner, Token::Ident(_))
+        {
+            let model = table.resolve_at(span, self.written_name(lf, i - 2));
+            if let Some(field) = table.model_field(model, name) {
+                return Some(field);
+            }
+        }
+
+        if let Some(global) = table.global(table.resolve_at(span, name)) {
+            return Some(global);
+        }
+
+        // A field of the model the enclosing block belongs to
+        let offset = span.start;
+        let model = self
+            .ast?
+            .blocks
+            .iter()
+            .flat_map(|spd| match &spd.inner {
+                AstBlockKind::Namespace(ns) => ns.blocks.as_slice(),
+                _ => std::slice::from_ref(spd),
+            })
+            .find_map(|block| {
+                let span = block.span;
+                if span.context != lf.file_id || offset < span.start || offset >= span.end {
+                    return None;
+                }
+                match &block.inner {
+                    AstBlockKind::Model(model) => Some(table.resolve(&model.symbol)),
+                    AstBlockKind::DataSource(ds) => Some(table.resolve(&ds.model)),
+                    AstBlockKind::Api(api) => Some(table.resolve(&api.symbol)),
+                    _ => None,
+                }
+            })?;
+        table.model_field(model, name)
+    }
+
+    /// The name ending at `tokens[i]` as written, including a leading namespace,
+    /// e.g. `billing::Invoice`.
+    fn written_name(&self, lf: &LexedFile<'src>, i: usize) -> &'src str {
+        let end = &lf.tokens[i];
+        let Token::Ident(name) = end.inner else {
+            unreachable!("written_name is only called on identifiers")
+        };
+        if i < 2 || !matches!(lf.tokens[i - 1].inner, Token::DoubleColon) {
+            return name;
+        }
+        let Token::Ident(_) = lf.tokens[i - 2].inner else {
+            return name;
+        };
+
+        let (src, _) = self.file_table.resolve(lf.file_id);
+        &src[lf.tokens[i - 2].span.start..end.span.end]
+    }
+
+    /// A one line description of a declaration, using the resolved [CidlType] when
+    /// semantic analysis succeeded.
+    fn describe(&self, table: &SymbolTable<'src, 'a>, decl: &Symbol<'src>) -> String {
+        let typed = |prefix: String, ty: Option<&CidlType>| match ty.unwrap_or(&decl.cidl_type) {
+            CidlType::Void => prefix,
+            ty => format!("{prefix}: {}", fmt_cidl_type(ty)),
+        };
+
+        let blocks = self
+            .ast
+            .iter()
+            .flat_map(|ast| &ast.blocks)
+            .flat_map(|spd| match &spd.inner {
+                AstBlockKind::Namespace(ns) => ns.blocks.as_slice(),
+                _ => std::slice::from_ref(spd),
+            });
+        for block in blocks.map(|spd| &spd.inner) {
+            match block {
+                AstBlockKind::Model(model) => {
+                    if model.symbol == *decl {
+                        return match &model.database_binding {
+                            Some(binding) => format!("model {} for {}", decl.name, binding.name),
+                            None => format!("model {}", decl.name),
+                        };
+                    }
+
+                    let is_field = model
+                        .blocks
+                        .iter()
+                        .flat_map(|b| b.inner.symbols())
+                        .any(|s| s == decl);
+                    if is_field {
+                        let ty = self
+                            .idl
+                            .and_then(|idl| idl.models.get(table.resolve(&model.symbol)))
+                            .and_then(|m| model_field_type(m, decl.name));
+                        return typed(format!("{}::{}", model.symbol.name, decl.name), ty);
+                    }
+                }
+                AstBlockKind::PlainOldObject(poo) => {
+                    if poo.symbol == *decl {
+                        return format!("poo {}", decl.name);
+                    }
+
+                    if poo.fields.iter().any(|s| s == decl) {
+                        let ty = self
+                            .idl
+                            .and_then(|idl| idl.poos.get(table.resolve(&poo.symbol)))
+                            .and_then(|p| p.fields.iter().find(|f| f.name == decl.name))
+                            .map(|f| &f.cidl_type);
+                        return typed(format!("{}::{}", poo.symbol.name, decl.name), ty);
+                    }
+                }
+                AstBlockKind::Enum(e) => {
+                    if e.symbol == *decl {
+                        return format!("enum {}", decl.name);
+                    }
+
+                    if e.members.iter().any(|s| s == decl) {
+                        return format!("{}::{}", e.symbol.name, decl.name);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        typed(decl.name.to_string(), None)
+    }
+}
+
+fn model_field_type<'m, 'src>(model: &'m Model<'src>, name: &str) -> Option<&'m CidlType<'src>> {
+    let columns = model.primary_columns.iter().chain(&model.columns);
+    columns
+        .map(|c| (&c.field.name, &c.field.cidl_type))
+        .chain(
+            model
+                .navigation_fields
+                .iter()
+                .map(|n| (&n.field.name, &n.field.cidl_type)),
+        )
+        .chain(
+            model
+                .kv_fields
+                .iter()
+                .map(|k| (&k.field.name, &k.field.cidl_type)),
+        )
+        .chain(
+            model
+                .r2_fields
+                .iter()
+                .map(|r| (&r.field.name, &r.field.cidl_type)),
+        )
+        .chain(model.route_fields.iter().map(|f| (&f.name, &f.cidl_type)))
+        .find(|(n, _)| *n == name)
+        .map(|(_, ty)| ty)
+}
+
+/// The range of just the name of a symbol, excluding any type that follows it.
+fn name_range(symbol: &Symbol) -> std::ops::Range<usize> {
+    symbol.span.start..symbol.span.start + symbol.name.len()
+}
+
+/// File URIs are keyed by their decoded path so a file opened in the editor and the same
+/// file loaded from disk are one document. Any other URI is keyed by itself.
+fn document_key(uri: &Uri) -> String {
+    match file_path(uri) {
+        Some(path) => path.to_string_lossy().into_owned(),
+        None => uri.as_str().to_string(),
+    }
+}
+
+/// The decoded path of a `file://` URI
+pub fn file_path(uri: &Uri) -> Option<PathBuf> {
+    if uri.scheme().map(|s| s.as_str()) != Some("file") {
+        return None;
+    }
+
+    let path = uri.path().as_str().as_bytes();
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut i = 0;
+    while i < path.len() {
+        let hex = path
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (path[i], hex) {
+            (b'%', Some(byte)) => {
+                bytes.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                bytes.push(byte);
+                i += 1;
+            }
+        }
+    }
+    Some(PathBuf::from(String::from_utf8_lossy(&bytes).into_owned()))
+}
+
+fn path_to_uri(path: &Path) -> Option<Uri> {
+    let path = path.to_str()?.replace('\\', "/");
+    let mut uri = String::from("file://");
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    uri.parse().ok()
+}
@@ -0,0 +1,405 @@
+use std::thread::JoinHandle;
+
+use lsp_server::{Connection, Message, Notification, Request, RequestId};
+use serde_json::{Value, json};
+
+const DOGS: &str = "file:///project/dogs.clo";
+const PEOPLE: &str = "file:///project/people.clo";
+const BILLING: &str = "file:///project/billing.clo";
+
+/// An in-process client driving the server over a memory [Connection].
+struct Client {
+    conn: Connection,
+    server: Option<JoinHandle<Result<(), String>>>,
+    next_id: i32,
+
+    /// Notifications received while waiting on a response
+    notifications: Vec<Notification>,
+}
+
+impl Client {
+    fn start() -> Self {
+        let (server_conn, conn) = Connection::memory();
+        let server = std::thread::spawn(move || lsp::run(server_conn));
+
+        let mut client = Self {
+            conn,
+            server: Some(server),
+            next_id: 0,
+            notifications: Vec::new(),
+        };
+
+        let res = client.request("initialize", json!({ "capabilities": {} }));
+        assert_eq!(res["capabilities"]["definitionProvider"], json!(true));
+        client.notify("initialized", json!({}));
+        client
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        self.next_id += 1;
+        let id = RequestId::from(self.next_id);
+        self.conn
+            .sender
+            .send(Request::new(id.clone(), method.into(), params).into())
+            .unwrap();
+
+        loop {
+            match self.conn.receiver.recv().unwrap() {
+                Message::Response(res) if res.id == id => {
+                    assert!(res.error.is_none(), "{method} failed: {:?}", res.error);
+                    return res.result.unwrap_or(Value::Null);
+                }
+                Message::Notification(n) => self.notifications.push(n),
+                other => panic!("unexpected message: {other:?}"),
+            }
+        }
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        self.conn
+            .sender
+            .send(Notification::new(method.into(), params).into())
+            .unwrap();
+    }
+
+    fn open(&mut self, uri: &str, text: &str) {
+        self.notify(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": { "uri": uri, "languageId": "cloesce", "version": 1, "text": text }
+            }),
+        );
+    }
+
+    /// The most recently published diagnostics for `uri`, waiting for the server to
+    /// publish a round of diagnostics for it.
+    fn diagnostics(&mut self, uri: &str) -> Vec<Value> {
+        // A request round trip guarantees preceding notifications have been handled.
+        self.request("textDocument/hover", position(uri, 0, 0));
+
+        let published = self
+            .notifications
+            .drain(..)
+            .rfind(|n| n.method == "textDocument/publishDiagnostics" && n.params["uri"] == uri)
+            .expect("diagnostics to be published");
+        published.params["diagnostics"].as_array().unwrap().clone()
+    }
+
+    fn shutdown(mut self) {
+        self.request("shutdown", Value::Null);
+        self.notify("exit", Value::Null);
+        let result = self.server.take().unwrap().join().unwrap();
+        assert_eq!(result, Ok(()));
+    }
+}
+
+fn position(uri: &str, line: u32, character: u32) -> Value {
+    json!({
+        "textDocument": { "uri": uri },
+        "position": { "line": line, "character": character }
+    })
+}
+
+fn range(line: u32, start: u32, end: u32) -> Value {
+    json!({
+        "start": { "line": line, "character": start },
+        "end": { "line": line, "character": end }
+    })
+}
+
+const DOGS_SRC: &str = r#"d1 { db }
+
+model Dog for db {
+    primary {
+        id: int
+    }
+}
+"#;
+
+const PEOPLE_SRC: &str = r#"model Person for db {
+    primary {
+        id: int
+    }
+
+    foreign Dog::id {
+        dogId
+    }
+
+    one Dog::id(dogId) { dog }
+}
+"#;
+
+#[test]
+fn publishes_and_clears_diagnostics() {
+    let mut client = Client::start();
+
+    // Unresolved model in a second file
+    client.open(DOGS, DOGS_SRC);
+    client.open(PEOPLE, &PEOPLE_SRC.replace("Dog::", "Cat::"));
+    let diagnostics = client.diagnostics(PEOPLE);
+    assert!(!diagnostics.is_empty());
+    assert!(
+        diagnostics
+            .iter()
+            .any(|d| d["message"] == "unresolved symbol 'Cat'" && d["range"] == range(5, 12, 15)),
+        "{diagnostics:#?}"
+    );
+
+    // Fixing the reference clears them
+    client.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": PEOPLE, "version": 2 },
+            "contentChanges": [{ "text": PEOPLE_SRC }]
+        }),
+    );
+    assert_eq!(client.diagnostics(PEOPLE), Vec::<Value>::new());
+
+    // Parse errors are reported too
+    client.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": DOGS, "version": 2 },
+            "contentChanges": [{ "text": "model Dog for db {" }]
+        }),
+    );
+    let diagnostics = client.diagnostics(DOGS);
+    assert_eq!(diagnostics.len(), 1);
+    let message = diagnostics[0]["message"].as_str().unwrap();
+    assert!(message.starts_with("found end of input"), "{message}");
+
+    client.shutdown();
+}
+
+#[test]
+fn publishes_cyclical_relationship_on_its_fields() {
+    let mut client = Client::start();
+
+    // Dog and Person require each other
+    let dogs = DOGS_SRC.replace(
+        "        id: int\n    }\n",
+        "        id: int\n    }\n\n    foreign Person::id {\n        ownerId\n    }\n",
+    );
+    client.open(DOGS, &dogs);
+    client.open(PEOPLE, PEOPLE_SRC);
+
+    let message = "cyclical relationship detected among: Dog -> Person";
+    let diagnostics = client.diagnostics(PEOPLE);
+    assert!(
+        diagnostics
+            .iter()
+            .any(|d| d["message"] == message && d["range"] == range(6, 8, 13)),
+        "{diagnostics:#?}"
+    );
+
+    // Each change republishes every file
+    client.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": PEOPLE, "version": 2 },
+            "contentChanges": [{ "text": PEOPLE_SRC }]
+        }),
+    );
+    let diagnostics = client.diagnostics(DOGS);
+    assert!(
+        diagnostics
+            .iter()
+            .any(|d| d["message"] == message && d["range"] == range(8, 8, 15)),
+        "{diagnostics:#?}"
+    );
+
+    client.shutdown();
+}
+
+#[test]
+fn goto_definition_and_references_across_files() {
+    let mut client = Client::start();
+    client.open(DOGS, DOGS_SRC);
+    client.open(PEOPLE, PEOPLE_SRC);
+
+    // `Dog` in `foreign Dog::id`
+    let def = client.request("textDocument/definition", position(PEOPLE, 5, 13));
+    assert_eq!(def, json!({ "uri": DOGS, "range": range(2, 6, 9) }));
+
+    // `id` in `foreign Dog::id`
+    let def = client.request("textDocument/definition", position(PEOPLE, 5, 18));
+    assert_eq!(def, json!({ "uri": DOGS, "range": range(4, 8, 10) }));
+
+    // `dogId` in `one Dog::id(dogId)` is the local foreign key
+    let def = client.request("textDocument/definition", position(PEOPLE, 9, 17));
+    assert_eq!(def, json!({ "uri": PEOPLE, "range": range(6, 8, 13) }));
+
+    // Keywords resolve to nothing
+    let def = client.request("textDocument/definition", position(PEOPLE, 0, 2));
+    assert_eq!(def, Value::Null);
+
+    let mut refs = client.request(
+        "textDocument/references",
+        json!({
+            "textDocument": { "uri": DOGS },
+            "position": { "line": 2, "character": 7 },
+            "context": { "includeDeclaration": true }
+        }),
+    );
+    let refs = refs.as_array_mut().unwrap();
+    let mut expected = vec![
+        json!({ "uri": DOGS, "range": range(2, 6, 9) }),
+        json!({ "uri": PEOPLE, "range": range(5, 12, 15) }),
+        json!({ "uri": PEOPLE, "range": range(9, 8, 11) }),
+    ];
+    refs.sort_by_key(|r| r.to_string());
+    expected.sort_by_key(|r| r.to_string());
+    assert_eq!(*refs, expected);
+
+    let refs = client.request(
+        "textDocument/references",
+        json!({
+            "textDocument": { "uri": DOGS },
+            "position": { "line": 4, "character": 9 },
+            "context": { "includeDeclaration": false }
+        }),
+    );
+    assert_eq!(refs.as_array().unwrap().len(), 2, "{refs:#?}");
+
+    client.shutdown();
+}
+
+#[test]
+fn hover_shows_resolved_types() {
+    let mut client = Client::start();
+    client.open(DOGS, DOGS_SRC);
+    client.open(PEOPLE, PEOPLE_SRC);
+
+    // A foreign key's type is only known after semantic analysis
+    let hover = client.request("textDocument/hover", position(PEOPLE, 6, 10));
+    assert_eq!(
+        hover["contents"]["value"],
+        "```cloesce\nPerson::dogId: int\n```"
+    );
+    assert_eq!(hover["range"], range(6, 8, 13));
+
+    let hover = client.request("textDocument/hover", position(PEOPLE, 9, 25));
+    assert_eq!(
+        hover["contents"]["value"],
+        "```cloesce\nPerson::dog: Dog\n```"
+    );
+
+    let hover = client.request("textDocument/hover", position(DOGS, 2, 7));
+    assert_eq!(
+        hover["contents"]["value"],
+        "```cloesce\nmodel Dog for db\n```"
+    );
+
+    client.shutdown();
+}
+
+const BILLING_SRC: &str = r#"d1 { db }
+
+namespace billing {
+    model Invoice for db {
+        primary {
+            id: int
+        }
+
+        foreign Item::id {
+            itemId
+        }
+    }
+
+    model Item for db {
+        primary {
+            id: int
+        }
+    }
+}
+
+model Payment for db {
+    primary {
+        id: int
+    }
+
+    foreign billing::Invoice::id {
+        invoiceId
+    }
+}
+"#;
+
+#[test]
+fn resolves_names_in_namespaces() {
+    let mut client = Client::start();
+    client.open(BILLING, BILLING_SRC);
+    assert_eq!(client.diagnostics(BILLING), Vec::<Value>::new());
+
+    // `Item` and `id` in `foreign Item::id`, written inside the namespace
+    let def = client.request("textDocument/definition", position(BILLING, 8, 17));
+    assert_eq!(def, json!({ "uri": BILLING, "range": range(13, 10, 14) }));
+    let def = client.request("textDocument/definition", position(BILLING, 8, 23));
+    assert_eq!(def, json!({ "uri": BILLING, "range": range(15, 12, 14) }));
+
+    // `Invoice` and `id` in `foreign billing::Invoice::id`, written outside it
+    let def = client.request("textDocument/definition", position(BILLING, 25, 23));
+    assert_eq!(def, json!({ "uri": BILLING, "range": range(3, 10, 17) }));
+    let def = client.request("textDocument/definition", position(BILLING, 25, 31));
+    assert_eq!(def, json!({ "uri": BILLING, "range": range(5, 12, 14) }));
+
+    let mut refs = client.request(
+        "textDocument/references",
+        json!({
+            "textDocument": { "uri": BILLING },
+            "position": { "line": 3, "character": 12 },
+            "context": { "includeDeclaration": true }
+        }),
+    );
+    let refs = refs.as_array_mut().unwrap();
+    let mut expected = vec![
+        json!({ "uri": BILLING, "range": range(3, 10, 17) }),
+        json!({ "uri": BILLING, "range": range(25, 21, 28) }),
+    ];
+    refs.sort_by_key(|r| r.to_string());
+    expected.sort_by_key(|r| r.to_string());
+    assert_eq!(*refs, expected);
+
+    // A field of a namespaced model is typed from the generated model
+    let hover = client.request("textDocument/hover", position(BILLING, 9, 14));
+    assert_eq!(
+        hover["contents"]["value"],
+        "```cloesce\nInvoice::itemId: int\n```"
+    );
+
+    client.shutdown();
+}
+
+#[test]
+fn formats_document() {
+    let mut client = Client::start();
+    let formatted = "model Dog for db {\n    primary {\n        id: int\n    }\n}";
+    client.open(DOGS, "model Dog for db { primary {   id: int } }\n");
+
+    let edits = client.request(
+        "textDocument/formatting",
+        json!({
+            "textDocument": { "uri": DOGS },
+            "options": { "tabSize": 4, "insertSpaces": true }
+        }),
+    );
+    let edits = edits.as_array().unwrap();
+    assert_eq!(edits.len(), 1);
+    assert_eq!(
+        edits[0]["range"]["start"],
+        json!({ "line": 0, "character": 0 })
+    );
+    assert_eq!(edits[0]["newText"], formatted);
+
+    // Already formatted documents need no edits
+    client.open(DOGS, formatted);
+    let edits = client.request(
+        "textDocument/formatting",
+        json!({
+            "textDocument": { "uri": DOGS },
+            "options": { "tabSize": 4, "insertSpaces": true }
+        }),
+    );
+    assert_eq!(edits, json!([]));
+
+    client.shutdown();
+}
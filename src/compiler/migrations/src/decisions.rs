@@ -0,0 +1,271 @@
+//! Declarative answers to [MigrationsDilemma]s, for running migrations without a prompt.
+//!
+//! A decisions file maps a model (`Model`) or column (`Model.column`) to a [MigrationsDecision]:
+//!
+//! ```json
+//! {
+//!     "User.fullName": { "rename_from": "name" },
+//!     "User.nickname": "drop",
+//!     "Person": { "rename_from": "User" }
+//! }
+//! ```
+//!
+//! A rename is keyed by the new name, while a drop is keyed by the name being dropped.
+
+use std::{borrow::Cow, cell::RefCell, collections::BTreeMap, fmt::Display};
+
+use serde::Deserialize;
+
+use crate::{MigrationsDilemma, MigrationsIntent};
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationsDecision {
+    /// The model or column no longer exists
+    Drop,
+
+    /// The model or column was renamed from the given name
+    RenameFrom(String),
+}
+
+/// A [MigrationsDilemma] posed to [MigrationsDecisions].
+#[derive(Debug, Clone)]
+pub struct PosedDilemma {
+    /// `model` or `column`
+    pub kind: &'static str,
+
+    /// The model name the dilemma is scoped to, if a column
+    pub model_name: Option<String>,
+
+    /// The model or column missing from the new schema
+    pub name: String,
+
+    /// Names the model or column could have been renamed to
+    pub options: Vec<String>,
+
+    /// The index of the chosen rename option, `Some(None)` for a drop, or
+    /// `None` when no decision answers the dilemma.
+    pub answer: Option<Option<usize>>,
+}
+
+impl PosedDilemma {
+    fn key(&self, name: &str) -> String {
+        match &self.model_name {
+            Some(model) => format!("{model}.{name}"),
+            None => name.to_string(),
+        }
+    }
+}
+
+impl Display for PosedDilemma {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let target = self.key(&self.name);
+        match self.answer {
+            Some(Some(i)) => write!(
+                f,
+                "{} \"{target}\" is renamed to \"{}\"",
+                self.kind,
+                self.key(&self.options[i])
+            ),
+            Some(None) => write!(f, "{} \"{target}\" is dropped", self.kind),
+            None => {
+                write!(
+                    f,
+                    "{} \"{target}\" was renamed or dropped, answer with \"{target}\": \"drop\"",
+                    self.kind
+                )?;
+                for option in &self.options {
+                    write!(
+                        f,
+                        " or \"{}\": {{ \"rename_from\": \"{}\" }}",
+                        self.key(option),
+                        self.name
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A [MigrationsIntent] answering from a set of [MigrationsDecision]s, recording every
+/// dilemma it is posed.
+///
+/// An unanswered dilemma is treated as a drop, so callers must check
+/// [MigrationsDecisions::unanswered] before using the generated migration.
+#[derive(Default)]
+pub struct MigrationsDecisions {
+    decisions: BTreeMap<String, MigrationsDecision>,
+    posed: RefCell<Vec<PosedDilemma>>,
+}
+
+impl MigrationsDecisions {
+    pub fn new(decisions: BTreeMap<String, MigrationsDecision>) -> Self {
+        Self {
+            decisions,
+            posed: RefCell::default(),
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json)
+            .map(Self::new)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Decisions undoing the renames answered in `posed`, for migrating back to the
+    /// last migrated state. Anything else is left unanswered, and so dropped.
+    pub fn reverse_of(posed: &[PosedDilemma]) -> Self {
+        let renames = posed.iter().filter_map(|d| match d.answer {
+            Some(Some(i)) => Some((d, d.options[i].as_str())),
+            _ => None,
+        });
+
+        // Columns are posed under the new model name, but reversed under the old.
+        let model_renames = renames
+            .clone()
+            .filter(|(d, _)| d.model_name.is_none())
+            .map(|(d, new)| (new, d.name.as_str()))
+            .collect::<BTreeMap<_, _>>();
+
+        let decisions = renames
+            .map(|(d, new)| {
+                let key = match &d.model_name {
+                    Some(model) => {
+                        let model = model_renames.get(model.as_str()).copied().unwrap_or(model);
+                        format!("{model}.{}", d.name)
+                    }
+                    None => d.name.clone(),
+                };
+                (key, MigrationsDecision::RenameFrom(new.to_string()))
+            })
+            .collect();
+
+        Self::new(decisions)
+    }
+
+    /// Every dilemma posed so far, in order.
+    pub fn posed(&self) -> Vec<PosedDilemma> {
+        self.posed.borrow().clone()
+    }
+
+    /// Dilemmas posed so far that no decision answered.
+    pub fn unanswered(&self) -> Vec<PosedDilemma> {
+        self.posed
+            .borrow()
+            .iter()
+            .filter(|d| d.answer.is_none())
+            .cloned()
+            .collect()
+    }
+
+    fn answer(&self, dilemma: &PosedDilemma) -> Option<Option<usize>> {
+        let rename = dilemma.options.iter().position(|option| {
+            self.decisions.get(&dilemma.key(option))
+                == Some(&MigrationsDecision::RenameFrom(dilemma.name.clone()))
+        });
+        if rename.is_some() {
+            return Some(rename);
+        }
+
+        match self.decisions.get(&dilemma.key(&dilemma.name)) {
+            Some(MigrationsDecision::Drop) => Some(None),
+            _ => None,
+        }
+    }
+}
+
+impl MigrationsIntent for MigrationsDecisions {
+    fn ask(&self, dilemma: MigrationsDilemma) -> Option<usize> {
+        let mut posed = PosedDilemma::from(dilemma);
+        posed.answer = self.answer(&posed);
+        let answer = posed.answer.flatten();
+        self.posed.borrow_mut().push(posed);
+        answer
+    }
+}
+
+/// Wraps a [MigrationsIntent], recording every dilemma posed to it along with its answer.
+pub struct MigrationsRecorder<'a> {
+    intent: &'a dyn MigrationsIntent,
+    posed: RefCell<Vec<PosedDilemma>>,
+}
+
+impl<'a> MigrationsRecorder<'a> {
+    pub fn new(intent: &'a dyn MigrationsIntent) -> Self {
+        Self {
+            intent,
+            posed: RefCell::default(),
+        }
+    }
+
+    /// Every dilemma posed so far, in order.
+    pub fn posed(&self) -> Vec<PosedDilemma> {
+        self.posed.borrow().clone()
+    }
+}
+
+impl MigrationsIntent for MigrationsRecorder<'_> {
+    fn ask(&self, dilemma: MigrationsDilemma) -> Option<usize> {
+        let mut posed = PosedDilemma::from(dilemma);
+        let answer = match &posed {
+            PosedDilemma {
+                model_name: None,
+                name,
+                options,
+                ..
+            } => self.intent.ask(MigrationsDilemma::RenameOrDropModel {
+                model_name: name,
+                options: &options.iter().map(String::as_str).collect(),
+            }),
+            PosedDilemma {
+                model_name: Some(model_name),
+                name,
+                options,
+                ..
+            } => {
+                let options = options
+                    .iter()
+                    .map(|o| Cow::Borrowed(o.as_str()))
+                    .collect::<Vec<_>>();
+                self.intent.ask(MigrationsDilemma::RenameOrDropColumn {
+                    model_name,
+                    column_name: name,
+                    options: &options.iter().collect(),
+                })
+            }
+        };
+
+        posed.answer = Some(answer);
+        self.posed.borrow_mut().push(posed);
+        answer
+    }
+}
+
+impl From<MigrationsDilemma<'_>> for PosedDilemma {
+    fn from(dilemma: MigrationsDilemma<'_>) -> Self {
+        match dilemma {
+            MigrationsDilemma::RenameOrDropModel {
+                model_name,
+                options,
+            } => PosedDilemma {
+                kind: "model",
+                model_name: None,
+                name: model_name.to_string(),
+                options: options.iter().map(|o| o.to_string()).collect(),
+                answer: None,
+            },
+            MigrationsDilemma::RenameOrDropColumn {
+                model_name,
+                column_name,
+                options,
+            } => PosedDilemma {
+                kind: "column",
+                model_name: Some(model_name.to_string()),
+                name: column_name.to_string(),
+                options: options.iter().map(|o| o.to_string()).collect(),
+                answer: None,
+            },
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Rollback (down) migrations, reversing a forward migration back to the last migrated state.
+//!
+//! A down migration is the forward migration run in reverse, from the new [MigrationsIdl] to the
+//! last migrated one, where the renames answered going forward are undone. Reversing
+//! can lose data: tables and columns added going forward are dropped, and anything dropped going
+//! forward is restored without its rows. Each such step is reported as a loss.
+
+use std::collections::{BTreeMap, HashMap};
+
+use indexmap::IndexMap;
+
+use crate::{MigrationsDecisions, MigrationsGenerator, MigrationsIdl, PosedDilemma};
+
+/// SQL reversing a forward migration, with every step that cannot be reversed without data loss.
+pub struct DownMigration {
+    pub sql: String,
+    pub losses: Vec<String>,
+}
+
+impl MigrationsGenerator {
+    /// Produces SQL reverting the forward migration from `lm_idl` to `idl`, given the
+    /// dilemmas answered while generating it.
+    ///
+    /// Without a last migrated IDL, every table is dropped.
+    pub fn migrate_down(
+        idl: &MigrationsIdl,
+        lm_idl: Option<&MigrationsIdl>,
+        posed: &[PosedDilemma],
+    ) -> DownMigration {
+        let empty = MigrationsIdl {
+            hash: 0,
+            models: IndexMap::default(),
+            enums: BTreeMap::default(),
+        };
+        let lm_idl = lm_idl.unwrap_or(&empty);
+
+        let intent = MigrationsDecisions::reverse_of(posed);
+        let sql = Self::migrate(lm_idl, Some(idl), &intent);
+        let losses = losses(idl, lm_idl, posed);
+
+        let sql = if losses.is_empty() {
+            sql
+        } else {
+            let warnings = losses
+                .iter()
+                .map(|l| format!("-- WARNING: {l}\n"))
+                .collect::<String>();
+            format!("{warnings}\n{sql}")
+        };
+
+        DownMigration { sql, losses }
+    }
+}
+
+/// Every step reverting `idl` to `lm_idl` that loses data.
+fn losses(idl: &MigrationsIdl, lm_idl: &MigrationsIdl, posed: &[PosedDilemma]) -> Vec<String> {
+    // Renames made going forward, from new name to last migrated name.
+    let mut model_renames = HashMap::<&str, &str>::new();
+    let mut column_renames = HashMap::<(&str, &str), &str>::new();
+    for dilemma in posed {
+        let Some(Some(i)) = dilemma.answer else {
+            continue;
+        };
+        let new = dilemma.options[i].as_str();
+        match &dilemma.model_name {
+            Some(model) => {
+                column_renames.insert((model.as_str(), new), dilemma.name.as_str());
+            }
+            None => {
+                model_renames.insert(new, dilemma.name.as_str());
+            }
+        }
+    }
+
+    let mut res = vec![];
+    let mut restored = lm_idl.models.keys().map(String::as_str).collect::<Vec<_>>();
+
+    for model in idl.models.values() {
+        let lm_name = model_renames
+            .get(model.name.as_str())
+            .copied()
+            .unwrap_or(&model.name);
+        let Some(lm_model) = lm_idl.models.get(lm_name) else {
+            res.push(format!(
+                "dropping table \"{}\" deletes all of its rows",
+                model.name
+            ));
+            continue;
+        };
+        restored.retain(|m| *m != lm_name);
+
+        if lm_model.hash == model.hash {
+            continue;
+        }
+
+        let mut restored_cols = lm_model
+            .all_columns()
+            .map(|(c, _)| c.field.name.as_ref())
+            .collect::<Vec<_>>();
+
+        for (col, _) in model.all_columns() {
+            let col_name = col.field.name.as_ref();
+            let lm_col_name = column_renames
+                .get(&(model.name.as_str(), col_name))
+                .copied()
+                .unwrap_or(col_name);
+            let Some((lm_col, _)) = lm_model
+                .all_columns()
+                .find(|(c, _)| c.field.name == lm_col_name)
+            else {
+                res.push(format!(
+                    "dropping column \"{}.{col_name}\" deletes its values",
+                    model.name
+                ));
+                continue;
+            };
+            restored_cols.retain(|c| *c != lm_col_name);
+
+            if lm_col.field.cidl_type != col.field.cidl_type {
+                res.push(format!(
+                    "reverting the type of column \"{}.{col_name}\" deletes its values",
+                    model.name
+                ));
+            }
+        }
+
+        for col_name in restored_cols {
+            res.push(format!(
+                "restored column \"{lm_name}.{col_name}\" is filled with default values"
+            ));
+        }
+    }
+
+    for lm_name in restored {
+        res.push(format!("restored table \"{lm_name}\" is empty"));
+    }
+
+    res
+}
@@ -0,0 +1,396 @@
+//! Drift detection between a [MigrationsIdl] and the schema of a live SQLite database.
+//!
+//! The schema is read through `sqlite_master` and the `table_info`, `foreign_key_list`,
+//! `index_list` and `index_info` pragmas into a [SqliteSchema], then compared table by table
+//! against the models of the IDL, producing a list of [Drift]s.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+};
+
+use idl::{CidlType, Column};
+use serde::Serialize;
+use sqlx::{Row, SqlitePool};
+
+use crate::{MigrationsIdl, MigrationsModel};
+
+/// Tables managed by SQLite, D1 or Cloesce itself, which are never drift.
+const INTERNAL_TABLES: [&str; 3] = ["sqlite_", "_cf_", "d1_migrations"];
+const CLOESCE_TMP_TABLE: &str = "$cloesce_tmp";
+
+/// The schema of a SQLite database, by table name.
+#[derive(Debug, Default)]
+pub struct SqliteSchema {
+    pub tables: BTreeMap<String, SqliteTable>,
+}
+
+#[derive(Debug, Default)]
+pub struct SqliteTable {
+    pub columns: Vec<SqliteColumn>,
+
+    /// `(column, referenced table, referenced column)`
+    pub foreign_keys: BTreeSet<(String, String, String)>,
+
+    /// Sorted column names of each unique constraint.
+    pub uniques: BTreeSet<Vec<String>>,
+}
+
+#[derive(Debug)]
+pub struct SqliteColumn {
+    pub name: String,
+    pub sql_type: String,
+    pub not_null: bool,
+    pub primary_key: bool,
+}
+
+impl SqliteSchema {
+    /// Reads the schema of every user table in the database.
+    pub async fn read(db: &SqlitePool) -> Result<Self, sqlx::Error> {
+        let names = sqlx::query_scalar::<_, String>(
+            "SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name",
+        )
+        .fetch_all(db)
+        .await?;
+
+        let mut tables = BTreeMap::new();
+        for name in names {
+            if name == CLOESCE_TMP_TABLE || INTERNAL_TABLES.iter().any(|t| name.starts_with(t)) {
+                continue;
+            }
+
+            let table = SqliteTable::read(db, &name).await?;
+            tables.insert(name, table);
+        }
+
+        Ok(Self { tables })
+    }
+}
+
+impl SqliteTable {
+    async fn read(db: &SqlitePool, name: &str) -> Result<Self, sqlx::Error> {
+        let name = name.replace('"', "\"\"");
+
+        let columns = sqlx::query(&format!("PRAGMA table_info(\"{name}\")"))
+            .fetch_all(db)
+            .await?
+            .iter()
+            .map(|row| SqliteColumn {
+                name: row.get("name"),
+                sql_type: row.get::<String, _>("type").to_lowercase(),
+                not_null: row.get("notnull"),
+                primary_key: row.get::<i64, _>("pk") > 0,
+            })
+            .collect();
+
+        let foreign_keys = sqlx::query(&format!("PRAGMA foreign_key_list(\"{name}\")"))
+            .fetch_all(db)
+            .await?
+            .iter()
+            .map(|row| (row.get("from"), row.get("table"), row.get("to")))
+            .collect();
+
+        let mut uniques = BTreeSet::new();
+        for index in sqlx::query(&format!("PRAGMA index_list(\"{name}\")"))
+            .fetch_all(db)
+            .await?
+        {
+            if index.get::<String, _>("origin") != "u" {
+                continue;
+            }
+
+            let index_name = index.get::<String, _>("name").replace('"', "\"\"");
+            let mut columns = sqlx::query(&format!("PRAGMA index_info(\"{index_name}\")"))
+                .fetch_all(db)
+                .await?
+                .iter()
+                .map(|row| row.get::<String, _>("name"))
+                .collect::<Vec<_>>();
+            columns.sort();
+            uniques.insert(columns);
+        }
+
+        Ok(Self {
+            columns,
+            foreign_keys,
+            uniques,
+        })
+    }
+}
+
+/// A difference between a [MigrationsIdl] and a [SqliteSchema].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Drift {
+    MissingTable {
+        table: String,
+    },
+    UnexpectedTable {
+        table: String,
+    },
+    MissingColumn {
+        table: String,
+        column: String,
+    },
+    UnexpectedColumn {
+        table: String,
+        column: String,
+    },
+    ColumnType {
+        table: String,
+        column: String,
+        expected: String,
+        found: String,
+    },
+
+    /// The database refuses `NULL` in a column the model allows it in.
+    ///
+    /// The reverse is not drift, as columns added to an existing table are never `NOT NULL`.
+    ColumnNotNull {
+        table: String,
+        column: String,
+    },
+    PrimaryKey {
+        table: String,
+        expected: Vec<String>,
+        found: Vec<String>,
+    },
+    MissingForeignKey {
+        table: String,
+        column: String,
+        references: String,
+    },
+    UnexpectedForeignKey {
+        table: String,
+        column: String,
+        references: String,
+    },
+    MissingUnique {
+        table: String,
+        columns: Vec<String>,
+    },
+    UnexpectedUnique {
+        table: String,
+        columns: Vec<String>,
+    },
+}
+
+impl Display for Drift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Drift::MissingTable { table } => write!(f, "table \"{table}\" is missing"),
+            Drift::UnexpectedTable { table } => write!(f, "table \"{table}\" is not in the schema"),
+            Drift::MissingColumn { table, column } => {
+                write!(f, "column \"{table}.{column}\" is missing")
+            }
+            Drift::UnexpectedColumn { table, column } => {
+                write!(f, "column \"{table}.{column}\" is not in the schema")
+            }
+            Drift::ColumnType {
+                table,
+                column,
+                expected,
+                found,
+            } => write!(
+                f,
+                "column \"{table}.{column}\" has type {found}, expected {expected}"
+            ),
+            Drift::ColumnNotNull { table, column } => {
+                write!(
+                    f,
+                    "column \"{table}.{column}\" is NOT NULL, expected nullable"
+                )
+            }
+            Drift::PrimaryKey {
+                table,
+                expected,
+                found,
+            } => write!(
+                f,
+                "table \"{table}\" has primary key ({}), expected ({})",
+                found.join(", "),
+                expected.join(", ")
+            ),
+            Drift::MissingForeignKey {
+                table,
+                column,
+                references,
+            } => write!(
+                f,
+                "foreign key \"{table}.{column}\" referencing \"{references}\" is missing"
+            ),
+            Drift::UnexpectedForeignKey {
+                table,
+                column,
+                references,
+            } => write!(
+                f,
+                "foreign key \"{table}.{column}\" referencing \"{references}\" is not in the schema"
+            ),
+            Drift::MissingUnique { table, columns } => write!(
+                f,
+                "unique constraint on \"{table}\" ({}) is missing",
+                columns.join(", ")
+            ),
+            Drift::UnexpectedUnique { table, columns } => write!(
+                f,
+                "unique constraint on \"{table}\" ({}) is not in the schema",
+                columns.join(", ")
+            ),
+        }
+    }
+}
+
+pub struct DriftDetector;
+impl DriftDetector {
+    /// Compares every model of the IDL against its table in the schema.
+    pub fn detect(idl: &MigrationsIdl, schema: &SqliteSchema) -> Vec<Drift> {
+        let mut res = vec![];
+
+        for model in idl.models.values() {
+            match schema.tables.get(&model.name) {
+                Some(table) => Self::table(model, table, &mut res),
+                None => res.push(Drift::MissingTable {
+                    table: model.name.clone(),
+                }),
+            }
+        }
+
+        for name in schema.tables.keys() {
+            if !idl.models.contains_key(name) {
+                res.push(Drift::UnexpectedTable {
+                    table: name.clone(),
+                });
+            }
+        }
+
+        res
+    }
+
+    fn table(model: &MigrationsModel, table: &SqliteTable, res: &mut Vec<Drift>) {
+        let name = &model.name;
+
+        for (col, _) in model.all_columns() {
+            let col_name = col.field.name.as_ref();
+            let Some(found) = table.columns.iter().find(|c| c.name == col_name) else {
+                res.push(Drift::MissingColumn {
+                    table: name.clone(),
+                    column: col_name.to_string(),
+                });
+                continue;
+            };
+
+            let expected = sql_type(col);
+            if found.sql_type != expected {
+                res.push(Drift::ColumnType {
+                    table: name.clone(),
+                    column: col_name.to_string(),
+                    expected: expected.to_string(),
+                    found: found.sql_type.clone(),
+                });
+            }
+
+            if found.not_null && col.field.cidl_type.is_nullable() {
+                res.push(Drift::ColumnNotNull {
+                    table: name.clone(),
+                    column: col_name.to_string(),
+                });
+            }
+        }
+
+        for found in &table.columns {
+            if !model.all_columns().any(|(c, _)| c.field.name == found.name) {
+                res.push(Drift::UnexpectedColumn {
+                    table: name.clone(),
+                    column: found.name.clone(),
+                });
+            }
+        }
+
+        let mut expected_pk = model
+            .primary_columns
+            .iter()
+            .map(|c| c.field.name.to_string())
+            .collect::<Vec<_>>();
+        let mut found_pk = table
+            .columns
+            .iter()
+            .filter(|c| c.primary_key)
+            .map(|c| c.name.clone())
+            .collect::<Vec<_>>();
+        expected_pk.sort();
+        found_pk.sort();
+        if expected_pk != found_pk {
+            res.push(Drift::PrimaryKey {
+                table: name.clone(),
+                expected: expected_pk,
+                found: found_pk,
+            });
+        }
+
+        let expected_fks = model
+            .all_columns()
+            .filter_map(|(c, _)| {
+                let fk = c.foreign_key_reference.as_ref()?;
+                Some((
+                    c.field.name.to_string(),
+                    fk.model_name.to_string(),
+                    fk.column_name.to_string(),
+                ))
+            })
+            .collect::<BTreeSet<_>>();
+        for (column, ref_table, ref_column) in expected_fks.difference(&table.foreign_keys) {
+            res.push(Drift::MissingForeignKey {
+                table: name.clone(),
+                column: column.clone(),
+                references: format!("{ref_table}.{ref_column}"),
+            });
+        }
+        for (column, ref_table, ref_column) in table.foreign_keys.difference(&expected_fks) {
+            res.push(Drift::UnexpectedForeignKey {
+                table: name.clone(),
+                column: column.clone(),
+                references: format!("{ref_table}.{ref_column}"),
+            });
+        }
+
+        let mut expected_uniques = BTreeMap::<usize, Vec<String>>::new();
+        for (col, _) in model.all_columns() {
+            for id in &col.unique_ids {
+                expected_uniques
+                    .entry(*id)
+                    .or_default()
+                    .push(col.field.name.to_string());
+            }
+        }
+        let expected_uniques = expected_uniques
+            .into_values()
+            .map(|mut cols| {
+                cols.sort();
+                cols
+            })
+            .collect::<BTreeSet<_>>();
+        for columns in expected_uniques.difference(&table.uniques) {
+            res.push(Drift::MissingUnique {
+                table: name.clone(),
+                columns: columns.clone(),
+            });
+        }
+        for columns in table.uniques.difference(&expected_uniques) {
+            res.push(Drift::UnexpectedUnique {
+                table: name.clone(),
+                columns: columns.clone(),
+            });
+        }
+    }
+}
+
+/// The SQLite type a column is created with, matching the migrations generator.
+fn sql_type(col: &Column) -> &'static str {
+    match col.field.cidl_type.root_type() {
+        CidlType::Int | CidlType::Boolean => "integer",
+        CidlType::Real => "real",
+        CidlType::Blob => "blob",
+        _ => "text",
+    }
+}
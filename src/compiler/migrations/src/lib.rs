@@ -9,23 +9,40 @@
 //! Some migration scenarios require user intervention, such as when a model or column is dropped in the new IDL but could
 //! potentially be a rename. Because it is impossible to determine the intent from the IDLs alone, the generator poses a
 //! [MigrationsDilemma] to a provided [MigrationsIntent], which is a blocking call to allow the user to respond
-//! with their intent.
+//! with their intent. [MigrationsDecisions] answers dilemmas from a declarative decisions file instead.
+//!
+//! ## Down Migrations
+//!
+//! [MigrationsGenerator::migrate_down] reverses a migration, undoing the renames answered going forward
+//! and reporting every step that loses data.
+//!
+//! ## Drift
+//!
+//! [DriftDetector] compares a [MigrationsIdl] against the [SqliteSchema] read from a live database.
 
 use std::{
     borrow::Cow,
     collections::{BTreeMap, HashMap, HashSet},
 };
 
-use idl::{CidlType, Column, ModelBacking};
+use idl::{CidlType, Column, ColumnDefault, Enum, ModelBacking, ModelIndex, Number};
 
 use indexmap::IndexMap;
 use sea_query::{
-    ColumnDef, Expr, ForeignKey, Index, Query, SchemaStatementBuilder, SqliteQueryBuilder, Table,
+    ColumnDef, Expr, ForeignKey, Index, Query, SchemaStatementBuilder, SimpleExpr,
+    SqliteQueryBuilder, Table,
 };
 use serde::{Deserialize, Serialize};
 
+mod decisions;
+mod down;
+mod drift;
 mod fmt;
 
+pub use decisions::{MigrationsDecision, MigrationsDecisions, MigrationsRecorder, PosedDilemma};
+pub use down::DownMigration;
+pub use drift::{Drift, DriftDetector, SqliteColumn, SqliteSchema, SqliteTable};
+
 /// A subset of [idl::Model] suited for migrations.
 #[derive(Serialize, Deserialize)]
 pub struct MigrationsModel<'src> {
@@ -40,6 +57,9 @@ pub struct MigrationsModel<'src> {
 
     #[serde(borrow)]
     pub columns: Vec<Column<'src>>,
+
+    #[serde(borrow, default)]
+    pub indexes: Vec<ModelIndex<'src>>,
 }
 
 impl<'src> MigrationsModel<'src> {
@@ -60,6 +80,10 @@ pub struct MigrationsIdl<'src> {
 
     #[serde(borrow)]
     pub models: IndexMap<String, MigrationsModel<'src>>,
+
+    /// Enums referenced by columns, stored as `text` with a `CHECK` constraint on their members.
+    #[serde(borrow, default)]
+    pub enums: BTreeMap<&'src str, Enum<'src>>,
 }
 
 impl<'src> MigrationsIdl<'src> {
@@ -154,10 +178,7 @@ enum AlterKind<'a> {
 struct MigrateTables;
 impl MigrateTables {
     /// Takes in a list of models, generating a list of naive insert queries.
-    fn create(
-        sorted_models: Vec<&MigrationsModel>,
-        model_lookup: &IndexMap<String, MigrationsModel>,
-    ) -> Vec<String> {
+    fn create(sorted_models: Vec<&MigrationsModel>, idl: &MigrationsIdl) -> Vec<String> {
         let mut res = vec![];
 
         for model in sorted_models {
@@ -172,7 +193,7 @@ impl MigrateTables {
             for (col, is_pk) in model.all_columns() {
                 // Set primary keys
                 if is_pk {
-                    let mut column = typed_column(&col.field.name, &col.field.cidl_type, false);
+                    let mut column = typed_column(col, false, &idl.enums);
                     if is_composite_pk {
                         column.not_null();
                     } else {
@@ -195,7 +216,8 @@ impl MigrateTables {
                     continue;
                 };
 
-                let ref_model_has_composite_pk = model_lookup
+                let ref_model_has_composite_pk = idl
+                    .models
                     .get(fk_ref.model_name)
                     .map(|m| m.primary_columns.len() > 1)
                     .unwrap_or(false);
@@ -222,7 +244,7 @@ impl MigrateTables {
 
             // Columns
             for col in model.columns.iter() {
-                let mut column = typed_column(&col.field.name, &col.field.cidl_type, false);
+                let mut column = typed_column(col, false, &idl.enums);
 
                 let single_column_unique = col.unique_ids.iter().any(|id| {
                     unique_columns_by_id
@@ -275,6 +297,12 @@ impl MigrateTables {
 
             res.push(to_sqlite(table));
             tracing::info!("Created table \"{}\"", model.name);
+
+            // Secondary indexes
+            for index in &model.indexes {
+                res.push(create_index(&model.name, index));
+                tracing::info!("Created index \"{}\"", index.name);
+            }
         }
 
         res
@@ -290,7 +318,8 @@ impl MigrateTables {
     /// table alteration on the (model, last migrated model) pair.
     fn alter<'a>(
         alter_models: Vec<(&'a MigrationsModel, &'a MigrationsModel)>,
-        model_lookup: &IndexMap<String, MigrationsModel>,
+        idl: &MigrationsIdl,
+        lm_enums: &BTreeMap<&str, Enum>,
         intent: &dyn MigrationsIntent,
     ) -> Vec<String> {
         const PRAGMA_FK_OFF: &str = "PRAGMA foreign_keys = OFF;";
@@ -303,7 +332,36 @@ impl MigrateTables {
         for (model, lm_model) in alter_models {
             let mut needs_rename_intent = HashMap::<&str, &Column>::new();
             let mut needs_drop_intent = vec![];
-            let alterations = identify_alterations(model, lm_model, &renamed);
+            let alterations = identify_alterations(model, lm_model, &renamed, &idl.enums, lm_enums);
+            let rebuild = alterations
+                .iter()
+                .any(|a| matches!(a, AlterKind::RebuildTable));
+
+            // An index is kept only if it is unchanged and none of its columns are
+            // altered. Anything else is dropped before the columns change, since SQLite
+            // refuses to drop an indexed column. A rebuild recreates every index.
+            let is_kept = |index: &ModelIndex| {
+                !rebuild
+                    && index.columns.iter().all(|name| {
+                        let ty = |m: &'a MigrationsModel| {
+                            m.all_columns()
+                                .find(|(c, _)| c.field.name == *name)
+                                .map(|(c, _)| &c.field.cidl_type)
+                        };
+                        ty(model).is_some() && ty(model) == ty(lm_model)
+                    })
+            };
+            let (kept, stale): (Vec<_>, Vec<_>) = lm_model
+                .indexes
+                .iter()
+                .partition(|lm_index| model.indexes.contains(lm_index) && is_kept(lm_index));
+
+            for lm_index in stale {
+                res.push(to_sqlite(
+                    Index::drop().name(&lm_index.name).if_exists().to_owned(),
+                ));
+                tracing::info!("Dropped index \"{}\"", lm_index.name);
+            }
 
             for kind in alterations {
                 match kind {
@@ -340,11 +398,7 @@ impl MigrateTables {
                             res.push(to_sqlite(
                                 Table::alter()
                                     .table(alias(&model.name))
-                                    .add_column(typed_column(
-                                        &col.field.name,
-                                        &col.field.cidl_type,
-                                        true,
-                                    ))
+                                    .add_column(typed_column(col, true, &idl.enums))
                                     .to_owned(),
                             ));
                         }
@@ -396,7 +450,7 @@ impl MigrateTables {
 
                         // Create the new model
                         {
-                            let create_stmts = Self::create(vec![model], model_lookup);
+                            let create_stmts = Self::create(vec![model], idl);
                             for stmt in create_stmts {
                                 res.push(stmt);
                             }
@@ -409,24 +463,20 @@ impl MigrateTables {
                                 .map(|(c, _)| (&c.field.name, &c.field))
                                 .collect::<HashMap<_, _>>();
 
-                            let columns = model
-                                .all_columns()
-                                .map(|(c, _)| &c.field)
-                                .collect::<Vec<_>>();
+                            let columns = model.all_columns().map(|(c, _)| c).collect::<Vec<_>>();
 
                             let insert = Query::insert()
                                 .into_table(alias(&model.name))
-                                .columns(columns.iter().map(|a| alias(a.name.as_ref())))
+                                .columns(columns.iter().map(|c| alias(c.field.name.as_ref())))
                                 .select_from(
                                     Query::select()
                                         .from(alias(name_hash))
-                                        .exprs(columns.iter().map(|model_c| {
+                                        .exprs(columns.iter().map(|c| {
+                                            let model_c = &c.field;
                                             let Some(lm_c) = lm_col_lookup.get(&model_c.name)
                                             else {
                                                 // Column is new, use a default value
-                                                return Expr::value(sql_default(
-                                                    &model_c.cidl_type,
-                                                ));
+                                                return sql_default(c, &idl.enums);
                                             };
 
                                             let col = Expr::col(alias(lm_c.name.as_ref()));
@@ -439,7 +489,9 @@ impl MigrateTables {
                                                 {
                                                     CidlType::Int | CidlType::Boolean => "integer",
                                                     CidlType::Real => "real",
-                                                    CidlType::String | CidlType::DateIso => "text",
+                                                    CidlType::String
+                                                    | CidlType::DateIso
+                                                    | CidlType::Enum { .. } => "text",
                                                     _ => unreachable!(),
                                                 };
 
@@ -519,15 +571,19 @@ impl MigrateTables {
                 res.push(to_sqlite(
                     Table::alter()
                         .table(alias(&model.name))
-                        .add_column(typed_column(
-                            &add_col.field.name,
-                            &add_col.field.cidl_type,
-                            true,
-                        ))
+                        .add_column(typed_column(add_col, true, &idl.enums))
                         .to_owned(),
                 ));
                 tracing::info!("Added a column \"{}.{}\"", model.name, add_col.field.name);
             }
+
+            // Create new indexes, after all columns exist
+            if !rebuild {
+                for index in model.indexes.iter().filter(|i| !kept.contains(i)) {
+                    res.push(create_index(&model.name, index));
+                    tracing::info!("Created index \"{}\"", index.name);
+                }
+            }
         }
 
         return res;
@@ -536,6 +592,8 @@ impl MigrateTables {
             model: &'a MigrationsModel,
             lm_model: &'a MigrationsModel,
             renamed: &HashSet<(&str, &str)>,
+            enums: &BTreeMap<&str, Enum>,
+            lm_enums: &BTreeMap<&str, Enum>,
         ) -> Vec<AlterKind<'a>> {
             let mut alterations = vec![];
 
@@ -562,6 +620,11 @@ impl MigrateTables {
                         return vec![AlterKind::RebuildTable];
                     }
 
+                    // SQLite cannot add a column with a non-constant default
+                    if matches!(col.default, Some(ColumnDefault::Now)) {
+                        return vec![AlterKind::RebuildTable];
+                    }
+
                     alterations.push(AlterKind::AddColumn { col });
                     continue;
                 };
@@ -596,7 +659,27 @@ impl MigrateTables {
                 }
 
                 if lm_col.field.cidl_type != col.field.cidl_type {
+                    // Altering a type re-adds the column, which cannot have a
+                    // non-constant default.
+                    if matches!(col.default, Some(ColumnDefault::Now)) {
+                        return vec![AlterKind::RebuildTable];
+                    }
+
                     alterations.push(AlterKind::AlterColumnType { col, lm_col });
+                    continue;
+                }
+
+                // SQLite cannot alter a column's default or an enum's `CHECK` constraint
+                // in place. The constraint only changes with the enum's members.
+                let check_changed = match col.field.cidl_type.root_type() {
+                    CidlType::Enum { name } => {
+                        enums.get(name).map(|e| &e.members)
+                            != lm_enums.get(name).map(|e| &e.members)
+                    }
+                    _ => false,
+                };
+                if lm_col.default != col.default || check_changed {
+                    return vec![AlterKind::RebuildTable];
                 }
             }
 
@@ -646,6 +729,8 @@ impl MigrateTables {
     ) -> String {
         let _empty = IndexMap::default();
         let lm_models = lm_idl.map(|a| &a.models).unwrap_or(&_empty);
+        let _empty_enums = BTreeMap::default();
+        let lm_enums = lm_idl.map(|a| &a.enums).unwrap_or(&_empty_enums);
 
         // Partition all models into three sets, discarding the rest.
         let (creates, alters, drops) = {
@@ -711,8 +796,11 @@ impl MigrateTables {
         let mut res = String::new();
         for (title, stmts) in [
             ("Dropped Models", &Self::drop(drops)),
-            ("New Models", &Self::create(creates, &idl.models)),
-            ("Altered Models", &Self::alter(alters, &idl.models, intent)),
+            ("New Models", &Self::create(creates, idl)),
+            (
+                "Altered Models",
+                &Self::alter(alters, idl, lm_enums, intent),
+            ),
         ] {
             if stmts.is_empty() {
                 continue;
@@ -752,28 +840,49 @@ fn is_same_primary_key(model: &MigrationsModel, lm_model: &MigrationsModel) -> b
         })
 }
 
-// TODO: User made default types
-fn sql_default(ty: &CidlType) -> sea_query::Value {
+/// The current time as an ISO 8601 string, matching JavaScript's `Date.toISOString`.
+const SQL_NOW: &str = "(strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))";
+
+/// The value of a column for rows written without it: the user's default when present,
+/// otherwise `NULL` or a zero value for the column's type.
+fn sql_default(col: &Column, enums: &BTreeMap<&str, Enum>) -> SimpleExpr {
+    if let Some(default) = &col.default {
+        return match default {
+            ColumnDefault::Number(Number::Int(i)) => Expr::value(*i),
+            ColumnDefault::Number(Number::Float(f)) => Expr::value(*f),
+            ColumnDefault::Boolean(b) => Expr::value(*b as i32),
+            ColumnDefault::String(s) => Expr::value(s.as_ref()),
+            ColumnDefault::Now => Expr::cust(SQL_NOW),
+        };
+    }
+
+    let ty = &col.field.cidl_type;
     if ty.is_nullable() {
-        return sea_query::Value::Int(None);
+        return Expr::value(sea_query::Value::Int(None));
     }
     match ty {
-        CidlType::Int => sea_query::Value::Int(Some(0i32)),
-        CidlType::Real => sea_query::Value::Float(Some(0.0)),
-        CidlType::String => sea_query::Value::String(Some(Box::new("".into()))),
+        CidlType::Int => Expr::value(0i32),
+        CidlType::Real => Expr::value(0.0f32),
+        CidlType::String => Expr::value(""),
+
+        // An enum defaults to its first member, satisfying the `CHECK` constraint
+        CidlType::Enum { name } => Expr::value(enum_members(name, enums)[0]),
         _ => unreachable!(),
     }
 }
 
-fn typed_column(name: &str, ty: &CidlType, with_default: bool) -> ColumnDef {
+/// A column definition for `col`. A user specified default is always included, while
+/// `with_default` fills in a zero value for columns added to a table with existing rows.
+fn typed_column(column: &Column, with_default: bool, enums: &BTreeMap<&str, Enum>) -> ColumnDef {
+    let name = column.field.name.as_ref();
     let mut col = ColumnDef::new(alias(name));
-    let inner = match ty {
+    let inner = match &column.field.cidl_type {
         CidlType::Nullable(inner) => inner.as_ref(),
         t => t,
     };
 
-    if with_default {
-        col.default(sql_default(ty));
+    if with_default || column.default.is_some() {
+        col.default(sql_default(column, enums));
     }
 
     match inner {
@@ -781,11 +890,35 @@ fn typed_column(name: &str, ty: &CidlType, with_default: bool) -> ColumnDef {
         CidlType::Real => col.decimal(),
         CidlType::String | CidlType::DateIso => col.text(),
         CidlType::Blob => col.blob(),
+        CidlType::Enum { name: enum_name } => {
+            let members = enum_members(enum_name, enums);
+            col.text()
+                .check(Expr::col(alias(name)).is_in(members.iter().copied()))
+        }
         _ => unreachable!("column type must be validated"),
     };
     col
 }
 
+fn enum_members<'a>(name: &str, enums: &'a BTreeMap<&str, Enum>) -> &'a [&'a str] {
+    enums
+        .get(name)
+        .map(|e| e.members.as_slice())
+        .expect("enum column to reference a declared enum")
+}
+
+fn create_index(model_name: &str, index: &ModelIndex) -> String {
+    let mut create = Index::create();
+    create
+        .name(&index.name)
+        .table(alias(model_name))
+        .if_not_exists();
+    for column in &index.columns {
+        create.col(alias(*column));
+    }
+    to_sqlite(create)
+}
+
 fn to_sqlite(builder: impl SchemaStatementBuilder) -> String {
     format!("{};", builder.to_string(SqliteQueryBuilder))
 }
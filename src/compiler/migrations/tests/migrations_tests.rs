@@ -3,12 +3,14 @@ use std::collections::HashMap;
 use idl::{CloesceIdl, ModelBacking};
 
 use compiler_test::{expected_str, src_to_idl};
+use migrations::{Drift, DriftDetector, SqliteSchema};
 use migrations::{
-    MigrationsDilemma, MigrationsGenerator, MigrationsIdl, MigrationsIntent, MigrationsModel,
+    MigrationsDecisions, MigrationsDilemma, MigrationsGenerator, MigrationsIdl, MigrationsIntent,
+    MigrationsModel, MigrationsRecorder,
 };
 
 use indexmap::IndexMap;
-use sqlx::SqlitePool;
+use sqlx::{Row, SqlitePool};
 
 async fn exists_in_db(db: &SqlitePool, name: &str) -> bool {
     sqlx::query_scalar::<_, i64>(
@@ -31,7 +33,12 @@ async fn query(db: &SqlitePool, sql: &str) -> Result<(), sqlx::Error> {
 }
 
 fn as_migration(idl: CloesceIdl) -> MigrationsIdl {
-    let CloesceIdl { hash, models, .. } = idl;
+    let CloesceIdl {
+        hash,
+        models,
+        enums,
+        ..
+    } = idl;
 
     // Convert each full Model -> MigrationsModel
     let migrations_models = models
@@ -47,6 +54,7 @@ fn as_migration(idl: CloesceIdl) -> MigrationsIdl {
                 }),
                 primary_columns: model.primary_columns,
                 columns: model.columns,
+                indexes: model.indexes,
             };
             (name.to_string(), m)
         })
@@ -55,6 +63,7 @@ fn as_migration(idl: CloesceIdl) -> MigrationsIdl {
     MigrationsIdl {
         hash,
         models: migrations_models,
+        enums,
     }
 }
 
@@ -705,6 +714,639 @@ async fn migrate_with_rename(db: SqlitePool) {
     assert!(!exists_in_db(&db, "User").await);
 }
 
+#[sqlx::test]
+async fn migrate_with_decisions(db: SqlitePool) {
+    // Arrange
+    let base = {
+        let migration = src_to_migration(
+            r#"
+            d1 { db }
+
+            model User for db {
+                primary {
+                    id: int
+                }
+
+                column {
+                    name: string
+                    nickname: string
+                }
+            }
+        "#,
+        );
+
+        let sql = MigrationsGenerator::migrate(&migration, None, &MockMigrationsIntent::default());
+        query(&db, &sql)
+            .await
+            .expect("Create table queries to work");
+
+        migration
+    };
+
+    let new = src_to_migration(
+        r#"
+        d1 { db }
+
+        model User for db {
+            primary {
+                id: int
+            }
+
+            column {
+                fullName: string
+            }
+        }
+    "#,
+    );
+
+    let decisions = MigrationsDecisions::from_json(
+        r#"{
+            "User.name": "drop",
+            "User.fullName": { "rename_from": "nickname" }
+        }"#,
+    )
+    .expect("decisions to parse");
+
+    // Act
+    let sql = MigrationsGenerator::migrate(&new, Some(&base), &decisions);
+
+    // Assert
+    assert!(decisions.unanswered().is_empty());
+    expected_str!(
+        sql,
+        r#"ALTER TABLE "User" RENAME COLUMN "nickname" TO "fullName""#
+    );
+    expected_str!(sql, r#"ALTER TABLE "User" DROP COLUMN "name""#);
+
+    query(&db, &sql).await.expect("Alter table queries to work");
+
+    // Act
+    let empty = MigrationsDecisions::default();
+    MigrationsGenerator::migrate(&new, Some(&base), &empty);
+
+    // Assert
+    let unanswered = empty.unanswered();
+    assert_eq!(unanswered.len(), 2);
+    assert!(unanswered.iter().any(|d| d.to_string()
+        == r#"column "User.name" was renamed or dropped, answer with "User.name": "drop" or "User.fullName": { "rename_from": "name" }"#));
+}
+
+#[sqlx::test]
+async fn migrate_down(db: SqlitePool) {
+    // Arrange
+    let base = {
+        let migration = src_to_migration(
+            r#"
+            d1 { db }
+
+            model User for db {
+                primary {
+                    id: int
+                }
+
+                column {
+                    nickname: string
+                    bio: string
+                }
+            }
+        "#,
+        );
+
+        let sql = MigrationsGenerator::migrate(&migration, None, &MockMigrationsIntent::default());
+        query(&db, &sql)
+            .await
+            .expect("Create table queries to work");
+
+        migration
+    };
+
+    let new = src_to_migration(
+        r#"
+        d1 { db }
+
+        model AppUser for db {
+            primary {
+                id: int
+            }
+
+            column {
+                fullName: string
+                age: int
+            }
+        }
+
+        model Post for db {
+            primary {
+                id: int
+            }
+        }
+    "#,
+    );
+
+    let mut intent = MockMigrationsIntent::default();
+    intent
+        .answers
+        .insert(("User".into(), None), Some("AppUser".into()));
+    intent.answers.insert(
+        ("AppUser".into(), Some("nickname".into())),
+        Some("fullName".into()),
+    );
+    intent
+        .answers
+        .insert(("AppUser".into(), Some("bio".into())), None);
+
+    let recorder = MigrationsRecorder::new(&intent);
+    let sql = MigrationsGenerator::migrate(&new, Some(&base), &recorder);
+    query(&db, &sql).await.expect("Forward queries to work");
+
+    // Act
+    let down = MigrationsGenerator::migrate_down(&new, Some(&base), &recorder.posed());
+
+    // Assert
+    assert_eq!(
+        down.losses,
+        vec![
+            r#"dropping column "AppUser.age" deletes its values"#,
+            r#"restored column "User.bio" is filled with default values"#,
+            r#"dropping table "Post" deletes all of its rows"#,
+        ]
+    );
+    expected_str!(
+        down.sql,
+        r#"-- WARNING: dropping table "Post" deletes all of its rows"#
+    );
+    expected_str!(down.sql, r#"ALTER TABLE "AppUser" RENAME TO "User""#);
+    expected_str!(
+        down.sql,
+        r#"ALTER TABLE "User" RENAME COLUMN "fullName" TO "nickname""#
+    );
+
+    query(&db, &down.sql).await.expect("Down queries to work");
+    assert!(exists_in_db(&db, "User").await);
+    assert!(!exists_in_db(&db, "AppUser").await);
+    assert!(!exists_in_db(&db, "Post").await);
+    sqlx::query("SELECT id, nickname, bio FROM User")
+        .fetch_all(&db)
+        .await
+        .expect("Restored columns to exist");
+
+    // Act
+    let down = MigrationsGenerator::migrate_down(&base, None, &[]);
+
+    // Assert
+    query(&db, &down.sql).await.expect("Down queries to work");
+    assert!(!exists_in_db(&db, "User").await);
+}
+
+#[sqlx::test]
+async fn detect_drift(db: SqlitePool) {
+    // Arrange
+    let migration = src_to_migration(
+        r#"
+        d1 { db }
+
+        model Boss for db {
+            primary {
+                id: int
+            }
+        }
+
+        [unique email]
+        model Person for db {
+            primary {
+                id: int
+            }
+
+            foreign Boss::id {
+                bossId
+            }
+
+            column {
+                email: string
+                nickname: option<string>
+                age: int
+            }
+        }
+
+        model Tag for db {
+            primary {
+                id: int
+            }
+        }
+    "#,
+    );
+
+    let sql = MigrationsGenerator::migrate(&migration, None, &MockMigrationsIntent::default());
+    query(&db, &sql)
+        .await
+        .expect("Create table queries to work");
+
+    // Act
+    let schema = SqliteSchema::read(&db).await.expect("schema to be read");
+
+    // Assert
+    assert_eq!(DriftDetector::detect(&migration, &schema), vec![]);
+
+    // Arrange
+    query(
+        &db,
+        r#"
+        DROP TABLE "Tag";
+        CREATE TABLE "Extra" ("id" integer PRIMARY KEY);
+        ALTER TABLE "Person" DROP COLUMN "age";
+        ALTER TABLE "Person" ADD COLUMN "age" text;
+        ALTER TABLE "Person" DROP COLUMN "nickname";
+        ALTER TABLE "Person" ADD COLUMN "nickname" text NOT NULL DEFAULT '';
+        ALTER TABLE "Person" ADD COLUMN "extra" integer;
+        "#,
+    )
+    .await
+    .expect("Drift queries to work");
+
+    // Act
+    let schema = SqliteSchema::read(&db).await.expect("schema to be read");
+    let drift = DriftDetector::detect(&migration, &schema);
+
+    // Assert
+    assert_eq!(
+        drift,
+        vec![
+            Drift::MissingTable {
+                table: "Tag".into(),
+            },
+            Drift::ColumnNotNull {
+                table: "Person".into(),
+                column: "nickname".into(),
+            },
+            Drift::ColumnType {
+                table: "Person".into(),
+                column: "age".into(),
+                expected: "integer".into(),
+                found: "text".into(),
+            },
+            Drift::UnexpectedColumn {
+                table: "Person".into(),
+                column: "extra".into(),
+            },
+            Drift::UnexpectedTable {
+                table: "Extra".into(),
+            },
+        ]
+    );
+}
+
+async fn index_exists_in_db(db: &SqlitePool, name: &str) -> bool {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*)
+         FROM sqlite_master
+         WHERE type='index' AND name=?1",
+    )
+    .bind(name)
+    .fetch_one(db)
+    .await
+    .expect("Failed to check index existence")
+        > 0
+}
+
+#[sqlx::test]
+async fn migrate_with_indexes(db: SqlitePool) {
+    // Arrange
+    let base = {
+        let migration = src_to_migration(
+            r#"
+            d1 { db }
+
+            model User for db {
+                primary {
+                    id: int
+                }
+            }
+
+            [index userId]
+            [index score]
+            model Post for db {
+                primary {
+                    id: int
+                }
+
+                column {
+                    title: string
+                    score: int
+                }
+
+                foreign User::id {
+                    userId
+                }
+            }
+        "#,
+        );
+
+        let sql = MigrationsGenerator::migrate(&migration, None, &MockMigrationsIntent::default());
+        expected_str!(
+            sql,
+            r#"CREATE INDEX IF NOT EXISTS "idx$Post$userId" ON "Post" ("userId")"#
+        );
+        query(&db, &sql)
+            .await
+            .expect("Create table queries to work");
+        assert!(index_exists_in_db(&db, "idx$Post$userId").await);
+        assert!(index_exists_in_db(&db, "idx$Post$score").await);
+
+        migration
+    };
+
+    let new = src_to_migration(
+        r#"
+        d1 { db }
+
+        model User for db {
+            primary {
+                id: int
+            }
+        }
+
+        [index userId]
+        [index userId, title]
+        model Post for db {
+            primary {
+                id: int
+            }
+
+            column {
+                title: string
+            }
+
+            foreign User::id {
+                userId
+            }
+        }
+    "#,
+    );
+
+    // Act
+    let sql = MigrationsGenerator::migrate(&new, Some(&base), &MockMigrationsIntent::default());
+
+    // Assert
+    expected_str!(sql, r#"DROP INDEX IF EXISTS "idx$Post$score""#);
+    expected_str!(
+        sql,
+        r#"CREATE INDEX IF NOT EXISTS "idx$Post$userId$title" ON "Post" ("userId", "title")"#
+    );
+    assert!(
+        !sql.contains("idx$Post$userId\""),
+        "unchanged index is kept"
+    );
+    assert!(!sql.contains("RENAME TO"), "index changes do not rebuild");
+    assert!(
+        sql.find("idx$Post$score").unwrap() < sql.find("DROP COLUMN").unwrap(),
+        "indexes are dropped before their columns"
+    );
+
+    query(&db, &sql).await.expect("Alter table queries to work");
+    assert!(index_exists_in_db(&db, "idx$Post$userId").await);
+    assert!(index_exists_in_db(&db, "idx$Post$userId$title").await);
+    assert!(!index_exists_in_db(&db, "idx$Post$score").await);
+}
+
+#[sqlx::test]
+async fn migrate_enum_columns(db: SqlitePool) {
+    // Arrange
+    let base = {
+        let migration = src_to_migration(
+            r#"
+            d1 { db }
+
+            enum Status { Open, Closed }
+
+            model Ticket for db {
+                primary {
+                    id: int
+                }
+
+                column {
+                    status: Status
+                }
+            }
+        "#,
+        );
+
+        let sql = MigrationsGenerator::migrate(&migration, None, &MockMigrationsIntent::default());
+        expected_str!(
+            sql,
+            r#""status" text CHECK ("status" IN ('Open', 'Closed')) NOT NULL"#
+        );
+        query(&db, &sql)
+            .await
+            .expect("Create table queries to work");
+
+        query(&db, r#"INSERT INTO "Ticket" VALUES (1, 'Open')"#)
+            .await
+            .expect("A member to be accepted");
+        assert!(
+            query(&db, r#"INSERT INTO "Ticket" VALUES (2, 'Pending')"#)
+                .await
+                .is_err(),
+            "a non-member is rejected"
+        );
+
+        migration
+    };
+
+    // Adding a member rebuilds the table to replace the constraint
+    let new = src_to_migration(
+        r#"
+        d1 { db }
+
+        enum Status { Open, Pending, Closed }
+
+        model Ticket for db {
+            primary {
+                id: int
+            }
+
+            column {
+                status: Status
+            }
+        }
+    "#,
+    );
+    assert_ne!(base.hash, new.hash);
+
+    // Act
+    let sql = MigrationsGenerator::migrate(&new, Some(&base), &MockMigrationsIntent::default());
+
+    // Assert
+    expected_str!(sql, "RENAME TO");
+    expected_str!(
+        sql,
+        r#""status" text CHECK ("status" IN ('Open', 'Pending', 'Closed')) NOT NULL"#
+    );
+
+    query(&db, &sql).await.expect("Rebuild queries to work");
+    query(&db, r#"INSERT INTO "Ticket" VALUES (2, 'Pending')"#)
+        .await
+        .expect("The new member to be accepted");
+
+    // A changed column hash that leaves the members alone does not rebuild
+    let mut rehashed = src_to_migration(
+        r#"
+        d1 { db }
+
+        enum Status { Open, Pending, Closed }
+
+        model Ticket for db {
+            primary {
+                id: int
+            }
+
+            column {
+                status: Status
+            }
+        }
+    "#,
+    );
+    rehashed.hash ^= 1;
+    let ticket = rehashed.models.get_mut("Ticket").unwrap();
+    ticket.hash ^= 1;
+    ticket.columns[0].hash ^= 1;
+
+    let sql = MigrationsGenerator::migrate(&rehashed, Some(&new), &MockMigrationsIntent::default());
+    assert!(!sql.contains("RENAME TO"), "unexpected rebuild:\n{sql}");
+}
+
+#[sqlx::test]
+async fn migrate_column_defaults(db: SqlitePool) {
+    // Arrange
+    let base = {
+        let migration = src_to_migration(
+            r#"
+            d1 { db }
+
+            model Player for db {
+                primary {
+                    id: int
+                }
+
+                column {
+                    name: string
+                }
+            }
+        "#,
+        );
+
+        let sql = MigrationsGenerator::migrate(&migration, None, &MockMigrationsIntent::default());
+        query(&db, &sql)
+            .await
+            .expect("Create table queries to work");
+        query(&db, r#"INSERT INTO "Player" VALUES (1, 'Ann')"#)
+            .await
+            .expect("Insert to work");
+
+        migration
+    };
+
+    // Adding a column with a constant default fills existing rows with it
+    let added = {
+        let migration = src_to_migration(
+            r#"
+            d1 { db }
+
+            model Player for db {
+                primary {
+                    id: int
+                }
+
+                column {
+                    name: string
+                    score: int = 100
+                    ratio: real = 0.5
+                    active: bool = true
+                }
+            }
+        "#,
+        );
+
+        let sql =
+            MigrationsGenerator::migrate(&migration, Some(&base), &MockMigrationsIntent::default());
+        expected_str!(sql, r#"COLUMN "score" integer DEFAULT 100"#);
+        expected_str!(sql, r#"COLUMN "ratio" real DEFAULT 0.5"#);
+        expected_str!(sql, r#"COLUMN "active" integer DEFAULT 1"#);
+        query(&db, &sql).await.expect("Alter queries to work");
+
+        migration
+    };
+
+    // `now` cannot be added in place, so the table is rebuilt
+    let new = src_to_migration(
+        r#"
+        d1 { db }
+
+        model Player for db {
+            primary {
+                id: int
+            }
+
+            column {
+                name: string
+                score: int = 100
+                ratio: real = 0.5
+                active: bool = true
+                joined: date = now
+            }
+        }
+    "#,
+    );
+
+    // Act
+    let sql = MigrationsGenerator::migrate(&new, Some(&added), &MockMigrationsIntent::default());
+
+    // Assert
+    expected_str!(sql, "RENAME TO");
+    expected_str!(
+        sql,
+        r#""joined" text DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')) NOT NULL"#
+    );
+    query(&db, &sql).await.expect("Rebuild queries to work");
+
+    let row = sqlx::query(r#"SELECT score, joined FROM "Player" WHERE "id" = 1"#)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+    assert_eq!(row.get::<i64, _>("score"), 100);
+    assert!(row.get::<String, _>("joined").ends_with('Z'));
+
+    query(
+        &db,
+        r#"INSERT INTO "Player" ("id", "name") VALUES (2, 'Bo')"#,
+    )
+    .await
+    .expect("Defaults to fill omitted columns");
+
+    // Changing a default rebuilds the table
+    let changed = src_to_migration(
+        r#"
+        d1 { db }
+
+        model Player for db {
+            primary {
+                id: int
+            }
+
+            column {
+                name: string
+                score: int = 50
+                ratio: real = 0.5
+                active: bool = true
+                joined: date = now
+            }
+        }
+    "#,
+    );
+    let sql = MigrationsGenerator::migrate(&changed, Some(&new), &MockMigrationsIntent::default());
+    expected_str!(sql, "RENAME TO");
+    expected_str!(sql, r#""score" integer DEFAULT 50 NOT NULL"#);
+}
+
 #[sqlx::test]
 async fn migrate_models_composite_pk_and_fk(db: SqlitePool) {
     let empty_idl = empty_migration();
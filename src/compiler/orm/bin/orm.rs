@@ -15,6 +15,7 @@
 use idl::ValidatedField;
 use idl::{CloesceIdl, IncludeTree};
 use orm::OrmErrorKind;
+use orm::query::delete;
 use orm::query::save;
 use orm::query::select;
 use orm::query::select::planner::SelectOperation;
@@ -103,7 +104,7 @@ unsafe fn read_str<'a>(ptr: *const u8, len: usize) -> &'a str {
     unsafe { str::from_utf8(slice::from_raw_parts(ptr, len)).unwrap() }
 }
 
-/// Plans a select (get or list) operation, returning a `SelectPlan` as JSON for
+/// Plans a select (get, list or count) operation, returning a `SelectPlan` as JSON for
 /// the runtime executor.
 ///
 /// Requires a previous call to [set_idl_ptr].
@@ -112,7 +113,7 @@ unsafe fn read_str<'a>(ptr: *const u8, len: usize) -> &'a str {
 ///
 /// # Safety
 /// `model_name_ptr` must be a pointer to a UTF-8 encoded string representing the model name,
-/// `operation_ptr` must be a pointer to a UTF-8 encoded string of either `get` or `list`,
+/// `operation_ptr` must be a pointer to a UTF-8 encoded string of `get`, `list` or `count`,
 /// and `include_tree_ptr` must be a pointer to a UTF-8 encoded JSON string representing the include tree.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn plan_select(
@@ -120,7 +121,7 @@ pub unsafe extern "C" fn plan_select(
     model_name_ptr: *const u8,
     model_name_len: usize,
 
-    // Operation ("get" | "list")
+    // Operation ("get" | "list" | "count")
     operation_ptr: *const u8,
     operation_len: usize,
 
@@ -135,6 +136,7 @@ pub unsafe extern "C" fn plan_select(
     let operation = match operation_raw {
         "get" => SelectOperation::Get,
         "list" => SelectOperation::List,
+        "count" => SelectOperation::Count,
         other => {
             yield_error(OrmErrorKind::SerializeError {
                 message: format!("Unknown select operation '{other}'"),
@@ -224,6 +226,158 @@ pub unsafe extern "C" fn plan_save(
     }
 }
 
+/// Plans a delete operation from a hydrated instance, returning a `DeletePlan` as JSON
+/// for the runtime executor.
+///
+/// Requires a previous call to [set_idl_ptr].
+///
+/// Returns 0 on pass 1 on fail. Stores result in [RETURN_PTR].
+///
+/// # Safety
+/// `model_name_ptr` must be a pointer to a UTF-8 encoded string representing the model name,
+/// `include_tree_ptr` must be a pointer to a UTF-8 encoded JSON string representing the include tree,
+/// and `payload_ptr` must be a pointer to a UTF-8 encoded JSON string representing the instance to delete.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn plan_delete(
+    // Model Name
+    model_name_ptr: *const u8,
+    model_name_len: usize,
+
+    // Include Tree
+    include_tree_ptr: *const u8,
+    include_tree_len: usize,
+
+    // Payload
+    payload_ptr: *const u8,
+    payload_len: usize,
+) -> i32 {
+    let model_name = unsafe { read_str(model_name_ptr, model_name_len) };
+    let include_tree_json = unsafe { read_str(include_tree_ptr, include_tree_len) };
+    let payload_json = unsafe { read_str(payload_ptr, payload_len) };
+
+    let tree = match serde_json::from_str::<Option<IncludeTree>>(include_tree_json) {
+        Ok(tree) => tree.unwrap_or_default(),
+        Err(e) => {
+            yield_error(serde_err(e));
+            return 1;
+        }
+    };
+
+    let payload = match serde_json::from_str::<serde_json::Value>(payload_json) {
+        Ok(payload) => payload,
+        Err(e) => {
+            yield_error(serde_err(e));
+            return 1;
+        }
+    };
+
+    let res = IDL.with(|idl| {
+        let idl = idl.borrow();
+        delete::planner::plan(model_name, &idl, &tree, &payload)
+            .map(|plan| serde_json::to_string(&plan).unwrap())
+    });
+
+    match res {
+        Ok(json) => {
+            yield_result(json.into_bytes());
+            0
+        }
+        Err(e) => {
+            yield_error(e);
+            1
+        }
+    }
+}
+
+/// Decodes a list's `cursor` param into the seek params its [select::plan::SelectPlan] binds,
+/// returning them as a JSON object.
+///
+/// Returns 0 on pass 1 on fail. Stores result in [RETURN_PTR].
+///
+/// # Safety
+/// `columns_ptr` must be a pointer to a UTF-8 encoded JSON array of the plan's cursor columns,
+/// and `cursor_ptr` must be a pointer to a UTF-8 encoded JSON string or null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn decode_cursor(
+    // Cursor Columns
+    columns_ptr: *const u8,
+    columns_len: usize,
+
+    // Cursor
+    cursor_ptr: *const u8,
+    cursor_len: usize,
+) -> i32 {
+    let columns_json = unsafe { read_str(columns_ptr, columns_len) };
+    let cursor_json = unsafe { read_str(cursor_ptr, cursor_len) };
+
+    let columns = match serde_json::from_str::<Vec<&str>>(columns_json) {
+        Ok(columns) => columns,
+        Err(e) => {
+            yield_error(serde_err(e));
+            return 1;
+        }
+    };
+
+    let cursor = match serde_json::from_str::<Option<&str>>(cursor_json) {
+        Ok(cursor) => cursor,
+        Err(e) => {
+            yield_error(serde_err(e));
+            return 1;
+        }
+    };
+
+    match select::cursor::decode(&columns, cursor) {
+        Ok(params) => {
+            yield_result(serde_json::to_string(&params).unwrap().into_bytes());
+            0
+        }
+        Err(e) => {
+            yield_error(e);
+            1
+        }
+    }
+}
+
+/// Encodes the cursor of the page after `row`, the last row a list returned.
+///
+/// Returns 0 on pass 1 on fail. Stores result in [RETURN_PTR].
+///
+/// # Safety
+/// `columns_ptr` must be a pointer to a UTF-8 encoded JSON array of the plan's cursor columns,
+/// and `row_ptr` must be a pointer to a UTF-8 encoded JSON object.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn encode_cursor(
+    // Cursor Columns
+    columns_ptr: *const u8,
+    columns_len: usize,
+
+    // Row
+    row_ptr: *const u8,
+    row_len: usize,
+) -> i32 {
+    let columns_json = unsafe { read_str(columns_ptr, columns_len) };
+    let row_json = unsafe { read_str(row_ptr, row_len) };
+
+    let columns = match serde_json::from_str::<Vec<&str>>(columns_json) {
+        Ok(columns) => columns,
+        Err(e) => {
+            yield_error(serde_err(e));
+            return 1;
+        }
+    };
+
+    let row = match serde_json::from_str::<serde_json::Value>(row_json) {
+        Ok(row) => row,
+        Err(e) => {
+            yield_error(serde_err(e));
+            return 1;
+        }
+    };
+
+    yield_result(select::cursor::encode(&columns, &row).into_bytes());
+    0
+}
+
 /// Validates a value against a ValidatedField
 ///
 /// Requires a previous call to [set_idl_ptr].
@@ -10,6 +10,7 @@ pub enum OrmErrorKind {
     ModelKeyCannotAutoIncrement { model: String, field: String },
     MissingField { expected: String, missing: String },
     TypeMismatch { expected: String, got: Value },
+    InvalidCursor { cursor: String },
 
     // Validators
     NotLessThan { expected: Number, got: Value },
@@ -21,6 +22,7 @@ pub enum OrmErrorKind {
     NotMinLength { expected: Number, got: Value },
     NotMaxLength { expected: Number, got: Value },
     UnmatchedRegex { got: Value, pattern: String },
+    NotEnumMember { expected: Vec<String>, got: Value },
 }
 
 impl std::fmt::Display for OrmErrorKind {
@@ -38,6 +40,9 @@ impl std::fmt::Display for OrmErrorKind {
             OrmErrorKind::TypeMismatch { expected, got } => {
                 write!(f, "Type mismatch: expected '{expected}', got '{got}'")
             }
+            OrmErrorKind::InvalidCursor { cursor } => {
+                write!(f, "Invalid cursor: '{cursor}'")
+            }
             OrmErrorKind::NotLessThan { expected, got } => {
                 write!(
                     f,
@@ -82,6 +87,11 @@ impl std::fmt::Display for OrmErrorKind {
                 f,
                 "Validation error: expected value to match regex pattern '{pattern}', got '{got}'"
             ),
+            OrmErrorKind::NotEnumMember { expected, got } => write!(
+                f,
+                "Validation error: expected one of '{}', got {got}",
+                expected.join("', '")
+            ),
         }
     }
 }
@@ -0,0 +1,72 @@
+//! The delete plan IR.
+//!
+//! Consists of a sequence of stages, each containing a set of steps that may execute in parallel.
+//!
+//! Each stage is intended to run after the previous stage has completed. Unlike a save, a delete
+//! produces no result, so no step reads from an earlier one; stages exist purely to order rows
+//! that reference each other across databases (a row must be deleted before the row it points at).
+//!
+//! NOTE: The `'src` lifetime is used to tie the plan to both the lifetime of the source code
+//! that made the Cloesce IDL, as well as the JSON payload (for the sake of this planner, they are the same).
+
+use serde::Serialize;
+
+use idl::TemplateSegment;
+
+use crate::query::Database;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Default)]
+pub struct DeletePlan<'src> {
+    pub stages: Vec<DeleteStage<'src>>,
+}
+
+impl<'src> DeletePlan<'src> {
+    /// Return the stage at `index`, creating it (and any stages before it)
+    /// if it does not yet exist.
+    pub fn stage_at(&mut self, index: usize) -> &mut DeleteStage<'src> {
+        if self.stages.len() <= index {
+            self.stages.resize_with(index + 1, DeleteStage::default);
+        }
+        &mut self.stages[index]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Default)]
+pub struct DeleteStage<'src> {
+    pub steps: Vec<DeleteStep<'src>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum DeleteStep<'src> {
+    /// Ordered `DELETE` statements executed as one transaction / batch against a
+    /// single database (a single DO stub when sharded).
+    ///
+    /// NOTE: Atomicity is per batch only, as with a save.
+    SqlBatch {
+        database: Database<'src>,
+        statements: Vec<DeleteStatement<'src>>,
+
+        /// For a Durable Object, the `(field, value)` pairs
+        /// routing to specific stubs. Empty otherwise.
+        shard: Vec<(&'src str, &'src serde_json::Value)>,
+    },
+
+    /// Delete the KV / R2 / DO-KV entry at the key described by `segments`.
+    KeyDelete {
+        database: Database<'src>,
+        segments: Vec<TemplateSegment<'src, &'src serde_json::Value>>,
+
+        /// For a Durable Object, the `(field, value)` pairs
+        /// routing to specific stubs. Empty otherwise.
+        shard: Vec<(&'src str, &'src serde_json::Value)>,
+    },
+}
+
+/// A single `DELETE FROM ... WHERE ...` keyed on one instance's primary key.
+///
+/// `?N` placeholders reference `arguments` (1-based).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeleteStatement<'src> {
+    pub sql: String,
+    pub arguments: Vec<&'src serde_json::Value>,
+}
@@ -0,0 +1,358 @@
+use idl::{CloesceIdl, IncludeTree, Model, NavigationCardinality, TemplateSegment};
+use serde_json::Value as JsonValue;
+
+use frontend::fmt_cidl_type;
+
+use crate::query::delete::plan::{DeletePlan, DeleteStatement, DeleteStep};
+use crate::query::save::planner::{parent_owns_one, quote};
+use crate::query::{Database, DatabaseKind};
+use crate::{OrmErrorKind, Result};
+
+/// Converts a hydrated instance into a [DeletePlan].
+///
+/// Accepts the instance as returned by a data source's `get` (so every included
+/// navigation, KV and R2 field is present) and deletes the instance along with
+/// everything the include tree reaches, adapting to the payload's shape just as a save does.
+///
+/// A row is always deleted before the row its foreign key points at:
+/// - A child-owned nav (`Many`, or a `One` keyed on the child) deletes its children first.
+/// - A parent-owned `One` nav deletes the parent row first, then the child.
+///
+/// KV / R2 entries are deleted in the stage after their owning row, so a failed row
+/// delete never strands a row pointing at a missing object.
+pub fn plan<'src>(
+    model: &str,
+    idl: &'src CloesceIdl<'src>,
+    tree: &IncludeTree<'src>,
+    payload: &'src JsonValue,
+) -> Result<DeletePlan<'src>> {
+    let mut planner = Planner {
+        idl,
+        batches: Vec::new(),
+        keys: Vec::new(),
+    };
+
+    let Some(root) = idl.models.get(model) else {
+        // Fail silently if the model is not found
+        return Ok(DeletePlan::default());
+    };
+
+    planner.visit(root, payload, tree, vec![])?;
+    Ok(planner.emit())
+}
+
+/// Some sub object of the original payload passed to [plan].
+type PayloadSubObject<'src> = Option<&'src serde_json::Map<String, JsonValue>>;
+
+/// A pending SQL transaction against one database / stub in one stage.
+struct Batch<'src> {
+    stage: usize,
+    database: Database<'src>,
+    shard: Vec<(&'src str, &'src JsonValue)>,
+    statements: Vec<DeleteStatement<'src>>,
+}
+
+struct Planner<'src> {
+    idl: &'src CloesceIdl<'src>,
+    batches: Vec<Batch<'src>>,
+    keys: Vec<(usize, DeleteStep<'src>)>,
+}
+
+impl<'src> Planner<'src> {
+    /// DFS over the payload graph.
+    ///
+    /// `deps` are the batches that must run before this instance's row is deleted.
+    ///
+    /// Returns the batch the instance's row was deleted in, if the model is sqlite-backed.
+    fn visit(
+        &mut self,
+        model: &'src Model<'src>,
+        payload: &'src JsonValue,
+        tree: &IncludeTree<'src>,
+        mut deps: Vec<usize>,
+    ) -> Result<Option<usize>> {
+        let obj = payload.as_object();
+
+        // Children holding a FK to this row go first.
+        for nav in &model.navigation_fields {
+            let Some((subtree, items, target)) = self.nav_parts(nav, obj, tree) else {
+                continue;
+            };
+            if matches!(nav.cardinality, NavigationCardinality::One)
+                && parent_owns_one(nav, target, model)
+            {
+                // Parent-owned, deleted after this row.
+                continue;
+            }
+
+            for item in items {
+                if let Some(batch) = self.visit(target, item, subtree, vec![])? {
+                    deps.push(batch);
+                }
+            }
+        }
+
+        let batch = if model.uses_sqlite() {
+            Some(self.delete_row(model, obj, &deps)?)
+        } else {
+            None
+        };
+
+        let key_stage = batch.map(|b| self.batches[b].stage + 1).unwrap_or(0);
+        self.delete_keys(model, obj, tree, key_stage)?;
+
+        // Rows this row holds a FK to go last.
+        for nav in &model.navigation_fields {
+            if !matches!(nav.cardinality, NavigationCardinality::One) {
+                continue;
+            }
+            let Some((subtree, items, target)) = self.nav_parts(nav, obj, tree) else {
+                continue;
+            };
+            if !parent_owns_one(nav, target, model) {
+                // Child-owned, already deleted before this row.
+                continue;
+            }
+
+            for item in items {
+                self.visit(target, item, subtree, batch.into_iter().collect())?;
+            }
+        }
+
+        Ok(batch)
+    }
+
+    /// Emit a keyed `DELETE` for a sqlite-backed row.
+    fn delete_row(
+        &mut self,
+        model: &'src Model<'src>,
+        obj: PayloadSubObject<'src>,
+        deps: &[usize],
+    ) -> Result<usize> {
+        let backing = model.backing.as_ref().expect("sqlite model has a backing");
+
+        let mut arguments = Vec::new();
+        let mut predicates = Vec::new();
+        for pk in &model.primary_columns {
+            let name = pk.field.name.as_ref();
+            let value =
+                obj.and_then(|o| o.get(name))
+                    .ok_or_else(|| OrmErrorKind::MissingField {
+                        expected: fmt_cidl_type(&pk.field.cidl_type),
+                        missing: name.to_string(),
+                    })?;
+            arguments.push(value);
+            predicates.push(format!("{} = ?{}", quote(name), arguments.len()));
+        }
+
+        // Shard fields are route fields, which the hydrated instance always carries.
+        let shard = backing
+            .fields
+            .iter()
+            .map(|f| {
+                let value =
+                    obj.and_then(|o| o.get(*f))
+                        .ok_or_else(|| OrmErrorKind::MissingField {
+                            expected: "all shard keys present".to_string(),
+                            missing: f.to_string(),
+                        })?;
+                Ok((*f, value))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let batch = self.find_or_create_batch(backing.into(), shard, deps);
+        self.batches[batch].statements.push(DeleteStatement {
+            sql: format!(
+                "DELETE FROM {} WHERE {}",
+                quote(model.name),
+                predicates.join(" AND ")
+            ),
+            arguments,
+        });
+        Ok(batch)
+    }
+
+    /// KV / R2 deletes for `model`, gated by the include `tree`.
+    ///
+    /// Every key is resolved from the instance's own fields, so all deletes land in `stage`.
+    fn delete_keys(
+        &mut self,
+        model: &'src Model<'src>,
+        obj: PayloadSubObject<'src>,
+        tree: &IncludeTree<'src>,
+        stage: usize,
+    ) -> Result<()> {
+        for r2 in &model.r2_fields {
+            if !tree.0.contains_key(r2.field.name.as_ref()) {
+                continue;
+            }
+
+            self.keys.push((
+                stage,
+                DeleteStep::KeyDelete {
+                    database: Database {
+                        name: r2.binding,
+                        kind: DatabaseKind::R2,
+                    },
+                    segments: template_segments(&r2.segments, obj)?,
+                    shard: vec![],
+                },
+            ));
+        }
+
+        for kv in &model.kv_fields {
+            if !tree.0.contains_key(kv.field.name.as_ref()) {
+                continue;
+            }
+
+            let is_do_kv = self
+                .idl
+                .wrangler_env
+                .durable_bindings
+                .iter()
+                .any(|b| b.name == kv.binding);
+            let (kind, shard_fields) = if is_do_kv {
+                (DatabaseKind::DurableObject, kv.shard_fields.as_slice())
+            } else {
+                (DatabaseKind::Kv, [].as_slice())
+            };
+
+            let shard = shard_fields
+                .iter()
+                .map(|f| Ok((*f, field_value(obj, f)?)))
+                .collect::<Result<Vec<_>>>()?;
+
+            self.keys.push((
+                stage,
+                DeleteStep::KeyDelete {
+                    database: Database {
+                        name: kv.binding,
+                        kind,
+                    },
+                    segments: template_segments(&kv.segments, obj)?,
+                    shard,
+                },
+            ));
+        }
+        Ok(())
+    }
+
+    /// Pick the batch a row lands in.
+    ///
+    /// `deps` are the batches that must run before this row.
+    /// - If a dep shares this rows binding + identical shard, the row may join it,
+    ///   appended after the dep's statements.
+    /// - Otherwise, or when joining would strand another dep, the row lands in at
+    ///   `stage = max(dep.stage + 1)`.
+    fn find_or_create_batch(
+        &mut self,
+        database: Database<'src>,
+        shard: Vec<(&'src str, &'src JsonValue)>,
+        deps: &[usize],
+    ) -> usize {
+        for &batch in deps {
+            let b = &self.batches[batch];
+            if b.database != database || b.shard != shard {
+                // The batch is for a different binding or shard, so this row cannot join it.
+                continue;
+            }
+            let clears_other_deps = deps.iter().all(|&other| {
+                other == batch || self.batches[batch].stage > self.batches[other].stage
+            });
+            if clears_other_deps {
+                return batch;
+            }
+        }
+
+        let stage = deps
+            .iter()
+            .map(|&batch| self.batches[batch].stage + 1)
+            .max()
+            .unwrap_or(0);
+
+        // Join an existing batch at this exact stage / binding / shard, else create one.
+        if let Some(idx) = self
+            .batches
+            .iter()
+            .position(|b| b.stage == stage && b.database == database && b.shard == shard)
+        {
+            return idx;
+        }
+
+        self.batches.push(Batch {
+            stage,
+            database,
+            shard,
+            statements: Vec::new(),
+        });
+        self.batches.len() - 1
+    }
+
+    /// (subtree, instances, target model) triple for a nav, if the include tree
+    /// requests it and the payload carries it.
+    ///
+    /// A `null` one nav or a non-array many nav yields no instances.
+    fn nav_parts<'t>(
+        &self,
+        nav: &'src idl::NavigationField<'src>,
+        obj: PayloadSubObject<'src>,
+        tree: &'t IncludeTree<'src>,
+    ) -> Option<(
+        &'t IncludeTree<'src>,
+        Vec<&'src JsonValue>,
+        &'src Model<'src>,
+    )> {
+        let subtree = tree.0.get(nav.field.name.as_ref())?;
+        let payload = obj.and_then(|o| o.get(nav.field.name.as_ref()))?;
+        let target = self.idl.models.get(nav.model_reference)?;
+
+        let items = match (&nav.cardinality, payload) {
+            (NavigationCardinality::Many, JsonValue::Array(items)) => items.iter().collect(),
+            (NavigationCardinality::One, value) if !value.is_null() => vec![value],
+            _ => vec![],
+        };
+        Some((subtree, items, target))
+    }
+
+    /// Collapse the accumulated batches and key deletes into a [DeletePlan].
+    fn emit(self) -> DeletePlan<'src> {
+        let Planner { batches, keys, .. } = self;
+        let mut plan = DeletePlan::default();
+
+        for batch in batches {
+            plan.stage_at(batch.stage).steps.push(DeleteStep::SqlBatch {
+                database: batch.database,
+                statements: batch.statements,
+                shard: batch.shard,
+            });
+        }
+
+        for (stage, step) in keys {
+            plan.stage_at(stage).steps.push(step);
+        }
+
+        plan
+    }
+}
+
+fn field_value<'src>(obj: PayloadSubObject<'src>, field: &str) -> Result<&'src JsonValue> {
+    obj.and_then(|o| o.get(field))
+        .ok_or_else(|| OrmErrorKind::MissingField {
+            expected: "all key fields present".to_string(),
+            missing: field.to_string(),
+        })
+}
+
+fn template_segments<'src>(
+    key: &'src [TemplateSegment<'src, &'src str>],
+    obj: PayloadSubObject<'src>,
+) -> Result<Vec<TemplateSegment<'src, &'src JsonValue>>> {
+    key.iter()
+        .map(|segment| match segment {
+            TemplateSegment::Literal(text) => Ok(TemplateSegment::Literal(text.clone())),
+            TemplateSegment::Value(placeholder) => {
+                field_value(obj, placeholder).map(TemplateSegment::Value)
+            }
+        })
+        .collect()
+}
@@ -1,8 +1,9 @@
-//! Render a [SavePlan] / [SelectPlan] as a human-readable set of stages and steps
+//! Render a [SavePlan] / [SelectPlan] / [DeletePlan] as a human-readable set of stages and steps
 //! in a SQL `EXPLAIN`-style tree.
 
 use idl::IncludeTree;
 
+use crate::query::delete::plan::DeletePlan;
 use crate::query::save::plan::SavePlan;
 use crate::query::select::plan::SelectPlan;
 use crate::query::select::planner::SelectOperation;
@@ -19,6 +20,18 @@ pub fn explain_save(model: &str, tree: &IncludeTree, plan: &SavePlan) -> String
     )
 }
 
+/// Render a [DeletePlan] as an `EXPLAIN`-style tree.
+pub fn explain_delete(model: &str, tree: &IncludeTree, plan: &DeletePlan) -> String {
+    render::explain(
+        format!("DELETE PLAN `{model}`"),
+        tree,
+        plan.stages
+            .iter()
+            .map(|stage| stage.steps.iter().map(delete::step).collect())
+            .collect(),
+    )
+}
+
 /// Render a [SelectPlan] as an `EXPLAIN`-style tree.
 pub fn explain_select(
     op: SelectOperation,
@@ -29,6 +42,7 @@ pub fn explain_select(
     let kind = match op {
         SelectOperation::Get => "GET",
         SelectOperation::List => "LIST",
+        SelectOperation::Count => "COUNT",
     };
     render::explain(
         format!("SELECT PLAN ({kind}) `{model}`"),
@@ -324,6 +338,65 @@ mod save {
     }
 }
 
+mod delete {
+    use super::render::Node;
+    use super::{fmt, sql};
+    use crate::query::delete::plan::{DeleteStatement, DeleteStep};
+
+    /// Converts a [DeleteStep] into a [Node]
+    pub fn step(step: &DeleteStep) -> Node {
+        match step {
+            DeleteStep::SqlBatch {
+                database: db,
+                statements,
+                shard,
+            } => Node {
+                text: format!(
+                    "BATCH ON {}{}",
+                    fmt::database(db),
+                    fmt::shard_clause(shard, arg)
+                ),
+                children: statements
+                    .iter()
+                    .map(|stmt| Node::leaf(summarize(stmt)))
+                    .collect(),
+            },
+            DeleteStep::KeyDelete {
+                database: db,
+                segments,
+                shard,
+            } => Node::leaf(format!(
+                "DELETE {} KEY {}{}",
+                fmt::database(db),
+                fmt::key_template(segments, arg),
+                fmt::shard_clause(shard, arg)
+            )),
+        }
+    }
+
+    fn arg(value: &&serde_json::Value) -> String {
+        fmt::truncate(&value.to_string())
+    }
+
+    /// `DELETE `Model` WHERE `id` = 1 AND ...`
+    fn summarize(stmt: &DeleteStatement) -> String {
+        let Some(rest) = stmt.sql.strip_prefix("DELETE FROM ") else {
+            return fmt::truncate(&stmt.sql);
+        };
+        let predicates = stmt
+            .arguments
+            .iter()
+            .zip(rest.split(" WHERE ").nth(1).unwrap_or("").split(" AND "))
+            .map(|(value, predicate)| {
+                let col = sql::leading_ident(predicate);
+                format!("`{col}` = {}", arg(value))
+            })
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        format!("DELETE `{}` WHERE {predicates}", sql::leading_ident(rest))
+    }
+}
+
 mod select {
     use std::fmt::Write;
 
@@ -1,7 +1,9 @@
-//! The query module splits into two planners:
+//! The query module splits into three planners:
 //! - [select] gets or lists
 //! - [save] upserts
+//! - [delete] cascades deletes through an include tree
 
+pub mod delete;
 pub mod explain;
 pub mod save;
 pub mod select;
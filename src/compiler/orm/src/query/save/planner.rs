@@ -287,6 +287,11 @@ impl<'src> Planner<'src> {
                     // Coerce to NULL for a missing nullable PK
                     ColSpec::Arg(SaveArg::Payload(Cow::Borrowed(&JsonValue::Null)))
                 }
+                (None, None, false) if col.default.is_some() => {
+                    // Omitted from the write: an insert takes the column's default,
+                    // while an upsert leaves the existing value in place.
+                    continue;
+                }
                 (None, None, false) if !pk_missing => {
                     // PK is present so this is an update OR insert (upsert).
                     if !col.field.cidl_type.is_nullable() {
@@ -1026,7 +1031,11 @@ impl<'src> RowSql<'_, 'src> {
 /// - Parent owned: some nav keys `target` is a PK of the child. Write the child first.
 /// - Child owned: no nav keys `target` is a PK of the child. Write the parent first.
 /// - Mixed: some nav keys `target` is a PK of the child, some not. This is treated as parent-owned.
-fn parent_owns_one(nav: &idl::NavigationField, target_model: &Model, parent_model: &Model) -> bool {
+pub(crate) fn parent_owns_one(
+    nav: &idl::NavigationField,
+    target_model: &Model,
+    parent_model: &Model,
+) -> bool {
     if parent_model.backing.is_none() {
         // A backingless parent has no row to hold a FK, so the child must be written first.
         return false;
@@ -1098,6 +1107,6 @@ fn dotted(path: &[PathSegment]) -> String {
         .join(".")
 }
 
-fn quote(ident: &str) -> String {
+pub(crate) fn quote(ident: &str) -> String {
     format!("\"{ident}\"")
 }
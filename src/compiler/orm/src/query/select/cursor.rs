@@ -0,0 +1,64 @@
+//! Opaque pagination cursors for a [super::planner::SelectOperation::List].
+//!
+//! A cursor is the URL-safe base64 encoding of a JSON array holding the last row's value for
+//! each of [super::plan::SelectPlan::cursor]'s columns. Clients only ever pass it back, so the
+//! columns a list pages over can change without breaking them.
+
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use serde_json::{Map, Value};
+
+use crate::{OrmErrorKind, ensure, fail};
+
+/// The runtime parameter carrying the cursor of the page to list, absent for the first page.
+pub const CURSOR_PARAM: &str = "cursor";
+
+/// The runtime parameter a cursor's value for `column` is decoded into.
+///
+/// The `$` prefix can't appear in a schema identifier, so it never collides with a
+/// parameter the user declared.
+pub fn seek_param(column: &str) -> String {
+    format!("$cursor_{column}")
+}
+
+/// Encodes the cursor of the page after `row`, the last row of the current page.
+///
+/// A column missing from `row` is encoded as null.
+pub fn encode(columns: &[impl AsRef<str>], row: &Value) -> String {
+    let values = columns
+        .iter()
+        .map(|c| row.get(c.as_ref()).cloned().unwrap_or(Value::Null))
+        .collect::<Vec<_>>();
+    BASE64_URL_SAFE_NO_PAD.encode(Value::Array(values).to_string())
+}
+
+/// Decodes `cursor` into one [seek_param] per column.
+///
+/// Without a cursor every value is null, requesting the first page.
+pub fn decode(
+    columns: &[impl AsRef<str>],
+    cursor: Option<&str>,
+) -> crate::Result<Map<String, Value>> {
+    let Some(cursor) = cursor else {
+        return Ok(columns
+            .iter()
+            .map(|c| (seek_param(c.as_ref()), Value::Null))
+            .collect());
+    };
+
+    let invalid = || OrmErrorKind::InvalidCursor {
+        cursor: cursor.to_string(),
+    };
+    let Ok(bytes) = BASE64_URL_SAFE_NO_PAD.decode(cursor) else {
+        fail!(invalid());
+    };
+    let Ok(Value::Array(values)) = serde_json::from_slice::<Value>(&bytes) else {
+        fail!(invalid());
+    };
+    ensure!(values.len() == columns.len(), invalid());
+
+    Ok(columns
+        .iter()
+        .zip(values)
+        .map(|(c, v)| (seek_param(c.as_ref()), v))
+        .collect())
+}
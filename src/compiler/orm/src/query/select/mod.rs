@@ -1,2 +1,3 @@
+pub mod cursor;
 pub mod plan;
 pub mod planner;
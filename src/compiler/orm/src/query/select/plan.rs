@@ -19,6 +19,13 @@ use crate::query::Database;
 pub struct SelectPlan<'src> {
     pub tables: Vec<TableDef<'src>>,
     pub stages: Vec<SelectStage<'src>>,
+
+    /// The columns a list pages over, in seek order. The runtime decodes the `cursor` param
+    /// into one value per column, and encodes the next page's cursor from the last row
+    /// (see [crate::query::select::cursor]).
+    ///
+    /// Empty for a get or count, and for a list of a model without a SQLite backing.
+    pub cursor: Vec<&'src str>,
 }
 
 impl<'src> SelectPlan<'src> {
@@ -1,7 +1,11 @@
 use std::collections::HashMap;
 
-use idl::{CloesceIdl, IncludeTree, Model, ModelBacking, NavigationField, TemplateSegment};
+use idl::{
+    CloesceIdl, Column, IncludeTree, ListQuery, Model, ModelBacking, NavigationField,
+    SortDirection, TemplateSegment,
+};
 
+use crate::query::select::cursor::seek_param;
 use crate::query::select::plan::{
     JoinKeys, Mapping, Select, SelectArg, SelectPlan, SelectStep, SqlArgument, SqlSegment,
     TableParent,
@@ -12,11 +16,15 @@ use crate::query::{Database, DatabaseKind};
 pub enum SelectOperation {
     Get,
     List,
+    Count,
 }
 
 /// The runtime parameter that bounds the number of root rows a `List` returns.
 const LIMIT_PARAM: &str = "limit";
 
+/// The field of the single row a `Count` returns, holding the number of matching rows.
+pub const COUNT_FIELD: &str = "count";
+
 /// Converts a [SelectOperation] into a [SelectPlan], detailing how the runtime should execute
 /// the operation against the underlying data sources.
 ///
@@ -27,6 +35,47 @@ pub fn plan<'src>(
     model: &str,
     idl: &'src CloesceIdl<'src>,
     tree: &IncludeTree<'src>,
+) -> SelectPlan<'src> {
+    build(operation, model, idl, tree, &ListQuery::default())
+}
+
+/// [plan] a [SelectOperation::List], ordered and filtered by `query`.
+///
+/// The list pages over [ListQuery::seek_columns], each bound from a [seek_param] decoded from
+/// the list's cursor. A null first value requests the first page.
+pub fn plan_list<'src>(
+    model: &str,
+    idl: &'src CloesceIdl<'src>,
+    tree: &IncludeTree<'src>,
+    query: &ListQuery<'src>,
+) -> SelectPlan<'src> {
+    build(SelectOperation::List, model, idl, tree, query)
+}
+
+/// [plan] a [SelectOperation::Count] of the rows matching `query`'s filters.
+///
+/// The plan is a single step returning one row, with the number of matches in its
+/// [COUNT_FIELD]. A model without a SQLite backing has no rows to count, so its plan is empty.
+pub fn plan_count<'src>(
+    model: &str,
+    idl: &'src CloesceIdl<'src>,
+    query: &ListQuery<'src>,
+) -> SelectPlan<'src> {
+    build(
+        SelectOperation::Count,
+        model,
+        idl,
+        &IncludeTree::default(),
+        query,
+    )
+}
+
+fn build<'src>(
+    operation: SelectOperation,
+    model: &str,
+    idl: &'src CloesceIdl<'src>,
+    tree: &IncludeTree<'src>,
+    query: &ListQuery<'src>,
 ) -> SelectPlan<'src> {
     let mut plan = SelectPlan::default();
 
@@ -38,6 +87,7 @@ pub fn plan<'src>(
     let mapping = match operation {
         SelectOperation::Get => Mapping::one(),
         SelectOperation::List => Mapping::many(),
+        SelectOperation::Count => return count(model, query),
     };
 
     let mut params = Params::default();
@@ -100,7 +150,7 @@ pub fn plan<'src>(
 
                 Select::Sql {
                     database: backing.into(),
-                    sql: select_sql(model, &predicates, None),
+                    sql: select_sql(model, &predicates, &primary_order(model), None),
                     arguments,
                     shard,
                     mapping,
@@ -108,48 +158,28 @@ pub fn plan<'src>(
                 }
             }
             SelectOperation::List => {
-                let pks = &model.primary_columns;
-
-                // ex: `"id" > ` Bind(0) or `("region", "num") > (` Bind(0) `, ` Bind(1) `)`
-                let predicate = if pks.len() == 1 {
-                    vec![
-                        SqlSegment::Literal(format!("\"{}\" > ", pks[0].field.name)),
-                        SqlSegment::Bind(0),
-                    ]
-                } else {
-                    let cols = pks
-                        .iter()
-                        .map(|c| format!("\"{}\"", c.field.name))
-                        .collect::<Vec<_>>()
-                        .join(", ");
-                    let mut predicate = vec![SqlSegment::Literal(format!("({cols}) > ("))];
-                    for i in 0..pks.len() {
-                        if i > 0 {
-                            predicate.push(SqlSegment::Literal(", ".into()));
-                        }
-                        predicate.push(SqlSegment::Bind(i));
-                    }
-                    predicate.push(SqlSegment::Literal(")".into()));
-                    predicate
-                };
-
-                // One `lastSeen_<pk>` cursor value per pk column, then `limit`.
-                let arguments = pks
-                    .iter()
-                    .map(|c| SelectArg::Param(format!("lastSeen_{}", c.field.name).into()))
-                    .chain(std::iter::once(SelectArg::Param(LIMIT_PARAM.into())))
-                    .map(SqlArgument::scalar)
-                    .collect::<Vec<_>>();
+                let seek = query.seek_columns(model);
+                let (mut predicates, mut arguments) = filter_predicates(query);
+
+                // One cursor value per seek column, then `limit`.
+                predicates.push(seek_predicate(&seek, arguments.len()));
+                arguments.extend(seek.iter().map(|(c, _)| {
+                    SqlArgument::scalar(SelectArg::Param(seek_param(&c.field.name).into()))
+                }));
+                plan.cursor = seek.iter().map(|(c, _)| c.field.name.as_ref()).collect();
+                let limit = arguments.len();
+                arguments.push(SqlArgument::scalar(SelectArg::Param(LIMIT_PARAM.into())));
 
                 Select::Sql {
                     database: backing.into(),
-                    sql: select_sql(model, &[predicate], Some(pks.len())),
+                    sql: select_sql(model, &predicates, &seek, Some(limit)),
                     arguments,
                     shard,
                     mapping,
                     route_fields,
                 }
             }
+            SelectOperation::Count => unreachable!("planned by `count`"),
         };
 
         plan.stage_at(0)
@@ -181,6 +211,66 @@ pub fn plan<'src>(
     plan
 }
 
+/// A [SelectOperation::Count]: one step counting the root rows that match `query`'s filters.
+/// Nothing is hydrated, so the step carries no route fields.
+fn count<'src>(model: &'src Model<'src>, query: &ListQuery<'src>) -> SelectPlan<'src> {
+    let mut plan = SelectPlan::default();
+    let Some(backing) = model.backing.as_ref().filter(|_| model.uses_sqlite()) else {
+        return plan;
+    };
+
+    let root = plan.register_table(None);
+    let (predicates, arguments) = filter_predicates(query);
+
+    // ex: `SELECT COUNT(*) AS "count" FROM "Post" WHERE "status" = ?1`
+    let mut sql = vec![SqlSegment::Literal(format!(
+        "SELECT COUNT(*) AS \"{COUNT_FIELD}\" FROM \"{}\"",
+        model.name
+    ))];
+    sql.extend(where_clause(&predicates));
+
+    let shard = backing
+        .fields
+        .iter()
+        .map(|f| (*f, SelectArg::Param((*f).into())))
+        .collect();
+
+    plan.stage_at(0).steps.push(SelectStep {
+        query: Select::Sql {
+            database: backing.into(),
+            sql: merge_literals(sql),
+            arguments,
+            shard,
+            mapping: Mapping::one(),
+            route_fields: vec![],
+        },
+        table: root,
+    });
+
+    plan
+}
+
+/// One equality predicate per `where` filter of `query`, each bound to the filter's param.
+///
+/// `IS` rather than `=`, so a null param matches the rows of a nullable column that are null.
+fn filter_predicates<'src>(
+    query: &ListQuery<'src>,
+) -> (Vec<Vec<SqlSegment>>, Vec<SqlArgument<'src>>) {
+    let mut predicates = Vec::new();
+    let mut arguments = Vec::new();
+
+    // ex: `"status" IS ` Bind(0)
+    for filter in &query.filters {
+        predicates.push(vec![
+            SqlSegment::Literal(format!("\"{}\" IS ", filter.column)),
+            SqlSegment::Bind(arguments.len()),
+        ]);
+        arguments.push(SqlArgument::scalar(SelectArg::Param(filter.param.into())));
+    }
+
+    (predicates, arguments)
+}
+
 fn hydrate_model<'src>(
     model: &'src Model<'src>,
     idl: &'src CloesceIdl<'src>,
@@ -462,7 +552,7 @@ fn select_navs<'src>(
         plan.stage_at(stage).steps.push(SelectStep {
             query: Select::Sql {
                 database: backing.into(),
-                sql: select_sql(target, &predicates, None),
+                sql: select_sql(target, &predicates, &primary_order(target), None),
                 arguments,
                 shard,
                 mapping: Mapping {
@@ -476,12 +566,13 @@ fn select_navs<'src>(
     }
 }
 
-/// Build an ordered SQL `SELECT` over the model's columns as [SqlSegment]s, ordered by
-/// primary key column(s). Each predicate is already split into its own segments, and
-/// `limit_bind` (0-based) appends a trailing `LIMIT` placeholder.
+/// Build an ordered SQL `SELECT` over the model's columns as [SqlSegment]s, sorted by
+/// `order`. Each predicate is already split into its own segments, and `limit_bind` (0-based)
+/// appends a trailing `LIMIT` placeholder.
 fn select_sql(
     model: &Model,
     preds: &[Vec<SqlSegment>],
+    order: &[(&Column, SortDirection)],
     limit_bind: Option<usize>,
 ) -> Vec<SqlSegment> {
     let columns = model
@@ -491,10 +582,15 @@ fn select_sql(
         .map(|c| format!("\"{}\"", c.field.name))
         .collect::<Vec<_>>()
         .join(", ");
-    let order = model
-        .primary_columns
+    let order = order
         .iter()
-        .map(|c| format!("\"{}\" ASC", c.field.name))
+        .map(|(c, direction)| {
+            let direction = match direction {
+                SortDirection::Asc => "ASC",
+                SortDirection::Desc => "DESC",
+            };
+            format!("\"{}\" {direction}", c.field.name)
+        })
         .collect::<Vec<_>>()
         .join(", ");
 
@@ -504,6 +600,23 @@ fn select_sql(
         model.name
     ))];
 
+    segments.extend(where_clause(preds));
+
+    // ... ORDER BY "id" ASC, "name" ASC
+    segments.push(SqlSegment::Literal(format!(" ORDER BY {order}")));
+
+    if let Some(bind) = limit_bind {
+        // ... LIMIT ?N
+        segments.push(SqlSegment::Literal(" LIMIT ".into()));
+        segments.push(SqlSegment::Bind(bind));
+    }
+
+    merge_literals(segments)
+}
+
+/// ` WHERE ` followed by `preds` joined with ` AND `, or nothing without predicates.
+fn where_clause(preds: &[Vec<SqlSegment>]) -> Vec<SqlSegment> {
+    let mut segments = Vec::new();
     if !preds.is_empty() {
         // ... WHERE "id" = ?1 AND "name" = ?2
         segments.push(SqlSegment::Literal(" WHERE ".into()));
@@ -514,17 +627,91 @@ fn select_sql(
             segments.extend(pred.iter().cloned());
         }
     }
+    segments
+}
 
-    // ... ORDER BY "id" ASC, "name" ASC
-    segments.push(SqlSegment::Literal(format!(" ORDER BY {order}")));
+/// The model's primary key column(s), ascending.
+fn primary_order<'m, 'src>(model: &'m Model<'src>) -> Vec<(&'m Column<'src>, SortDirection)> {
+    model
+        .primary_columns
+        .iter()
+        .map(|c| (c, SortDirection::Asc))
+        .collect()
+}
 
-    if let Some(bind) = limit_bind {
-        // ... LIMIT ?N
-        segments.push(SqlSegment::Literal(" LIMIT ".into()));
-        segments.push(SqlSegment::Bind(bind));
+/// The keyset predicate paging past the cursor bound at `cursor` onwards (one bind per
+/// `seek` column):
+/// - a single column, ex: `"id" > ` Bind(0)
+/// - columns sharing a direction as a row value, ex: `("a", "b") > (` Bind(0) `, ` Bind(1) `)`
+/// - mixed directions expanded, ex: `(("a" > ` Bind(0) `) OR ("a" = ` Bind(0) ` AND "b" < ` Bind(1) `))`
+///
+/// Matches every row while the cursor's first value is null, so no cursor lists the first page.
+fn seek_predicate(seek: &[(&Column, SortDirection)], cursor: usize) -> Vec<SqlSegment> {
+    let op = |direction: &SortDirection| match direction {
+        SortDirection::Asc => ">",
+        SortDirection::Desc => "<",
+    };
+    let col = |c: &Column| format!("\"{}\"", c.field.name);
+
+    let mut predicate = Vec::new();
+    if let [(c, direction)] = seek {
+        predicate.push(SqlSegment::Literal(format!(
+            "{} {} ",
+            col(c),
+            op(direction)
+        )));
+        predicate.push(SqlSegment::Bind(cursor));
+    } else if seek.iter().all(|(_, d)| *d == seek[0].1) {
+        let cols = seek
+            .iter()
+            .map(|(c, _)| col(c))
+            .collect::<Vec<_>>()
+            .join(", ");
+        predicate.push(SqlSegment::Literal(format!(
+            "({cols}) {} (",
+            op(&seek[0].1)
+        )));
+        for i in 0..seek.len() {
+            if i > 0 {
+                predicate.push(SqlSegment::Literal(", ".into()));
+            }
+            predicate.push(SqlSegment::Bind(cursor + i));
+        }
+        predicate.push(SqlSegment::Literal(")".into()));
+    } else {
+        // Row values compare in a single direction, so spell out each tier: equal on
+        // every earlier column, past the cursor on this one.
+        predicate.push(SqlSegment::Literal("(".into()));
+        for (tier, (c, direction)) in seek.iter().enumerate() {
+            if tier > 0 {
+                predicate.push(SqlSegment::Literal(" OR ".into()));
+            }
+            predicate.push(SqlSegment::Literal("(".into()));
+            for (i, (prev, _)) in seek[..tier].iter().enumerate() {
+                predicate.push(SqlSegment::Literal(format!("{} = ", col(prev))));
+                predicate.push(SqlSegment::Bind(cursor + i));
+                predicate.push(SqlSegment::Literal(" AND ".into()));
+            }
+            predicate.push(SqlSegment::Literal(format!(
+                "{} {} ",
+                col(c),
+                op(direction)
+            )));
+            predicate.push(SqlSegment::Bind(cursor + tier));
+            predicate.push(SqlSegment::Literal(")".into()));
+        }
+        predicate.push(SqlSegment::Literal(")".into()));
     }
 
-    merge_literals(segments)
+    // ex: `( Bind(0) ` IS NULL OR "createdAt" < ` Bind(0) `)`
+    let mut wrapped = vec![
+        SqlSegment::Literal("(".into()),
+        SqlSegment::Bind(cursor),
+        SqlSegment::Literal(" IS NULL OR ".into()),
+    ];
+    wrapped.extend(predicate);
+    wrapped.push(SqlSegment::Literal(")".into()));
+    wrapped
 }
 
 /// Coalesce adjacent [SqlSegment::Literal]s into one, so a composed statement carries a
@@ -93,6 +93,18 @@ pub fn validate_cidl_type(
             }
         }
 
+        CidlType::Enum { name } => {
+            let members = &idl.enums.get(name).unwrap().members;
+            match &value {
+                Value::String(s) if members.contains(&s.as_str()) => Some(value),
+                Value::String(_) => fail!(OrmErrorKind::NotEnumMember {
+                    expected: members.iter().map(|m| m.to_string()).collect(),
+                    got: value,
+                }),
+                _ => fail!(type_mismatch_err(value)),
+            }
+        }
+
         CidlType::Boolean => match &value {
             Value::Bool(_) => Some(value),
             Value::String(s) if s.eq_ignore_ascii_case("true") => Some(Value::Bool(true)),
@@ -0,0 +1,88 @@
+//! A mock runtime executor for [DeletePlan].
+
+use idl::TemplateSegment;
+use orm::query::DatabaseKind;
+use orm::query::delete::plan::{DeletePlan, DeleteStep};
+use serde_json::Value;
+
+use crate::common::bind_value;
+use crate::common::setup::MockStorage;
+
+/// Executes a delete plan against a mutable [MockStorage].
+///
+/// Steps run sequentially in stage order; any failed statement is a hard failure.
+pub async fn execute(plan: &DeletePlan<'_>, storage: &mut MockStorage) {
+    for stage in &plan.stages {
+        for step in &stage.steps {
+            run_step(step, storage).await;
+        }
+    }
+}
+
+async fn run_step(step: &DeleteStep<'_>, storage: &mut MockStorage) {
+    match step {
+        DeleteStep::SqlBatch {
+            database,
+            statements,
+            shard,
+        } => {
+            let pool = match &database.kind {
+                DatabaseKind::D1 => storage
+                    .d1
+                    .get(database.name)
+                    .expect("D1 binding to exist in tests")
+                    .clone(),
+                DatabaseKind::DurableObject => {
+                    let tuple = shard.iter().map(|(_, v)| (*v).clone()).collect::<Vec<_>>();
+                    storage.durable_pool(database.name, &tuple).await.clone()
+                }
+                other => panic!("unsupported delete database {other:?}"),
+            };
+
+            let mut tx = pool.begin().await.expect("begin");
+            for statement in statements {
+                statement
+                    .arguments
+                    .iter()
+                    .fold(sqlx::query(&statement.sql), |q, v| bind_value(q, v))
+                    .execute(&mut *tx)
+                    .await
+                    .expect("delete to succeed in tests");
+            }
+            tx.commit().await.expect("commit");
+        }
+        DeleteStep::KeyDelete {
+            database,
+            segments,
+            shard,
+        } => {
+            let key = resolve_key(segments);
+            let map = match database.kind {
+                DatabaseKind::R2 => storage.r2.get_mut(database.name),
+                DatabaseKind::Kv => storage.kv.get_mut(database.name),
+                DatabaseKind::DurableObject => {
+                    let tuple = shard.iter().map(|(_, v)| (*v).clone()).collect::<Vec<_>>();
+                    storage
+                        .durable_kv
+                        .get_mut(database.name)
+                        .and_then(|shards| shards.get_mut(&tuple))
+                }
+                ref other => unreachable!("key delete routed at non-storage kind {other:?}"),
+            };
+            if let Some(map) = map {
+                map.remove(&key);
+            }
+        }
+    }
+}
+
+fn resolve_key(segments: &[TemplateSegment<'_, &Value>]) -> String {
+    segments
+        .iter()
+        .map(|segment| match segment {
+            TemplateSegment::Literal(text) => text.as_ref().to_string(),
+            TemplateSegment::Value(Value::String(s)) => s.clone(),
+            TemplateSegment::Value(other) => other.to_string(),
+        })
+        .collect()
+}
@@ -1,5 +1,8 @@
 use serde_json::Value;
-use sqlx::{Column, Row};
+use sqlx::{Column, Row, ValueRef};
+
+#[allow(dead_code)]
+pub mod delete_executor;
 
 #[allow(dead_code)]
 pub mod save_executor;
@@ -35,13 +38,16 @@ fn bind_value<'q>(
 }
 
 /// Convert a [sqlx::sqlite::SqliteRow] into a JSON object, with column names as keys and
-/// values as JSON values.
+/// values as JSON values. SQL `NULL` becomes JSON `null`.
 fn row_to_json(row: &sqlx::sqlite::SqliteRow) -> Value {
     Value::Object(
         row.columns()
             .iter()
             .map(|c| {
                 let i = c.ordinal();
+                if row.try_get_raw(i).is_ok_and(|v| v.is_null()) {
+                    return (c.name().to_string(), Value::Null);
+                }
                 let value = row
                     .try_get::<i64, _>(i)
                     .map(Value::from)
@@ -1,6 +1,6 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use idl::{BackingKind, CloesceIdl, IncludeTree, Model};
+use idl::{BackingKind, CloesceIdl, Enum, IncludeTree, Model};
 use migrations::{MigrationsGenerator, MigrationsIdl, MigrationsModel};
 use serde_json::Value;
 use sqlx::SqlitePool;
@@ -18,7 +18,11 @@ impl migrations::MigrationsIntent for MockIntent {
 }
 
 /// Generate the schema migration for a set of models sharing one database.
-fn migration_for(models: &[&Model<'_>], hash: u64) -> String {
+fn migration_for<'src>(
+    models: &[&Model<'src>],
+    enums: &BTreeMap<&'src str, Enum<'src>>,
+    hash: u64,
+) -> String {
     let migrations_models = models
         .iter()
         .map(|model| {
@@ -30,6 +34,7 @@ fn migration_for(models: &[&Model<'_>], hash: u64) -> String {
                     backing: None,
                     primary_columns: clone_columns(&model.primary_columns),
                     columns: clone_columns(&model.columns),
+                    indexes: model.indexes.clone(),
                 },
             )
         })
@@ -38,6 +43,7 @@ fn migration_for(models: &[&Model<'_>], hash: u64) -> String {
     let idl = MigrationsIdl {
         hash,
         models: migrations_models,
+        enums: enums.clone(),
     };
     MigrationsGenerator::migrate(&idl, None, &MockIntent)
 }
@@ -129,7 +135,7 @@ impl MockStorage {
         let shards = shard_inits.iter().cloned().collect::<HashMap<_, _>>();
 
         for (binding, (kind, models)) in by_binding {
-            let migration = migration_for(&models, idl.hash);
+            let migration = migration_for(&models, &idl.enums, idl.hash);
             match kind {
                 BackingKind::D1 => {
                     let pool = new_pool(&migration).await;
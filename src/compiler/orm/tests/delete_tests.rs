@@ -0,0 +1,325 @@
+mod common;
+
+use common::setup::{MockStorage, tree};
+use compiler_test::src_to_idl;
+use idl::CloesceIdl;
+use orm::query::delete::plan::{DeletePlan, DeleteStep};
+use orm::query::delete::planner::plan;
+use serde_json::{Value, json};
+
+fn statements<'a>(plan: &'a DeletePlan, stage: usize, step: usize) -> Vec<&'a str> {
+    match &plan.stages[stage].steps[step] {
+        DeleteStep::SqlBatch { statements, .. } => {
+            statements.iter().map(|s| s.sql.as_str()).collect()
+        }
+        other => panic!("expected SqlBatch, got {other:?}"),
+    }
+}
+
+async fn seed(
+    idl: &CloesceIdl<'_>,
+    model: &str,
+    include: Value,
+    payload: Value,
+    storage: &mut MockStorage,
+) {
+    let payload: &'static Value = Box::leak(Box::new(payload));
+    let plan = orm::query::save::planner::plan(model, idl, &tree(include), payload)
+        .expect("seed plan to succeed");
+    common::save_executor::execute(&plan, storage).await;
+}
+
+async fn delete_ok<'idl>(
+    idl: &'idl CloesceIdl<'_>,
+    model: &str,
+    include: Value,
+    payload: Value,
+    storage: &mut MockStorage,
+) -> DeletePlan<'idl> {
+    let payload: &'static Value = Box::leak(Box::new(payload));
+    let plan = plan(model, idl, &tree(include), payload).expect("plan to succeed");
+    common::delete_executor::execute(&plan, storage).await;
+    plan
+}
+
+async fn count(storage: &MockStorage, db: &str, table: &str) -> i64 {
+    sqlx::query_scalar(&format!("SELECT COUNT(*) FROM \"{table}\""))
+        .fetch_one(storage.d1.get(db).unwrap())
+        .await
+        .unwrap()
+}
+
+#[sqlx::test]
+async fn delete_one_to_many_children_first() {
+    // Arrange
+    let idl = src_to_idl(
+        r#"
+        d1 { db }
+
+        model User for db {
+            primary { id: int }
+            many Dog::userId(id) { dogs }
+        }
+
+        model Dog for db {
+            primary { id: int }
+            foreign User::id { userId }
+            column { name: string }
+        }
+        "#,
+    );
+    let mut storage = MockStorage::from_idl(&idl, &[]).await;
+    seed(
+        &idl,
+        "User",
+        json!({ "dogs": {} }),
+        json!({ "id": 1, "dogs": [ { "id": 1, "name": "A" }, { "id": 2, "name": "B" } ] }),
+        &mut storage,
+    )
+    .await;
+
+    // Act
+    let plan = delete_ok(
+        &idl,
+        "User",
+        json!({ "dogs": {} }),
+        json!({
+            "id": 1,
+            "dogs": [
+                { "id": 1, "userId": 1, "name": "A" },
+                { "id": 2, "userId": 1, "name": "B" }
+            ]
+        }),
+        &mut storage,
+    )
+    .await;
+
+    // Assert
+    assert_eq!(plan.stages.len(), 1);
+    assert_eq!(plan.stages[0].steps.len(), 1, "one SqlBatch");
+    assert_eq!(
+        statements(&plan, 0, 0),
+        vec![
+            r#"DELETE FROM "Dog" WHERE "id" = ?1"#,
+            r#"DELETE FROM "Dog" WHERE "id" = ?1"#,
+            r#"DELETE FROM "User" WHERE "id" = ?1"#,
+        ],
+        "each dog is deleted before the user it references"
+    );
+    assert_eq!(count(&storage, "db", "Dog").await, 0);
+    assert_eq!(count(&storage, "db", "User").await, 0);
+}
+
+#[sqlx::test]
+async fn delete_parent_owned_one_after_row() {
+    // Arrange
+    let idl = src_to_idl(
+        r#"
+        d1 { db }
+
+        model Person for db {
+            primary { id: int }
+            foreign Dog::id { dogId }
+            one Dog::id(dogId) { dog }
+        }
+
+        model Dog for db {
+            primary { id: int }
+            column { name: string }
+        }
+        "#,
+    );
+    let mut storage = MockStorage::from_idl(&idl, &[]).await;
+    seed(
+        &idl,
+        "Person",
+        json!({ "dog": {} }),
+        json!({ "id": 5, "dog": { "id": 3, "name": "Fido" } }),
+        &mut storage,
+    )
+    .await;
+
+    // Act
+    let plan = delete_ok(
+        &idl,
+        "Person",
+        json!({ "dog": {} }),
+        json!({ "id": 5, "dogId": 3, "dog": { "id": 3, "name": "Fido" } }),
+        &mut storage,
+    )
+    .await;
+
+    // Assert
+    assert_eq!(
+        statements(&plan, 0, 0),
+        vec![
+            r#"DELETE FROM "Person" WHERE "id" = ?1"#,
+            r#"DELETE FROM "Dog" WHERE "id" = ?1"#,
+        ],
+        "the person holds the FK, so it goes before the dog"
+    );
+    assert_eq!(count(&storage, "db", "Person").await, 0);
+    assert_eq!(count(&storage, "db", "Dog").await, 0);
+}
+
+#[sqlx::test]
+async fn delete_excluded_nav_is_untouched() {
+    // Arrange
+    let idl = src_to_idl(
+        r#"
+        d1 { db }
+
+        model Person for db {
+            primary { id: int }
+            foreign Dog::id { dogId }
+            one Dog::id(dogId) { dog }
+        }
+
+        model Dog for db {
+            primary { id: int }
+            column { name: string }
+        }
+        "#,
+    );
+    let mut storage = MockStorage::from_idl(&idl, &[]).await;
+    seed(
+        &idl,
+        "Person",
+        json!({ "dog": {} }),
+        json!({ "id": 5, "dog": { "id": 3, "name": "Fido" } }),
+        &mut storage,
+    )
+    .await;
+
+    // Act
+    let plan = delete_ok(
+        &idl,
+        "Person",
+        json!({}),
+        json!({ "id": 5, "dogId": 3, "dog": { "id": 3, "name": "Fido" } }),
+        &mut storage,
+    )
+    .await;
+
+    // Assert
+    assert_eq!(
+        statements(&plan, 0, 0),
+        vec![r#"DELETE FROM "Person" WHERE "id" = ?1"#],
+        "the dog is outside the include tree"
+    );
+    assert_eq!(count(&storage, "db", "Dog").await, 1);
+}
+
+#[sqlx::test]
+async fn delete_cross_db_child_waits_for_parent_row() {
+    // Arrange
+    let idl = src_to_idl(
+        r#"
+        d1 { people }
+        d1 { animals }
+
+        model Person for people {
+            primary { id: int }
+            column { dogId: int }
+            one Dog::id(dogId) { dog }
+        }
+
+        model Dog for animals {
+            primary { id: int }
+            column { name: string }
+        }
+        "#,
+    );
+    let mut storage = MockStorage::from_idl(&idl, &[]).await;
+
+    // Act
+    let payload: &'static Value = Box::leak(Box::new(
+        json!({ "id": 1, "dogId": 10, "dog": { "id": 10, "name": "Fido" } }),
+    ));
+    let plan = plan("Person", &idl, &tree(json!({ "dog": {} })), payload).expect("plan");
+
+    // Assert
+    assert_eq!(plan.stages.len(), 2, "one stage per database");
+    assert_eq!(
+        statements(&plan, 0, 0),
+        vec![r#"DELETE FROM "Person" WHERE "id" = ?1"#]
+    );
+    assert_eq!(
+        statements(&plan, 1, 0),
+        vec![r#"DELETE FROM "Dog" WHERE "id" = ?1"#]
+    );
+    common::delete_executor::execute(&plan, &mut storage).await;
+}
+
+#[sqlx::test]
+async fn delete_r2_after_owning_row() {
+    // Arrange
+    let idl = src_to_idl(
+        r#"
+        d1 { db }
+
+        r2 Bucket {
+            photo {
+                id: int
+                "photos/{id}"
+            }
+        }
+
+        model Person for db {
+            primary { id: int }
+            r2 Bucket::photo(id) { photo }
+        }
+        "#,
+    );
+    let mut storage = MockStorage::from_idl(&idl, &[]).await;
+    seed(&idl, "Person", json!({}), json!({ "id": 4 }), &mut storage).await;
+    storage
+        .r2
+        .entry("Bucket".into())
+        .or_default()
+        .insert("photos/4".into(), json!("bytes"));
+
+    // Act
+    let plan = delete_ok(
+        &idl,
+        "Person",
+        json!({ "photo": {} }),
+        json!({ "id": 4, "photo": "bytes" }),
+        &mut storage,
+    )
+    .await;
+
+    // Assert
+    assert_eq!(plan.stages.len(), 2);
+    assert!(
+        matches!(plan.stages[1].steps[0], DeleteStep::KeyDelete { .. }),
+        "the object is deleted in the stage after its row"
+    );
+    assert_eq!(count(&storage, "db", "Person").await, 0);
+    assert!(storage.r2["Bucket"].is_empty());
+}
+
+#[test]
+fn delete_missing_primary_key_fails() {
+    // Arrange
+    let idl = src_to_idl(
+        r#"
+        d1 { db }
+
+        model Horse for db {
+            primary { id: int }
+            column { name: string }
+        }
+        "#,
+    );
+    let payload = json!({ "name": "Spirit" });
+
+    // Act
+    let err = plan("Horse", &idl, &tree(json!({})), &payload).unwrap_err();
+
+    // Assert
+    assert!(
+        matches!(err, orm::OrmErrorKind::MissingField { .. }),
+        "a delete is always keyed on the primary key"
+    );
+}
@@ -4,7 +4,8 @@ mod common;
 
 use common::setup::tree;
 use compiler_test::src_to_idl;
-use orm::query::explain::{explain_save, explain_select};
+use orm::query::delete::planner::plan as delete_plan;
+use orm::query::explain::{explain_delete, explain_save, explain_select};
 use orm::query::save::planner::plan as save_plan;
 use orm::query::select::planner::{SelectOperation, plan as select_plan};
 use serde_json::{Value, json};
@@ -76,6 +77,25 @@ fn explain_save_snapshot() {
     insta::assert_snapshot!(explain_save("Org", &tree(include()), &plan));
 }
 
+#[test]
+fn explain_delete_snapshot() {
+    let idl = src_to_idl(SRC);
+    let payload = json!({
+        "id": 1,
+        "tenantId": 7,
+        "board": {
+            "pid": 3,
+            "tenantId": 7,
+            "banner": null,
+            "top": null,
+            "entries": [ { "id": 9, "tenantId": 7, "score": 42, "boardId": 3 } ]
+        }
+    });
+
+    let plan = delete_plan("Org", &idl, &tree(include()), &payload).expect("plan");
+    insta::assert_snapshot!(explain_delete("Org", &tree(include()), &plan));
+}
+
 #[test]
 fn explain_select_list_snapshot() {
     let idl = src_to_idl(SRC);
@@ -91,3 +111,11 @@ fn explain_select_get_snapshot() {
     let plan = select_plan(SelectOperation::Get, "Org", &idl, &inc);
     insta::assert_snapshot!(explain_select(SelectOperation::Get, "Org", &inc, &plan));
 }
+
+#[test]
+fn explain_select_count_snapshot() {
+    let idl = src_to_idl(SRC);
+    let inc = tree(json!({}));
+    let plan = select_plan(SelectOperation::Count, "Entry", &idl, &inc);
+    insta::assert_snapshot!(explain_select(SelectOperation::Count, "Entry", &inc, &plan));
+}
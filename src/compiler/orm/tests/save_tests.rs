@@ -138,6 +138,81 @@ async fn save_auto_increment_pk() {
     );
 }
 
+#[sqlx::test]
+async fn save_omitted_columns_take_defaults() {
+    // Arrange
+    let idl = src_to_idl(
+        r#"
+        d1 { db }
+
+        enum Gait { Walk, Trot, Gallop }
+
+        model Horse for db {
+            primary { id: int }
+            column {
+                name: string
+                score: int = 100
+                gait: Gait = Trot
+                born: date = now
+            }
+        }
+        "#,
+    );
+    let mut storage = MockStorage::from_idl(&idl, &[]).await;
+
+    // Insert
+    {
+        // Act
+        let (plan, body) = save_ok(
+            &idl,
+            "Horse",
+            json!({}),
+            json!({ "name": "Spirit" }),
+            &mut storage,
+        )
+        .await;
+
+        // Assert
+        assert_eq!(
+            write_sql(&batches(&plan, 0, 0)[0]),
+            r#"INSERT INTO "Horse" ("name") VALUES (?1)"#,
+            "defaulted columns are left to the database"
+        );
+        assert_eq!(body["score"], json!(100));
+        assert_eq!(body["gait"], json!("Trot"));
+        assert!(
+            body["born"].as_str().is_some_and(|s| s.ends_with('Z')),
+            "{body}"
+        );
+    }
+
+    // Upsert
+    {
+        save_ok(
+            &idl,
+            "Horse",
+            json!({}),
+            json!({ "id": 1, "name": "Spirit", "score": 5 }),
+            &mut storage,
+        )
+        .await;
+
+        // Act
+        let (_, body) = save_ok(
+            &idl,
+            "Horse",
+            json!({}),
+            json!({ "id": 1, "name": "Rain" }),
+            &mut storage,
+        )
+        .await;
+
+        // Assert
+        assert_eq!(body["name"], json!("Rain"));
+        assert_eq!(body["score"], json!(5), "an omitted column keeps its value");
+    }
+}
+
 #[sqlx::test]
 async fn save_one_to_one_same_db() {
     // Arrange
@@ -3,9 +3,13 @@ mod common;
 use common::setup::{MockStorage, tree};
 use compiler_test::src_to_idl;
 use idl::CloesceIdl;
-use orm::query::select::{
-    plan::{Select, SelectPlan, SqlArgument, SqlSegment},
-    planner::SelectOperation,
+use orm::{
+    OrmErrorKind,
+    query::select::{
+        cursor,
+        plan::{Select, SelectPlan, SqlArgument, SqlSegment},
+        planner::SelectOperation,
+    },
 };
 
 use serde_json::{Value, json};
@@ -42,6 +46,18 @@ fn sql_literals(sql: &[SqlSegment]) -> String {
         .collect()
 }
 
+/// Decode the `cursor` param (if any) into the seek params `plan` binds, as the runtime does.
+fn with_cursor(plan: &SelectPlan, params: Value) -> serde_json::Map<String, Value> {
+    let Value::Object(mut params) = params else {
+        panic!("params must be an object")
+    };
+    let cursor = params.remove(cursor::CURSOR_PARAM);
+    let seek = cursor::decode(&plan.cursor, cursor.as_ref().and_then(Value::as_str))
+        .expect("cursor to decode");
+    params.extend(seek);
+    params
+}
+
 async fn seed(
     idl: &CloesceIdl<'_>,
     model: &str,
@@ -62,10 +78,8 @@ async fn execute_ok<'idl>(
     params: Value,
     storage: &MockStorage,
 ) -> (SelectPlan<'idl>, Value) {
-    let Value::Object(params) = params else {
-        panic!("params must be an object")
-    };
     let plan = orm::query::select::planner::plan(op, model, idl, &tree(include));
+    let params = with_cursor(&plan, params);
 
     // Panics on any error, since the test is expected to succeed.
     let value = common::select_executor::execute(&plan, params, storage).await;
@@ -131,7 +145,7 @@ async fn scalar_d1_get_and_list() {
             SelectOperation::List,
             "Person",
             json!({}),
-            json!({ "lastSeen_id": 0, "limit": 2 }),
+            json!({ "limit": 2 }),
             &storage,
         )
         .await;
@@ -158,7 +172,7 @@ async fn scalar_d1_get_and_list() {
             "Person",
             json!({}),
             // seek past the last id seen on page 1, returning the remainder.
-            json!({ "lastSeen_id": 2, "limit": 2 }),
+            json!({ "cursor": cursor::encode(&["id"], &json!({ "id": 2 })), "limit": 2 }),
             &storage,
         )
         .await;
@@ -207,7 +221,10 @@ async fn composite_pk_list_seek() {
         SelectOperation::List,
         "Order",
         json!({}),
-        json!({ "lastSeen_region": 1, "lastSeen_num": 10, "limit": 10 }),
+        json!({
+            "cursor": cursor::encode(&["region", "num"], &json!({ "region": 1, "num": 10 })),
+            "limit": 10
+        }),
         &storage,
     )
     .await;
@@ -290,7 +307,7 @@ async fn one_nav_same_db() {
             SelectOperation::List,
             "Person",
             json!({ "dog": {} }),
-            json!({ "lastSeen_id": 0, "limit": 10 }),
+            json!({ "limit": 10 }),
             &storage,
         )
         .await;
@@ -392,7 +409,7 @@ async fn many_nav_same_db() {
             SelectOperation::List,
             "User",
             json!({ "posts": {} }),
-            json!({ "lastSeen_id": 0, "limit": 10 }),
+            json!({ "limit": 10 }),
             &storage,
         )
         .await;
@@ -461,7 +478,7 @@ async fn cross_database_nav() {
         SelectOperation::List,
         "Person",
         json!({ "dog": {} }),
-        json!({ "lastSeen_id": 0, "limit": 10 }),
+        json!({ "limit": 10 }),
         &storage,
     )
     .await;
@@ -528,7 +545,7 @@ async fn nested_depth_two() {
         SelectOperation::List,
         "User",
         json!({ "dog": { "toy": {} } }),
-        json!({ "lastSeen_id": 0, "limit": 10 }),
+        json!({ "limit": 10 }),
         &storage,
     )
     .await;
@@ -652,7 +669,7 @@ async fn d1_root_do_child_fanout() {
         SelectOperation::List,
         "Company",
         json!({ "tenant": {} }),
-        json!({ "lastSeen_id": 0, "limit": 10 }),
+        json!({ "limit": 10 }),
         &storage,
     )
     .await;
@@ -787,7 +804,7 @@ async fn empty_form_nav() {
         SelectOperation::List,
         "Page",
         json!({ "banners": {} }),
-        json!({ "lastSeen_id": 0, "limit": 10 }),
+        json!({ "limit": 10 }),
         &storage,
     )
     .await;
@@ -1125,7 +1142,7 @@ async fn r2_field_list_fanout() {
         SelectOperation::List,
         "User",
         json!({ "avatar": {} }),
-        json!({ "lastSeen_id": 0, "limit": 10 }),
+        json!({ "limit": 10 }),
         &storage,
     )
     .await;
@@ -1310,7 +1327,7 @@ async fn kv_field_list_fanout() {
         SelectOperation::List,
         "Item",
         json!({ "entry": {} }),
-        json!({ "lastSeen_id": 0, "limit": 10 }),
+        json!({ "limit": 10 }),
         &storage,
     )
     .await;
@@ -1503,7 +1520,7 @@ async fn do_kv_fanout_from_d1_list() {
         SelectOperation::List,
         "Org",
         json!({ "board": { "top": {} } }),
-        json!({ "lastSeen_id": 0, "limit": 10 }),
+        json!({ "limit": 10 }),
         &storage,
     )
     .await;
@@ -2021,7 +2038,7 @@ async fn route_field_on_sql_nav_child() {
         SelectOperation::List,
         "Parent",
         json!({ "child": {} }),
-        json!({ "lastSeen_id": 0, "limit": 10 }),
+        json!({ "limit": 10 }),
         &storage,
     )
     .await;
@@ -2104,7 +2121,7 @@ async fn kv_key_straddles_nav_inherited_and_local_fields() {
         SelectOperation::List,
         "Org",
         json!({ "board": { "entry": {} } }),
-        json!({ "lastSeen_id": 0, "limit": 10 }),
+        json!({ "limit": 10 }),
         &storage,
     )
     .await;
@@ -2135,3 +2152,361 @@ async fn kv_key_straddles_nav_inherited_and_local_fields() {
          so straddling tables costs no extra staging)"
     );
 }
+
+/// Execute a data source's generated `list`, shaped by its declared [idl::ListQuery].
+async fn list_ok<'idl>(
+    idl: &'idl CloesceIdl<'_>,
+    model: &str,
+    source: &str,
+    params: Value,
+    storage: &MockStorage,
+) -> (SelectPlan<'idl>, Value) {
+    let ds = &idl.models[model].data_sources[source];
+    let plan = orm::query::select::planner::plan_list(model, idl, &ds.tree, &ds.list_query);
+    let params = with_cursor(&plan, params);
+    let value = common::select_executor::execute(&plan, params, storage).await;
+
+    (plan, value)
+}
+
+#[sqlx::test]
+async fn list_order_by_and_where() {
+    // Arrange
+    let idl = src_to_idl(
+        r#"
+        d1 { db }
+
+        model Post for db {
+            primary { id: int }
+            column {
+                createdAt: int
+                status: string
+            }
+        }
+
+        source Recent for Post {
+            include {}
+
+            list {
+                orderBy createdAt desc
+                where status == status
+            }
+        }
+        "#,
+    );
+
+    let mut storage = MockStorage::from_idl(&idl, &[]).await;
+    for (id, created_at, status) in [
+        (1, 10, "pub"),
+        (2, 30, "pub"),
+        (3, 30, "pub"),
+        (4, 20, "draft"),
+    ] {
+        seed(
+            &idl,
+            "Post",
+            json!({}),
+            json!({ "id": id, "createdAt": created_at, "status": status }),
+            &mut storage,
+        )
+        .await;
+    }
+
+    // First page
+    {
+        // Act
+        let (plan, body) = list_ok(
+            &idl,
+            "Post",
+            "Recent",
+            json!({ "status": "pub", "limit": 2 }),
+            &storage,
+        )
+        .await;
+
+        // Assert
+        let Select::Sql { sql, .. } = &plan.stages[0].steps[0].query else {
+            panic!("expected a root sql step");
+        };
+        assert_eq!(
+            sql_literals(sql),
+            r#"SELECT "id", "createdAt", "status" FROM "Post" WHERE "status" IS <?> AND (<?> IS NULL OR ("createdAt", "id") < (<?>, <?>)) ORDER BY "createdAt" DESC, "id" DESC LIMIT <?>"#,
+            "the pk tie-breaker follows the last orderBy direction"
+        );
+        assert_eq!(plan.cursor, vec!["createdAt", "id"]);
+        assert_eq!(
+            body,
+            json!([
+                { "id": 3, "createdAt": 30, "status": "pub" },
+                { "id": 2, "createdAt": 30, "status": "pub" },
+            ]),
+            "a null cursor starts at the newest matching post"
+        );
+    }
+
+    // Second page
+    {
+        // Act
+        let (_, body) = list_ok(
+            &idl,
+            "Post",
+            "Recent",
+            json!({
+                "status": "pub",
+                "cursor": cursor::encode(&["createdAt", "id"], &json!({ "id": 2, "createdAt": 30 })),
+                "limit": 2
+            }),
+            &storage,
+        )
+        .await;
+
+        // Assert
+        assert_eq!(
+            body,
+            json!([{ "id": 1, "createdAt": 10, "status": "pub" }]),
+            "seeking past (30, 2) skips the draft and returns the remainder"
+        );
+    }
+}
+
+#[sqlx::test]
+async fn list_where_matches_null() {
+    // Arrange
+    let idl = src_to_idl(
+        r#"
+        d1 { db }
+
+        model Post for db {
+            primary { id: int }
+            column { category: option<string> }
+        }
+
+        source ByCategory for Post {
+            include {}
+
+            list {
+                where category == category
+            }
+        }
+        "#,
+    );
+
+    let mut storage = MockStorage::from_idl(&idl, &[]).await;
+    for (id, category) in [(1, json!("news")), (2, json!(null)), (3, json!(null))] {
+        seed(
+            &idl,
+            "Post",
+            json!({}),
+            json!({ "id": id, "category": category }),
+            &mut storage,
+        )
+        .await;
+    }
+
+    // Act
+    let (_, uncategorized) = list_ok(
+        &idl,
+        "Post",
+        "ByCategory",
+        json!({ "category": null, "limit": 10 }),
+        &storage,
+    )
+    .await;
+    let (_, news) = list_ok(
+        &idl,
+        "Post",
+        "ByCategory",
+        json!({ "category": "news", "limit": 10 }),
+        &storage,
+    )
+    .await;
+
+    // Assert
+    assert_eq!(
+        uncategorized,
+        json!([
+            { "id": 2, "category": null },
+            { "id": 3, "category": null },
+        ]),
+        "a null param matches the null rows"
+    );
+    assert_eq!(news, json!([{ "id": 1, "category": "news" }]));
+}
+
+#[sqlx::test]
+async fn list_mixed_directions_expand_keyset() {
+    // Arrange
+    let idl = src_to_idl(
+        r#"
+        d1 { db }
+
+        model Score for db {
+            primary { id: int }
+            column {
+                level: int
+                points: int
+            }
+        }
+
+        source Leaderboard for Score {
+            include {}
+
+            list {
+                orderBy level asc, points desc
+            }
+        }
+        "#,
+    );
+
+    let mut storage = MockStorage::from_idl(&idl, &[]).await;
+    for (id, level, points) in [(1, 1, 50), (2, 1, 90), (3, 2, 70), (4, 1, 90)] {
+        seed(
+            &idl,
+            "Score",
+            json!({}),
+            json!({ "id": id, "level": level, "points": points }),
+            &mut storage,
+        )
+        .await;
+    }
+
+    // Act
+    let (plan, body) = list_ok(
+        &idl,
+        "Score",
+        "Leaderboard",
+        json!({
+            "cursor": cursor::encode(
+                &["level", "points", "id"],
+                &json!({ "id": 2, "level": 1, "points": 90 })
+            ),
+            "limit": 10
+        }),
+        &storage,
+    )
+    .await;
+
+    // Assert
+    let Select::Sql { sql, .. } = &plan.stages[0].steps[0].query else {
+        panic!("expected a root sql step");
+    };
+    assert_eq!(
+        sql_literals(sql),
+        r#"SELECT "id", "level", "points" FROM "Score" WHERE (<?> IS NULL OR (("level" > <?>) OR ("level" = <?> AND "points" < <?>) OR ("level" = <?> AND "points" = <?> AND "id" < <?>))) ORDER BY "level" ASC, "points" DESC, "id" DESC LIMIT <?>"#
+    );
+    assert_eq!(
+        body,
+        json!([
+            { "id": 1, "level": 1, "points": 50 },
+            { "id": 3, "level": 2, "points": 70 },
+        ]),
+        "rows after (1, 90, 2) in (level asc, points desc, id desc) order"
+    );
+}
+
+#[sqlx::test]
+async fn count_applies_where_filters() {
+    // Arrange
+    let idl = src_to_idl(
+        r#"
+        d1 { db }
+
+        model Post for db {
+            primary { id: int }
+            column { status: string }
+        }
+
+        source Published for Post {
+            include {}
+
+            list {
+                where status == status
+            }
+        }
+        "#,
+    );
+
+    let mut storage = MockStorage::from_idl(&idl, &[]).await;
+    for (id, status) in [(1, "pub"), (2, "draft"), (3, "pub")] {
+        seed(
+            &idl,
+            "Post",
+            json!({}),
+            json!({ "id": id, "status": status }),
+            &mut storage,
+        )
+        .await;
+    }
+    let ds = &idl.models["Post"].data_sources["Published"];
+
+    // Act
+    let plan = orm::query::select::planner::plan_count("Post", &idl, &ds.list_query);
+    let filtered = common::select_executor::execute(
+        &plan,
+        with_cursor(&plan, json!({ "status": "pub" })),
+        &storage,
+    )
+    .await;
+    let (_, all) = execute_ok(
+        &idl,
+        SelectOperation::Count,
+        "Post",
+        json!({}),
+        json!({}),
+        &storage,
+    )
+    .await;
+
+    // Assert
+    let Select::Sql { sql, .. } = &plan.stages[0].steps[0].query else {
+        panic!("expected a root sql step");
+    };
+    assert_eq!(
+        sql_literals(sql),
+        r#"SELECT COUNT(*) AS "count" FROM "Post" WHERE "status" IS <?>"#
+    );
+    assert_eq!(plan.tables.len(), 1, "a count hydrates nothing");
+    assert_eq!(filtered, json!({ "count": 2 }));
+    assert_eq!(
+        all,
+        json!({ "count": 3 }),
+        "without filters every row counts"
+    );
+}
+
+#[test]
+fn cursor_round_trips_and_rejects_invalid() {
+    // Arrange
+    let columns = ["createdAt", "id"];
+    let row = json!({ "id": 2, "createdAt": "2024-01-01T00:00:00Z", "title": "ignored" });
+
+    // Act
+    let encoded = cursor::encode(&columns, &row);
+    let decoded = cursor::decode(&columns, Some(&encoded)).expect("cursor to decode");
+    let first_page = cursor::decode(&columns, None).expect("no cursor to decode");
+
+    // Assert
+    assert_eq!(
+        Value::Object(decoded),
+        json!({ "$cursor_createdAt": "2024-01-01T00:00:00Z", "$cursor_id": 2 })
+    );
+    assert_eq!(
+        Value::Object(first_page),
+        json!({ "$cursor_createdAt": null, "$cursor_id": null }),
+        "no cursor requests the first page"
+    );
+
+    for invalid in [
+        "not base64!",
+        &cursor::encode(&["id"], &json!({ "id": 2 })),
+        "eyJpZCI6IDJ9", // `{"id": 2}`, not an array
+    ] {
+        assert!(
+            matches!(
+                cursor::decode(&columns, Some(invalid)),
+                Err(OrmErrorKind::InvalidCursor { .. })
+            ),
+            "`{invalid}` should be rejected"
+        );
+    }
+}
@@ -889,6 +889,51 @@ fn validator_regex() {
     }
 }
 
+#[test]
+fn enum_members() {
+    let idl = src_to_idl(
+        r#"
+        enum Status { Open, Closed }
+        "#,
+    );
+    let status = || CidlType::Enum { name: "Status" };
+
+    // Pass: a declared member
+    {
+        let result = validate(status(), Some(json!("Open")), &idl);
+        assert_eq!(result.unwrap(), Some(json!("Open")));
+    }
+
+    // Fail: members are case sensitive
+    {
+        let result = validate(status(), Some(json!("open")), &idl);
+        assert!(matches!(result, Err(OrmErrorKind::NotEnumMember { .. })));
+    }
+
+    // Fail: not a string
+    {
+        let result = validate(status(), Some(json!(1)), &idl);
+        assert!(matches!(result, Err(OrmErrorKind::TypeMismatch { .. })));
+    }
+
+    // Pass: each element of an array
+    {
+        let result = validate(
+            CidlType::array(status()),
+            Some(json!(["Open", "Closed"])),
+            &idl,
+        );
+        assert!(result.is_ok());
+
+        let result = validate(
+            CidlType::array(status()),
+            Some(json!(["Open", "Pending"])),
+            &idl,
+        );
+        assert!(matches!(result, Err(OrmErrorKind::NotEnumMember { .. })));
+    }
+}
+
 #[test]
 fn validators_in_model() {
     let idl = src_to_idl(
@@ -4,12 +4,12 @@ pub mod analysis {
     use crate::{
         LocalSymbolKind, SymbolTable, ensure,
         err::{ErrorSink, SemanticError},
-        resolve_cidl_type, resolve_inject, resolve_validator_tags,
+        guard, resolve_cidl_type, resolve_inject, resolve_validator_tags,
     };
-    use frontend::{ApiBlockMethod, SpdSlice, Tag};
+    use frontend::{ApiBlockMethod, SpdSlice, Symbol, Tag};
     use idl::{
-        ApiMethod, ApiMethodParam, CidlType, DEFAULT_DATA_SOURCE_NAME, HttpVerb, MediaType, Model,
-        ParamSource, ValidatedField,
+        ApiMethod, ApiMethodParam, CidlType, CloesceIdl, DEFAULT_DATA_SOURCE_NAME, HttpVerb,
+        MediaType, Model, ParamSource, ValidatedField,
     };
     use indexmap::IndexMap;
 
@@ -21,23 +21,43 @@ pub mod analysis {
     ) -> Vec<(&'src str, Vec<ApiMethod<'src>>)> {
         let mut result = Vec::new();
 
+        // Every custom route seen so far, as `(verb, route, method)`
+        let mut routes: Vec<(HttpVerb, &'src str, &'p Symbol<'src>)> = Vec::new();
+
         for api_block in &table.apis {
-            let Some(model) = table.models.get(api_block.symbol.name) else {
+            let namespace = table.resolve(&api_block.symbol);
+            if !table.models.contains_key(namespace) {
                 sink.push(SemanticError::ApiUnknownNamespaceReference {
                     api: &api_block.symbol,
                 });
                 continue;
-            };
+            }
 
-            let model = models
-                .get(model.symbol.name)
-                .expect("unresolved symbols to have been caught earlier");
+            // A model that failed analysis has already reported its errors
+            let Some(model) = models.get(namespace) else {
+                continue;
+            };
 
             let mut methods = Vec::new();
             for api_method in api_block.methods.inners() {
-                if let Some(m) = method(model, api_method, table, sink) {
-                    methods.push(m);
+                let Some(m) = method(model, api_method, table, sink) else {
+                    continue;
+                };
+
+                if let Some(route) = m.route {
+                    match routes
+                        .iter()
+                        .find(|(verb, other, _)| *verb == m.http_verb && overlaps(other, route))
+                    {
+                        Some((_, _, first)) => sink.push(SemanticError::ApiOverlappingRoute {
+                            first,
+                            second: &api_method.symbol,
+                        }),
+                        None => routes.push((m.http_verb, route, &api_method.symbol)),
+                    }
                 }
+
+                methods.push(m);
             }
             result.push((model.name, methods));
         }
@@ -45,6 +65,70 @@ pub mod analysis {
         result
     }
 
+    /// Rejects custom routes that would hide a derived route once the IDL is
+    /// expanded. The router tries custom routes first, so a custom route matching
+    /// `Model/method`, `Model/{key...}/method` or a CRUD route would shadow it.
+    pub fn derived_route_collisions<'src, 'p>(
+        idl: &CloesceIdl<'src>,
+        table: &SymbolTable<'src, 'p>,
+    ) -> Vec<SemanticError<'src, 'p>> {
+        let derived = idl
+            .models
+            .values()
+            .flat_map(|model| {
+                model.apis.iter().filter(|m| m.route.is_none()).map(|m| {
+                    let keys = m
+                        .data_source
+                        .and_then(|ds| model.data_sources.get(ds))
+                        .map_or(0, |ds| ds.get.parameters.len());
+                    let mut segments = vec![model.name.to_string()];
+                    segments.extend(std::iter::repeat_n("{}".to_string(), keys));
+                    segments.push(m.name.to_string());
+                    (m.http_verb, segments.join("/"))
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut errors = Vec::new();
+        for model in idl.models.values() {
+            for m in &model.apis {
+                let Some(route) = m.route else {
+                    continue;
+                };
+                let Some((_, derived)) = derived
+                    .iter()
+                    .find(|(verb, derived)| *verb == m.http_verb && overlaps(derived, route))
+                else {
+                    continue;
+                };
+                let Some(method) = table
+                    .apis
+                    .iter()
+                    .filter(|api| table.resolve(&api.symbol) == model.name)
+                    .flat_map(|api| api.methods.inners())
+                    .find(|api_method| api_method.symbol.name == m.name)
+                else {
+                    continue;
+                };
+                errors.push(SemanticError::ApiRouteShadowsDerived {
+                    method: &method.symbol,
+                    derived: derived.clone(),
+                });
+            }
+        }
+        errors
+    }
+
+    /// Whether some request path matches both route templates, where a
+    /// `{variable}` segment matches any value.
+    fn overlaps(a: &str, b: &str) -> bool {
+        let is_variable = |seg: &str| seg.starts_with('{');
+        a.split('/').count() == b.split('/').count()
+            && a.split('/')
+                .zip(b.split('/'))
+                .all(|(a, b)| a == b || is_variable(a) || is_variable(b))
+    }
+
     fn method<'src, 'p>(
         model: &Model<'src>,
         method: &'p ApiBlockMethod<'src>,
@@ -71,6 +155,19 @@ pub mod analysis {
             .not()
             .then(|| data_source_name.unwrap_or("Default"));
 
+        let route = route(method, is_static, sink);
+
+        let mut guards = Vec::new();
+        for tag in &method.symbol.tags {
+            match &tag.inner {
+                Tag::Guard { guard } => guard::resolve(guard, table, sink, &mut guards),
+                _ => sink.push(SemanticError::TagInvalidInContext {
+                    tag,
+                    symbol: &method.symbol,
+                }),
+            }
+        }
+
         // An instantiated method runs inside the Durable Object its data source's
         // `get` resolves, so it inherits that `get`'s durable target during
         // expansion.
@@ -94,15 +191,108 @@ pub mod analysis {
             is_static,
             data_source,
             http_verb: method.http_verb,
+            route,
             return_media,
             return_type,
             parameters_media,
             parameters,
             injected,
             durable_target,
+            guards,
         })
     }
 
+    /// Validates a method's route template against its `[path]` parameters, returning the route
+    /// without any leading or trailing `/`.
+    fn route<'src, 'p>(
+        method: &'p ApiBlockMethod<'src>,
+        is_static: bool,
+        sink: &mut ErrorSink<'src, 'p>,
+    ) -> Option<&'src str> {
+        let path_params = method
+            .parameters
+            .iter()
+            .filter(|p| p.tags.iter().any(|t| matches!(t.inner, Tag::Path)))
+            .collect::<Vec<_>>();
+
+        let invalid = |reason: &str| SemanticError::ApiInvalidRoute {
+            method: &method.symbol,
+            reason: reason.to_string(),
+        };
+
+        let Some(route) = method.route.map(|r| r.trim_matches('/')) else {
+            for param in path_params {
+                sink.push(SemanticError::ApiPathParamNotInRoute {
+                    method: &method.symbol,
+                    param,
+                });
+            }
+            return None;
+        };
+
+        if !is_static {
+            sink.push(invalid(
+                "only static methods may declare a route, as instance routes are derived from their data source",
+            ));
+            return None;
+        }
+
+        if route.is_empty() {
+            sink.push(invalid("a route must have at least one segment"));
+            return None;
+        }
+
+        let mut variables = Vec::new();
+        for seg in route.split('/') {
+            if seg.is_empty() {
+                sink.push(invalid("a route cannot have an empty segment"));
+                return None;
+            }
+
+            let Some(variable) = seg
+                .strip_prefix('{')
+                .and_then(|s| s.strip_suffix('}'))
+                .filter(|v| !v.contains(['{', '}']))
+            else {
+                if seg.contains(['{', '}']) {
+                    sink.push(invalid(
+                        "a `{variable}` must be a whole segment, as in `users/{id}`",
+                    ));
+                    return None;
+                }
+                continue;
+            };
+
+            if variables.contains(&variable) {
+                sink.push(invalid(&format!("'{variable}' appears more than once")));
+                return None;
+            }
+            variables.push(variable);
+        }
+
+        let mut valid = true;
+        for variable in &variables {
+            if !path_params.iter().any(|p| p.name == *variable) {
+                sink.push(SemanticError::ApiRouteUnknownParam {
+                    method: &method.symbol,
+                    variable,
+                });
+                valid = false;
+            }
+        }
+        for param in path_params {
+            if !variables.contains(&param.name) {
+                sink.push(SemanticError::ApiPathParamNotInRoute {
+                    method: &method.symbol,
+                    param,
+                });
+                valid = false;
+            }
+        }
+
+        valid.then_some(route)
+    }
+
     fn return_type<'src, 'p>(
         method: &'p ApiBlockMethod<'src>,
         table: &SymbolTable<'src, 'p>,
@@ -188,11 +378,23 @@ pub mod analysis {
             // Validate tags
             let mut source = ParamSource::Body;
             for tag in &param.tags {
-                match &tag.inner {
-                    Tag::Validator { .. } => {}
-                    Tag::Header => source = ParamSource::Header,
-                    _ => sink.push(SemanticError::TagInvalidInContext { tag, symbol: param }),
+                let tag_source = match &tag.inner {
+                    Tag::Validator { .. } => continue,
+                    Tag::Header => ParamSource::Header,
+                    Tag::Query => ParamSource::Query,
+                    Tag::Path => ParamSource::Path,
+                    _ => {
+                        sink.push(SemanticError::TagInvalidInContext { tag, symbol: param });
+                        continue;
+                    }
+                };
+
+                // A parameter is read from exactly one place
+                if source != ParamSource::Body {
+                    sink.push(SemanticError::TagInvalidInContext { tag, symbol: param });
+                    continue;
                 }
+                source = tag_source;
             }
 
             let resolved_type = match resolve_cidl_type(param, &param.cidl_type, table) {
@@ -206,6 +408,30 @@ pub mod analysis {
                 method: &method.symbol,
                 param,
             };
+
+            if matches!(source, ParamSource::Query | ParamSource::Path) {
+                // A URL only carries scalars, and a path segment is never absent
+                let scalar = match (&resolved_type, source) {
+                    (CidlType::Nullable(inner), ParamSource::Query) => inner.as_ref(),
+                    (t, _) => t,
+                };
+                ensure!(
+                    matches!(
+                        scalar,
+                        CidlType::String
+                            | CidlType::Int
+                            | CidlType::Real
+                            | CidlType::Boolean
+                            | CidlType::DateIso
+                            | CidlType::Enum { .. }
+                    ),
+                    sink,
+                    SemanticError::ApiInvalidParamSource {
+                        method: &method.symbol,
+                        param,
+                    }
+                );
+            }
             match resolved_type.root_type() {
                 CidlType::Object { .. } | CidlType::Partial { .. } => {
                     // GET requests do not support Object parameters
@@ -304,21 +530,21 @@ pub mod expansion {
     pub fn expand(idl: &mut CloesceIdl) {
         for model in idl.models.values_mut() {
             for api in &mut model.apis {
-                let Some(ds_name) = api.data_source else {
-                    continue;
-                };
-
-                let Some(get_target) = model
-                    .data_sources
-                    .get(ds_name)
-                    .and_then(|ds| ds.get.durable_target.as_ref())
+                let Some(ds) = api
+                    .data_source
+                    .and_then(|name| model.data_sources.get(name))
                 else {
                     continue;
                 };
 
+                // The source's guards run before the method's own
+                api.guards = merge_guards(&ds.guards, &api.guards);
+
                 // An instantiated method runs inside the Durable Object its data
                 // source's `get` resolves, so it inherits that `get`'s durable target.
-                api.durable_target = Some(get_target.clone());
+                if let Some(get_target) = &ds.get.durable_target {
+                    api.durable_target = Some(get_target.clone());
+                }
             }
 
             let mut crud_methods = vec![];
@@ -330,14 +556,27 @@ pub mod expansion {
         }
     }
 
+    /// `first` followed by each guard of `then` not already in it.
+    fn merge_guards<'src>(first: &[&'src str], then: &[&'src str]) -> Vec<&'src str> {
+        let mut guards = first.to_vec();
+        for guard in then {
+            if !guards.contains(guard) {
+                guards.push(guard);
+            }
+        }
+        guards
+    }
+
     /// Returns a list of API methods for the given [CrudKind].
     ///
-    /// Each CRUD verb produces one route per DS (e.g. `$get_WithKv`, `$save_Foo`, etc).
+    /// Each CRUD verb produces one route per DS (e.g. `$get_WithKv`, `$save_Foo`, `$delete`,
+    /// `$count_Recent`, etc).
     /// The route is named by combining the verb with the DS name, except in the case
     /// of the `Default` DS, which omits the suffix (e.g. `$get` instead of `$get_Default`).
     ///
     /// All generated methods inherit the [ApiMethod::durable_target] of their associated
-    /// data source method.
+    /// data source method, and run the data source's guards followed by the model's
+    /// [Model::crud_guards].
     fn generate_crud_methods<'src>(crud: &CrudKind, model: &Model<'src>) -> Vec<ApiMethod<'src>> {
         let sources = model.data_sources.values().filter(|ds| !ds.is_internal);
         let format_name = |ds: &DataSource<'src>| {
@@ -345,6 +584,8 @@ pub mod expansion {
                 CrudKind::Get => "get",
                 CrudKind::List => "list",
                 CrudKind::Save => "save",
+                CrudKind::Delete => "delete",
+                CrudKind::Count => "count",
             };
             if ds.name == "Default" {
                 format!("${verb}").into()
@@ -360,12 +601,14 @@ pub mod expansion {
                     is_static: true,
                     data_source: None,
                     http_verb: HttpVerb::Get,
+                    route: None,
                     return_type: CidlType::Object { name: model.name },
                     return_media: MediaType::Json,
                     parameters_media: MediaType::Json,
                     parameters: body_params(ds.get.parameters.iter().map(|p| p.parameter.clone())),
                     injected: ds.get.injected.clone(),
                     durable_target: ds.get.durable_target.clone(),
+                    guards: merge_guards(&ds.guards, &model.crud_guards),
                 })
                 .collect(),
             CrudKind::List => sources
@@ -374,12 +617,14 @@ pub mod expansion {
                     is_static: true,
                     data_source: None,
                     http_verb: HttpVerb::Get,
-                    return_type: CidlType::array(CidlType::Object { name: model.name }),
+                    route: None,
+                    return_type: CidlType::page(CidlType::Object { name: model.name }),
                     return_media: MediaType::Json,
                     parameters_media: MediaType::Json,
                     parameters: body_params(ds.list.parameters.iter().cloned()),
                     injected: ds.list.injected.clone(),
                     durable_target: ds.list.durable_target.clone(),
+                    guards: merge_guards(&ds.guards, &model.crud_guards),
                 })
                 .collect(),
             CrudKind::Save => sources
@@ -388,12 +633,50 @@ pub mod expansion {
                     is_static: true,
                     data_source: None,
                     http_verb: HttpVerb::Post,
+                    route: None,
                     return_type: CidlType::Object { name: model.name },
                     return_media: MediaType::Json,
                     parameters_media: MediaType::Json,
                     parameters: body_params(ds.save.parameters.iter().cloned()),
                     injected: ds.save.injected.clone(),
                     durable_target: ds.save.durable_target.clone(),
+                    guards: merge_guards(&ds.guards, &model.crud_guards),
+                })
+                .collect(),
+            // A delete first resolves the instance through the source's `get`, so it
+            // takes the same parameters and runs against the same target.
+            CrudKind::Delete => sources
+                .map(|ds| ApiMethod {
+                    name: format_name(ds),
+                    is_static: true,
+                    data_source: None,
+                    http_verb: HttpVerb::Delete,
+                    route: None,
+                    return_type: CidlType::Void,
+                    return_media: MediaType::Json,
+                    parameters_media: MediaType::Json,
+                    parameters: body_params(ds.get.parameters.iter().map(|p| p.parameter.clone())),
+                    injected: ds.get.injected.clone(),
+                    durable_target: ds.get.durable_target.clone(),
+                    guards: merge_guards(&ds.guards, &model.crud_guards),
+                })
+                .collect(),
+            // A count shares its source's generated `list` filters; analysis rejects
+            // `[crud count]` on a source with a stub `list`.
+            CrudKind::Count => sources
+                .map(|ds| ApiMethod {
+                    name: format_name(ds),
+                    is_static: true,
+                    data_source: None,
+                    http_verb: HttpVerb::Get,
+                    route: None,
+                    return_type: CidlType::Int,
+                    return_media: MediaType::Json,
+                    parameters_media: MediaType::Json,
+                    parameters: body_params(ds.count.parameters.iter().cloned()),
+                    injected: ds.count.injected.clone(),
+                    durable_target: ds.count.durable_target.clone(),
+                    guards: merge_guards(&ds.guards, &model.crud_guards),
                 })
                 .collect(),
         }
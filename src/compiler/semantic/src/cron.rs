@@ -0,0 +1,292 @@
+use std::ops::Range;
+
+use frontend::{InjectEntry, Span, SpdSlice};
+use idl::CronTrigger;
+
+use crate::{
+    SymbolTable,
+    err::{ErrorSink, SemanticError},
+    resolve_inject,
+};
+
+/// Builds a [CronTrigger] for every `cron` block, validating its expression and
+/// resolving its injected bindings.
+pub fn analyze<'src, 'p>(
+    table: &SymbolTable<'src, 'p>,
+    sink: &mut ErrorSink<'src, 'p>,
+) -> Vec<CronTrigger<'src>> {
+    let mut crons: Vec<CronTrigger<'src>> = Vec::new();
+    let mut seen: Vec<(&'src str, Span)> = Vec::new();
+
+    for block in &table.crons {
+        let expression = &block.expression;
+
+        // The string literal's content starts just past its opening quote.
+        let content_start = expression.span.start + 1;
+        if let Err(CronError { range, reason }) = validate_cron(expression.inner) {
+            sink.push(SemanticError::CronInvalidExpression {
+                span: Span {
+                    start: content_start + range.start,
+                    end: content_start + range.end,
+                    context: expression.span.context,
+                },
+                reason,
+            });
+            continue;
+        }
+
+        if let Some((_, first)) = seen.iter().find(|(e, _)| *e == expression.inner) {
+            sink.push(SemanticError::CronDuplicateExpression {
+                first: *first,
+                second: expression.span,
+            });
+            continue;
+        }
+        seen.push((expression.inner, expression.span));
+
+        let mut has_context = false;
+        for entry in block.injects.iter().flat_map(|b| b.inner.entries.inners()) {
+            if let InjectEntry::Context { symbol, .. } = entry {
+                sink.push(SemanticError::CronDurableContext { context: symbol });
+                has_context = true;
+            }
+        }
+        if has_context {
+            continue;
+        }
+
+        let (injected, _) = resolve_inject(&block.injects, &mut [], table, sink);
+        crons.push(CronTrigger {
+            expression: expression.inner,
+            injected,
+        });
+    }
+
+    crons
+}
+
+struct CronError {
+    /// Byte range of the offending text within the expression.
+    range: Range<usize>,
+    reason: String,
+}
+
+struct CronField {
+    name: &'static str,
+    min: u32,
+    max: u32,
+
+    /// Case-insensitive names for the values `min..`, e.g. `JAN` for month 1.
+    names: &'static [&'static str],
+}
+
+/// The five fields of a cron expression, as Cloudflare Cron Triggers accept them.
+const FIELDS: [CronField; 5] = [
+    CronField {
+        name: "minute",
+        min: 0,
+        max: 59,
+        names: &[],
+    },
+    CronField {
+        name: "hour",
+        min: 0,
+        max: 23,
+        names: &[],
+    },
+    CronField {
+        name: "day-of-month",
+        min: 1,
+        max: 31,
+        names: &[],
+    },
+    CronField {
+        name: "month",
+        min: 1,
+        max: 12,
+        names: &[
+            "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+        ],
+    },
+    CronField {
+        name: "day-of-week",
+        min: 1,
+        max: 7,
+        names: &["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"],
+    },
+];
+const DAY_OF_MONTH: usize = 2;
+const DAY_OF_WEEK: usize = 4;
+
+/// Validates a five field cron expression (`minute hour day-of-month month day-of-week`).
+///
+/// Each field is a comma separated list of `*`, `n`, `n-m`, optionally stepped with `/k`.
+/// Day-of-month additionally accepts `L`, `LW` and `nW`; day-of-week accepts `nL` and `n#k`.
+fn validate_cron(expr: &str) -> Result<(), CronError> {
+    let fields = split_offsets(expr, char::is_whitespace);
+    if fields.len() != FIELDS.len() {
+        let range = match fields.get(FIELDS.len()) {
+            Some((start, _)) => *start..expr.trim_end().len(),
+            None => 0..expr.len(),
+        };
+        return Err(CronError {
+            range,
+            reason: format!(
+                "expected 5 fields (minute hour day-of-month month day-of-week), found {}",
+                fields.len()
+            ),
+        });
+    }
+
+    for (idx, (offset, text)) in fields.into_iter().enumerate() {
+        if text.starts_with(',') || text.ends_with(',') || text.contains(",,") {
+            return Err(CronError {
+                range: offset..offset + text.len(),
+                reason: format!("empty list entry in the {} field", FIELDS[idx].name),
+            });
+        }
+
+        for (item_offset, item) in split_offsets(text, |c| c == ',') {
+            validate_item(idx, item).map_err(|e| CronError {
+                range: offset + item_offset + e.range.start..offset + item_offset + e.range.end,
+                reason: e.reason,
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Validates one list entry of the field at `idx`. Ranges are relative to `item`.
+fn validate_item(idx: usize, item: &str) -> Result<(), CronError> {
+    let field = &FIELDS[idx];
+    let err = |range: Range<usize>, reason: String| Err(CronError { range, reason });
+
+    // `L`, `LW`, `nW` (day-of-month) and `nL`, `n#k` (day-of-week)
+    let upper = item.to_ascii_uppercase();
+    if idx == DAY_OF_MONTH {
+        if upper == "L" || upper == "LW" {
+            return Ok(());
+        }
+        if let Some(day) = upper.strip_suffix('W') {
+            return parse_value(field, day, 0).map(|_| ());
+        }
+    }
+    if idx == DAY_OF_WEEK {
+        if let Some(day) = upper.strip_suffix('L') {
+            return parse_value(field, day, 0).map(|_| ());
+        }
+        if let Some((day, nth)) = upper.split_once('#') {
+            parse_value(field, day, 0)?;
+            let nth_start = day.len() + 1;
+            return match nth.parse::<u32>() {
+                Ok(1..=5) => Ok(()),
+                _ => err(
+                    nth_start..item.len(),
+                    format!("`{nth}` is not a valid occurrence, expected 1-5"),
+                ),
+            };
+        }
+    }
+    if upper.contains(['L', 'W', '#']) && field.names.is_empty() {
+        return err(
+            0..item.len(),
+            format!("`{item}` is not supported in the {} field", field.name),
+        );
+    }
+
+    let (base, step) = match item.split_once('/') {
+        Some((base, step)) => (base, Some(step)),
+        None => (item, None),
+    };
+
+    if let Some(step) = step {
+        let step_start = base.len() + 1;
+        match step.parse::<u32>() {
+            Ok(k) if (1..=field.max - field.min + 1).contains(&k) => {}
+            _ => {
+                return err(
+                    step_start..item.len(),
+                    format!(
+                        "`{step}` is not a valid step for the {} field, expected 1-{}",
+                        field.name,
+                        field.max - field.min + 1
+                    ),
+                );
+            }
+        }
+    }
+
+    if base == "*" {
+        return Ok(());
+    }
+
+    match base.split_once('-') {
+        Some((lo, hi)) => {
+            let lo_value = parse_value(field, lo, 0)?;
+            let hi_value = parse_value(field, hi, lo.len() + 1)?;
+            if lo_value > hi_value {
+                return err(
+                    0..base.len(),
+                    format!("range start `{lo}` is after its end `{hi}`"),
+                );
+            }
+            Ok(())
+        }
+        None => parse_value(field, base, 0).map(|_| ()),
+    }
+}
+
+/// Parses a single number or name of `field`, where `offset` locates `text` within its item.
+fn parse_value(field: &CronField, text: &str, offset: usize) -> Result<u32, CronError> {
+    let range = offset..offset + text.len();
+    if text.is_empty() {
+        return Err(CronError {
+            range,
+            reason: format!("expected a value in the {} field", field.name),
+        });
+    }
+
+    if let Some(pos) = field
+        .names
+        .iter()
+        .position(|n| n.eq_ignore_ascii_case(text))
+    {
+        return Ok(field.min + pos as u32);
+    }
+
+    match text.parse::<u32>() {
+        Ok(v) if (field.min..=field.max).contains(&v) => Ok(v),
+        Ok(_) => Err(CronError {
+            range,
+            reason: format!(
+                "`{text}` is out of range for the {} field ({}-{})",
+                field.name, field.min, field.max
+            ),
+        }),
+        Err(_) => Err(CronError {
+            range,
+            reason: format!("`{text}` is not a valid {} value", field.name),
+        }),
+    }
+}
+
+/// Splits `s` on `sep`, returning each non-empty part alongside its byte offset.
+fn split_offsets(s: &str, sep: impl Fn(char) -> bool) -> Vec<(usize, &str)> {
+    let mut out = Vec::new();
+    let mut start = None;
+    for (i, c) in s.char_indices() {
+        match (sep(c), start) {
+            (false, None) => start = Some(i),
+            (true, Some(st)) => {
+                out.push((st, &s[st..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(st) = start {
+        out.push((st, &s[st..]));
+    }
+    out
+}
@@ -4,18 +4,16 @@ use idl::{IncludeTree, Model, NavigationCardinality};
 use indexmap::IndexMap;
 
 pub mod analysis {
-    use std::ops::Not;
-
-    use frontend::{ParsedIncludeTree, Spd, Symbol, Tag};
+    use frontend::{DataSourceBlockMethod, ListClause, ParsedIncludeTree, Spd, Symbol, Tag};
     use idl::{
-        CidlType, DataSource, DataSourceGetMethod, DataSourceGetMethodParam, DataSourceMethod,
-        ValidatedField, Validator,
+        CidlType, CrudKind, DataSource, DataSourceGetMethod, DataSourceGetMethodParam,
+        DataSourceMethod, ListFilter, ListOrder, ListQuery, SortDirection, ValidatedField,
     };
 
     use crate::{
         SymbolTable,
         err::{ErrorSink, SemanticError},
-        is_valid_sql_type, resolve_inject, resolve_validator_tags,
+        guard, is_valid_sql_type, resolve_enum_type, resolve_inject, resolve_validator_tags,
     };
 
     use super::{IncludeTree, IndexMap, Model, include_dfs};
@@ -36,24 +34,29 @@ pub mod analysis {
         for ds in &table.data_sources {
             // Validate tags
             let mut is_internal = false;
+            let mut guards = Vec::new();
             for tag in &ds.symbol.tags {
-                if matches!(&tag.inner, Tag::Internal).not() {
-                    sink.push(SemanticError::TagInvalidInContext {
+                match &tag.inner {
+                    Tag::Internal => is_internal = true,
+                    Tag::Guard { guard } => guard::resolve(guard, table, sink, &mut guards),
+                    _ => sink.push(SemanticError::TagInvalidInContext {
                         tag,
                         symbol: &ds.symbol,
-                    });
-                    continue;
+                    }),
                 }
-                is_internal = true;
             }
 
             // Validate the model reference
-            let Some(model_sym) = table.models.get(ds.model.name).map(|m| &m.symbol) else {
-                sink.push(SemanticError::DataSourceUnknownModelReference { source: &ds.symbol });
+            let model_name = table.resolve(&ds.model);
+            let Some(model_sym) = table.models.get(model_name).map(|m| &m.symbol) else {
+                sink.push(SemanticError::DataSourceUnknownModelReference {
+                    source: &ds.symbol,
+                    model: &ds.model,
+                });
                 continue;
             };
 
-            let Some(model) = models.get(model_sym.name) else {
+            let Some(model) = models.get(model_name) else {
                 // Model must be invalid for some reason, skip.
                 continue;
             };
@@ -106,28 +109,39 @@ pub mod analysis {
                 }
             }
 
+            // A `list` holding clauses is generated (shaped by the clauses), not a stub.
+            let list_query = ds
+                .list
+                .as_ref()
+                .filter(|method| !method.inner.clauses.is_empty())
+                .map(|method| analyze_list_query(method, model, &ds.symbol, sink))
+                .unwrap_or_default();
+
+            // `$count` shares a generated `list`'s filters, so a stub `list` has nothing to count
+            let is_stub_list = ds.list.as_ref().is_some_and(|m| m.inner.clauses.is_empty());
+            if is_stub_list && !is_internal && model.cruds.contains(&CrudKind::Count) {
+                sink.push(SemanticError::DataSourceCountWithStubList { source: &ds.symbol });
+            }
+
             // For each verb: if the user declared a stub, validate and capture it.
             // Otherwise a default-valued method is left in place for the expansion pass to fill.
             let list = ds
                 .list
                 .as_ref()
+                .filter(|method| method.inner.clauses.is_empty())
                 .map(|method| {
                     let mut parameters = method
                         .inner
                         .parameters
                         .iter()
                         .map(|p| {
-                            let (validators, instance_tag) =
-                                validate_ds_param(p, &ds.symbol, DsMethodKind::Scalar, sink);
+                            let (field, instance_tag) =
+                                validate_ds_param(p, &ds.symbol, DsMethodKind::Scalar, table, sink);
                             if let Some(tag) = instance_tag {
                                 // Not allowed on list methods
                                 sink.push(SemanticError::TagInvalidInContext { tag, symbol: p });
                             }
-                            ValidatedField {
-                                name: p.name.into(),
-                                cidl_type: p.cidl_type.clone(),
-                                validators,
-                            }
+                            field
                         })
                         .collect::<Vec<_>>();
 
@@ -152,8 +166,8 @@ pub mod analysis {
                         .parameters
                         .iter()
                         .map(|p| {
-                            let (validators, instance_tag) =
-                                validate_ds_param(p, &ds.symbol, DsMethodKind::Scalar, sink);
+                            let (field, instance_tag) =
+                                validate_ds_param(p, &ds.symbol, DsMethodKind::Scalar, table, sink);
 
                             if let Some(tag) = instance_tag {
                                 let is_field = model.columns.iter().any(|c| c.field.name == p.name)
@@ -170,14 +184,7 @@ pub mod analysis {
                                 }
                             }
 
-                            (
-                                ValidatedField {
-                                    name: p.name.into(),
-                                    cidl_type: p.cidl_type.clone(),
-                                    validators,
-                                },
-                                instance_tag.is_some(),
-                            )
+                            (field, instance_tag.is_some())
                         })
                         .unzip();
 
@@ -211,17 +218,13 @@ pub mod analysis {
                         .parameters
                         .iter()
                         .map(|p| {
-                            let (validators, instance_tag) =
-                                validate_ds_param(p, &ds.symbol, DsMethodKind::Body, sink);
+                            let (field, instance_tag) =
+                                validate_ds_param(p, &ds.symbol, DsMethodKind::Body, table, sink);
                             if let Some(tag) = instance_tag {
                                 // Not allowed on save methods
                                 sink.push(SemanticError::TagInvalidInContext { tag, symbol: p });
                             }
-                            ValidatedField {
-                                name: p.name.into(),
-                                cidl_type: p.cidl_type.clone(),
-                                validators,
-                            }
+                            field
                         })
                         .collect::<Vec<_>>();
 
@@ -238,25 +241,28 @@ pub mod analysis {
                 .unwrap_or_default();
 
             res.push((
-                model_sym.name,
+                model_name,
                 DataSource {
                     name: ds.symbol.name,
                     tree: match &ds.tree {
                         Some(tree) => parsed_include_tree_to_idl(tree),
-                        None => include_dfs(
-                            models,
-                            model_sym.name,
-                            &mut std::collections::HashSet::new(),
-                        ),
+                        None => {
+                            include_dfs(models, model_name, &mut std::collections::HashSet::new())
+                        }
                     },
                     list,
+                    list_query,
                     get,
                     save,
+                    count: DataSourceMethod::default(),
                     is_internal,
+                    guards,
                     get_plan: None,
                     list_plan: None,
+                    count_plan: None,
                     get_explain: String::new(),
                     list_explain: String::new(),
+                    count_explain: String::new(),
                 },
             ));
         }
@@ -264,16 +270,110 @@ pub mod analysis {
         res
     }
 
+    /// Validates the `orderBy` / `where` clauses of a generated `list` against `model`.
+    fn analyze_list_query<'src, 'p>(
+        method: &'p Spd<DataSourceBlockMethod<'src>>,
+        model: &Model<'src>,
+        source_sym: &'p Symbol<'src>,
+        sink: &mut ErrorSink<'src, 'p>,
+    ) -> ListQuery<'src> {
+        if !method.inner.parameters.is_empty() || !method.inner.injects.is_empty() {
+            sink.push(SemanticError::DataSourceListClauseOnStub {
+                source: source_sym,
+                method: &method.inner.method,
+            });
+        }
+
+        let find_column = |name: &str| {
+            model
+                .primary_columns
+                .iter()
+                .chain(&model.columns)
+                .find(|c| c.field.name == name)
+        };
+
+        let mut query = ListQuery::default();
+        let mut ordered: Vec<&'p Symbol<'src>> = Vec::new();
+        let mut params: Vec<&'p Symbol<'src>> = Vec::new();
+        for clause in &method.inner.clauses {
+            match &clause.inner {
+                ListClause::OrderBy { terms } => {
+                    for (column, direction) in terms {
+                        let Some(col) = find_column(column.name) else {
+                            sink.push(SemanticError::DataSourceListUnknownColumn {
+                                source: source_sym,
+                                column,
+                            });
+                            continue;
+                        };
+                        if col.field.cidl_type.is_nullable() {
+                            sink.push(SemanticError::DataSourceListNullableOrder {
+                                source: source_sym,
+                                column,
+                            });
+                            continue;
+                        }
+                        if let Some(first) = ordered.iter().find(|o| o.name == column.name) {
+                            sink.push(SemanticError::DuplicateSymbol {
+                                first,
+                                second: column,
+                            });
+                            continue;
+                        }
+
+                        ordered.push(column);
+                        query.order_by.push(ListOrder {
+                            column: column.name,
+                            direction: direction.unwrap_or(SortDirection::Asc),
+                        });
+                    }
+                }
+                ListClause::Where { column, param } => {
+                    if find_column(column.name).is_none() {
+                        sink.push(SemanticError::DataSourceListUnknownColumn {
+                            source: source_sym,
+                            column,
+                        });
+                        continue;
+                    }
+
+                    // Filter params share the generated list's signature with the shard
+                    // fields, `cursor` and `limit`.
+                    let taken = param.name == "limit"
+                        || param.name == "cursor"
+                        || model.route_fields.iter().any(|f| f.name == param.name)
+                        || params.iter().any(|p| p.name == param.name);
+                    if taken {
+                        sink.push(SemanticError::DataSourceListDuplicateParam {
+                            source: source_sym,
+                            param,
+                        });
+                        continue;
+                    }
+
+                    params.push(param);
+                    query.filters.push(ListFilter {
+                        column: column.name,
+                        param: param.name,
+                    });
+                }
+            }
+        }
+
+        query
+    }
+
     /// Validates that a parameter has a sensible type for its method kind and only
     /// carries tags valid in a data source method parameter.
     ///
-    /// Returns the resolved validators and the `instance` tag if present.
+    /// Returns the resolved field and the `instance` tag if present.
     fn validate_ds_param<'src, 'p>(
         param: &'p Symbol<'src>,
         source_sym: &'p Symbol<'src>,
         kind: DsMethodKind,
+        table: &SymbolTable<'src, 'p>,
         sink: &mut ErrorSink<'src, 'p>,
-    ) -> (Vec<Validator<'src>>, Option<&'p Spd<Tag<'src>>>) {
+    ) -> (ValidatedField<'src>, Option<&'p Spd<Tag<'src>>>) {
         let mut instance_tag = None;
 
         for tag in param.tags.iter() {
@@ -284,9 +384,10 @@ pub mod analysis {
             }
         }
 
+        let cidl_type = resolve_enum_type(param, table);
         let valid_type = match kind {
-            DsMethodKind::Scalar => is_valid_sql_type(&param.cidl_type),
-            DsMethodKind::Body => !matches!(param.cidl_type.root_type(), CidlType::Stream),
+            DsMethodKind::Scalar => is_valid_sql_type(&cidl_type),
+            DsMethodKind::Body => !matches!(cidl_type.root_type(), CidlType::Stream),
         };
         if !valid_type {
             sink.push(SemanticError::DataSourceInvalidMethodParam {
@@ -295,13 +396,20 @@ pub mod analysis {
             });
         }
 
-        match resolve_validator_tags(param) {
+        let (validators, instance_tag) = match resolve_validator_tags(param) {
             Ok(v) => (v, instance_tag),
             Err(errs) => {
                 sink.extend(errs);
                 (vec![], None)
             }
-        }
+        };
+
+        let field = ValidatedField {
+            name: param.name.into(),
+            cidl_type,
+            validators,
+        };
+        (field, instance_tag)
     }
 
     fn parsed_include_tree_to_idl<'src>(tree: &ParsedIncludeTree<'src>) -> IncludeTree<'src> {
@@ -317,12 +425,12 @@ pub mod analysis {
 pub mod expansion {
     use idl::{
         BackingKind, CidlType, CloesceIdl, DataSource, DataSourceGetMethod,
-        DataSourceGetMethodParam, DataSourceMethod, DurableTarget, ModelBacking, Number,
-        ValidatedField, Validator, model_bindings,
+        DataSourceGetMethodParam, DataSourceMethod, DurableTarget, IncludeTree, ListQuery,
+        ModelBacking, Number, ValidatedField, Validator, model_bindings,
     };
 
     use orm::query::explain::explain_select;
-    use orm::query::select::planner::{SelectOperation, plan};
+    use orm::query::select::planner::{SelectOperation, plan, plan_count, plan_list};
 
     use super::{HashSet, Model, include_dfs};
 
@@ -331,9 +439,10 @@ pub mod expansion {
         get: Option<DataSourceGetMethod<'src>>,
         list: Option<DataSourceMethod<'src>>,
         save: Option<DataSourceMethod<'src>>,
+        count: Option<DataSourceMethod<'src>>,
     }
 
-    /// Precompiled `get`/`list` [orm] select plans for a data source, and their
+    /// Precompiled `get`/`list`/`count` [orm] select plans for a data source, and their
     /// rendered `EXPLAIN`-style text.
     ///
     /// The plans borrow from the IDL, so they cannot be stored directly
@@ -342,8 +451,10 @@ pub mod expansion {
     struct PrecompiledPlans {
         get_plan: serde_json::Value,
         list_plan: serde_json::Value,
+        count_plan: serde_json::Value,
         get_explain: String,
         list_explain: String,
+        count_explain: String,
     }
 
     fn precompile<'src>(
@@ -352,13 +463,21 @@ pub mod expansion {
         ds: &DataSource<'src>,
     ) -> PrecompiledPlans {
         let get = plan(SelectOperation::Get, model.name, idl, &ds.tree);
-        let list = plan(SelectOperation::List, model.name, idl, &ds.tree);
+        let list = plan_list(model.name, idl, &ds.tree, &ds.list_query);
+        let count = plan_count(model.name, idl, &ds.list_query);
 
         PrecompiledPlans {
             get_explain: explain_select(SelectOperation::Get, model.name, &ds.tree, &get),
             list_explain: explain_select(SelectOperation::List, model.name, &ds.tree, &list),
+            count_explain: explain_select(
+                SelectOperation::Count,
+                model.name,
+                &IncludeTree::default(),
+                &count,
+            ),
             get_plan: serde_json::to_value(&get).expect("SelectPlan serializes"),
             list_plan: serde_json::to_value(&list).expect("SelectPlan serializes"),
+            count_plan: serde_json::to_value(&count).expect("SelectPlan serializes"),
         }
     }
 
@@ -382,13 +501,18 @@ pub mod expansion {
                     name: "Default",
                     tree,
                     list: DataSourceMethod::default(),
+                    list_query: ListQuery::default(),
                     get: DataSourceGetMethod::default(),
                     save: DataSourceMethod::default(),
+                    count: DataSourceMethod::default(),
                     is_internal: false,
+                    guards: Vec::new(),
                     get_plan: None,
                     list_plan: None,
+                    count_plan: None,
                     get_explain: String::new(),
                     list_explain: String::new(),
+                    count_explain: String::new(),
                 },
             );
         }
@@ -427,11 +551,16 @@ pub mod expansion {
             if let Some(save) = generated.save.filter(|_| !ds.save.is_stub) {
                 ds.save = save;
             }
+            if let Some(count) = generated.count {
+                ds.count = count;
+            }
 
             ds.get_plan = Some(plans.get_plan);
             ds.list_plan = Some(plans.list_plan);
+            ds.count_plan = Some(plans.count_plan);
             ds.get_explain = plans.get_explain;
             ds.list_explain = plans.list_explain;
+            ds.count_explain = plans.count_explain;
         }
     }
 
@@ -477,15 +606,32 @@ pub mod expansion {
             validators: vec![],
         };
 
-        // Seek-pagination params: `lastSeen_<pk>...`, `limit`.
-        let list_params = || {
-            model
-                .primary_columns
-                .iter()
-                .map(|pk| ValidatedField {
-                    name: format!("lastSeen_{}", pk.field.name).into(),
-                    ..pk.field.clone()
+        // One param per `where` filter, typed like its column.
+        let filter_params = || {
+            ds.list_query.filters.iter().filter_map(|f| {
+                let column = model
+                    .primary_columns
+                    .iter()
+                    .chain(&model.columns)
+                    .find(|c| c.field.name == f.column)?;
+                Some(ValidatedField {
+                    name: f.param.into(),
+                    ..column.field.clone()
                 })
+            })
+        };
+
+        // Filter params, then the pagination params: an opaque `cursor` (absent for the
+        // first page) and `limit`.
+        let list_params = || {
+            let cursor = ValidatedField {
+                name: "cursor".into(),
+                cidl_type: CidlType::nullable(CidlType::String),
+                validators: vec![],
+            };
+
+            filter_params()
+                .chain(std::iter::once(cursor))
                 .chain(std::iter::once(ValidatedField {
                     name: "limit".into(),
                     cidl_type: CidlType::Int,
@@ -527,6 +673,17 @@ pub mod expansion {
                         .collect(),
                     injected,
                     is_stub: false,
+                    durable_target: durable_target.clone(),
+                }),
+                // A count reads no relations, so it needs only the model's own backing.
+                count: Some(DataSourceMethod {
+                    parameters: shard_fields
+                        .iter()
+                        .cloned()
+                        .chain(filter_params())
+                        .collect(),
+                    injected: model_bindings(idl, model, Some(&IncludeTree::default())),
+                    is_stub: false,
                     durable_target,
                 }),
             };
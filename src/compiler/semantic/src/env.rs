@@ -1,9 +1,12 @@
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+};
 
-use frontend::{SpdSlice, Symbol};
+use frontend::{EnvBlockKind, SpdSlice, Symbol};
 use idl::{
-    Binding, BindingTemplate, CidlType, DurableBinding, Field, TemplateSegment, ValidatedField,
-    WranglerEnv,
+    Binding, BindingTemplate, CidlType, DurableBinding, Field, QueueBinding, TemplateSegment,
+    ValidatedField, WranglerEnv,
 };
 
 use crate::{
@@ -13,7 +16,7 @@ use crate::{
 };
 
 /// Builds the [WranglerEnv] from the symbol table, resolving and validating
-/// KV/R2 binding templates and their parameters along the way.
+/// KV/R2 binding templates, their parameters and queue messages along the way.
 pub fn analyze<'src, 'p>(
     table: &SymbolTable<'src, 'p>,
     sink: &mut ErrorSink<'src, 'p>,
@@ -21,9 +24,16 @@ pub fn analyze<'src, 'p>(
     let d1_bindings = table
         .d1_bindings
         .iter()
-        .flat_map(|b| b.bindings.iter().map(|s| s.name))
+        .flat_map(|b| b.bindings.iter().map(|b| b.symbol.name))
         .collect::<Vec<_>>();
 
+    let d1_databases = table
+        .d1_bindings
+        .iter()
+        .flat_map(|b| &b.bindings)
+        .filter_map(|b| Some((b.symbol.name, b.database_name.as_ref()?.inner)))
+        .collect::<BTreeMap<_, _>>();
+
     let vars = table
         .vars_blocks
         .iter()
@@ -34,6 +44,43 @@ pub fn analyze<'src, 'p>(
         })
         .collect::<Vec<_>>();
 
+    let mut secrets = Vec::new();
+    for secret in table.secrets_blocks.iter().flat_map(|b| &b.secrets) {
+        let var = table
+            .vars_blocks
+            .iter()
+            .flat_map(|b| &b.vars)
+            .find(|v| v.name == secret.name);
+        if let Some(var) = var {
+            sink.push(SemanticError::SecretDeclaredAsVar { var, secret });
+            continue;
+        }
+
+        // Secrets stay out of the global namespace until here, so the first declaration
+        // of a name resolves to itself
+        if let Some(first) = table
+            .global(secret.name)
+            .filter(|first| !std::ptr::eq(*first, secret))
+        {
+            sink.push(SemanticError::DuplicateSymbol {
+                first,
+                second: secret,
+            });
+            continue;
+        }
+
+        // Workers hands every secret to the Worker as a string
+        if secret.cidl_type != CidlType::String {
+            sink.push(SemanticError::SecretNotString { secret });
+            continue;
+        }
+
+        secrets.push(Field {
+            name: secret.name.into(),
+            cidl_type: secret.cidl_type.clone(),
+        });
+    }
+
     let mut kv_bindings = Vec::new();
     for block in table.kv_bindings.values() {
         let mut templates = Vec::new();
@@ -151,13 +198,163 @@ pub fn analyze<'src, 'p>(
         });
     }
 
+    let mut queue_bindings = Vec::new();
+    for block in table.queue_bindings.values() {
+        let Some(message) = validate_symbol(&block.message, sink, table) else {
+            continue;
+        };
+
+        if matches!(
+            message.cidl_type.root_type(),
+            CidlType::Stream | CidlType::R2Object | CidlType::Partial { .. }
+        ) {
+            sink.push(SemanticError::QueueInvalidMessageType {
+                queue: &block.symbol,
+                message: &block.message,
+            });
+            continue;
+        }
+
+        queue_bindings.push(QueueBinding {
+            name: block.symbol.name,
+            message,
+        });
+    }
+
     WranglerEnv {
         d1_bindings,
+        d1_databases,
         r2_bindings,
         kv_bindings,
         durable_bindings,
+        queue_bindings,
+        // Resolved separately by `service::analyze`, from CIDL read outside the AST.
+        service_bindings: Vec::new(),
         vars,
+        secrets,
+    }
+}
+
+/// Resolves each `env` block against the top level `wrangler_env`, checking every
+/// environment binds the D1 databases its models are stored in.
+///
+/// As in Wrangler, an environment's `d1` block replaces the top level D1 bindings, while its
+/// vars are added to the top level vars.
+pub fn analyze_envs<'src, 'p>(
+    table: &SymbolTable<'src, 'p>,
+    wrangler_env: &WranglerEnv<'src>,
+    sink: &mut ErrorSink<'src, 'p>,
+) -> BTreeMap<&'src str, WranglerEnv<'src>> {
+    let mut envs = BTreeMap::new();
+    for block in table.envs.values() {
+        let mut names = HashMap::new();
+        let mut insert_name = |sink: &mut ErrorSink<'src, 'p>, symbol: &'p Symbol<'src>| {
+            if let Some(first) = names.insert(symbol.name, symbol) {
+                sink.push(SemanticError::DuplicateSymbol {
+                    first,
+                    second: symbol,
+                });
+                return false;
+            }
+            true
+        };
+
+        let mut d1_bindings = wrangler_env.d1_bindings.clone();
+        let mut d1_databases = wrangler_env.d1_databases.clone();
+        let mut vars = wrangler_env
+            .vars
+            .iter()
+            .map(|v| Field {
+                name: v.name.clone(),
+                cidl_type: v.cidl_type.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        let d1_blocks = block.blocks.inners().filter_map(|b| match b {
+            EnvBlockKind::D1Binding(d1) => Some(d1),
+            EnvBlockKind::Var(_) => None,
+        });
+        for (i, d1) in d1_blocks.enumerate() {
+            if i == 0 {
+                d1_bindings.clear();
+                d1_databases.clear();
+            }
+
+            for binding in &d1.bindings {
+                let name = binding.symbol.name;
+                if !wrangler_env.d1_bindings.contains(&name) {
+                    sink.push(SemanticError::UnresolvedSymbol {
+                        symbol: &binding.symbol,
+                    });
+                    continue;
+                }
+                if !insert_name(sink, &binding.symbol) {
+                    continue;
+                }
+
+                d1_bindings.push(name);
+                if let Some(database_name) = &binding.database_name {
+                    d1_databases.insert(name, database_name.inner);
+                }
+            }
+        }
+
+        let var_blocks = block.blocks.inners().filter_map(|b| match b {
+            EnvBlockKind::Var(v) => Some(v),
+            EnvBlockKind::D1Binding(_) => None,
+        });
+        for var in var_blocks.flat_map(|v| &v.vars) {
+            let secret = table
+                .secrets_blocks
+                .iter()
+                .flat_map(|b| &b.secrets)
+                .find(|s| s.name == var.name);
+            if let Some(secret) = secret {
+                sink.push(SemanticError::SecretDeclaredAsVar { var, secret });
+                continue;
+            }
+            if let Some(first) = table.global(var.name) {
+                sink.push(SemanticError::DuplicateSymbol { first, second: var });
+                continue;
+            }
+            if !insert_name(sink, var) {
+                continue;
+            }
+
+            vars.push(Field {
+                name: var.name.into(),
+                cidl_type: var.cidl_type.clone(),
+            });
+        }
+
+        for model in table.models.values() {
+            let Some(binding) = &model.database_binding else {
+                continue;
+            };
+
+            if wrangler_env.d1_bindings.contains(&binding.name)
+                && !d1_bindings.contains(&binding.name)
+            {
+                sink.push(SemanticError::EnvMissingBinding {
+                    env: &block.symbol,
+                    model: &model.symbol,
+                    binding,
+                });
+            }
+        }
+
+        envs.insert(
+            block.symbol.name,
+            WranglerEnv {
+                d1_bindings,
+                d1_databases,
+                vars,
+                ..Default::default()
+            },
+        );
     }
+
+    envs
 }
 
 /// Validates that every `{var}` referenced in a binding field's key format
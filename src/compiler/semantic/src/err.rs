@@ -1,5 +1,9 @@
 use ariadne::{Color, Config, IndexType, Label, Report, ReportKind};
-use frontend::{FileTable, Span, Spd, Tag, err::DisplayError};
+use frontend::{
+    DefaultValue, FileTable, Span, Spd, Tag,
+    err::{Diagnostic, DisplayError},
+    fmt_cidl_type,
+};
 
 use crate::Symbol;
 
@@ -39,6 +43,13 @@ pub enum SemanticError<'src, 'p> {
         column: &'p Symbol<'src>,
     },
 
+    /// A column's default value is not assignable to the column's type
+    InvalidColumnDefault {
+        column: &'p Symbol<'src>,
+        default: &'p Spd<DefaultValue<'src>>,
+        reason: String,
+    },
+
     /// A primary key column in a D1 model cannot be nullable
     NullablePrimaryKey {
         column: &'p Symbol<'src>,
@@ -78,6 +89,9 @@ pub enum SemanticError<'src, 'p> {
 
     CyclicalRelationship {
         cycle: Vec<&'src str>,
+
+        /// The foreign key fields forming the cycle.
+        fields: Vec<&'p Symbol<'src>>,
     },
 
     /// A model's KV/R2 reference supplies a different number of args than the binding field's params.
@@ -117,9 +131,87 @@ pub enum SemanticError<'src, 'p> {
         field: &'p Symbol<'src>,
     },
 
+    /// A queue message must be serializable, so cannot be a `stream`, `r2object` or `partial`.
+    QueueInvalidMessageType {
+        queue: &'p Symbol<'src>,
+        message: &'p Symbol<'src>,
+    },
+
+    /// A service's CIDL could not be read.
+    ServiceMissingCidl {
+        service: &'p Symbol<'src>,
+        path: Span,
+        reason: String,
+    },
+
+    /// A service's CIDL could not be understood, e.g. it was generated by an incompatible
+    /// version of Cloesce.
+    ServiceIncompatibleCidl {
+        service: &'p Symbol<'src>,
+        path: Span,
+        reason: String,
+    },
+
+    /// A cron expression is malformed. The span covers only the offending part of the expression.
+    CronInvalidExpression {
+        span: Span,
+        reason: String,
+    },
+
+    /// The same cron expression is scheduled by more than one `cron` block.
+    CronDuplicateExpression {
+        first: Span,
+        second: Span,
+    },
+
+    /// A cron handler runs on the Worker, so it cannot run in a Durable Object context.
+    CronDurableContext {
+        context: &'p Symbol<'src>,
+    },
+
+    /// A guard runs before its route is dispatched, so it cannot run in a Durable Object context.
+    GuardDurableContext {
+        context: &'p Symbol<'src>,
+    },
+
+    /// A `[guard X]` tag names a guard that does not exist.
+    GuardUnknownReference {
+        guard: &'p Symbol<'src>,
+    },
+
+    /// An environment's `d1` block leaves out a D1 binding a model is stored in.
+    EnvMissingBinding {
+        env: &'p Symbol<'src>,
+        model: &'p Symbol<'src>,
+        binding: &'p Symbol<'src>,
+    },
+
+    /// A name is declared both as a var, which is committed to the Wrangler config, and a secret.
+    SecretDeclaredAsVar {
+        var: &'p Symbol<'src>,
+        secret: &'p Symbol<'src>,
+    },
+
+    /// A secret declared with a type other than `string`, the only type Workers secrets have.
+    SecretNotString {
+        secret: &'p Symbol<'src>,
+    },
+
+    /// An enum must declare at least one member.
+    EmptyEnum {
+        symbol: &'p Symbol<'src>,
+    },
+
     /// A data source references a model that does not exist or is not a model.
     DataSourceUnknownModelReference {
         source: &'p Symbol<'src>,
+        model: &'p Symbol<'src>,
+    },
+
+    /// A data source with a user-written `list` on a model tagged `[crud count]`, which
+    /// can only count the rows a generated `list` filters.
+    DataSourceCountWithStubList {
+        source: &'p Symbol<'src>,
     },
 
     /// A data source include tree references a name that is not a navigation property, KV, or R2 on the model.
@@ -135,6 +227,31 @@ pub enum SemanticError<'src, 'p> {
         param: &'p Symbol<'src>,
     },
 
+    /// A `list` declares `orderBy` / `where` clauses alongside parameters or injects,
+    /// which would make it a stub the clauses cannot apply to.
+    DataSourceListClauseOnStub {
+        source: &'p Symbol<'src>,
+        method: &'p Symbol<'src>,
+    },
+
+    /// A `list` clause references a name that is not a column of the model.
+    DataSourceListUnknownColumn {
+        source: &'p Symbol<'src>,
+        column: &'p Symbol<'src>,
+    },
+
+    /// A `list` orders by a nullable column, which keyset pagination cannot seek past.
+    DataSourceListNullableOrder {
+        source: &'p Symbol<'src>,
+        column: &'p Symbol<'src>,
+    },
+
+    /// A `where` parameter collides with another parameter of the generated `list`.
+    DataSourceListDuplicateParam {
+        source: &'p Symbol<'src>,
+        param: &'p Symbol<'src>,
+    },
+
     /// An API block references a model that does not exist.
     ApiUnknownNamespaceReference {
         api: &'p Symbol<'src>,
@@ -157,6 +274,44 @@ pub enum SemanticError<'src, 'p> {
         param: &'p Symbol<'src>,
     },
 
+    /// A `[query]` or `[path]` parameter has a type that cannot be read from a URL.
+    ApiInvalidParamSource {
+        method: &'p Symbol<'src>,
+        param: &'p Symbol<'src>,
+    },
+
+    /// An API method's route template is malformed, or the method cannot have one.
+    ApiInvalidRoute {
+        method: &'p Symbol<'src>,
+        reason: String,
+    },
+
+    /// A route template references a `{variable}` that is not a `[path]` parameter.
+    ApiRouteUnknownParam {
+        method: &'p Symbol<'src>,
+        variable: &'src str,
+    },
+
+    /// A `[path]` parameter does not appear in its method's route template.
+    ApiPathParamNotInRoute {
+        method: &'p Symbol<'src>,
+        param: &'p Symbol<'src>,
+    },
+
+    /// Two API methods share an HTTP verb and have route templates that some
+    /// request path matches both of, e.g. `users/{id}` and `users/me`.
+    ApiOverlappingRoute {
+        first: &'p Symbol<'src>,
+        second: &'p Symbol<'src>,
+    },
+
+    /// A custom route matches a route derived for another method, e.g. `Model/method`
+    /// or a CRUD route, and would be tried first.
+    ApiRouteShadowsDerived {
+        method: &'p Symbol<'src>,
+        derived: String,
+    },
+
     ApiInjectsDurableWhenSourceInjectsDurable {
         method: &'p Symbol<'src>,
     },
@@ -201,6 +356,13 @@ pub enum SemanticError<'src, 'p> {
         tag: &'p Spd<Tag<'src>>,
         symbol: &'p Symbol<'src>,
     },
+
+    /// A `[crud]` tag under the `rust` target, whose generated backend only routes
+    /// user-written API methods.
+    CrudUnsupportedByRustTarget {
+        tag: &'p Spd<Tag<'src>>,
+        model: &'p Symbol<'src>,
+    },
 }
 
 /// A sink for accumulating semantic errors during analysis,
@@ -249,7 +411,28 @@ macro_rules! ensure {
 impl DisplayError for SemanticError<'_, '_> {
     fn display_error(&self, file_table: &FileTable) {
         let mut cache = file_table.cache();
-        display(self, file_table, &mut cache);
+        let (_, report) = report(self, file_table);
+        report.write(&mut cache, std::io::stderr()).ok();
+    }
+
+    fn diagnostics(&self, file_table: &FileTable) -> Vec<Diagnostic> {
+        // A cycle is reported on every field forming it
+        if let SemanticError::CyclicalRelationship { cycle, fields } = self {
+            return fields
+                .iter()
+                .map(|field| {
+                    let (path, range) = span_parts(&field.span, file_table);
+                    Diagnostic {
+                        path,
+                        range,
+                        message: cyclical_relationship_message(cycle),
+                    }
+                })
+                .collect();
+        }
+
+        let (diagnostic, _) = report(self, file_table);
+        vec![diagnostic]
     }
 }
 
@@ -258,6 +441,13 @@ fn span_parts(span: &Span, file_table: &FileTable) -> (String, std::ops::Range<u
     (path.display().to_string(), span.start..span.end)
 }
 
+fn cyclical_relationship_message(cycle: &[&str]) -> String {
+    format!(
+        "cyclical relationship detected among: {}",
+        cycle.join(" -> ")
+    )
+}
+
 /// Returns the textual name of a validator tag, e.g. "gt" / "len" / "regex".
 fn validator_name(tag: &Tag<'_>) -> &'static str {
     match tag {
@@ -266,25 +456,41 @@ fn validator_name(tag: &Tag<'_>) -> &'static str {
     }
 }
 
-/// Displays a [SemanticError] to stderr using [ariadne]
-fn display(
+/// Builds the [ariadne] report for a [SemanticError], along with the [Diagnostic]
+/// for its primary label.
+fn report<'a>(
     error: &SemanticError<'_, '_>,
     file_table: &FileTable,
-    cache: &mut impl ariadne::Cache<String>,
-) {
+) -> (Diagnostic, Report<'a, (String, std::ops::Range<usize>)>) {
+    let diagnostic;
     macro_rules! report {
-        ($path:expr, $range:expr) => {
-            Report::build(ReportKind::Error, ($path, $range))
+        ($path:expr, $range:expr, $message:expr) => {{
+            let (path, range, message): (String, std::ops::Range<usize>, String) =
+                ($path, $range, $message.into());
+            diagnostic = Diagnostic {
+                path: path.clone(),
+                range: range.clone(),
+                message: message.clone(),
+            };
+            Report::build(ReportKind::Error, (path, range))
                 .with_config(Config::new().with_index_type(IndexType::Byte))
-        };
+                .with_message(message)
+        }};
     }
 
     let report = match error {
         SemanticError::DuplicateSymbol { first, second } => {
             let (first_path, first_range) = span_parts(&first.span, file_table);
             let (second_path, second_range) = span_parts(&second.span, file_table);
-            report!(second_path.clone(), second_range.clone())
-                .with_message(format!("'{}' is defined more than once", second.name))
+            let message = if first_path == second_path {
+                format!("'{}' is defined more than once", second.name)
+            } else {
+                format!(
+                    "'{}' is defined in both {} and {}",
+                    second.name, first_path, second_path
+                )
+            };
+            report!(second_path.clone(), second_range.clone(), message)
                 .with_label(
                     Label::new((second_path, second_range))
                         .with_message("duplicate definition here")
@@ -298,81 +504,147 @@ fn display(
         }
         SemanticError::UnresolvedSymbol { symbol } => {
             let (path, range) = span_parts(&symbol.span, file_table);
-            report!(path.clone(), range.clone())
-                .with_message(format!("unresolved symbol '{}'", symbol.name))
-                .with_label(
-                    Label::new((path, range))
-                        .with_message("this name could not be resolved")
-                        .with_color(Color::Red),
-                )
+            report!(
+                path.clone(),
+                range.clone(),
+                format!("unresolved symbol '{}'", symbol.name)
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message("this name could not be resolved")
+                    .with_color(Color::Red),
+            )
         }
         SemanticError::ModelMissingDatabaseBinding { model } => {
             let (path, range) = span_parts(&model.span, file_table);
-            report!(path.clone(), range.clone())
-                .with_message(format!(
+            report!(
+                path.clone(),
+                range.clone(),
+                format!(
                     "model '{}' has SQL blocks but no backing binding is specified",
                     model.name
-                ))
-                .with_label(
-                    Label::new((path, range))
-                        .with_message("add a `[use \"<binding>\"]` tag to this model")
-                        .with_color(Color::Red),
                 )
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message("add a `[use \"<binding>\"]` tag to this model")
+                    .with_color(Color::Red),
+            )
         }
         SemanticError::ModelInvalidBinding { model, binding } => {
             let (model_path, model_range) = span_parts(&model.span, file_table);
             let (binding_path, binding_range) = span_parts(&binding.span, file_table);
-            report!(binding_path.clone(), binding_range.clone())
-                .with_message(format!(
-                    "'{}' is not a valid D1 or DO binding",
-                    binding.name
-                ))
-                .with_label(
-                    Label::new((binding_path, binding_range))
-                        .with_message("must be declared as a top-level `d1` or `do` binding")
-                        .with_color(Color::Red),
-                )
-                .with_label(
-                    Label::new((model_path, model_range))
-                        .with_message(format!("required by model '{}'", model.name))
-                        .with_color(Color::Yellow),
-                )
+            report!(
+                binding_path.clone(),
+                binding_range.clone(),
+                format!("'{}' is not a valid D1 or DO binding", binding.name)
+            )
+            .with_label(
+                Label::new((binding_path, binding_range))
+                    .with_message("must be declared as a top-level `d1` or `do` binding")
+                    .with_color(Color::Red),
+            )
+            .with_label(
+                Label::new((model_path, model_range))
+                    .with_message(format!("required by model '{}'", model.name))
+                    .with_color(Color::Yellow),
+            )
+        }
+        SemanticError::SecretDeclaredAsVar { var, secret } => {
+            let (secret_path, secret_range) = span_parts(&secret.span, file_table);
+            let (var_path, var_range) = span_parts(&var.span, file_table);
+            report!(
+                secret_path.clone(),
+                secret_range.clone(),
+                format!("'{}' is declared as both a var and a secret", secret.name)
+            )
+            .with_label(
+                Label::new((secret_path, secret_range))
+                    .with_message("declared as a secret here")
+                    .with_color(Color::Red),
+            )
+            .with_label(
+                Label::new((var_path, var_range))
+                    .with_message(
+                        "vars are written to the Wrangler config; remove this declaration",
+                    )
+                    .with_color(Color::Yellow),
+            )
+        }
+        SemanticError::SecretNotString { secret } => {
+            let (path, range) = span_parts(&secret.span, file_table);
+            report!(
+                path.clone(),
+                range.clone(),
+                format!("secret '{}' must be a string", secret.name)
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message(format!(
+                        "Workers secrets are always strings, not '{}'",
+                        fmt_cidl_type(&secret.cidl_type)
+                    ))
+                    .with_color(Color::Red),
+            )
         }
         SemanticError::ModelMissingPrimaryKey { model } => {
             let (path, range) = span_parts(&model.span, file_table);
-            report!(path.clone(), range.clone())
-                .with_message(format!(
-                    "model '{}' does not declare a primary key",
-                    model.name
-                ))
-                .with_label(
-                    Label::new((path, range))
-                        .with_message("add a `primary { ... }` block to this model")
-                        .with_color(Color::Red),
-                )
+            report!(
+                path.clone(),
+                range.clone(),
+                format!("model '{}' does not declare a primary key", model.name)
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message("add a `primary { ... }` block to this model")
+                    .with_color(Color::Red),
+            )
         }
         SemanticError::InvalidColumnType { column } => {
             let (path, range) = span_parts(&column.span, file_table);
-            report!(path.clone(), range.clone())
-                .with_message(format!("'{}' is not a valid SQLite type", column.name))
-                .with_label(
-                    Label::new((path, range))
-                        .with_message("allowed types: string, int, real, date, json, bool, blob")
-                        .with_color(Color::Red),
-                )
+            report!(
+                path.clone(),
+                range.clone(),
+                format!("'{}' is not a valid SQLite type", column.name)
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message(
+                        "allowed types: string, int, real, date, json, bool, blob, or an enum",
+                    )
+                    .with_color(Color::Red),
+            )
+        }
+        SemanticError::InvalidColumnDefault {
+            column,
+            default,
+            reason,
+        } => {
+            let (path, range) = span_parts(&column.span, file_table);
+            let (d_path, d_range) = span_parts(&default.span, file_table);
+            report!(
+                path.clone(),
+                range.clone(),
+                format!("invalid default value for column '{}'", column.name)
+            )
+            .with_label(
+                Label::new((d_path, d_range))
+                    .with_message(reason.as_str())
+                    .with_color(Color::Red),
+            )
         }
         SemanticError::NullablePrimaryKey { column } => {
             let (path, range) = span_parts(&column.span, file_table);
-            report!(path.clone(), range.clone())
-                .with_message(format!(
-                    "primary key column '{}' cannot be nullable",
-                    column.name
-                ))
-                .with_label(
-                    Label::new((path, range))
-                        .with_message("remove the `option` from this column's type")
-                        .with_color(Color::Red),
-                )
+            report!(
+                path.clone(),
+                range.clone(),
+                format!("primary key column '{}' cannot be nullable", column.name)
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message("remove the `option` from this column's type")
+                    .with_color(Color::Red),
+            )
         }
         SemanticError::ForeignKeyReferencesDifferentDatabase {
             model,
@@ -381,42 +653,48 @@ fn display(
         } => {
             let (model_path, model_range) = span_parts(&model.span, file_table);
             let (fk_path, fk_range) = span_parts(&fk_model.span, file_table);
-            report!(fk_path.clone(), fk_range.clone())
-                .with_message(format!(
+            report!(
+                fk_path.clone(),
+                fk_range.clone(),
+                format!(
                     "foreign key on model '{}' references model '{}' in a different database",
                     model.name, fk_model.name
-                ))
-                .with_label(
-                    Label::new((fk_path, fk_range))
-                        .with_message(match fk_binding {
-                            Some(sym) => {
-                                format!(
-                                    "model '{}' belongs to binding '{}'",
-                                    fk_model.name, sym.name
-                                )
-                            }
-                            None => format!("model '{}' has no D1 binding", fk_model.name),
-                        })
-                        .with_color(Color::Red),
-                )
-                .with_label(
-                    Label::new((model_path, model_range))
-                        .with_message(format!("model '{}' defined here", model.name))
-                        .with_color(Color::Yellow),
                 )
+            )
+            .with_label(
+                Label::new((fk_path, fk_range))
+                    .with_message(match fk_binding {
+                        Some(sym) => {
+                            format!(
+                                "model '{}' belongs to binding '{}'",
+                                fk_model.name, sym.name
+                            )
+                        }
+                        None => format!("model '{}' has no D1 binding", fk_model.name),
+                    })
+                    .with_color(Color::Red),
+            )
+            .with_label(
+                Label::new((model_path, model_range))
+                    .with_message(format!("model '{}' defined here", model.name))
+                    .with_color(Color::Yellow),
+            )
         }
         SemanticError::ForeignKeyInvalidColumnType { field } => {
             let (path, range) = span_parts(&field.span, file_table);
-            report!(path.clone(), range.clone())
-                .with_message(format!(
+            report!(
+                path.clone(),
+                range.clone(),
+                format!(
                     "foreign key references column '{}' which is not a valid SQLite type",
                     field.name
-                ))
-                .with_label(
-                    Label::new((path, range))
-                        .with_message("foreign key columns must be a valid SQLite type")
-                        .with_color(Color::Red),
                 )
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message("foreign key columns must be a valid SQLite type")
+                    .with_color(Color::Red),
+            )
         }
         SemanticError::ForeignKeyInconsistentFieldAdj {
             span,
@@ -424,8 +702,7 @@ fn display(
             field_count,
         } => {
             let (path, range) = span_parts(span, file_table);
-            report!(path.clone(), range.clone())
-                .with_message("foreign key has mismatched adjacency and field counts")
+            report!(path.clone(), range.clone(), "foreign key has mismatched adjacency and field counts")
                 .with_label(
                     Label::new((path, range))
                         .with_message(format!(
@@ -436,65 +713,86 @@ fn display(
         }
         SemanticError::RelationMissingDiscriminator { field, missing } => {
             let (path, range) = span_parts(&field.span, file_table);
-            report!(path.clone(), range.clone())
-                .with_message(format!(
+            report!(
+                path.clone(),
+                range.clone(),
+                format!(
                     "relation '{}' is missing the discriminator '{missing}'",
                     field.name
-                ))
-                .with_label(
-                    Label::new((path, range))
-                        .with_message(format!(
-                            "the target's '{missing}' must be supplied to construct its state"
-                        ))
-                        .with_color(Color::Red),
                 )
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message(format!(
+                        "the target's '{missing}' must be supplied to construct its state"
+                    ))
+                    .with_color(Color::Red),
+            )
         }
         SemanticError::RelationMissingLocalKey { target } => {
             let (path, range) = span_parts(&target.span, file_table);
-            report!(path.clone(), range.clone())
-                .with_message(format!(
+            report!(
+                path.clone(),
+                range.clone(),
+                format!(
                     "relation discriminator '{}' is missing a local field",
                     target.name
-                ))
-                .with_label(
+                )
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message(format!(
+                        "supply the local field that resolves it, e.g. `{}(localField)`",
+                        target.name
+                    ))
+                    .with_color(Color::Red),
+            )
+        }
+        SemanticError::CyclicalRelationship { cycle, fields } => {
+            let (path, range) = span_parts(&fields[0].span, file_table);
+            let mut report = report!(
+                path.clone(),
+                range.clone(),
+                cyclical_relationship_message(cycle)
+            );
+            for field in fields {
+                let (path, range) = span_parts(&field.span, file_table);
+                report = report.with_label(
                     Label::new((path, range))
                         .with_message(format!(
-                            "supply the local field that resolves it, e.g. `{}(localField)`",
-                            target.name
+                            "required foreign key '{}' is part of the cycle",
+                            field.name
                         ))
                         .with_color(Color::Red),
-                )
-        }
-        SemanticError::CyclicalRelationship { cycle } => {
-            eprintln!(
-                "error: cyclical relationship detected among: {}",
-                cycle.join(" -> ")
-            );
-            return;
+                );
+            }
+            report
         }
         SemanticError::TemplateUnknownVariable { field, variable } => {
             let (path, range) = span_parts(&field.span, file_table);
-            report!(path.clone(), range.clone())
-                .with_message(format!(
-                    "template format references unknown variable '${variable}'"
-                ))
-                .with_label(
-                    Label::new((path, range))
-                        .with_message(
-                            "this variable is not declared as a parameter in the template",
-                        )
-                        .with_color(Color::Red),
-                )
+            report!(
+                path.clone(),
+                range.clone(),
+                format!("template format references unknown variable '${variable}'")
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message("this variable is not declared as a parameter in the template")
+                    .with_color(Color::Red),
+            )
         }
         SemanticError::TemplateInvalidFormat { field, reason } => {
             let (path, range) = span_parts(&field.span, file_table);
-            report!(path.clone(), range.clone())
-                .with_message("invalid template format string")
-                .with_label(
-                    Label::new((path, range))
-                        .with_message(reason.as_str())
-                        .with_color(Color::Red),
-                )
+            report!(
+                path.clone(),
+                range.clone(),
+                "invalid template format string"
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message(reason.as_str())
+                    .with_color(Color::Red),
+            )
         }
         SemanticError::ArgCountMismatch {
             field,
@@ -502,35 +800,38 @@ fn display(
             got,
         } => {
             let (path, range) = span_parts(&field.span, file_table);
-            report!(path.clone(), range.clone())
-                .with_message(format!(
-                    "'{}' expects {expected} argument(s), got {got}",
-                    field.name
-                ))
-                .with_label(
-                    Label::new((path, range))
-                        .with_message(format!("expected {expected}, got {got}"))
-                        .with_color(Color::Red),
-                )
+            report!(
+                path.clone(),
+                range.clone(),
+                format!("'{}' expects {expected} argument(s), got {got}", field.name)
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message(format!("expected {expected}, got {got}"))
+                    .with_color(Color::Red),
+            )
         }
         SemanticError::ArgTypeMismatch { field, arg } => {
             let (path, range) = span_parts(&field.span, file_table);
             let (arg_path, arg_range) = span_parts(&arg.span, file_table);
-            report!(arg_path.clone(), arg_range.clone())
-                .with_message(format!(
+            report!(
+                arg_path.clone(),
+                arg_range.clone(),
+                format!(
                     "argument '{}' has the wrong type for '{}'",
                     arg.name, field.name
-                ))
-                .with_label(
-                    Label::new((arg_path, arg_range))
-                        .with_message("type does not match the expected parameter type")
-                        .with_color(Color::Red),
-                )
-                .with_label(
-                    Label::new((path, range))
-                        .with_message(format!("'{}' declared here", field.name))
-                        .with_color(Color::Yellow),
                 )
+            )
+            .with_label(
+                Label::new((arg_path, arg_range))
+                    .with_message("type does not match the expected parameter type")
+                    .with_color(Color::Red),
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message(format!("'{}' declared here", field.name))
+                    .with_color(Color::Yellow),
+            )
         }
         SemanticError::KvTemplateCount { field, count } => {
             let (path, range) = span_parts(&field.span, file_table);
@@ -539,44 +840,232 @@ fn display(
             } else {
                 format!("{count} storage templates are referenced")
             };
-            report!(path.clone(), range.clone())
-                .with_message(format!(
+            report!(
+                path.clone(),
+                range.clone(),
+                format!(
                     "'{}' must reference exactly one storage template, but {detail}",
                     field.name
-                ))
-                .with_label(
-                    Label::new((path, range))
-                        .with_message("a kv field needs exactly one `template(args)` reference")
-                        .with_color(Color::Red),
                 )
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message("a kv field needs exactly one `template(args)` reference")
+                    .with_color(Color::Red),
+            )
         }
         SemanticError::PlainOldObjectInvalidFieldType { field } => {
             let (path, range) = span_parts(&field.span, file_table);
-            report!(path.clone(), range.clone())
-                .with_message(format!(
+            report!(
+                path.clone(),
+                range.clone(),
+                format!(
                     "field '{}' has an invalid type for a plain object",
                     field.name
-                ))
-                .with_label(
-                    Label::new((path, range))
-                        .with_message(
-                            "`stream` and `void` are not valid field types in a `poo` block",
-                        )
-                        .with_color(Color::Red),
                 )
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message("`stream` and `void` are not valid field types in a `poo` block")
+                    .with_color(Color::Red),
+            )
+        }
+        SemanticError::QueueInvalidMessageType { queue, message } => {
+            let (path, range) = span_parts(&message.span, file_table);
+            report!(
+                path.clone(),
+                range.clone(),
+                format!("queue '{}' has an invalid message type", queue.name)
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message("`stream`, `r2object` and `partial` are not valid message types")
+                    .with_color(Color::Red),
+            )
+        }
+        SemanticError::ServiceMissingCidl {
+            service,
+            path,
+            reason,
+        } => {
+            let (path, range) = span_parts(path, file_table);
+            report!(
+                path.clone(),
+                range.clone(),
+                format!("could not read the CIDL of service '{}'", service.name)
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message(format!("{reason}; compile the service first"))
+                    .with_color(Color::Red),
+            )
+        }
+        SemanticError::ServiceIncompatibleCidl {
+            service,
+            path,
+            reason,
+        } => {
+            let (path, range) = span_parts(path, file_table);
+            report!(
+                path.clone(),
+                range.clone(),
+                format!("the CIDL of service '{}' is incompatible", service.name)
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message(format!(
+                        "{reason}; recompile the service with this version of Cloesce"
+                    ))
+                    .with_color(Color::Red),
+            )
+        }
+        SemanticError::CronInvalidExpression { span, reason } => {
+            let (path, range) = span_parts(span, file_table);
+            report!(path.clone(), range.clone(), "invalid cron expression").with_label(
+                Label::new((path, range))
+                    .with_message(reason)
+                    .with_color(Color::Red),
+            )
         }
-        SemanticError::DataSourceUnknownModelReference { source } => {
+        SemanticError::CronDuplicateExpression { first, second } => {
+            let (first_path, first_range) = span_parts(first, file_table);
+            let (second_path, second_range) = span_parts(second, file_table);
+            report!(
+                second_path.clone(),
+                second_range.clone(),
+                "cron expression is scheduled more than once"
+            )
+            .with_label(
+                Label::new((first_path, first_range))
+                    .with_message("first scheduled here")
+                    .with_color(Color::Yellow),
+            )
+            .with_label(
+                Label::new((second_path, second_range))
+                    .with_message("scheduled again here")
+                    .with_color(Color::Red),
+            )
+        }
+        SemanticError::CronDurableContext { context } => {
+            let (path, range) = span_parts(&context.span, file_table);
+            report!(
+                path.clone(),
+                range.clone(),
+                format!("cron handler cannot run in the '{}' context", context.name)
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message(
+                        "cron handlers run on the Worker; inject the binding without `::` instead",
+                    )
+                    .with_color(Color::Red),
+            )
+        }
+        SemanticError::GuardDurableContext { context } => {
+            let (path, range) = span_parts(&context.span, file_table);
+            report!(
+                path.clone(),
+                range.clone(),
+                format!("guard cannot run in the '{}' context", context.name)
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message(
+                        "guards run before their route; inject the binding without `::` instead",
+                    )
+                    .with_color(Color::Red),
+            )
+        }
+        SemanticError::GuardUnknownReference { guard } => {
+            let (path, range) = span_parts(&guard.span, file_table);
+            report!(
+                path.clone(),
+                range.clone(),
+                format!("unknown guard '{}'", guard.name)
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message(format!("declare a `guard {}` block", guard.name))
+                    .with_color(Color::Red),
+            )
+        }
+        SemanticError::EnvMissingBinding {
+            env,
+            model,
+            binding,
+        } => {
+            let (env_path, env_range) = span_parts(&env.span, file_table);
+            let (binding_path, binding_range) = span_parts(&binding.span, file_table);
+            report!(
+                env_path.clone(),
+                env_range.clone(),
+                format!(
+                    "environment '{}' does not bind '{}'",
+                    env.name, binding.name
+                )
+            )
+            .with_label(
+                Label::new((env_path, env_range))
+                    .with_message(format!(
+                        "add '{}' to this environment's `d1` block",
+                        binding.name
+                    ))
+                    .with_color(Color::Red),
+            )
+            .with_label(
+                Label::new((binding_path, binding_range))
+                    .with_message(format!("required by model '{}'", model.name))
+                    .with_color(Color::Yellow),
+            )
+        }
+        SemanticError::EmptyEnum { symbol } => {
+            let (path, range) = span_parts(&symbol.span, file_table);
+            report!(
+                path.clone(),
+                range.clone(),
+                format!("enum '{}' has no members", symbol.name)
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message(
+                        "declare at least one member, e.g. `enum Status { Open, Closed }`",
+                    )
+                    .with_color(Color::Red),
+            )
+        }
+        SemanticError::DataSourceUnknownModelReference { source, .. } => {
             let (path, range) = span_parts(&source.span, file_table);
-            report!(path.clone(), range.clone())
-                .with_message(format!(
+            report!(
+                path.clone(),
+                range.clone(),
+                format!(
                     "data source '{}' references an unknown or non-model type",
                     source.name
-                ))
-                .with_label(
-                    Label::new((path, range))
-                        .with_message("this model does not exist")
-                        .with_color(Color::Red),
                 )
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message("this model does not exist")
+                    .with_color(Color::Red),
+            )
+        }
+        SemanticError::DataSourceCountWithStubList { source } => {
+            let (path, range) = span_parts(&source.span, file_table);
+            report!(
+                path.clone(),
+                range.clone(),
+                format!(
+                    "data source '{}' has a user-written list, which `[crud count]` cannot count",
+                    source.name
+                )
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message(
+                        "give its list `where` or `orderBy` clauses, or mark it `[internal]`",
+                    )
+                    .with_color(Color::Red),
+            )
         }
         SemanticError::DataSourceInvalidIncludeTreeReference {
             source,
@@ -585,30 +1074,32 @@ fn display(
         } => {
             let (field_path, field_range) = span_parts(&field.span, file_table);
             let (source_path, source_range) = span_parts(&source.span, file_table);
-            report!(field_path.clone(), field_range.clone())
-                .with_message(format!(
+            report!(
+                field_path.clone(),
+                field_range.clone(),
+                format!(
                     "'{}' is not a valid include on model '{}'",
                     field.name, model.name
-                ))
-                .with_label(
-                    Label::new((field_path, field_range))
-                        .with_message(format!(
-                            "not a navigation property, KV, or R2 on '{}'",
-                            model.name
-                        ))
-                        .with_color(Color::Red),
-                )
-                .with_label(
-                    Label::new((source_path, source_range))
-                        .with_message(format!("data source '{}' declared here", source.name))
-                        .with_color(Color::Yellow),
                 )
+            )
+            .with_label(
+                Label::new((field_path, field_range))
+                    .with_message(format!(
+                        "not a navigation property, KV, or R2 on '{}'",
+                        model.name
+                    ))
+                    .with_color(Color::Red),
+            )
+            .with_label(
+                Label::new((source_path, source_range))
+                    .with_message(format!("data source '{}' declared here", source.name))
+                    .with_color(Color::Yellow),
+            )
         }
         SemanticError::DataSourceInvalidMethodParam { source, param } => {
             let (param_path, param_range) = span_parts(&param.span, file_table);
             let (source_path, source_range) = span_parts(&source.span, file_table);
-            report!(param_path.clone(), param_range.clone())
-                .with_message(format!(
+            report!(param_path.clone(), param_range.clone(), format!(
                     "parameter '{}' on data source '{}' is not a valid SQLite type",
                     param.name, source.name
                 ))
@@ -623,62 +1114,166 @@ fn display(
                         .with_color(Color::Yellow),
                 )
         }
-        SemanticError::ApiUnknownNamespaceReference { api } => {
-            let (path, range) = span_parts(&api.span, file_table);
-            report!(path.clone(), range.clone())
-                .with_message(format!(
-                    "API block '{}' references an unknown model",
-                    api.name
+        SemanticError::DataSourceListClauseOnStub { source, method } => {
+            let (method_path, method_range) = span_parts(&method.span, file_table);
+            let (source_path, source_range) = span_parts(&source.span, file_table);
+            report!(method_path.clone(), method_range.clone(), format!(
+                    "'list' on data source '{}' mixes clauses with parameters or injects",
+                    source.name
                 ))
                 .with_label(
-                    Label::new((path, range))
-                        .with_message("this model does not exist")
+                    Label::new((method_path, method_range))
+                        .with_message("'orderBy' and 'where' only apply to a generated list, which declares no parameters or injects")
                         .with_color(Color::Red),
                 )
+                .with_label(
+                    Label::new((source_path, source_range))
+                        .with_message(format!("data source '{}' declared here", source.name))
+                        .with_color(Color::Yellow),
+                )
+        }
+        SemanticError::DataSourceListUnknownColumn { source, column } => {
+            let (column_path, column_range) = span_parts(&column.span, file_table);
+            let (source_path, source_range) = span_parts(&source.span, file_table);
+            report!(
+                column_path.clone(),
+                column_range.clone(),
+                format!(
+                    "'{}' is not a column of the model data source '{}' lists",
+                    column.name, source.name
+                )
+            )
+            .with_label(
+                Label::new((column_path, column_range))
+                    .with_message("not a primary key or column")
+                    .with_color(Color::Red),
+            )
+            .with_label(
+                Label::new((source_path, source_range))
+                    .with_message(format!("data source '{}' declared here", source.name))
+                    .with_color(Color::Yellow),
+            )
+        }
+        SemanticError::DataSourceListNullableOrder { source, column } => {
+            let (column_path, column_range) = span_parts(&column.span, file_table);
+            let (source_path, source_range) = span_parts(&source.span, file_table);
+            report!(
+                column_path.clone(),
+                column_range.clone(),
+                format!(
+                    "data source '{}' cannot order by nullable column '{}'",
+                    source.name, column.name
+                )
+            )
+            .with_label(
+                Label::new((column_path, column_range))
+                    .with_message("pagination cannot seek past a null value")
+                    .with_color(Color::Red),
+            )
+            .with_label(
+                Label::new((source_path, source_range))
+                    .with_message(format!("data source '{}' declared here", source.name))
+                    .with_color(Color::Yellow),
+            )
+        }
+        SemanticError::DataSourceListDuplicateParam { source, param } => {
+            let (param_path, param_range) = span_parts(&param.span, file_table);
+            let (source_path, source_range) = span_parts(&source.span, file_table);
+            report!(
+                param_path.clone(),
+                param_range.clone(),
+                format!(
+                    "parameter '{}' is already a parameter of the 'list' on data source '{}'",
+                    param.name, source.name
+                )
+            )
+            .with_label(
+                Label::new((param_path, param_range))
+                    .with_message("choose a different parameter name")
+                    .with_color(Color::Red),
+            )
+            .with_label(
+                Label::new((source_path, source_range))
+                    .with_message(format!("data source '{}' declared here", source.name))
+                    .with_color(Color::Yellow),
+            )
+        }
+        SemanticError::ApiUnknownNamespaceReference { api } => {
+            let (path, range) = span_parts(&api.span, file_table);
+            report!(
+                path.clone(),
+                range.clone(),
+                format!("API block '{}' references an unknown model", api.name)
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message("this model does not exist")
+                    .with_color(Color::Red),
+            )
         }
         SemanticError::ApiUnknownDataSourceReference {
             method,
             data_source,
         } => {
             let (path, range) = span_parts(&method.span, file_table);
-            report!(path.clone(), range.clone())
-                .with_message(format!(
+            report!(
+                path.clone(),
+                range.clone(),
+                format!(
                     "API method '{}' references unknown data source '{}'",
                     method.name, data_source.name
-                ))
-                .with_label(
-                    Label::new((path, range))
-                        .with_message(format!(
-                            "'{}' is not defined on the model",
-                            data_source.name
-                        ))
-                        .with_color(Color::Red),
                 )
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message(format!(
+                        "'{}' is not defined on the model",
+                        data_source.name
+                    ))
+                    .with_color(Color::Red),
+            )
         }
         SemanticError::ApiInvalidReturn { method } => {
             let (path, range) = span_parts(&method.span, file_table);
-            report!(path.clone(), range.clone())
-                .with_message(format!(
-                    "API method '{}' has an invalid return type",
-                    method.name
+            report!(
+                path.clone(),
+                range.clone(),
+                format!("API method '{}' has an invalid return type", method.name)
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message("`stream` must be the top-level return type, not wrapped")
+                    .with_color(Color::Red),
+            )
+        }
+        SemanticError::ApiInvalidParam { method, param } => {
+            let (param_path, param_range) = span_parts(&param.span, file_table);
+            let (method_path, method_range) = span_parts(&method.span, file_table);
+            report!(param_path.clone(), param_range.clone(), format!(
+                    "parameter '{}' on API method '{}' has an invalid type",
+                    param.name, method.name
                 ))
                 .with_label(
-                    Label::new((path, range))
-                        .with_message("`stream` must be the top-level return type, not wrapped")
+                    Label::new((param_path, param_range))
+                        .with_message("object, r2object, and stream parameters are not allowed on GET methods; stream must be the only non-injected parameter")
                         .with_color(Color::Red),
                 )
+                .with_label(
+                    Label::new((method_path, method_range))
+                        .with_message(format!("method '{}' declared here", method.name))
+                        .with_color(Color::Yellow),
+                )
         }
-        SemanticError::ApiInvalidParam { method, param } => {
+        SemanticError::ApiInvalidParamSource { method, param } => {
             let (param_path, param_range) = span_parts(&param.span, file_table);
             let (method_path, method_range) = span_parts(&method.span, file_table);
-            report!(param_path.clone(), param_range.clone())
-                .with_message(format!(
-                    "parameter '{}' on API method '{}' has an invalid type",
+            report!(param_path.clone(), param_range.clone(), format!(
+                    "parameter '{}' on API method '{}' cannot be read from the URL",
                     param.name, method.name
                 ))
                 .with_label(
                     Label::new((param_path, param_range))
-                        .with_message("object, r2object, and stream parameters are not allowed on GET methods; stream must be the only non-injected parameter")
+                        .with_message("[query] and [path] parameters must be a string, int, real, bool, date or enum, and only [query] parameters may be an option")
                         .with_color(Color::Red),
                 )
                 .with_label(
@@ -687,10 +1282,98 @@ fn display(
                         .with_color(Color::Yellow),
                 )
         }
+        SemanticError::ApiInvalidRoute { method, reason } => {
+            let (path, range) = span_parts(&method.span, file_table);
+            report!(
+                path.clone(),
+                range.clone(),
+                format!("API method '{}' has an invalid route", method.name)
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message(reason.as_str())
+                    .with_color(Color::Red),
+            )
+        }
+        SemanticError::ApiRouteUnknownParam { method, variable } => {
+            let (path, range) = span_parts(&method.span, file_table);
+            report!(
+                path.clone(),
+                range.clone(),
+                format!(
+                    "route of API method '{}' references unknown parameter '{variable}'",
+                    method.name
+                )
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message(format!("declare a `[path] {variable}` parameter"))
+                    .with_color(Color::Red),
+            )
+        }
+        SemanticError::ApiPathParamNotInRoute { method, param } => {
+            let (param_path, param_range) = span_parts(&param.span, file_table);
+            let (method_path, method_range) = span_parts(&method.span, file_table);
+            report!(
+                param_path.clone(),
+                param_range.clone(),
+                format!(
+                    "path parameter '{}' does not appear in the route of API method '{}'",
+                    param.name, method.name
+                )
+            )
+            .with_label(
+                Label::new((param_path, param_range))
+                    .with_message(format!("add `{{{}}}` to the method's route", param.name))
+                    .with_color(Color::Red),
+            )
+            .with_label(
+                Label::new((method_path, method_range))
+                    .with_message(format!("method '{}' declared here", method.name))
+                    .with_color(Color::Yellow),
+            )
+        }
+        SemanticError::ApiOverlappingRoute { first, second } => {
+            let (first_path, first_range) = span_parts(&first.span, file_table);
+            let (second_path, second_range) = span_parts(&second.span, file_table);
+            report!(
+                second_path.clone(),
+                second_range.clone(),
+                format!(
+                    "API methods '{}' and '{}' have overlapping routes",
+                    first.name, second.name
+                )
+            )
+            .with_label(
+                Label::new((second_path, second_range))
+                    .with_message("a request path matching this route also matches the first")
+                    .with_color(Color::Red),
+            )
+            .with_label(
+                Label::new((first_path, first_range))
+                    .with_message(format!("first declared by '{}'", first.name))
+                    .with_color(Color::Yellow),
+            )
+        }
+        SemanticError::ApiRouteShadowsDerived { method, derived } => {
+            let (path, range) = span_parts(&method.span, file_table);
+            report!(
+                path.clone(),
+                range.clone(),
+                format!(
+                    "route of API method '{}' overlaps the derived route '{derived}'",
+                    method.name
+                )
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message("custom routes are matched first, hiding the derived route")
+                    .with_color(Color::Red),
+            )
+        }
         SemanticError::ApiInjectsDurableWhenSourceInjectsDurable { method } => {
             let (method_path, method_range) = span_parts(&method.span, file_table);
-            report!(method_path.clone(), method_range.clone())
-                .with_message(format!(
+            report!(method_path.clone(), method_range.clone(), format!(
                     "API method '{}' injects a Durable Object context but already inherits one from its data source",
                     method.name
                 ))
@@ -702,47 +1385,56 @@ fn display(
         }
         SemanticError::ApiMultipleDurableContexts { context } => {
             let (path, range) = span_parts(&context.span, file_table);
-            report!(path.clone(), range.clone())
-                .with_message("more than one Durable Object context injected")
-                .with_label(
-                    Label::new((path, range))
-                        .with_message("a method may inject at most one Durable Object context")
-                        .with_color(Color::Red),
-                )
+            report!(
+                path.clone(),
+                range.clone(),
+                "more than one Durable Object context injected"
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message("a method may inject at most one Durable Object context")
+                    .with_color(Color::Red),
+            )
         }
         SemanticError::DurableUnknownShardField { binding, target } => {
             let (b_path, b_range) = span_parts(&binding.span, file_table);
             let (t_path, t_range) = span_parts(&target.span, file_table);
-            report!(t_path.clone(), t_range.clone())
-                .with_message(format!(
+            report!(
+                t_path.clone(),
+                t_range.clone(),
+                format!(
                     "'{}' is not a shard field of Durable Object '{}'",
                     target.name, binding.name
-                ))
-                .with_label(
-                    Label::new((t_path, t_range))
-                        .with_message("no such shard field on this Durable Object")
-                        .with_color(Color::Red),
-                )
-                .with_label(
-                    Label::new((b_path, b_range))
-                        .with_message(format!("Durable Object '{}' referenced here", binding.name))
-                        .with_color(Color::Yellow),
                 )
+            )
+            .with_label(
+                Label::new((t_path, t_range))
+                    .with_message("no such shard field on this Durable Object")
+                    .with_color(Color::Red),
+            )
+            .with_label(
+                Label::new((b_path, b_range))
+                    .with_message(format!("Durable Object '{}' referenced here", binding.name))
+                    .with_color(Color::Yellow),
+            )
         }
         SemanticError::DurableMissingShardField { context, missing } => {
             let (path, range) = span_parts(&context.span, file_table);
-            report!(path.clone(), range.clone())
-                .with_message(format!(
+            report!(
+                path.clone(),
+                range.clone(),
+                format!(
                     "Durable Object context '{}' is missing shard field '{missing}'",
                     context.name
-                ))
-                .with_label(
-                    Label::new((path, range))
-                        .with_message(format!(
-                            "supply an initializer for '{missing}', e.g. `{missing}(arg)`"
-                        ))
-                        .with_color(Color::Red),
                 )
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message(format!(
+                        "supply an initializer for '{missing}', e.g. `{missing}(arg)`"
+                    ))
+                    .with_color(Color::Red),
+            )
         }
         SemanticError::ValidatorInvalidArgument {
             validator,
@@ -751,83 +1443,109 @@ fn display(
         } => {
             let (path, range) = span_parts(&symbol.span, file_table);
             let (v_path, v_range) = span_parts(&validator.span, file_table);
-            report!(path.clone(), range.clone())
-                .with_message(format!(
+            report!(
+                path.clone(),
+                range.clone(),
+                format!(
                     "invalid argument for validator `{}`",
                     validator_name(&validator.inner)
-                ))
-                .with_label(
-                    Label::new((v_path, v_range))
-                        .with_message(reason.as_str())
-                        .with_color(Color::Red),
-                )
-                .with_label(
-                    Label::new((path, range))
-                        .with_message("applied to this field")
-                        .with_color(Color::Yellow),
                 )
+            )
+            .with_label(
+                Label::new((v_path, v_range))
+                    .with_message(reason.as_str())
+                    .with_color(Color::Red),
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message("applied to this field")
+                    .with_color(Color::Yellow),
+            )
         }
         SemanticError::ValidatorInvalidForType { validator, symbol } => {
             let (path, range) = span_parts(&symbol.span, file_table);
             let (v_path, v_range) = span_parts(&validator.span, file_table);
-            report!(path.clone(), range.clone())
-                .with_message(format!(
+            report!(
+                path.clone(),
+                range.clone(),
+                format!(
                     "validator `{}` is not valid for this type",
                     validator_name(&validator.inner)
-                ))
-                .with_label(
-                    Label::new((v_path, v_range))
-                        .with_message("this validator cannot be applied to this field type")
-                        .with_color(Color::Red),
-                )
-                .with_label(
-                    Label::new((path, range))
-                        .with_message("applied to this field")
-                        .with_color(Color::Yellow),
                 )
+            )
+            .with_label(
+                Label::new((v_path, v_range))
+                    .with_message("this validator cannot be applied to this field type")
+                    .with_color(Color::Red),
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message("applied to this field")
+                    .with_color(Color::Yellow),
+            )
         }
         SemanticError::TagInvalidInContext { tag, symbol } => {
             let (path, range) = span_parts(&symbol.span, file_table);
             let (t_path, t_range) = span_parts(&tag.span, file_table);
-            report!(path.clone(), range.clone())
-                .with_message("tag is not valid in this context")
-                .with_label(
-                    Label::new((t_path, t_range))
-                        .with_message("this tag cannot be applied here")
-                        .with_color(Color::Red),
-                )
-                .with_label(
-                    Label::new((path, range))
-                        .with_message("applied to this symbol")
-                        .with_color(Color::Yellow),
+            report!(
+                path.clone(),
+                range.clone(),
+                "tag is not valid in this context"
+            )
+            .with_label(
+                Label::new((t_path, t_range))
+                    .with_message("this tag cannot be applied here")
+                    .with_color(Color::Red),
+            )
+            .with_label(
+                Label::new((path, range))
+                    .with_message("applied to this symbol")
+                    .with_color(Color::Yellow),
+            )
+        }
+        SemanticError::CrudUnsupportedByRustTarget { tag, model } => {
+            let (path, range) = span_parts(&model.span, file_table);
+            let (t_path, t_range) = span_parts(&tag.span, file_table);
+            report!(
+                path.clone(),
+                range.clone(),
+                format!(
+                    "model '{}' declares CRUD routes, which the rust target does not support",
+                    model.name
                 )
+            )
+            .with_label(
+                Label::new((t_path, t_range))
+                    .with_message("remove this tag or use the typescript target")
+                    .with_color(Color::Red),
+            )
         }
         SemanticError::InstanceTagOnNonField { source, param, tag } => {
             let (s_path, s_range) = span_parts(&source.span, file_table);
             let (p_path, p_range) = span_parts(&param.span, file_table);
             let (t_path, t_range) = span_parts(&tag.span, file_table);
-            report!(s_path.clone(), s_range.clone())
-                .with_message(format!(
-                    "instance tag applied to non-field symbol '{}'",
-                    source.name
-                ))
-                .with_label(
-                    Label::new((s_path, s_range))
-                        .with_message("instance tags can only be applied to fields")
-                        .with_color(Color::Red),
-                )
-                .with_label(
-                    Label::new((p_path, p_range))
-                        .with_message(format!("this parameter '{}' is not a field", param.name))
-                        .with_color(Color::Yellow),
-                )
-                .with_label(
-                    Label::new((t_path, t_range))
-                        .with_message("this tag is an instance tag")
-                        .with_color(Color::Blue),
-                )
+            report!(
+                s_path.clone(),
+                s_range.clone(),
+                format!("instance tag applied to non-field symbol '{}'", source.name)
+            )
+            .with_label(
+                Label::new((s_path, s_range))
+                    .with_message("instance tags can only be applied to fields")
+                    .with_color(Color::Red),
+            )
+            .with_label(
+                Label::new((p_path, p_range))
+                    .with_message(format!("this parameter '{}' is not a field", param.name))
+                    .with_color(Color::Yellow),
+            )
+            .with_label(
+                Label::new((t_path, t_range))
+                    .with_message("this tag is an instance tag")
+                    .with_color(Color::Blue),
+            )
         }
     };
 
-    report.finish().write(cache, std::io::stderr()).ok();
+    (diagnostic, report.finish())
 }
@@ -0,0 +1,55 @@
+use frontend::{InjectEntry, SpdSlice, Symbol};
+use idl::Guard;
+
+use crate::{
+    SymbolTable,
+    err::{ErrorSink, SemanticError},
+    resolve_inject,
+};
+
+/// Builds a [Guard] for every `guard` block, resolving its injected bindings.
+pub fn analyze<'src, 'p>(
+    table: &SymbolTable<'src, 'p>,
+    sink: &mut ErrorSink<'src, 'p>,
+) -> Vec<Guard<'src>> {
+    let mut guards = Vec::new();
+
+    for block in table.guards.values() {
+        let mut has_context = false;
+        for entry in block.injects.iter().flat_map(|b| b.inner.entries.inners()) {
+            if let InjectEntry::Context { symbol, .. } = entry {
+                sink.push(SemanticError::GuardDurableContext { context: symbol });
+                has_context = true;
+            }
+        }
+        if has_context {
+            continue;
+        }
+
+        let (injected, _) = resolve_inject(&block.injects, &mut [], table, sink);
+        guards.push(Guard {
+            name: block.symbol.name,
+            injected,
+        });
+    }
+
+    guards
+}
+
+/// Resolves the guard named by a `[guard X]` tag, appending it to `guards` unless
+/// it is already listed.
+pub fn resolve<'src, 'p>(
+    guard: &'p Symbol<'src>,
+    table: &SymbolTable<'src, 'p>,
+    sink: &mut ErrorSink<'src, 'p>,
+    guards: &mut Vec<&'src str>,
+) {
+    if !table.guards.contains_key(guard.name) {
+        sink.push(SemanticError::GuardUnknownReference { guard });
+        return;
+    }
+
+    if !guards.contains(&guard.name) {
+        guards.push(guard.name);
+    }
+}
@@ -18,12 +18,15 @@
 //! Some errors may cause a certain structure to be escaped or treated as if it were not present, but will be reported in the final error list.
 
 use frontend::{
-    ApiBlock, ArgumentLiteral, Ast, AstBlockKind, D1BindingBlock, DataSourceBlock,
-    DurableBindingBlock, InjectBlock, InjectEntry, InjectInitializer, KvBindingBlock,
-    MethodInjectBlock, ModelBlock, PlainOldObjectBlock, R2BindingBlock, Spd, SpdSlice, Symbol, Tag,
-    VarBlock,
+    ApiBlock, ArgumentLiteral, Ast, AstBlockKind, CronBlock, D1BindingBlock, DataSourceBlock,
+    DurableBindingBlock, EnumBlock, EnvBlock, GuardBlock, InjectBlock, InjectEntry,
+    InjectInitializer, KvBindingBlock, MethodInjectBlock, ModelBlock, NamespaceBlock,
+    PlainOldObjectBlock, QueueBindingBlock, R2BindingBlock, SecretBlock, ServiceBindingBlock, Span,
+    Spd, SpdSlice, Symbol, Tag, VarBlock,
+};
+use idl::{
+    CidlType, CloesceIdl, DurableTarget, Enum, Number, PlainOldObject, ValidatedField, Validator,
 };
-use idl::{CidlType, CloesceIdl, DurableTarget, Number, PlainOldObject, ValidatedField, Validator};
 use indexmap::IndexMap;
 
 use std::collections::{BTreeMap, HashMap};
@@ -34,20 +37,43 @@ use crate::{
 };
 
 mod api;
+mod cron;
 mod data_source;
 mod env;
 pub mod err;
+mod guard;
 mod model;
+mod service;
+
+pub use service::{ServiceCidls, read_service_cidls};
 
 /// Undergoes semantic analysis and expansion on the provided [Ast],
 /// returning either a valid [CloesceIdl] or a list of [SemanticError]s.
-pub fn analyze<'src, 'p>(
+///
+/// Any `service` block is reported as missing its CIDL; see [analyze_with_services].
+///
+/// Names in the resulting IDL borrow from the [Ast] rather than the source, since
+/// namespaced declarations are generated under names that never appear in the source.
+pub fn analyze<'src: 'p, 'p>(
     ast: &'p Ast<'src>,
-) -> Result<CloesceIdl<'src>, Vec<SemanticError<'src, 'p>>> {
+) -> Result<CloesceIdl<'p>, Vec<SemanticError<'p, 'p>>> {
+    static NO_SERVICES: ServiceCidls = ServiceCidls::new();
+    analyze_with_services(ast, &NO_SERVICES)
+}
+
+/// As [analyze], typing each `service` block by its CIDL in `services`
+/// (see [read_service_cidls]).
+pub fn analyze_with_services<'src: 'p, 'p>(
+    ast: &'p Ast<'src>,
+    services: &'p ServiceCidls,
+) -> Result<CloesceIdl<'p>, Vec<SemanticError<'p, 'p>>> {
+    let ast: &'p Ast<'p> = ast;
     let mut sink = ErrorSink::new();
     let table = SymbolTable::from_ast(ast, &mut sink);
-    let wrangler_env = env::analyze(&table, &mut sink);
+    let mut wrangler_env = env::analyze(&table, &mut sink);
+    wrangler_env.service_bindings = service::analyze(&table, services, &mut sink);
     let poos = analyze_poos(&table, &mut sink);
+    let enums = analyze_enums(&table, &mut sink);
 
     let mut models = match ModelAnalysis::new(&wrangler_env).analyze(&table) {
         Ok(models) => models,
@@ -71,6 +97,10 @@ pub fn analyze<'src, 'p>(
         }
     }
 
+    let crons = cron::analyze(&table, &mut sink);
+    let guards = guard::analyze(&table, &mut sink);
+    let envs = env::analyze_envs(&table, &wrangler_env, &mut sink);
+
     let injects = table
         .injects
         .iter()
@@ -82,15 +112,31 @@ pub fn analyze<'src, 'p>(
         wrangler_env,
         models,
         poos,
+        enums,
         injects,
+        crons,
+        guards,
+        envs,
     };
-    let errs = sink.drain();
-    if !errs.is_empty() {
+    // A block that failed to parse leaves the IDL incomplete, so analysis stops here even
+    // if every remaining error follows from it (the parser has already reported the cause)
+    let errs = sink
+        .drain()
+        .into_iter()
+        .filter(|err| !table.cascades_from_parse_error(err))
+        .collect::<Vec<_>>();
+    if !errs.is_empty() || !table.error_names.is_empty() {
         return Err(errs);
     }
 
     data_source::expansion::expand(&mut idl);
     api::expansion::expand(&mut idl);
+
+    let errs = api::analysis::derived_route_collisions(&idl, &table);
+    if !errs.is_empty() {
+        return Err(errs);
+    }
+
     idl.set_merkle_hash();
 
     Ok(idl)
@@ -102,8 +148,7 @@ fn analyze_poos<'src, 'p>(
 ) -> BTreeMap<&'src str, PlainOldObject<'src>> {
     let mut res = BTreeMap::new();
 
-    for poo in table.poos.values() {
-        let poo_name = poo.symbol.name;
+    for (&poo_name, poo) in &table.poos {
         let mut fields = Vec::new();
 
         for field in &poo.fields {
@@ -151,6 +196,32 @@ fn analyze_poos<'src, 'p>(
     res
 }
 
+fn analyze_enums<'src, 'p>(
+    table: &SymbolTable<'src, 'p>,
+    sink: &mut ErrorSink<'src, 'p>,
+) -> BTreeMap<&'src str, Enum<'src>> {
+    let mut res = BTreeMap::new();
+
+    for (&name, block) in &table.enums {
+        if block.members.is_empty() {
+            sink.push(SemanticError::EmptyEnum {
+                symbol: &block.symbol,
+            });
+            continue;
+        }
+
+        res.insert(
+            name,
+            Enum {
+                name,
+                members: block.members.iter().map(|m| m.name).collect(),
+            },
+        );
+    }
+
+    res
+}
+
 /// Scopes for any symbol that is nested within some other symbol,
 /// (called a local symbol) e.g. a field within a model or a parameter within an API method.
 #[derive(Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -176,6 +247,10 @@ enum LocalSymbolKind<'src> {
         poo: &'src str,
         name: &'src str,
     },
+    EnumMember {
+        enum_name: &'src str,
+        name: &'src str,
+    },
     ApiMethodDecl {
         namespace: &'src str,
         name: &'src str,
@@ -198,48 +273,209 @@ enum LocalSymbolKind<'src> {
 
 /// A table that maps a symbol name to its definition in the [Ast].
 #[derive(Default)]
-struct SymbolTable<'src, 'p> {
+pub struct SymbolTable<'src, 'p> {
     // Globals
     models: BTreeMap<&'src str, &'p ModelBlock<'src>>,
     poos: BTreeMap<&'src str, &'p PlainOldObjectBlock<'src>>,
+    enums: BTreeMap<&'src str, &'p EnumBlock<'src>>,
     d1_bindings: Vec<&'p D1BindingBlock<'src>>,
     kv_bindings: BTreeMap<&'src str, &'p KvBindingBlock<'src>>,
     r2_bindings: BTreeMap<&'src str, &'p R2BindingBlock<'src>>,
     durable_bindings: BTreeMap<&'src str, &'p DurableBindingBlock<'src>>,
+    queue_bindings: BTreeMap<&'src str, &'p QueueBindingBlock<'src>>,
+    service_bindings: BTreeMap<&'src str, &'p ServiceBindingBlock<'src>>,
+    crons: Vec<&'p CronBlock<'src>>,
+    guards: BTreeMap<&'src str, &'p GuardBlock<'src>>,
+    envs: BTreeMap<&'src str, &'p EnvBlock<'src>>,
     vars_blocks: Vec<&'p VarBlock<'src>>,
+    secrets_blocks: Vec<&'p SecretBlock<'src>>,
     injects: Vec<&'p InjectBlock<'src>>,
     apis: Vec<&'p ApiBlock<'src>>,
     data_sources: Vec<&'p DataSourceBlock<'src>>,
 
     // Locals
     local: BTreeMap<LocalSymbolKind<'src>, &'p Symbol<'src>>,
+
+    /// Names declared by blocks that failed to parse
+    error_names: Vec<&'src str>,
+
+    scopes: Scopes<'src, 'p>,
+}
+
+/// The namespaces of an [Ast], used to resolve a written name to the name its
+/// declaration is generated under.
+#[derive(Default)]
+struct Scopes<'src, 'p> {
+    namespaces: Vec<Spd<&'p NamespaceBlock<'src>>>,
+
+    /// Generated names of namespace members, keyed by `(namespace, name)`
+    qualified: BTreeMap<(&'src str, &'src str), &'src str>,
+}
+
+impl<'src> Scopes<'src, '_> {
+    fn resolve(&self, symbol: &Symbol<'src>) -> &'src str {
+        self.resolve_at(&symbol.span, symbol.name)
+    }
+
+    /// Resolves `name` as written at `span`.
+    fn resolve_at(&self, span: &Span, name: &'src str) -> &'src str {
+        let key = match name.split_once("::") {
+            Some(key) => key,
+            None => match self.enclosing(span) {
+                Some(namespace) => (namespace, name),
+                None => return name,
+            },
+        };
+
+        self.qualified.get(&key).copied().unwrap_or(name)
+    }
+
+    /// The namespace `span` lies within, if any.
+    fn enclosing(&self, span: &Span) -> Option<&'src str> {
+        self.namespaces
+            .iter()
+            .find(|ns| {
+                ns.span.context == span.context
+                    && ns.span.start <= span.start
+                    && span.end <= ns.span.end
+            })
+            .map(|ns| ns.inner.symbol.name)
+    }
+}
+
+/// Reports every `[crud]` tag, as the backend generated for the `rust` target only
+/// routes user-written API methods and would answer each CRUD route with a 404.
+pub fn check_rust_target<'src: 'p, 'p>(ast: &'p Ast<'src>) -> Vec<SemanticError<'p, 'p>> {
+    let table = SymbolTable::new(ast);
+    table
+        .models
+        .values()
+        .flat_map(|model| {
+            model
+                .symbol
+                .tags
+                .iter()
+                .filter(|tag| matches!(tag.inner, Tag::Crud { .. }))
+                .map(|tag| SemanticError::CrudUnsupportedByRustTarget {
+                    tag,
+                    model: &model.symbol,
+                })
+        })
+        .collect()
 }
 
 impl<'src, 'p> SymbolTable<'src, 'p> {
+    /// The declaration of a top level name: a model, plain old object, enum, binding, var, secret or inject.
+    pub fn global(&self, name: &str) -> Option<&'p Symbol<'src>> {
+        if let Some(model) = self.models.get(name) {
+            return Some(&model.symbol);
+        }
+        if let Some(poo) = self.poos.get(name) {
+            return Some(&poo.symbol);
+        }
+        if let Some(e) = self.enums.get(name) {
+            return Some(&e.symbol);
+        }
+        if let Some(kv) = self.kv_bindings.get(name) {
+            return Some(&kv.symbol);
+        }
+        if let Some(r2) = self.r2_bindings.get(name) {
+            return Some(&r2.symbol);
+        }
+        if let Some(durable) = self.durable_bindings.get(name) {
+            return Some(&durable.symbol);
+        }
+        if let Some(queue) = self.queue_bindings.get(name) {
+            return Some(&queue.symbol);
+        }
+        if let Some(service) = self.service_bindings.get(name) {
+            return Some(&service.symbol);
+        }
+
+        let d1 = self
+            .d1_bindings
+            .iter()
+            .flat_map(|b| b.bindings.iter().map(|b| &b.symbol));
+        let vars = self.vars_blocks.iter().flat_map(|b| &b.vars);
+        let injects = self.injects.iter().flat_map(|b| &b.symbols);
+        let secrets = self.secrets_blocks.iter().flat_map(|b| &b.secrets);
+        d1.chain(vars)
+            .chain(injects)
+            .chain(secrets)
+            .find(|s| s.name == name)
+    }
+
+    /// The declaration of a field on a model, e.g. `id` in `Dog::id`.
+    pub fn model_field(&self, model: &'src str, name: &'src str) -> Option<&'p Symbol<'src>> {
+        self.local
+            .get(&LocalSymbolKind::ModelField { model, name })
+            .copied()
+    }
+
+    /// The name a model, plain old object or enum reference is declared and generated under,
+    /// e.g. `billing_Invoice` for `billing::Invoice`, or for `Invoice` written inside `namespace billing`.
+    ///
+    /// Unknown names are returned as written.
+    pub fn resolve(&self, symbol: &Symbol<'src>) -> &'src str {
+        self.scopes.resolve(symbol)
+    }
+
+    /// Whether `err` only follows from a block that failed to parse, e.g. an unresolved
+    /// reference to a model whose declaration has a syntax error.
+    fn cascades_from_parse_error(&self, err: &SemanticError<'src, 'p>) -> bool {
+        let referenced = match err {
+            SemanticError::UnresolvedSymbol { symbol } => symbol.name,
+            SemanticError::GuardUnknownReference { guard } => guard.name,
+            SemanticError::DataSourceUnknownModelReference { model, .. } => model.name,
+            SemanticError::ApiUnknownNamespaceReference { api } => api.name,
+            SemanticError::ApiUnknownDataSourceReference { data_source, .. } => data_source.name,
+            _ => return false,
+        };
+
+        // Declarations in a failed block never reach their namespace's scope, so compare
+        // names without one
+        let name = referenced.rsplit("::").next().unwrap_or(referenced);
+        self.error_names.contains(&name)
+    }
+
+    /// As [Self::resolve], for a `name` written at `span`.
+    pub fn resolve_at(&self, span: &Span, name: &'src str) -> &'src str {
+        self.scopes.resolve_at(span, name)
+    }
+}
+
+impl<'p> SymbolTable<'p, 'p> {
+    /// Creates a [SymbolTable] for tooling (e.g. a language server), which has no use for
+    /// the [SemanticError::DuplicateSymbol] errors caught along the way.
+    pub fn new<'src: 'p>(ast: &'p Ast<'src>) -> Self {
+        Self::from_ast(ast, &mut ErrorSink::new())
+    }
+
     /// Creates a [SymbolTable] by walking the [Ast].
     ///
     /// Catches [SemanticError::DuplicateSymbol] errors.
-    fn from_ast(ast: &'p Ast<'src>, sink: &mut ErrorSink<'src, 'p>) -> Self {
+    fn from_ast(ast: &'p Ast<'p>, sink: &mut ErrorSink<'p, 'p>) -> Self {
         let mut st = SymbolTable::default();
         let mut global_names = HashMap::new();
 
-        // Insert a symbol into the global namespace, returning false if it was a duplicate.
-        let mut insert_global = |sink: &mut ErrorSink<'src, 'p>, symbol: &'p Symbol<'src>| {
-            if let Some(first) = global_names.insert(symbol.name, symbol) {
-                sink.push(SemanticError::DuplicateSymbol {
-                    first,
-                    second: symbol,
+        for spd in &ast.blocks {
+            if let AstBlockKind::Namespace(block) = &spd.inner {
+                st.scopes.namespaces.push(Spd {
+                    inner: block,
+                    span: spd.span,
                 });
-                return false;
+                for (name, qualified) in &block.qualified {
+                    st.scopes
+                        .qualified
+                        .insert((block.symbol.name, *name), qualified.as_str());
+                }
             }
-            true
-        };
+        }
+        let scopes = &st.scopes;
 
-        // Insert a symbol into the local namespace, returning false if it was a duplicate.
-        let mut insert_local = |sink: &mut ErrorSink<'src, 'p>,
-                                symbol: &'p Symbol<'src>,
-                                kind: LocalSymbolKind<'src>| {
-            if let Some(first) = st.local.insert(kind, symbol) {
+        // Insert a symbol into the global namespace, returning false if it was a duplicate.
+        let mut insert_global = |sink: &mut ErrorSink<'p, 'p>, symbol: &'p Symbol<'p>| {
+            if let Some(first) = global_names.insert(scopes.resolve(symbol), symbol) {
                 sink.push(SemanticError::DuplicateSymbol {
                     first,
                     second: symbol,
@@ -249,18 +485,38 @@ impl<'src, 'p> SymbolTable<'src, 'p> {
             true
         };
 
-        for block in ast.blocks.inners() {
+        // Insert a symbol into the local namespace, returning false if it was a duplicate.
+        let mut insert_local =
+            |sink: &mut ErrorSink<'p, 'p>, symbol: &'p Symbol<'p>, kind: LocalSymbolKind<'p>| {
+                if let Some(first) = st.local.insert(kind, symbol) {
+                    sink.push(SemanticError::DuplicateSymbol {
+                        first,
+                        second: symbol,
+                    });
+                    return false;
+                }
+                true
+            };
+
+        // Namespace members are declared alongside top level blocks, under their generated names
+        let blocks = ast.blocks.iter().flat_map(|spd| match &spd.inner {
+            AstBlockKind::Namespace(block) => block.blocks.as_slice(),
+            _ => std::slice::from_ref(spd),
+        });
+
+        for block in blocks.map(|spd| &spd.inner) {
             match block {
                 AstBlockKind::Model(model_block) => {
+                    let model_name = scopes.resolve(&model_block.symbol);
                     insert_global(sink, &model_block.symbol);
-                    st.models.insert(model_block.symbol.name, model_block);
+                    st.models.insert(model_name, model_block);
 
                     for symbol in model_block.blocks.iter().flat_map(|b| b.inner.symbols()) {
                         insert_local(
                             sink,
                             symbol,
                             LocalSymbolKind::ModelField {
-                                model: model_block.symbol.name,
+                                model: model_name,
                                 name: symbol.name,
                             },
                         );
@@ -271,36 +527,53 @@ impl<'src, 'p> SymbolTable<'src, 'p> {
                             sink,
                             arg,
                             LocalSymbolKind::ModelField {
-                                model: model_block.symbol.name,
+                                model: model_name,
                                 name: arg.name,
                             },
                         );
                     }
                 }
                 AstBlockKind::PlainOldObject(plain_old_object_block) => {
+                    let poo_name = scopes.resolve(&plain_old_object_block.symbol);
                     insert_global(sink, &plain_old_object_block.symbol);
-                    st.poos
-                        .insert(plain_old_object_block.symbol.name, plain_old_object_block);
+                    st.poos.insert(poo_name, plain_old_object_block);
 
                     for field in &plain_old_object_block.fields {
                         insert_local(
                             sink,
                             field,
                             LocalSymbolKind::PlainOldObjectField {
-                                poo: plain_old_object_block.symbol.name,
+                                poo: poo_name,
                                 name: field.name,
                             },
                         );
                     }
                 }
+                AstBlockKind::Enum(enum_block) => {
+                    let enum_name = scopes.resolve(&enum_block.symbol);
+                    insert_global(sink, &enum_block.symbol);
+                    st.enums.insert(enum_name, enum_block);
+
+                    for member in &enum_block.members {
+                        insert_local(
+                            sink,
+                            member,
+                            LocalSymbolKind::EnumMember {
+                                enum_name,
+                                name: member.name,
+                            },
+                        );
+                    }
+                }
                 AstBlockKind::Api(api_block) => {
+                    let namespace = scopes.resolve(&api_block.symbol);
                     st.apis.push(api_block);
                     for method in api_block.methods.inners() {
                         insert_local(
                             sink,
                             &method.symbol,
                             LocalSymbolKind::ApiMethodDecl {
-                                namespace: api_block.symbol.name,
+                                namespace,
                                 name: method.symbol.name,
                             },
                         );
@@ -310,7 +583,7 @@ impl<'src, 'p> SymbolTable<'src, 'p> {
                                 sink,
                                 param,
                                 LocalSymbolKind::ApiMethodParam {
-                                    namespace: api_block.symbol.name,
+                                    namespace,
                                     method: method.symbol.name,
                                     name: param.name,
                                 },
@@ -324,7 +597,7 @@ impl<'src, 'p> SymbolTable<'src, 'p> {
                         sink,
                         &data_source_block.symbol,
                         LocalSymbolKind::DataSourceDecl {
-                            model: data_source_block.model.name,
+                            model: scopes.resolve(&data_source_block.model),
                             name: data_source_block.symbol.name,
                         },
                     );
@@ -351,8 +624,8 @@ impl<'src, 'p> SymbolTable<'src, 'p> {
                 }
                 AstBlockKind::D1Binding(block) => {
                     st.d1_bindings.push(block);
-                    for symbol in &block.bindings {
-                        insert_global(sink, symbol);
+                    for binding in &block.bindings {
+                        insert_global(sink, &binding.symbol);
                     }
                 }
                 AstBlockKind::KvBinding(block) => {
@@ -450,18 +723,60 @@ impl<'src, 'p> SymbolTable<'src, 'p> {
                         }
                     }
                 }
+                AstBlockKind::QueueBinding(block) => {
+                    if insert_global(sink, &block.symbol) {
+                        st.queue_bindings.insert(block.symbol.name, block);
+                    }
+                }
+                AstBlockKind::ServiceBinding(block) => {
+                    if insert_global(sink, &block.symbol) {
+                        st.service_bindings.insert(block.symbol.name, block);
+                    }
+                }
+                AstBlockKind::Cron(block) => {
+                    st.crons.push(block);
+                }
+                AstBlockKind::Guard(block) => {
+                    if insert_global(sink, &block.symbol) {
+                        st.guards.insert(block.symbol.name, block);
+                    }
+                }
+                AstBlockKind::Env(block) => {
+                    // Environments are named apart from the global namespace
+                    match st.envs.get(block.symbol.name) {
+                        Some(first) => sink.push(SemanticError::DuplicateSymbol {
+                            first: &first.symbol,
+                            second: &block.symbol,
+                        }),
+                        None => {
+                            st.envs.insert(block.symbol.name, block);
+                        }
+                    }
+                }
                 AstBlockKind::Var(block) => {
                     st.vars_blocks.push(block);
                     for symbol in &block.vars {
                         insert_global(sink, symbol);
                     }
                 }
+                AstBlockKind::Secret(block) => {
+                    // Conflicts are reported by `env::analyze`, which tells a secret
+                    // shadowing a var apart from any other duplicate
+                    st.secrets_blocks.push(block);
+                }
                 AstBlockKind::Inject(inject_block) => {
                     st.injects.push(inject_block);
                     for symbol in &inject_block.symbols {
                         insert_global(sink, symbol);
                     }
                 }
+                AstBlockKind::Namespace(_) => {
+                    unreachable!("namespaces are flattened above and cannot be nested")
+                }
+                AstBlockKind::Error { symbol } => {
+                    // Reported by the parser, the rest of the Ast is still analyzed
+                    st.error_names.extend(symbol.as_ref().map(|s| s.name));
+                }
             }
         }
 
@@ -470,6 +785,7 @@ impl<'src, 'p> SymbolTable<'src, 'p> {
 }
 
 /// Resolves references inside of [CidlType::Object] and [CidlType::Partial] to ensure they point to a valid model or POO.
+/// An object type naming an enum resolves to [CidlType::Enum].
 ///
 /// Returns an error if the type cannot be resolved or is invalid.
 fn resolve_cidl_type<'src, 'p>(
@@ -479,14 +795,19 @@ fn resolve_cidl_type<'src, 'p>(
 ) -> Result<CidlType<'src>, SemanticError<'src, 'p>> {
     match cidl_type {
         CidlType::Object { name } => {
+            let name = table.scopes.resolve_at(&symbol.span, name);
+            if table.enums.contains_key(name) {
+                return Ok(CidlType::Enum { name });
+            }
             if table.models.contains_key(name) || table.poos.contains_key(name) {
-                return Ok(cidl_type.clone());
+                return Ok(CidlType::Object { name });
             }
             Err(SemanticError::UnresolvedSymbol { symbol })
         }
         CidlType::Partial { object_name } => {
+            let object_name = table.scopes.resolve_at(&symbol.span, object_name);
             if table.models.contains_key(object_name) || table.poos.contains_key(object_name) {
-                return Ok(cidl_type.clone());
+                return Ok(CidlType::Partial { object_name });
             }
             Err(SemanticError::UnresolvedSymbol { symbol })
         }
@@ -506,6 +827,31 @@ fn resolve_cidl_type<'src, 'p>(
     }
 }
 
+/// Resolves any [CidlType::Object] naming an enum into a [CidlType::Enum], leaving all
+/// other types as they are.
+///
+/// Used for SQL columns and parameters, where any other object type is rejected by [is_valid_sql_type].
+fn resolve_enum_type<'src>(symbol: &Symbol<'src>, table: &SymbolTable<'src, '_>) -> CidlType<'src> {
+    fn resolve<'src>(
+        span: &Span,
+        cidl_type: &CidlType<'src>,
+        table: &SymbolTable<'src, '_>,
+    ) -> CidlType<'src> {
+        match cidl_type {
+            CidlType::Object { name } => match table.scopes.resolve_at(span, name) {
+                name if table.enums.contains_key(name) => CidlType::Enum { name },
+                _ => cidl_type.clone(),
+            },
+            CidlType::Nullable(inner) => CidlType::nullable(resolve(span, inner, table)),
+            CidlType::Array(inner) => CidlType::array(resolve(span, inner, table)),
+            CidlType::KvObject(inner) => CidlType::KvObject(Box::new(resolve(span, inner, table))),
+            _ => cidl_type.clone(),
+        }
+    }
+
+    resolve(&symbol.span, &symbol.cidl_type, table)
+}
+
 /// Resolves validators for a given symbol, returning an error if
 /// any validator is invalid.
 fn resolve_validator_tags<'src, 'p>(
@@ -727,15 +1073,22 @@ fn resolve_inject<'src, 'p>(
             .d1_bindings
             .iter()
             .flat_map(|b| b.bindings.iter())
-            .any(|s| s.name == name)
+            .any(|b| b.symbol.name == name)
             || table.kv_bindings.contains_key(name)
             || table.r2_bindings.contains_key(name)
             || table.durable_bindings.contains_key(name)
+            || table.queue_bindings.contains_key(name)
+            || table.service_bindings.contains_key(name)
             || table
                 .vars_blocks
                 .iter()
                 .flat_map(|v| v.vars.iter())
                 .any(|s| s.name == name)
+            || table
+                .secrets_blocks
+                .iter()
+                .flat_map(|v| v.secrets.iter())
+                .any(|s| s.name == name)
             || table
                 .injects
                 .iter()
@@ -846,5 +1199,6 @@ fn is_valid_sql_type(cidl_type: &CidlType) -> bool {
             | CidlType::Blob
             | CidlType::Boolean
             | CidlType::DateIso
+            | CidlType::Enum { .. }
     )
 }
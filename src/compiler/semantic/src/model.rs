@@ -1,16 +1,18 @@
 use crate::{
     LocalSymbolKind, SymbolTable,
     err::{BatchResult, ErrorSink, SemanticError},
-    is_valid_sql_type, resolve_cidl_type, resolve_validator_tags,
+    guard, is_valid_sql_type, resolve_cidl_type, resolve_enum_type, resolve_validator_tags,
 };
 use frontend::{
-    Cardinality, ForeignBlock, KvFieldArgument, KvFieldBlock, ModelBlock, ModelBlockKind,
-    NavigationBlock, R2FieldBlock, SpdSlice, SqlBlockKind, Symbol, Tag,
+    ArgumentLiteral, Cardinality, DefaultValue, ForeignBlock, KvFieldArgument, KvFieldBlock,
+    ModelBlock, ModelBlockKind, NavigationBlock, R2FieldBlock, SpdSlice, SqlBlockKind, Symbol, Tag,
+    fmt_cidl_type,
 };
 use idl::{
-    BackingKind, BindingTemplate, CidlType, Column, Field, ForeignKeyReference, KvField, Model,
-    ModelBacking, NavigationCardinality, NavigationField, NavigationKeyMapping, R2Field,
-    TemplateSegment, ValidatedField, WranglerEnv,
+    BackingKind, BindingTemplate, CidlType, Column, ColumnDefault, CrudKind, Field,
+    ForeignKeyReference, KvField, Model, ModelBacking, ModelIndex, NavigationCardinality,
+    NavigationField, NavigationKeyMapping, Number, R2Field, TemplateSegment, ValidatedField,
+    WranglerEnv,
 };
 use indexmap::IndexMap;
 use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
@@ -20,6 +22,9 @@ pub struct ModelAnalysis<'src, 'p, 'sem> {
     sink: ErrorSink<'src, 'p>,
     in_degree: BTreeMap<&'src str, usize>,
     graph: BTreeMap<&'src str, Vec<&'src str>>,
+
+    /// Each required foreign key field, with its model and the model it references.
+    fk_fields: Vec<(&'src str, &'src str, &'p Symbol<'src>)>,
 }
 
 impl<'src, 'p, 'sem> ModelAnalysis<'src, 'p, 'sem> {
@@ -29,6 +34,7 @@ impl<'src, 'p, 'sem> ModelAnalysis<'src, 'p, 'sem> {
             sink: ErrorSink::new(),
             in_degree: BTreeMap::new(),
             graph: BTreeMap::new(),
+            fk_fields: Vec::new(),
         }
     }
 
@@ -38,20 +44,30 @@ impl<'src, 'p, 'sem> ModelAnalysis<'src, 'p, 'sem> {
     ) -> BatchResult<'src, 'p, IndexMap<&'src str, Model<'src>>> {
         let mut models: IndexMap<&'src str, Model<'src>> = IndexMap::new();
 
-        for &model_block in table.models.values() {
+        for (&model_name, &model_block) in &table.models {
             // Validate tags
             let mut dedup_cruds = HashSet::new();
             let mut cruds = Vec::new();
+            let mut crud_guards = Vec::new();
+            let mut guard_tags = Vec::new();
+            let mut count_tags = Vec::new();
             for tag in &model_block.symbol.tags {
                 match &tag.inner {
                     Tag::Crud { kinds } => {
+                        if kinds.iter().any(|k| k.inner == CrudKind::Count) {
+                            count_tags.push(tag);
+                        }
                         for kind in kinds {
                             if dedup_cruds.insert(kind.inner.clone()) {
                                 cruds.push(kind);
                             }
                         }
                     }
-                    Tag::Unique { .. } => {
+                    Tag::Guard { guard } => {
+                        guard::resolve(guard, table, &mut self.sink, &mut crud_guards);
+                        guard_tags.push(tag);
+                    }
+                    Tag::Unique { .. } | Tag::Index { .. } => {
                         if model_block.database_binding.is_none() {
                             self.sink.push(SemanticError::TagInvalidInContext {
                                 tag,
@@ -59,7 +75,7 @@ impl<'src, 'p, 'sem> ModelAnalysis<'src, 'p, 'sem> {
                             });
                         }
 
-                        // Unique constraints are validated in the ModelBuilder
+                        // Unique constraints and indexes are validated in the ModelBuilder
                     }
                     _ => self.sink.push(SemanticError::TagInvalidInContext {
                         tag,
@@ -68,12 +84,33 @@ impl<'src, 'p, 'sem> ModelAnalysis<'src, 'p, 'sem> {
                 }
             }
 
-            let builder = ModelBuilder::new(model_block);
+            // A model's guards only apply to its generated CRUD methods
+            if cruds.is_empty() {
+                for tag in guard_tags {
+                    self.sink.push(SemanticError::TagInvalidInContext {
+                        tag,
+                        symbol: &model_block.symbol,
+                    });
+                }
+            }
+
+            let builder = ModelBuilder::new(model_name, model_block);
             let Some(mut model) = builder.build(&mut self, table) else {
                 continue;
             };
 
+            // Only a SQLite backed model has rows to count
+            if !model.uses_sqlite() {
+                for tag in count_tags {
+                    self.sink.push(SemanticError::TagInvalidInContext {
+                        tag,
+                        symbol: &model_block.symbol,
+                    });
+                }
+            }
+
             model.cruds = cruds.into_iter().map(|c| c.inner.clone()).collect();
+            model.crud_guards = crud_guards;
             models.insert(model.name, model);
         }
 
@@ -86,8 +123,15 @@ impl<'src, 'p, 'sem> ModelAnalysis<'src, 'p, 'sem> {
                     a_rank.cmp(&b_rank).then_with(|| a_name.cmp(b_name))
                 });
             }
-            Err(e) => {
-                self.sink.push(e);
+            Err(cycle) => {
+                let fields = self
+                    .fk_fields
+                    .iter()
+                    .filter(|(model, adj, _)| cycle.contains(model) && cycle.contains(adj))
+                    .map(|(_, _, field)| *field)
+                    .collect();
+                self.sink
+                    .push(SemanticError::CyclicalRelationship { cycle, fields });
             }
         }
 
@@ -105,6 +149,7 @@ struct ModelBuilder<'src, 'p> {
     composite_seed: usize,
     primary_columns: Vec<Column<'src>>,
     columns: Vec<Column<'src>>,
+    indexes: Vec<ModelIndex<'src>>,
     navigation_fields: Vec<NavigationField<'src>>,
     kv_fields: Vec<KvField<'src>>,
     r2_fields: Vec<R2Field<'src>>,
@@ -113,9 +158,9 @@ struct ModelBuilder<'src, 'p> {
 }
 
 impl<'src, 'p> ModelBuilder<'src, 'p> {
-    pub fn new(model_block: &'p ModelBlock<'src>) -> Self {
+    pub fn new(name: &'src str, model_block: &'p ModelBlock<'src>) -> Self {
         Self {
-            name: model_block.symbol.name,
+            name,
             symbol: &model_block.symbol,
             model: model_block,
 
@@ -123,6 +168,7 @@ impl<'src, 'p> ModelBuilder<'src, 'p> {
             composite_seed: 0,
             primary_columns: Vec::new(),
             columns: Vec::new(),
+            indexes: Vec::new(),
             navigation_fields: Vec::new(),
             kv_fields: Vec::new(),
             r2_fields: Vec::new(),
@@ -163,7 +209,7 @@ impl<'src, 'p, 'sem> ModelBuilder<'src, 'p> {
                 .d1_bindings
                 .iter()
                 .flat_map(|b| b.bindings.iter())
-                .any(|s| s.name == binding_sym.name);
+                .any(|b| b.symbol.name == binding_sym.name);
 
             if !is_durable && !is_d1 {
                 // A model can't be backed by non DO/ D1 bindings
@@ -193,7 +239,7 @@ impl<'src, 'p, 'sem> ModelBuilder<'src, 'p> {
             match block {
                 ModelBlockKind::Column(symbols) => {
                     for symbol in symbols {
-                        self.column(ma, symbol, false);
+                        self.column(ma, table, symbol, false);
                     }
                 }
                 ModelBlockKind::Foreign(fk) => {
@@ -203,7 +249,7 @@ impl<'src, 'p, 'sem> ModelBuilder<'src, 'p> {
                     for block in blocks {
                         match &block.inner {
                             SqlBlockKind::Column(symbol) => {
-                                self.column(ma, symbol, true);
+                                self.column(ma, table, symbol, true);
                             }
                             SqlBlockKind::Foreign(foreign_block) => {
                                 self.foreign(ma, table, binding.unwrap().name, foreign_block, true)
@@ -216,7 +262,7 @@ impl<'src, 'p, 'sem> ModelBuilder<'src, 'p> {
                 }
                 ModelBlockKind::Route(symbols) => {
                     for symbol in symbols {
-                        self.route_field(ma, symbol);
+                        self.route_field(ma, table, symbol);
                     }
                 }
                 ModelBlockKind::Kv(_) | ModelBlockKind::R2(_) => {
@@ -234,10 +280,11 @@ impl<'src, 'p, 'sem> ModelBuilder<'src, 'p> {
         }
 
         for tag in &self.model.symbol.tags {
-            let Tag::Unique { fields: symbols } = &tag.inner else {
-                continue;
-            };
-            self.unique_constraint(ma, symbols);
+            match &tag.inner {
+                Tag::Unique { fields: symbols } => self.unique_constraint(ma, symbols),
+                Tag::Index { fields: symbols } => self.index(ma, symbols),
+                _ => {}
+            }
         }
 
         if needs_pk && self.primary_columns.is_empty() {
@@ -251,6 +298,7 @@ impl<'src, 'p, 'sem> ModelBuilder<'src, 'p> {
             backing: self.backing,
             primary_columns: self.primary_columns,
             columns: self.columns,
+            indexes: self.indexes,
             kv_fields: self.kv_fields,
             r2_fields: self.r2_fields,
             navigation_fields: self.navigation_fields,
@@ -339,10 +387,11 @@ impl<'src, 'p, 'sem> ModelBuilder<'src, 'p> {
     fn column(
         &mut self,
         ma: &mut ModelAnalysis<'src, 'p, 'sem>,
+        table: &SymbolTable<'src, 'p>,
         symbol: &'p Symbol<'src>,
         is_primary: bool,
     ) {
-        let cidl_type = symbol.cidl_type.clone();
+        let cidl_type = resolve_enum_type(symbol, table);
 
         if !is_valid_sql_type(&cidl_type) {
             ma.sink
@@ -371,6 +420,20 @@ impl<'src, 'p, 'sem> ModelBuilder<'src, 'p> {
             }
         };
 
+        let default = symbol.default.as_ref().and_then(|default| {
+            match resolve_column_default(&cidl_type, &default.inner, table) {
+                Ok(d) => Some(d),
+                Err(reason) => {
+                    ma.sink.push(SemanticError::InvalidColumnDefault {
+                        column: symbol,
+                        default,
+                        reason,
+                    });
+                    None
+                }
+            }
+        });
+
         let col = Column {
             hash: 0,
             field: ValidatedField {
@@ -381,6 +444,7 @@ impl<'src, 'p, 'sem> ModelBuilder<'src, 'p> {
             foreign_key_reference: None,
             unique_ids: Vec::new(),
             composite_id: None,
+            default,
         };
 
         if is_primary {
@@ -390,8 +454,13 @@ impl<'src, 'p, 'sem> ModelBuilder<'src, 'p> {
         }
     }
 
-    fn route_field(&mut self, ma: &mut ModelAnalysis<'src, 'p, 'sem>, symbol: &'p Symbol<'src>) {
-        let cidl_type = symbol.cidl_type.clone();
+    fn route_field(
+        &mut self,
+        ma: &mut ModelAnalysis<'src, 'p, 'sem>,
+        table: &SymbolTable<'src, 'p>,
+        symbol: &'p Symbol<'src>,
+    ) {
+        let cidl_type = resolve_enum_type(symbol, table);
 
         if !is_valid_sql_type(&cidl_type) {
             ma.sink
@@ -430,7 +499,8 @@ impl<'src, 'p, 'sem> ModelBuilder<'src, 'p> {
     ) {
         // Check that the adjacent model exists
         let adj_model_sym = &fk.model;
-        let Some(adj_model_block) = table.models.get(adj_model_sym.name) else {
+        let adj_model_name = table.resolve(adj_model_sym);
+        let Some(adj_model_block) = table.models.get(adj_model_name) else {
             ma.sink.push(SemanticError::UnresolvedSymbol {
                 symbol: adj_model_sym,
             });
@@ -476,7 +546,7 @@ impl<'src, 'p, 'sem> ModelBuilder<'src, 'p> {
 
             // Validate the field from the adjacent model
             let Some(adj_field_sym) = table.local.get(&LocalSymbolKind::ModelField {
-                model: adj_model_sym.name,
+                model: adj_model_name,
                 name: adj_field_sym.name,
             }) else {
                 ma.sink.push(SemanticError::UnresolvedSymbol {
@@ -485,7 +555,8 @@ impl<'src, 'p, 'sem> ModelBuilder<'src, 'p> {
                 continue;
             };
 
-            if !is_valid_sql_type(&adj_field_sym.cidl_type) {
+            let adj_cidl_type = resolve_enum_type(adj_field_sym, table);
+            if !is_valid_sql_type(&adj_cidl_type) {
                 ma.sink.push(SemanticError::ForeignKeyInvalidColumnType {
                     field: adj_field_sym,
                 });
@@ -494,11 +565,9 @@ impl<'src, 'p, 'sem> ModelBuilder<'src, 'p> {
             if !fk.is_optional {
                 // One To One: Person has a Dog ..(sql)=> Person has a fk to Dog
                 // Dog must come before Person
-                ma.graph
-                    .entry(adj_model_sym.name)
-                    .or_default()
-                    .push(self.name);
+                ma.graph.entry(adj_model_name).or_default().push(self.name);
                 *ma.in_degree.entry(self.name).or_insert(0) += 1;
+                ma.fk_fields.push((self.name, adj_model_name, field));
             }
 
             // No reason to push these errors, it will be caught during
@@ -510,18 +579,19 @@ impl<'src, 'p, 'sem> ModelBuilder<'src, 'p> {
                 field: ValidatedField {
                     name: field.name.into(),
                     cidl_type: if fk.is_optional {
-                        CidlType::nullable(adj_field_sym.cidl_type.clone())
+                        CidlType::nullable(adj_cidl_type)
                     } else {
-                        adj_field_sym.cidl_type.clone()
+                        adj_cidl_type
                     },
                     validators: adj_validators,
                 },
                 foreign_key_reference: Some(ForeignKeyReference {
-                    model_name: adj_model_sym.name,
+                    model_name: adj_model_name,
                     column_name: adj_field_sym.name,
                 }),
                 unique_ids: Vec::new(),
                 composite_id,
+                default: None,
             };
 
             if is_primary {
@@ -540,7 +610,8 @@ impl<'src, 'p, 'sem> ModelBuilder<'src, 'p> {
     ) {
         let field = &nav.field.inner;
 
-        let Some(target_block) = table.models.get(nav.model.name) else {
+        let target_name = table.resolve(&nav.model);
+        let Some(target_block) = table.models.get(target_name) else {
             ma.sink
                 .push(SemanticError::UnresolvedSymbol { symbol: &nav.model });
             return;
@@ -552,7 +623,7 @@ impl<'src, 'p, 'sem> ModelBuilder<'src, 'p> {
         let mut keys = Vec::with_capacity(nav.keys.len());
         for key in &nav.keys {
             let Some(target_field) = table.local.get(&LocalSymbolKind::ModelField {
-                model: nav.model.name,
+                model: target_name,
                 name: key.target.name,
             }) else {
                 ma.sink.push(SemanticError::UnresolvedSymbol {
@@ -613,9 +684,7 @@ impl<'src, 'p, 'sem> ModelBuilder<'src, 'p> {
             }
         }
 
-        let object = CidlType::Object {
-            name: target_block.symbol.name,
-        };
+        let object = CidlType::Object { name: target_name };
         let (cidl_type, cardinality) = match nav.cardinality {
             Cardinality::One => (object, NavigationCardinality::One),
             Cardinality::Many => (
@@ -629,7 +698,7 @@ impl<'src, 'p, 'sem> ModelBuilder<'src, 'p> {
                 name: field.name.into(),
                 cidl_type,
             },
-            model_reference: target_block.symbol.name,
+            model_reference: target_name,
             target_backing,
             cardinality,
             keys,
@@ -703,6 +772,47 @@ impl<'src, 'p, 'sem> ModelBuilder<'src, 'p> {
         }
     }
 
+    /// NOTE: Ran after all columns are processed
+    fn index(&mut self, ma: &mut ModelAnalysis<'src, 'p, 'sem>, fields: &'p [Symbol<'src>]) {
+        let mut columns: Vec<&'p Symbol<'src>> = Vec::new();
+        for field in fields {
+            let exists = self
+                .primary_columns
+                .iter()
+                .chain(&self.columns)
+                .any(|c| c.field.name == field.name);
+            if !exists {
+                ma.sink
+                    .push(SemanticError::UnresolvedSymbol { symbol: field });
+                continue;
+            }
+
+            if let Some(first) = columns.iter().find(|c| c.name == field.name) {
+                ma.sink.push(SemanticError::DuplicateSymbol {
+                    first,
+                    second: field,
+                });
+                continue;
+            }
+            columns.push(field);
+        }
+
+        if columns.is_empty() {
+            return;
+        }
+
+        let columns = columns.iter().map(|c| c.name).collect::<Vec<_>>();
+
+        // `$` can't appear in an identifier, so distinct column lists never share a name
+        let name = format!("idx${}${}", self.name, columns.join("$"));
+        if self.indexes.iter().any(|i| i.name == name) {
+            // An identical index was already declared
+            return;
+        }
+
+        self.indexes.push(ModelIndex { name, columns });
+    }
+
     /// NOTE: Ran after all columns are processed
     fn kv_field(
         &mut self,
@@ -1010,15 +1120,67 @@ impl<'src, 'p, 'sem> ModelBuilder<'src, 'p> {
     }
 }
 
+/// Resolves a column's `= value` against the column's type, returning the reason it
+/// is not assignable on failure.
+fn resolve_column_default<'src>(
+    cidl_type: &CidlType<'src>,
+    default: &DefaultValue<'src>,
+    table: &SymbolTable<'src, '_>,
+) -> Result<ColumnDefault<'src>, String> {
+    let inner = match cidl_type {
+        CidlType::Nullable(inner) => inner.as_ref(),
+        t => t,
+    };
+
+    let resolved = match (inner, default) {
+        (CidlType::Int, DefaultValue::Literal(ArgumentLiteral::Int(s))) => s
+            .parse()
+            .ok()
+            .map(|n| ColumnDefault::Number(Number::Int(n))),
+        (
+            CidlType::Real,
+            DefaultValue::Literal(ArgumentLiteral::Int(s) | ArgumentLiteral::Real(s)),
+        ) => s
+            .parse()
+            .ok()
+            .map(|n| ColumnDefault::Number(Number::Float(n))),
+        (CidlType::String | CidlType::DateIso, DefaultValue::Literal(ArgumentLiteral::Str(s))) => {
+            Some(ColumnDefault::String((*s).into()))
+        }
+        (CidlType::DateIso, DefaultValue::Ident("now")) => Some(ColumnDefault::Now),
+        (CidlType::Boolean, DefaultValue::Ident("true")) => Some(ColumnDefault::Boolean(true)),
+        (CidlType::Boolean, DefaultValue::Ident("false")) => Some(ColumnDefault::Boolean(false)),
+        (CidlType::Enum { name }, DefaultValue::Ident(member)) => table
+            .enums
+            .get(name)
+            .is_some_and(|e| e.members.iter().any(|m| m.name == *member))
+            .then(|| ColumnDefault::String((*member).into())),
+        _ => None,
+    };
+
+    resolved.ok_or_else(|| match inner {
+        CidlType::Int => "expected an integer".into(),
+        CidlType::Real => "expected a number".into(),
+        CidlType::String => "expected a string".into(),
+        CidlType::DateIso => "expected a date string or `now`".into(),
+        CidlType::Boolean => "expected `true` or `false`".into(),
+        CidlType::Enum { name } => format!("expected a member of enum '{name}'"),
+        t => format!(
+            "columns of type '{}' cannot have a default",
+            fmt_cidl_type(t)
+        ),
+    })
+}
+
 /// Kahns algorithm for topological sort + cycle detection.
 ///
 /// If no cycles, returns a map of name to position used for sorting
-/// the original collection.
-fn kahns<'src, 'p>(
+/// the original collection. Otherwise, returns the names on a cycle.
+fn kahns<'src>(
     graph: BTreeMap<&'src str, Vec<&'src str>>,
     mut in_degree: BTreeMap<&'src str, usize>,
     len: usize,
-) -> Result<HashMap<&'src str, usize>, SemanticError<'src, 'p>> {
+) -> Result<HashMap<&'src str, usize>, Vec<&'src str>> {
     let mut queue = in_degree
         .iter()
         .filter_map(|(&name, &deg)| (deg == 0).then_some(name))
@@ -1044,13 +1206,28 @@ fn kahns<'src, 'p>(
     }
 
     if rank.len() != len {
-        let cycle: Vec<&str> = in_degree
+        let mut cycle: Vec<&str> = in_degree
             .iter()
             .filter_map(|(&n, &d)| (d > 0).then_some(n))
             .collect();
 
+        // Models left over only depend on a cycle; prune those that nothing left
+        // depends on until just the cycle remains.
+        loop {
+            let before = cycle.len();
+            let remaining = cycle.clone();
+            cycle.retain(|n| {
+                graph
+                    .get(n)
+                    .is_some_and(|adjs| adjs.iter().any(|adj| remaining.contains(adj)))
+            });
+            if cycle.len() == before {
+                break;
+            }
+        }
+
         if !cycle.is_empty() {
-            return Err(SemanticError::CyclicalRelationship { cycle });
+            return Err(cycle);
         }
     }
 
@@ -0,0 +1,81 @@
+use std::{collections::BTreeMap, path::Path};
+
+use frontend::{Ast, AstBlockKind, FileTable};
+use idl::{CloesceIdl, ServiceBinding};
+
+use crate::{
+    SymbolTable,
+    err::{ErrorSink, SemanticError},
+};
+
+/// The CIDL source of each `service` block, keyed by binding name.
+///
+/// An `Err` holds the reason the CIDL could not be read.
+pub type ServiceCidls = BTreeMap<String, Result<String, String>>;
+
+/// Reads the CIDL of every `service` block in the [Ast], resolving each path
+/// relative to the file declaring it.
+pub fn read_service_cidls(ast: &Ast<'_>, file_table: &FileTable<'_>) -> ServiceCidls {
+    ast.blocks
+        .iter()
+        .filter_map(|block| match &block.inner {
+            AstBlockKind::ServiceBinding(service) => Some(service),
+            _ => None,
+        })
+        .map(|service| {
+            let (_, file) = file_table.resolve(service.cidl_path.span.context);
+            let path = file
+                .parent()
+                .unwrap_or(Path::new("."))
+                .join(service.cidl_path.inner);
+            let cidl =
+                std::fs::read_to_string(&path).map_err(|e| format!("{}: {e}", path.display()));
+            (service.symbol.name.to_string(), cidl)
+        })
+        .collect()
+}
+
+/// Builds a [ServiceBinding] for every `service` block from its CIDL in `services`.
+pub fn analyze<'src, 'p>(
+    table: &SymbolTable<'src, 'p>,
+    services: &'src ServiceCidls,
+    sink: &mut ErrorSink<'src, 'p>,
+) -> Vec<ServiceBinding<'src>> {
+    let mut bindings = Vec::new();
+    for block in table.service_bindings.values() {
+        let path = block.cidl_path.span;
+        let json = match services.get(block.symbol.name) {
+            Some(Ok(json)) => json,
+            Some(Err(reason)) => {
+                sink.push(SemanticError::ServiceMissingCidl {
+                    service: &block.symbol,
+                    path,
+                    reason: reason.clone(),
+                });
+                continue;
+            }
+            None => {
+                sink.push(SemanticError::ServiceMissingCidl {
+                    service: &block.symbol,
+                    path,
+                    reason: format!("{} was not found", block.cidl_path.inner),
+                });
+                continue;
+            }
+        };
+
+        match serde_json::from_str::<CloesceIdl<'src>>(json) {
+            Ok(cidl) => bindings.push(ServiceBinding {
+                name: block.symbol.name,
+                cidl: Box::new(cidl),
+            }),
+            Err(e) => sink.push(SemanticError::ServiceIncompatibleCidl {
+                service: &block.symbol,
+                path,
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    bindings
+}
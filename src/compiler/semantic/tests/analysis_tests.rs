@@ -1,14 +1,18 @@
 #![allow(unused_variables)]
 
 use compiler_test::lex_and_ast;
-use frontend::Ast;
+use frontend::{
+    Ast,
+    lexer::{self, LexTarget},
+    parser,
+};
 use idl::{
-    BackingKind, CidlType, CloesceIdl, MediaType, NavigationCardinality, Number, ParamSource,
-    TemplateSegment, Validator,
+    BackingKind, CidlType, CloesceIdl, ColumnDefault, MediaType, NavigationCardinality, Number,
+    ParamSource, TemplateSegment, Validator,
 };
-use semantic::err::SemanticError;
+use semantic::{ServiceCidls, err::SemanticError};
 
-fn analyze<'src, 'p>(ast: &'p Ast<'src>) -> (CloesceIdl<'src>, Vec<SemanticError<'src, 'p>>) {
+fn analyze<'src: 'p, 'p>(ast: &'p Ast<'src>) -> (CloesceIdl<'p>, Vec<SemanticError<'p, 'p>>) {
     match semantic::analyze(ast) {
         Ok(idl) => (idl, vec![]),
         Err(errors) => (CloesceIdl::default(), errors),
@@ -526,6 +530,14 @@ fn d1_model_cyclical_relationship_error() {
 
             one A::id(aId) { toA }
         }
+
+        model D for my_d1 {
+            primary { id: int }
+
+            foreign A::id {
+                aId
+            }
+        }
         "#,
     );
 
@@ -535,14 +547,13 @@ fn d1_model_cyclical_relationship_error() {
 
     // Assert
     assert_eq!(errors.len(), 1);
-    let cycle = expect_err!(errors,
-        SemanticError::CyclicalRelationship { cycle } => cycle.clone()
+    let (cycle, fields) = expect_err!(errors,
+        SemanticError::CyclicalRelationship { cycle, fields } => (cycle.clone(), fields.clone())
     );
-    // Cycle contains model names now
-    assert_eq!(cycle.len(), 3);
-    assert!(cycle.contains(&"A"));
-    assert!(cycle.contains(&"B"));
-    assert!(cycle.contains(&"C"));
+    // D only depends on the cycle, so is not part of it
+    assert_eq!(cycle, vec!["A", "B", "C"]);
+    let field_names = fields.iter().map(|f| f.name).collect::<Vec<_>>();
+    assert_eq!(field_names, vec!["bId2", "cId", "aId"]);
 }
 
 #[test]
@@ -1170,6 +1181,243 @@ fn api_header_param_source() {
     assert_eq!(body.source, ParamSource::Body);
 }
 
+#[test]
+fn api_route_query_and_path_params() {
+    // Arrange
+    let src = &with_env(
+        r#"
+        model User for my_d1 {
+            primary {
+                id: int
+            }
+        }
+
+        api User {
+            get posts "/users/{id}/posts/" -> string {
+                [path]
+                id: int
+
+                [query]
+                limit: option<int>
+            }
+        }
+    "#,
+    );
+    let parse = lex_and_ast(src);
+
+    // Act
+    let (result, errors) = analyze(&parse);
+
+    // Assert
+    assert_eq!(errors.len(), 0, "unexpected errors: {:#?}", errors);
+    let method = result
+        .models
+        .get("User")
+        .unwrap()
+        .apis
+        .iter()
+        .find(|m| m.name == "posts")
+        .unwrap();
+    assert_eq!(method.route, Some("users/{id}/posts"));
+
+    let sources = method
+        .parameters
+        .iter()
+        .map(|p| (p.field.name.as_ref(), p.source))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        sources,
+        vec![("id", ParamSource::Path), ("limit", ParamSource::Query)]
+    );
+}
+
+#[test]
+fn api_route_errors() {
+    // Arrange
+    let src = &with_env(
+        r#"
+        model User for my_d1 {
+            primary {
+                id: int
+            }
+        }
+
+        api User {
+            get unknownVariable "users/{id}" {}
+
+            get missingVariable "users" {
+                [path]
+                id: int
+            }
+
+            get partialSegment "users/id-{id}" {
+                [path]
+                id: int
+            }
+
+            self get onInstance "users/posts" {}
+
+            get objectQuery {
+                [query]
+                filter: json
+            }
+
+            get optionalPath "users/{id}/x" {
+                [path]
+                id: option<int>
+            }
+
+            get twoSources "users/{id}/y" {
+                [path]
+                [query]
+                id: int
+            }
+
+            get first "users/{a}/z" {
+                [path]
+                a: int
+            }
+
+            get second "users/{b}/z" {
+                [path]
+                b: int
+            }
+        }
+    "#,
+    );
+    let parse = lex_and_ast(src);
+
+    // Act
+    let (_, errors) = analyze(&parse);
+
+    // Assert
+    let variable = expect_err!(
+        errors,
+        SemanticError::ApiRouteUnknownParam { variable, .. } => *variable
+    );
+    assert_eq!(variable, "id");
+
+    let param = expect_err!(
+        errors,
+        SemanticError::ApiPathParamNotInRoute { param, .. } => param.name
+    );
+    assert_eq!(param, "id");
+
+    assert_eq!(
+        count_errs!(errors, SemanticError::ApiInvalidRoute { .. }),
+        2
+    );
+    assert_eq!(
+        count_errs!(errors, SemanticError::ApiInvalidParamSource { .. }),
+        2
+    );
+    expect_err!(errors, SemanticError::TagInvalidInContext { .. });
+
+    let (first, second) = expect_err!(
+        errors,
+        SemanticError::ApiOverlappingRoute { first, second } => (first.name, second.name)
+    );
+    assert_eq!((first, second), ("first", "second"));
+}
+
+#[test]
+fn api_overlapping_routes() {
+    // Arrange
+    let src = &with_env(
+        r#"
+        model User for my_d1 {
+            primary {
+                id: int
+            }
+        }
+
+        api User {
+            get byId "users/{id}" {
+                [path]
+                id: int
+            }
+
+            get me "users/me" {}
+
+            post create "users/me" {}
+        }
+    "#,
+    );
+    let parse = lex_and_ast(src);
+
+    // Act
+    let (_, errors) = analyze(&parse);
+
+    // Assert
+    assert_eq!(
+        count_errs!(errors, SemanticError::ApiOverlappingRoute { .. }),
+        1
+    );
+    let (first, second) = expect_err!(
+        errors,
+        SemanticError::ApiOverlappingRoute { first, second } => (first.name, second.name)
+    );
+    assert_eq!((first, second), ("byId", "me"));
+}
+
+#[test]
+fn api_route_shadows_derived_route() {
+    // Arrange
+    let src = &with_env(
+        r#"
+        [crud get]
+        model User for my_d1 {
+            primary {
+                id: int
+            }
+        }
+
+        api User {
+            get ping -> string {}
+
+            self get profile -> string {}
+
+            get shadowsStatic "User/{x}" {
+                [path]
+                x: string
+            }
+
+            get shadowsInstance "{a}/{b}/profile" {
+                [path]
+                a: string
+                [path]
+                b: int
+            }
+
+            post noClash "User/ping" {}
+        }
+    "#,
+    );
+    let parse = lex_and_ast(src);
+
+    // Act
+    let (_, errors) = analyze(&parse);
+
+    // Assert
+    let mut shadowed = errors
+        .iter()
+        .filter_map(|e| match e {
+            SemanticError::ApiRouteShadowsDerived { method, derived } => {
+                Some((method.name, derived.as_str()))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    shadowed.sort();
+    assert_eq!(
+        shadowed,
+        vec![
+            ("shadowsInstance", "User/{}/profile"),
+            ("shadowsStatic", "User/ping"),
+        ]
+    );
+}
+
 #[test]
 fn api_bare_self_defaults_to_default() {
     // Arrange
@@ -1294,6 +1542,75 @@ fn data_source_errors() {
     expect_err!(errors, SemanticError::DataSourceInvalidMethodParam { .. });
 }
 
+#[test]
+fn data_source_list_clause_errors() {
+    // Arrange
+    let src = &with_env(
+        r#"
+        model Post for my_d1 {
+            primary {
+                id: int
+            }
+            column {
+                title: string
+                archivedAt: option<date>
+            }
+        }
+
+        // Clauses alongside params
+        source MixedSource for Post {
+            include {}
+            list {
+                orderBy title
+                limit: int
+            }
+        }
+
+        // Unknown column, nullable order, duplicate order
+        source BadOrderSource for Post {
+            include {}
+            list {
+                orderBy bogus, archivedAt desc, title, title
+            }
+        }
+
+        // Unknown column, params collide with the pagination params
+        source BadWhereSource for Post {
+            include {}
+            list {
+                where missing == missing
+                where title == limit
+                where title == cursor
+            }
+        }
+    "#,
+    );
+    let parse = lex_and_ast(src);
+
+    // Act
+    let (_, errors) = analyze(&parse);
+
+    // Assert
+    expect_err!(errors, SemanticError::DataSourceListClauseOnStub { .. });
+    expect_err!(errors, SemanticError::DataSourceListNullableOrder { .. });
+    assert_eq!(
+        count_errs!(errors, SemanticError::DataSourceListDuplicateParam { .. }),
+        2
+    );
+    assert!(errors.iter().any(|e| matches!(
+        e,
+        SemanticError::DataSourceListUnknownColumn { column, .. } if column.name == "bogus"
+    )));
+    assert!(errors.iter().any(|e| matches!(
+        e,
+        SemanticError::DataSourceListUnknownColumn { column, .. } if column.name == "missing"
+    )));
+    assert!(errors.iter().any(|e| matches!(
+        e,
+        SemanticError::DuplicateSymbol { second, .. } if second.name == "title"
+    )));
+}
+
 #[test]
 fn data_source_include_tree_kv_r2() {
     // Arrange
@@ -1359,27 +1676,26 @@ fn poo_errors() {
 }
 
 #[test]
-fn cidl_types_resolve() {
+fn queue_bindings_resolve() {
     // Arrange
     let src = r#"
-        d1 { my_d1 }
-
-        model User for my_d1 {
-            primary {
-                id: int
-            }
+        poo EmailJob {
+            to: string
+            sentAt: date
         }
 
-        poo MyPoo {
-            field: string
+        queue Emails {
+            message: EmailJob
         }
 
-        api User {
-            self post resolveAll -> string {
-                p: array<MyPoo>
-                u: User
+        queue Pings {
+            message: array<int>
+        }
 
-                inject { my_d1 }
+        model M {}
+        api M {
+            post notify {
+                inject { Emails }
             }
         }
     "#;
@@ -1391,37 +1707,1140 @@ fn cidl_types_resolve() {
     // Assert
     assert_eq!(errors.len(), 0, "unexpected errors: {:#?}", errors);
 
-    let api = result.models.get("User").unwrap().apis.first().unwrap();
-    let param_types: Vec<_> = api
-        .parameters
-        .iter()
-        .map(|p| p.field.cidl_type.clone())
-        .collect();
+    let queues = &result.wrangler_env.queue_bindings;
+    assert_eq!(queues.len(), 2);
+    assert_eq!(queues[0].name, "Emails");
     assert_eq!(
-        param_types,
-        vec![
-            CidlType::Array(Box::new(CidlType::Object { name: "MyPoo" })),
-            CidlType::Object { name: "User" },
-        ]
+        queues[0].message.cidl_type,
+        CidlType::Object { name: "EmailJob" }
     );
-    assert_eq!(api.injected, vec!["my_d1"]);
+    assert_eq!(queues[1].name, "Pings");
+    assert_eq!(queues[1].message.cidl_type, CidlType::array(CidlType::Int));
+
+    let api = &result.models.get("M").unwrap().apis[0];
+    assert_eq!(api.injected, vec!["Emails"]);
 }
 
 #[test]
-fn fk_inherits_validators() {
+fn queue_binding_errors() {
     // Arrange
-    let src = with_env(
-        r#"
-        model User for my_d1 {
-            primary {
-                [gt 0]
-                id: int
-            }
+    let src = r#"
+        queue Uploads {
+            message: stream
+        }
 
-            column {
-                [lt 100]
-                age: int
-            }
+        queue Unknown {
+            message: NotAType
+        }
+
+        queue Uploads {
+            message: int
+        }
+    "#;
+
+    // Act
+    let parse = lex_and_ast(src);
+    let (_result, errors) = analyze(&parse);
+
+    // Assert
+    let queue = expect_err!(errors,
+        SemanticError::QueueInvalidMessageType { queue, .. } => queue);
+    assert_eq!(queue.name, "Uploads");
+
+    let symbol = expect_err!(errors,
+        SemanticError::UnresolvedSymbol { symbol } => symbol);
+    assert_eq!(symbol.name, "message");
+
+    expect_err!(errors, SemanticError::DuplicateSymbol { .. });
+    assert_eq!(errors.len(), 3);
+}
+
+#[test]
+fn cron_triggers_resolve() {
+    // Arrange
+    let src = r#"
+        d1 { db }
+        inject { Auth }
+
+        cron "*/15 9-17 * JAN-MAR,DEC MON-FRI" {
+            inject { db Auth }
+        }
+
+        cron "0 12 LW * *" {}
+        cron "30 2 * * 6#2,7L" {}
+    "#;
+
+    // Act
+    let parse = lex_and_ast(src);
+    let (result, errors) = analyze(&parse);
+
+    // Assert
+    assert_eq!(errors.len(), 0, "unexpected errors: {:#?}", errors);
+
+    let crons = &result.crons;
+    assert_eq!(crons.len(), 3);
+    assert_eq!(crons[0].expression, "*/15 9-17 * JAN-MAR,DEC MON-FRI");
+    assert_eq!(crons[0].injected, vec!["db", "Auth"]);
+    assert_eq!(crons[1].expression, "0 12 LW * *");
+    assert!(crons[1].injected.is_empty());
+    assert_eq!(crons[2].expression, "30 2 * * 6#2,7L");
+}
+
+#[test]
+fn cron_trigger_errors() {
+    // Arrange
+    let src = r#"
+        durable Counter {}
+
+        cron "0 24 * * *" {}
+        cron "* * * *" {}
+        cron "*/0 * * * *" {}
+        cron "0 0 * FOO *" {}
+        cron "5-1 * * * *" {}
+        cron "0 0 * * *" {
+            inject { Missing }
+        }
+        cron "0 0 * * *" {}
+        cron "0 1 * * *" {
+            inject { Counter::{} }
+        }
+    "#;
+
+    // Act
+    let parse = lex_and_ast(src);
+    let (_result, errors) = analyze(&parse);
+
+    // Assert
+    let invalid = errors
+        .iter()
+        .filter_map(|e| match e {
+            SemanticError::CronInvalidExpression { span, reason } => {
+                Some((&src[span.start..span.end], reason.as_str()))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        invalid,
+        vec![
+            ("24", "`24` is out of range for the hour field (0-23)"),
+            (
+                "* * * *",
+                "expected 5 fields (minute hour day-of-month month day-of-week), found 4"
+            ),
+            (
+                "0",
+                "`0` is not a valid step for the minute field, expected 1-60"
+            ),
+            ("FOO", "`FOO` is not a valid month value"),
+            ("5-1", "range start `5` is after its end `1`"),
+        ]
+    );
+
+    let symbol = expect_err!(errors,
+        SemanticError::UnresolvedSymbol { symbol } => symbol);
+    assert_eq!(symbol.name, "Missing");
+
+    let (first, second) = expect_err!(errors,
+        SemanticError::CronDuplicateExpression { first, second } => (first, second));
+    assert!(first.start < second.start);
+
+    let context = expect_err!(errors,
+        SemanticError::CronDurableContext { context } => context);
+    assert_eq!(context.name, "Counter");
+
+    assert_eq!(errors.len(), 8);
+}
+
+#[test]
+fn guards_resolve() {
+    // Arrange
+    let src = &with_env(
+        r#"
+        inject { Auth }
+
+        guard RequireAdmin {
+            inject { my_d1 Auth }
+        }
+
+        guard RequireUser {}
+
+        [crud get, list]
+        [guard RequireUser]
+        model User for my_d1 {
+            primary {
+                id: int
+            }
+        }
+
+        [guard RequireAdmin]
+        source Admin for User {
+            include {}
+
+            get {
+                [instance]
+                id: int
+            }
+        }
+
+        api User {
+            [guard RequireUser]
+            get ping -> string {}
+
+            [guard RequireUser]
+            [guard RequireUser]
+            self(Admin) post promote {}
+        }
+    "#,
+    );
+    let parse = lex_and_ast(src);
+
+    // Act
+    let (result, errors) = analyze(&parse);
+
+    // Assert
+    assert_eq!(errors.len(), 0, "unexpected errors: {:#?}", errors);
+
+    let guards = result
+        .guards
+        .iter()
+        .map(|g| (g.name, g.injected.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        guards,
+        vec![
+            ("RequireAdmin", vec!["my_d1", "Auth"]),
+            ("RequireUser", vec![]),
+        ]
+    );
+
+    let user = result.models.get("User").unwrap();
+    assert_eq!(user.crud_guards, vec!["RequireUser"]);
+    assert_eq!(
+        user.data_sources.get("Admin").unwrap().guards,
+        vec!["RequireAdmin"]
+    );
+
+    let method_guards = |name: &str| {
+        user.apis
+            .iter()
+            .find(|m| m.name == name)
+            .unwrap_or_else(|| panic!("method {name}"))
+            .guards
+            .clone()
+    };
+    assert_eq!(method_guards("ping"), vec!["RequireUser"]);
+
+    // The data source's guards run first, then the method's own, without duplicates.
+    assert_eq!(
+        method_guards("promote"),
+        vec!["RequireAdmin", "RequireUser"]
+    );
+
+    // CRUD methods run the model's guards after those of their data source.
+    assert_eq!(method_guards("$get"), vec!["RequireUser"]);
+    assert_eq!(
+        method_guards("$list_Admin"),
+        vec!["RequireAdmin", "RequireUser"]
+    );
+}
+
+#[test]
+fn guard_errors() {
+    // Arrange
+    let src = &with_env(
+        r#"
+        durable Counter {}
+
+        guard InContext {
+            inject { Counter::{} }
+        }
+
+        model User for my_d1 {
+            primary {
+                id: int
+            }
+        }
+
+        api User {
+            [guard Missing]
+            get ping -> string {}
+        }
+    "#,
+    );
+    let parse = lex_and_ast(src);
+
+    // Act
+    let (_result, errors) = analyze(&parse);
+
+    // Assert
+    let context = expect_err!(errors,
+        SemanticError::GuardDurableContext { context } => context);
+    assert_eq!(context.name, "Counter");
+
+    let guard = expect_err!(errors,
+        SemanticError::GuardUnknownReference { guard } => guard);
+    assert_eq!(guard.name, "Missing");
+
+    assert_eq!(errors.len(), 2);
+}
+
+#[test]
+fn guard_on_model_without_cruds() {
+    // Arrange
+    let src = &with_env(
+        r#"
+        guard RequireUser {}
+
+        [guard RequireUser]
+        model User for my_d1 {
+            primary {
+                id: int
+            }
+        }
+    "#,
+    );
+    let parse = lex_and_ast(src);
+
+    // Act
+    let (_result, errors) = analyze(&parse);
+
+    // Assert
+    // A model without CRUD methods has nothing for its guards to run before.
+    let symbol = expect_err!(errors,
+        SemanticError::TagInvalidInContext { symbol, .. } => symbol);
+    assert_eq!(symbol.name, "User");
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn count_crud_requires_sqlite() {
+    // Arrange
+    let src = &with_env(
+        r#"
+        [crud get, count]
+        model Session {
+            route {
+                id: string
+            }
+        }
+    "#,
+    );
+    let parse = lex_and_ast(src);
+
+    // Act
+    let (_result, errors) = analyze(&parse);
+
+    // Assert
+    // A model without a SQLite backing has no rows to count.
+    let symbol = expect_err!(errors,
+        SemanticError::TagInvalidInContext { symbol, .. } => symbol);
+    assert_eq!(symbol.name, "Session");
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn count_crud_rejects_stub_list() {
+    // Arrange
+    let src = &with_env(
+        r#"
+        [crud count]
+        model Post for my_d1 {
+            primary {
+                id: int
+            }
+        }
+
+        source Recent for Post {
+            include {}
+
+            list {
+                limit: int
+            }
+        }
+
+        [internal]
+        source Hidden for Post {
+            include {}
+
+            list {
+                limit: int
+            }
+        }
+    "#,
+    );
+    let parse = lex_and_ast(src);
+
+    // Act
+    let (_result, errors) = analyze(&parse);
+
+    // Assert
+    // A user-written list has no filters for `$count` to share; internal sources get no CRUD.
+    let source = expect_err!(errors,
+        SemanticError::DataSourceCountWithStubList { source } => source);
+    assert_eq!(source.name, "Recent");
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn analysis_after_parse_recovery_skips_cascading_errors() {
+    // Arrange
+    let src = r#"
+        d1 { db }
+
+        model Broken for db db {
+            primary { id: int }
+        }
+
+        model Person for db {
+            primary { id: int }
+            foreign Broken::id { brokenId }
+            one Broken::id(brokenId) { broken }
+        }
+
+        source Everyone for Broken {
+            include {}
+        }
+
+        poo Report {
+            person: Person
+            missing: Missing
+        }
+    "#;
+    let (lexed, file_table) = lexer::lex(vec![LexTarget {
+        src,
+        path: "<test>".into(),
+    }])
+    .unwrap_or_else(|_| panic!("lexing should succeed"));
+    let (ast, parse_errors) = parser::parse_recovering(&lexed, &file_table);
+    assert_eq!(parse_errors.len(), 1);
+
+    // Act
+    let errors = match semantic::analyze(&ast) {
+        Ok(_) => panic!("an Ast with a failed block should not analyze"),
+        Err(errors) => errors,
+    };
+
+    // Assert
+    // References to `Broken` follow from its parse error; only `Missing` is a real mistake.
+    let symbol = expect_err!(errors,
+        SemanticError::UnresolvedSymbol { symbol } => symbol);
+    assert_eq!(symbol.name, "missing");
+    assert_eq!(errors.len(), 1, "unexpected errors: {errors:#?}");
+}
+
+#[test]
+fn envs_resolve() {
+    // Arrange
+    let src = r#"
+        d1 {
+            db: "prod-db"
+            audit
+        }
+
+        var {
+            API_URL: string
+        }
+
+        env staging {
+            d1 {
+                db: "staging-db"
+                audit
+            }
+
+            var {
+                DEBUG: bool
+            }
+        }
+
+        env preview {}
+
+        model User for db {
+            primary {
+                id: int
+            }
+        }
+    "#;
+
+    // Act
+    let parse = lex_and_ast(src);
+    let (result, errors) = analyze(&parse);
+
+    // Assert
+    assert_eq!(errors.len(), 0, "unexpected errors: {:#?}", errors);
+    assert_eq!(result.wrangler_env.d1_databases.get("db"), Some(&"prod-db"));
+    assert_eq!(result.wrangler_env.vars.len(), 1);
+
+    let staging = &result.envs["staging"];
+    assert_eq!(staging.d1_bindings, vec!["db", "audit"]);
+    assert_eq!(staging.d1_databases.get("db"), Some(&"staging-db"));
+    assert!(!staging.d1_databases.contains_key("audit"));
+    assert_eq!(
+        staging
+            .vars
+            .iter()
+            .map(|v| v.name.as_ref())
+            .collect::<Vec<_>>(),
+        vec!["API_URL", "DEBUG"]
+    );
+
+    // No overlay inherits the top level
+    let preview = &result.envs["preview"];
+    assert_eq!(preview.d1_bindings, vec!["db", "audit"]);
+    assert_eq!(preview.d1_databases.get("db"), Some(&"prod-db"));
+    assert_eq!(preview.vars.len(), 1);
+}
+
+#[test]
+fn env_errors() {
+    // Arrange
+    let src = r#"
+        d1 { db audit }
+
+        var {
+            API_URL: string
+        }
+
+        env staging {
+            d1 {
+                audit
+                missing
+            }
+
+            var {
+                API_URL: string
+            }
+        }
+
+        env staging {}
+
+        model User for db {
+            primary {
+                id: int
+            }
+        }
+    "#;
+
+    // Act
+    let parse = lex_and_ast(src);
+    let (_result, errors) = analyze(&parse);
+
+    // Assert
+    let (env, model, binding) = expect_err!(errors,
+        SemanticError::EnvMissingBinding { env, model, binding } => (env, model, binding));
+    assert_eq!(env.name, "staging");
+    assert_eq!(model.name, "User");
+    assert_eq!(binding.name, "db");
+
+    let symbol = expect_err!(errors,
+        SemanticError::UnresolvedSymbol { symbol } => symbol);
+    assert_eq!(symbol.name, "missing");
+
+    let duplicates = errors
+        .iter()
+        .filter_map(|e| match e {
+            SemanticError::DuplicateSymbol { second, .. } => Some(second.name),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(duplicates, vec!["staging", "API_URL"]);
+
+    assert_eq!(errors.len(), 4);
+}
+
+#[test]
+fn secrets_resolve() {
+    // Arrange
+    let src = r#"
+        var {
+            API_URL: string
+        }
+
+        secret {
+            STRIPE_KEY: string
+        }
+
+        cron "0 * * * *" {
+            inject { STRIPE_KEY }
+        }
+    "#;
+
+    // Act
+    let parse = lex_and_ast(src);
+    let (result, errors) = analyze(&parse);
+
+    // Assert
+    assert_eq!(errors.len(), 0, "unexpected errors: {:#?}", errors);
+    assert_eq!(result.wrangler_env.vars.len(), 1);
+    assert_eq!(result.wrangler_env.secrets.len(), 1);
+    assert_eq!(result.wrangler_env.secrets[0].name, "STRIPE_KEY");
+    assert_eq!(result.wrangler_env.secrets[0].cidl_type, CidlType::String);
+    assert_eq!(result.crons[0].injected, vec!["STRIPE_KEY"]);
+}
+
+#[test]
+fn secret_errors() {
+    // Arrange
+    let src = r#"
+        var {
+            API_URL: string
+        }
+
+        secret {
+            API_URL: string
+            STRIPE_KEY: string
+            STRIPE_KEY: string
+            MAX_RETRIES: int
+        }
+
+        env staging {
+            var {
+                STRIPE_KEY: string
+            }
+        }
+    "#;
+
+    // Act
+    let parse = lex_and_ast(src);
+    let (_result, errors) = analyze(&parse);
+
+    // Assert
+    let conflicts = errors
+        .iter()
+        .filter_map(|e| match e {
+            SemanticError::SecretDeclaredAsVar { var, secret } => {
+                assert_eq!(var.name, secret.name);
+                Some(secret.name)
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(conflicts, vec!["API_URL", "STRIPE_KEY"]);
+
+    let second = expect_err!(errors,
+        SemanticError::DuplicateSymbol { second, .. } => second);
+    assert_eq!(second.name, "STRIPE_KEY");
+
+    let secret = expect_err!(errors,
+        SemanticError::SecretNotString { secret } => secret);
+    assert_eq!(secret.name, "MAX_RETRIES");
+
+    assert_eq!(errors.len(), 4);
+}
+
+#[test]
+fn namespaces_resolve() {
+    // Arrange
+    let src = r#"
+        d1 { db }
+
+        namespace billing {
+            enum Status { Open, Paid }
+
+            model Invoice for db {
+                primary { id: int }
+                column { status: Status }
+                foreign shared::Customer::id { customerId }
+                many LineItem::invoiceId(id) { items }
+            }
+
+            model LineItem for db {
+                primary { id: int }
+                foreign Invoice::id { invoiceId }
+            }
+
+            api Invoice {
+                self post pay -> Status {}
+            }
+        }
+
+        namespace shared {
+            model Customer for db {
+                primary { id: int }
+                many billing::Invoice::customerId(id) { invoices }
+            }
+        }
+
+        poo Invoice { total: real }
+
+        api billing::LineItem {
+            self get invoice -> billing::Invoice {}
+        }
+
+        source Recent for shared::Customer {
+            include { invoices }
+        }
+    "#;
+
+    // Act
+    let parse = lex_and_ast(src);
+    let (result, errors) = analyze(&parse);
+
+    // Assert
+    assert_eq!(errors.len(), 0, "unexpected errors: {:#?}", errors);
+    assert_eq!(
+        result.models.keys().copied().collect::<Vec<_>>(),
+        vec!["shared_Customer", "billing_Invoice", "billing_LineItem"]
+    );
+    assert!(result.poos.contains_key("Invoice"));
+    assert!(result.enums.contains_key("billing_Status"));
+
+    let invoice = result.models.get("billing_Invoice").unwrap();
+    assert_eq!(
+        invoice.columns[0].field.cidl_type,
+        CidlType::Enum {
+            name: "billing_Status"
+        }
+    );
+    assert_eq!(
+        invoice.columns[1]
+            .foreign_key_reference
+            .as_ref()
+            .unwrap()
+            .model_name,
+        "shared_Customer"
+    );
+    assert_eq!(
+        invoice.navigation_fields[0].model_reference,
+        "billing_LineItem"
+    );
+    assert!(invoice.apis.iter().any(|m| m.name == "pay"
+        && m.return_type
+            == CidlType::Enum {
+                name: "billing_Status"
+            }));
+
+    let line_item = result.models.get("billing_LineItem").unwrap();
+    assert!(line_item.apis.iter().any(|m| m.name == "invoice"
+        && m.return_type
+            == CidlType::Object {
+                name: "billing_Invoice"
+            }));
+
+    let customer = result.models.get("shared_Customer").unwrap();
+    assert_eq!(
+        customer.navigation_fields[0].model_reference,
+        "billing_Invoice"
+    );
+    assert!(customer.data_sources.contains_key("Recent"));
+}
+
+#[test]
+fn namespace_errors() {
+    // Arrange
+    let src = r#"
+        namespace billing {
+            poo Invoice { total: real }
+            poo Invoice { amount: real }
+            poo Receipt { invoice: Invoice }
+        }
+
+        namespace shared {
+            poo Customer { invoice: Invoice }
+        }
+
+        poo Order { invoice: billing::Missing }
+    "#;
+
+    // Act
+    let parse = lex_and_ast(src);
+    let (_result, errors) = analyze(&parse);
+
+    // Assert
+    let second = expect_err!(errors,
+        SemanticError::DuplicateSymbol { second, .. } => second);
+    assert_eq!(second.name, "Invoice");
+
+    let unresolved = errors
+        .iter()
+        .filter_map(|e| match e {
+            SemanticError::UnresolvedSymbol { symbol } => Some(symbol.name),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(unresolved, vec!["invoice", "invoice"]);
+
+    assert_eq!(errors.len(), 3);
+}
+
+#[test]
+fn service_bindings_resolve() {
+    // Arrange
+    let remote = compiler_test::src_to_idl(
+        r#"
+        model Session {}
+        api Session {
+            post login -> string {
+                name: string
+            }
+        }
+        "#,
+    )
+    .to_json();
+    let services = ServiceCidls::from([("Auth".to_string(), Ok(remote))]);
+
+    let src = r#"
+        service Auth from "../auth/.cloesce/cidl.json"
+
+        model M {}
+        api M {
+            post signIn {
+                inject { Auth }
+            }
+        }
+
+        cron "0 0 * * *" {
+            inject { Auth }
+        }
+    "#;
+
+    // Act
+    let parse = lex_and_ast(src);
+    let result = semantic::analyze_with_services(&parse, &services)
+        .unwrap_or_else(|errors| panic!("unexpected errors: {:#?}", errors));
+
+    // Assert
+    let bindings = &result.wrangler_env.service_bindings;
+    assert_eq!(bindings.len(), 1);
+    assert_eq!(bindings[0].name, "Auth");
+    assert_eq!(bindings[0].cidl.models["Session"].apis[0].name, "login");
+
+    assert_eq!(result.models["M"].apis[0].injected, vec!["Auth"]);
+    assert_eq!(result.crons[0].injected, vec!["Auth"]);
+}
+
+#[test]
+fn service_binding_errors() {
+    // Arrange
+    let services = ServiceCidls::from([
+        (
+            "Unbuilt".to_string(),
+            Err("unbuilt.json: No such file or directory".to_string()),
+        ),
+        ("Stale".to_string(), Ok(r#"{ "models": [] }"#.to_string())),
+    ]);
+
+    let src = r#"
+        service Unbuilt from "unbuilt.json"
+        service Stale from "stale.json"
+        service Unread from "unread.json"
+    "#;
+
+    // Act
+    let parse = lex_and_ast(src);
+    let errors = match semantic::analyze_with_services(&parse, &services) {
+        Ok(_) => panic!("expected errors"),
+        Err(errors) => errors,
+    };
+
+    // Assert
+    let missing = errors
+        .iter()
+        .filter_map(|e| match e {
+            SemanticError::ServiceMissingCidl {
+                service,
+                path,
+                reason,
+            } => Some((service.name, &src[path.start..path.end], reason.as_str())),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        missing,
+        vec![
+            (
+                "Unbuilt",
+                "\"unbuilt.json\"",
+                "unbuilt.json: No such file or directory"
+            ),
+            ("Unread", "\"unread.json\"", "unread.json was not found"),
+        ]
+    );
+
+    let service = expect_err!(errors,
+        SemanticError::ServiceIncompatibleCidl { service, .. } => service);
+    assert_eq!(service.name, "Stale");
+
+    assert_eq!(errors.len(), 3);
+}
+
+#[test]
+fn cidl_types_resolve() {
+    // Arrange
+    let src = r#"
+        d1 { my_d1 }
+
+        model User for my_d1 {
+            primary {
+                id: int
+            }
+        }
+
+        poo MyPoo {
+            field: string
+        }
+
+        api User {
+            self post resolveAll -> string {
+                p: array<MyPoo>
+                u: User
+
+                inject { my_d1 }
+            }
+        }
+    "#;
+
+    // Act
+    let parse = lex_and_ast(src);
+    let (result, errors) = analyze(&parse);
+
+    // Assert
+    assert_eq!(errors.len(), 0, "unexpected errors: {:#?}", errors);
+
+    let api = result.models.get("User").unwrap().apis.first().unwrap();
+    let param_types: Vec<_> = api
+        .parameters
+        .iter()
+        .map(|p| p.field.cidl_type.clone())
+        .collect();
+    assert_eq!(
+        param_types,
+        vec![
+            CidlType::Array(Box::new(CidlType::Object { name: "MyPoo" })),
+            CidlType::Object { name: "User" },
+        ]
+    );
+    assert_eq!(api.injected, vec!["my_d1"]);
+}
+
+#[test]
+fn enum_types_resolve() {
+    // Arrange
+    let src = r#"
+        d1 { my_d1 }
+
+        enum Status { Open, Closed }
+
+        model Ticket for my_d1 {
+            primary {
+                id: int
+            }
+
+            column {
+                status: Status
+                previous: option<Status>
+            }
+        }
+
+        model Comment for my_d1 {
+            primary {
+                id: int
+            }
+
+            foreign Ticket::status {
+                ticketStatus
+            }
+        }
+
+        poo Filter {
+            statuses: array<Status>
+        }
+
+        api Ticket {
+            get byStatus -> array<Ticket> {
+                status: Status
+
+                inject { my_d1 }
+            }
+        }
+    "#;
+
+    // Act
+    let parse = lex_and_ast(src);
+    let (result, errors) = analyze(&parse);
+
+    // Assert
+    assert_eq!(errors.len(), 0, "unexpected errors: {:#?}", errors);
+    assert_eq!(
+        result.enums.get("Status").unwrap().members,
+        ["Open", "Closed"]
+    );
+
+    let status = || CidlType::Enum { name: "Status" };
+    let ticket = result.models.get("Ticket").unwrap();
+    assert_eq!(ticket.columns[0].field.cidl_type, status());
+    assert_eq!(
+        ticket.columns[1].field.cidl_type,
+        CidlType::nullable(status())
+    );
+
+    let comment = result.models.get("Comment").unwrap();
+    assert_eq!(comment.columns[0].field.cidl_type, status());
+
+    let filter = result.poos.get("Filter").unwrap();
+    assert_eq!(filter.fields[0].cidl_type, CidlType::array(status()));
+
+    let api = ticket.apis.iter().find(|a| a.name == "byStatus").unwrap();
+    assert_eq!(api.parameters[0].field.cidl_type, status());
+}
+
+#[test]
+fn enum_errors() {
+    // Arrange
+    let src = r#"
+        enum Empty {}
+
+        enum Status { Open, Closed, Open }
+
+        poo Status {}
+
+        poo Form {
+            [regex /^O/]
+            status: Status
+        }
+    "#;
+
+    // Act
+    let parse = lex_and_ast(src);
+    let (_result, errors) = analyze(&parse);
+
+    // Assert
+    let symbol = expect_err!(errors, SemanticError::EmptyEnum { symbol } => symbol);
+    assert_eq!(symbol.name, "Empty");
+
+    let mut duplicates = errors
+        .iter()
+        .filter_map(|e| match e {
+            SemanticError::DuplicateSymbol { second, .. } => Some(second.name),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    duplicates.sort();
+    assert_eq!(duplicates, ["Open", "Status"]);
+
+    let symbol = expect_err!(errors,
+        SemanticError::ValidatorInvalidForType { symbol, .. } => symbol
+    );
+    assert_eq!(symbol.name, "status");
+}
+
+#[test]
+fn column_defaults_resolve() {
+    // Arrange
+    let src = r#"
+        d1 { my_d1 }
+
+        enum Status { Open, Closed }
+
+        model Ticket for my_d1 {
+            primary {
+                id: int
+            }
+
+            column {
+                score: int = 100
+                ratio: real = 1
+                title: string = "untitled"
+                open: bool = false
+                created: date = now
+                due: option<date> = "2026-01-01"
+                status: Status = Closed
+                plain: int
+            }
+        }
+    "#;
+
+    // Act
+    let parse = lex_and_ast(src);
+    let (result, errors) = analyze(&parse);
+
+    // Assert
+    assert!(errors.is_empty(), "{errors:?}");
+    let defaults = result.models["Ticket"]
+        .columns
+        .iter()
+        .map(|c| (c.field.name.as_ref(), c.default.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        defaults,
+        vec![
+            ("score", Some(ColumnDefault::Number(Number::Int(100)))),
+            ("ratio", Some(ColumnDefault::Number(Number::Float(1.0)))),
+            ("title", Some(ColumnDefault::String("untitled".into()))),
+            ("open", Some(ColumnDefault::Boolean(false))),
+            ("created", Some(ColumnDefault::Now)),
+            ("due", Some(ColumnDefault::String("2026-01-01".into()))),
+            ("status", Some(ColumnDefault::String("Closed".into()))),
+            ("plain", None),
+        ]
+    );
+}
+
+#[test]
+fn column_default_errors() {
+    // Arrange
+    let src = r#"
+        d1 { my_d1 }
+
+        enum Status { Open, Closed }
+
+        model Ticket for my_d1 {
+            primary {
+                id: int
+            }
+
+            column {
+                score: int = 1.5
+                open: bool = "yes"
+                created: date = today
+                status: Status = Pending
+                data: blob = "x"
+            }
+        }
+    "#;
+
+    // Act
+    let parse = lex_and_ast(src);
+    let (_result, errors) = analyze(&parse);
+
+    // Assert
+    let mut reasons = errors
+        .iter()
+        .filter_map(|e| match e {
+            SemanticError::InvalidColumnDefault { column, reason, .. } => {
+                Some((column.name, reason.as_str()))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    reasons.sort();
+    assert_eq!(
+        reasons,
+        vec![
+            ("created", "expected a date string or `now`"),
+            ("data", "columns of type 'blob' cannot have a default"),
+            ("open", "expected `true` or `false`"),
+            ("score", "expected an integer"),
+            ("status", "expected a member of enum 'Status'"),
+        ]
+    );
+}
+
+#[test]
+fn fk_inherits_validators() {
+    // Arrange
+    let src = with_env(
+        r#"
+        model User for my_d1 {
+            primary {
+                [gt 0]
+                id: int
+            }
+
+            column {
+                [lt 100]
+                age: int
+            }
         }
 
         model Post for my_d1 {
@@ -2017,6 +3436,129 @@ fn unique_tag() {
     );
 }
 
+#[test]
+fn index_tag() {
+    // Arrange
+    let src = with_env(
+        r#"
+    [index a, id]
+    [index b]
+    [index b]
+    model User for my_d1 {
+        primary {
+            id: int
+        }
+        column {
+            a: string
+            b: string
+        }
+    }
+    "#,
+    );
+
+    // Act
+    let parse = lex_and_ast(&src);
+    let (result, errors) = analyze(&parse);
+
+    // Assert
+    assert_eq!(errors.len(), 0, "unexpected errors: {:#?}", errors);
+    let user = result.models.get("User").unwrap();
+    let indexes = user
+        .indexes
+        .iter()
+        .map(|i| (i.name.as_str(), i.columns.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        indexes,
+        vec![
+            ("idx$User$a$id", vec!["a", "id"]),
+            ("idx$User$b", vec!["b"])
+        ],
+        "identical indexes are declared once"
+    );
+}
+
+#[test]
+fn index_names_are_unambiguous() {
+    // Arrange
+    let src = with_env(
+        r#"
+    [index bar_baz]
+    [index bar, baz]
+    model Foo for my_d1 {
+        primary {
+            id: int
+        }
+        column {
+            bar_baz: string
+            bar: string
+            baz: string
+        }
+    }
+
+    [index baz]
+    model Foo_bar for my_d1 {
+        primary {
+            id: int
+        }
+        column {
+            baz: string
+        }
+    }
+    "#,
+    );
+
+    // Act
+    let parse = lex_and_ast(&src);
+    let (result, errors) = analyze(&parse);
+
+    // Assert
+    assert_eq!(errors.len(), 0, "unexpected errors: {:#?}", errors);
+    let names = ["Foo", "Foo_bar"]
+        .iter()
+        .flat_map(|m| &result.models.get(*m).unwrap().indexes)
+        .map(|i| i.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec!["idx$Foo$bar_baz", "idx$Foo$bar$baz", "idx$Foo_bar$baz"]
+    );
+}
+
+#[test]
+fn index_tag_errors() {
+    // Arrange
+    let src = with_env(
+        r#"
+    [index missing]
+    [index a, a]
+    model User for my_d1 {
+        primary {
+            id: int
+        }
+        column {
+            a: string
+        }
+    }
+    "#,
+    );
+
+    // Act
+    let parse = lex_and_ast(&src);
+    let (_, errors) = analyze(&parse);
+
+    // Assert
+    let unresolved = expect_err!(errors,
+        SemanticError::UnresolvedSymbol { symbol } => symbol
+    );
+    assert_eq!(unresolved.name, "missing");
+
+    let dup = expect_err!(errors,
+        SemanticError::DuplicateSymbol { second, .. } => second
+    );
+    assert_eq!(dup.name, "a");
+}
+
 // Comprehensive test for cross-database relationships
 #[test]
 fn proposal_relationship_matrix() {
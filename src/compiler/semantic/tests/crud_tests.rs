@@ -1,5 +1,6 @@
-use compiler_test::src_to_idl;
-use idl::{ApiMethod, HttpVerb, Model, Number, Validator};
+use compiler_test::{lex_and_ast, src_to_idl};
+use idl::{ApiMethod, CidlType, HttpVerb, Model, Number, Validator};
+use semantic::err::SemanticError;
 
 fn find_method<'src>(model: &'src Model, name: &str) -> Option<&'src ApiMethod<'src>> {
     model
@@ -46,6 +47,115 @@ fn adds_crud_methods_to_models() {
     assert!(get_method.is_static);
 }
 
+#[test]
+fn delete_crud_takes_get_parameters() {
+    // Act
+    let idl = src_to_idl(
+        r#"
+        d1 { db }
+
+        [crud delete]
+        model OrderItem for db {
+            primary {
+                orderId: int
+                productId: int
+            }
+        }
+
+        source ByOrder for OrderItem {
+            include {}
+
+            get {
+                orderId: int
+            }
+        }
+    "#,
+    );
+
+    // Assert
+    let order_item = idl.models.get("OrderItem").unwrap();
+    assert!(find_method(order_item, "$get").is_none());
+
+    let delete = find_method(order_item, "$delete").unwrap();
+    assert!(matches!(delete.http_verb, HttpVerb::Delete));
+    assert!(matches!(delete.return_type, CidlType::Void));
+    assert_eq!(
+        delete
+            .parameters
+            .iter()
+            .map(|p| p.field.name.to_string())
+            .collect::<Vec<_>>(),
+        vec!["orderId", "productId"]
+    );
+
+    let by_order = find_method(order_item, "$delete_ByOrder").unwrap();
+    assert_eq!(
+        by_order
+            .parameters
+            .iter()
+            .map(|p| p.field.name.to_string())
+            .collect::<Vec<_>>(),
+        vec!["orderId"]
+    );
+}
+
+#[test]
+fn count_crud_takes_where_parameters() {
+    // Act
+    let idl = src_to_idl(
+        r#"
+        d1 { db }
+
+        [crud count]
+        model Post for db {
+            primary {
+                id: int
+            }
+
+            column {
+                status: string
+            }
+        }
+
+        source Published for Post {
+            include {}
+
+            list {
+                where status == status
+            }
+        }
+    "#,
+    );
+
+    // Assert
+    let post = idl.models.get("Post").unwrap();
+    assert!(find_method(post, "$list").is_none());
+
+    let count = find_method(post, "$count").unwrap();
+    assert!(matches!(count.http_verb, HttpVerb::Get));
+    assert!(matches!(count.return_type, CidlType::Int));
+    assert!(count.parameters.is_empty());
+
+    let published = find_method(post, "$count_Published").unwrap();
+    assert_eq!(
+        published
+            .parameters
+            .iter()
+            .map(|p| p.field.name.to_string())
+            .collect::<Vec<_>>(),
+        vec!["status"],
+        "a count filters like its source's list, without the pagination params"
+    );
+
+    let ds = &post.data_sources["Published"];
+    assert!(ds.count_plan.is_some());
+    assert!(
+        ds.count_explain.starts_with("SELECT PLAN (COUNT) `Post`"),
+        "count_explain should render an EXPLAIN header: {}",
+        ds.count_explain
+    );
+}
+
 #[test]
 fn crud_methods_namespace_sources_inherit_validators() {
     // Act
@@ -71,7 +181,7 @@ fn crud_methods_namespace_sources_inherit_validators() {
 
             list {
                 [step 10]
-                lastSeen_id: int
+                afterId: int
 
                 [gt 0]
                 limit: int
@@ -123,36 +233,36 @@ fn crud_methods_namespace_sources_inherit_validators() {
     {
         let method = find_method(product, "$list").unwrap();
 
-        let id = method
+        // The key's validators don't apply to the opaque cursor.
+        let cursor = method
             .parameters
             .iter()
-            .find(|p| p.field.name == "lastSeen_id")
+            .find(|p| p.field.name == "cursor")
             .unwrap();
-        assert!(
-            id.field
-                .validators
-                .first()
-                .map(|v| matches!(v, Validator::GreaterThan(Number::Int(0))))
-                .unwrap_or(false),
-        );
+        assert_eq!(cursor.field.cidl_type, CidlType::nullable(CidlType::String));
+        assert!(cursor.field.validators.is_empty());
         method
             .parameters
             .iter()
             .find(|p| p.field.name == "limit")
             .unwrap();
+        assert_eq!(
+            method.return_type,
+            CidlType::page(CidlType::Object { name: "Product" })
+        );
     }
 
     // $list_CustomDs
     {
         let method = find_method(product, "$list_CustomDs").unwrap();
 
-        let last_id = method
+        let after_id = method
             .parameters
             .iter()
-            .find(|p| p.field.name == "lastSeen_id")
+            .find(|p| p.field.name == "afterId")
             .unwrap();
         assert!(
-            last_id
+            after_id
                 .field
                 .validators
                 .first()
@@ -175,3 +285,34 @@ fn crud_methods_namespace_sources_inherit_validators() {
         );
     }
 }
+
+#[test]
+fn rust_target_rejects_crud() {
+    // Arrange
+    let src = r#"
+        d1 { db }
+
+        [crud get, list]
+        model Order for db {
+            primary { id: int }
+        }
+
+        model Plain for db {
+            primary { id: int }
+        }
+    "#;
+    let ast = lex_and_ast(src);
+
+    // Act
+    let errors = semantic::check_rust_target(&ast);
+
+    // Assert
+    let models = errors
+        .iter()
+        .map(|e| match e {
+            SemanticError::CrudUnsupportedByRustTarget { model, .. } => model.name,
+            other => panic!("unexpected error: {other:?}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(models, vec!["Order"]);
+}
@@ -1,4 +1,5 @@
 use compiler_test::src_to_idl;
+use idl::CidlType;
 
 #[test]
 fn default_data_source_tree_includes_all_relationships() {
@@ -456,12 +457,9 @@ fn default_data_source_composite_pk() {
         .collect();
     assert_eq!(get_params, vec!["orderId", "productId"]);
 
-    // LIST with seek pagination
+    // LIST pages with a single opaque cursor, whatever the key's width.
     let list_params: Vec<&str> = ds.list.parameters.iter().map(|p| p.name.as_ref()).collect();
-    assert_eq!(
-        list_params,
-        vec!["lastSeen_orderId", "lastSeen_productId", "limit"]
-    );
+    assert_eq!(list_params, vec!["cursor", "limit"]);
 }
 
 #[test]
@@ -668,7 +666,7 @@ fn default_data_source_durable_sqlite() {
     assert_eq!(get_params, vec!["tenantId", "id"]);
 
     let list_params: Vec<&str> = ds.list.parameters.iter().map(|p| p.name.as_ref()).collect();
-    assert_eq!(list_params, vec!["tenantId", "lastSeen_id", "limit"]);
+    assert_eq!(list_params, vec!["tenantId", "cursor", "limit"]);
 
     let save_params: Vec<&str> = ds.save.parameters.iter().map(|p| p.name.as_ref()).collect();
     assert_eq!(save_params, vec!["tenantId", "model"]);
@@ -689,3 +687,60 @@ fn default_data_source_durable_sqlite() {
     assert_eq!(target.binding, "LeaderboardDo");
     assert_eq!(target.shard_args, vec!["tenantId"]);
 }
+
+#[test]
+fn list_clauses_shape_generated_params() {
+    let idl = src_to_idl(
+        r#"
+        d1 { db }
+
+        model Post for db {
+            primary {
+                id: int
+            }
+
+            column {
+                createdAt: date
+                status: string
+            }
+        }
+
+        source Recent for Post {
+            include {}
+
+            list {
+                orderBy createdAt desc
+                where status == status
+            }
+        }
+        "#,
+    );
+
+    let post = idl.models.get("Post").unwrap();
+    let ds = post.data_sources.get("Recent").unwrap();
+    assert!(!ds.list.is_stub, "a list with clauses is generated");
+
+    let params = ds
+        .list
+        .parameters
+        .iter()
+        .map(|p| (p.name.to_string(), p.cidl_type.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        params,
+        vec![
+            ("status".to_string(), CidlType::String),
+            ("cursor".to_string(), CidlType::nullable(CidlType::String)),
+            ("limit".to_string(), CidlType::Int),
+        ],
+        "filter params, then the optional cursor, then limit"
+    );
+    assert!(
+        ds.list_plan
+            .as_ref()
+            .unwrap()
+            .to_string()
+            .contains(r#"ORDER BY \"createdAt\" DESC, \"id\" DESC"#),
+        "the precompiled list plan honors the orderBy"
+    );
+}
@@ -0,0 +1,33 @@
+import type { ScheduledController } from "@cloudflare/workers-types";
+import { Cidl, CronTrigger } from "../cidl.js";
+import { InternalError } from "../common.js";
+
+/**
+ * @internal
+ * Run the handler of the Cron Trigger that fired `controller`.
+ *
+ * A handler that injects bindings is called with `env` first, mirroring API routes;
+ * one that injects nothing receives only the `controller`.
+ */
+export async function runCron(
+  cidl: Cidl,
+  env: unknown,
+  controller: ScheduledController,
+  handlers: Record<string, (...args: any[]) => unknown>,
+): Promise<void> {
+  const cron = cidl.crons?.find((c: CronTrigger) => c.expression === controller.cron);
+  if (!cron) {
+    throw new InternalError(`Cron Trigger ${controller.cron} is not in the CIDL.`);
+  }
+
+  const handler = handlers[cron.expression];
+  if (!handler) {
+    throw new InternalError(`No handler was given for the Cron Trigger ${cron.expression}.`);
+  }
+
+  if (cron.injected.length > 0) {
+    await handler(env, controller);
+  } else {
+    await handler(controller);
+  }
+}
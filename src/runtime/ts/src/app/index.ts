@@ -1,11 +1,15 @@
 import { Cidl } from "../cidl.js";
-import { RuntimeContainer, router } from "../router/router.js";
+import { GuardImplementation, RuntimeContainer, router } from "../router/router.js";
 import { attachStores, overlayTraps } from "./store.js";
 import { durableSqlBatch } from "../router/orm.js";
 import { applyDurableMigrations, DurableMigration } from "../ui/migrations.js";
 import { DurableObjectState } from "@cloudflare/workers-types";
 
 export { attachStores, attachBinding } from "./store.js";
+export { queueHelpers, consumeQueue } from "./queue.js";
+export { runCron } from "./cron.js";
+export { serviceHelpers } from "./service.js";
+export type { ServiceClient } from "./service.js";
 
 /**
  * Attach the Cloesce RPC surface onto a Durable Object instance's prototype.
@@ -34,6 +38,10 @@ export function attachDurableRpc(host: object): void {
   proto.__cloesceKvPut = function (this: { ctx: DurableObjectState }, key: string, value: unknown) {
     this.ctx.storage.kv.put(key, value);
   };
+
+  proto.__cloesceKvDelete = function (this: { ctx: DurableObjectState }, key: string) {
+    this.ctx.storage.kv.delete(key);
+  };
 }
 
 /** Brand returned by the capability gate when a required binding is absent. */
@@ -71,6 +79,13 @@ export interface InjectableTag<Name extends string, T> {
   readonly __injectable: true;
 }
 
+/** Registration handle for a guard; `F` is the guard's hook signature. */
+export interface GuardTag<Name extends string, F> {
+  readonly __name: Name;
+  readonly __type?: F;
+  readonly __guard: true;
+}
+
 /** Build a model registration handle. */
 export function modelTag<Name extends string, Impl>(name: Name): ModelTag<Name, Impl> {
   return { __name: name } as ModelTag<Name, Impl>;
@@ -86,11 +101,16 @@ export function injectableTag<Name extends string, T>(name: Name): InjectableTag
   return { __name: name, __injectable: true } as InjectableTag<Name, T>;
 }
 
+/** Build a guard registration handle. */
+export function guardTag<Name extends string, F>(name: Name): GuardTag<Name, F> {
+  return { __name: name, __guard: true } as GuardTag<Name, F>;
+}
+
 /**
  * The typed assembly builder. Utilizes a phantom union to surface missing
  * bindings at compile time.
  *
- * - `register` supplies a model's implementation or an injectable's value widening the phantom `Reg` union.
+ * - `register` supplies a model's implementation, an injectable's value or a guard's hook, widening the phantom `Reg` union.
  * - `run` is not callable until `Reg` covers the host's owed set `Owed`.
  * - `Env` is the host's fully-upgraded environment, exposed via `env`.
  */
@@ -108,10 +128,11 @@ export interface AppBuilder<Owed extends string, Reg extends string, Env> {
   /**
    * Supply one thing the host owes:
    * - a model's implementation (`register(Model, impl)`)
-   * - an injectable's value (`register(Injectable, value)`).
+   * - an injectable's value (`register(Injectable, value)`)
+   * - a guard's hook (`register(Guard, hook)`).
    */
   register<Name extends Owed, T>(
-    tag: ModelTag<Name, T> | InjectableTag<Name, T>,
+    tag: ModelTag<Name, T> | InjectableTag<Name, T> | GuardTag<Name, T>,
     value: NoInfer<T>,
   ): AppBuilder<Owed, Reg | Name, Env>;
 
@@ -141,6 +162,7 @@ function overlayEnv(parent: any): any {
 export class RuntimeApp {
   readonly env: any;
   private registry = new Map<string, any>();
+  private guards = new Map<string, GuardImplementation>();
 
   /** Per-builder injectable values. Never written onto the shared `env` (see `register`). */
   private injected: Record<string, any> = {};
@@ -160,7 +182,7 @@ export class RuntimeApp {
   }
 
   /**
-   * Supply a model implementation or an injectable value.
+   * Supply a model implementation, an injectable value or a guard hook.
    */
   register(tag: any, value: any): RuntimeApp {
     if (tag && tag.__injectable) {
@@ -177,12 +199,24 @@ export class RuntimeApp {
       });
       return next;
     }
+    if (tag && tag.__guard) {
+      this.guards.set(nameOf(tag), value);
+      return this;
+    }
     this.registry.set(nameOf(tag), value);
     return this;
   }
 
   run(request: Request): Promise<Response> {
-    return router(request, this.cidl, this.workerUrl, this.env, this.registry, this.ctx);
+    return router(
+      request,
+      this.cidl,
+      this.workerUrl,
+      this.env,
+      this.registry,
+      this.ctx,
+      this.guards,
+    );
   }
 
   /** Force the ORM WASM module to initialize (for tests that read `env` before `run`). */
@@ -0,0 +1,122 @@
+import type {
+  Message,
+  MessageBatch,
+  MessageSendRequest,
+  Queue,
+  QueueSendBatchOptions,
+  QueueSendOptions,
+} from "@cloudflare/workers-types";
+import { Cidl, QueueBinding } from "../cidl.js";
+import { Either, InternalError } from "../common.js";
+import { RuntimeContainer } from "../router/router.js";
+import { WasmResource, invokeOrmWasm } from "../router/wasm.js";
+import { hydrateType } from "../router/orm.js";
+
+/** The native `send` / `sendBatch` of a queue binding, kept aside before they are upgraded. */
+const RAW_QUEUE = Symbol("cloesce.rawQueue");
+
+/**
+ * @internal
+ * Validate a queue message against the message type of its binding, returning
+ * the validated JSON value or the reason it is invalid.
+ */
+async function validateMessage(
+  cidl: Cidl,
+  binding: string,
+  body: unknown,
+): Promise<Either<string, unknown>> {
+  const queue = cidl.wrangler_env?.queue_bindings?.find((q: QueueBinding) => q.name === binding);
+  if (!queue) {
+    throw new InternalError(`Queue binding ${binding} is not in the CIDL.`);
+  }
+
+  await RuntimeContainer.init(cidl);
+  const { wasm } = RuntimeContainer.get();
+
+  return invokeOrmWasm(
+    wasm.validate_type,
+    [
+      WasmResource.fromString(JSON.stringify(queue.message), wasm),
+      WasmResource.fromString(JSON.stringify(body), wasm),
+    ],
+    wasm,
+  ).map((json) => JSON.parse(json));
+}
+
+/**
+ * @internal
+ * Typed `send` / `sendBatch` for a queue binding, validating every message before it is
+ * handed to the native binding. An invalid message rejects without sending anything.
+ */
+export function queueHelpers<T>(cidl: Cidl, binding: string, queue: Queue<T>) {
+  const raw: Pick<Queue<unknown>, "send" | "sendBatch"> = ((queue as any)[RAW_QUEUE] ??= {
+    send: queue.send.bind(queue),
+    sendBatch: queue.sendBatch.bind(queue),
+  });
+
+  const validate = async (body: T): Promise<unknown> => {
+    const res = await validateMessage(cidl, binding, body);
+    if (res.isLeft()) {
+      throw new TypeError(`Invalid message for queue ${binding}: ${res.unwrapLeft()}`);
+    }
+    return res.unwrap();
+  };
+
+  return {
+    send: async (body: T, options?: QueueSendOptions): Promise<void> => {
+      await raw.send(await validate(body), options);
+    },
+    sendBatch: async (
+      messages: Iterable<MessageSendRequest<T>>,
+      options?: QueueSendBatchOptions,
+    ): Promise<void> => {
+      const validated: MessageSendRequest<unknown>[] = [];
+      for (const message of messages) {
+        validated.push({ ...message, body: await validate(message.body) });
+      }
+      await raw.sendBatch(validated, options);
+    },
+  };
+}
+
+/**
+ * @internal
+ * Run `handler` on every message of a batch, after validating it against the message type
+ * of its binding.
+ *
+ * - A message is acknowledged once `handler` resolves.
+ * - A message that is invalid can never become valid, so it is logged and acknowledged.
+ * - A message whose `handler` throws is logged and retried.
+ */
+export async function consumeQueue<T>(
+  cidl: Cidl,
+  binding: string,
+  batch: MessageBatch<unknown>,
+  handler: (message: T, raw: Message<unknown>) => unknown,
+): Promise<void> {
+  const queue = cidl.wrangler_env?.queue_bindings?.find((q: QueueBinding) => q.name === binding);
+
+  for (const message of batch.messages) {
+    const res = await validateMessage(cidl, binding, message.body);
+    if (res.isLeft()) {
+      console.error(`Invalid message ${message.id} on queue ${binding}: ${res.unwrapLeft()}`);
+      message.ack();
+      continue;
+    }
+
+    const value = res.unwrap();
+    const body = hydrateType(value, queue!.message.cidl_type, {
+      idl: cidl,
+      includeTree: null,
+      env: undefined,
+    });
+
+    try {
+      await handler((body ?? value) as T, message);
+      message.ack();
+    } catch (e) {
+      console.error(`Message ${message.id} on queue ${binding} failed:`, e);
+      message.retry();
+    }
+  }
+}
@@ -0,0 +1,52 @@
+import type { Fetcher } from "@cloudflare/workers-types";
+
+/** A generated client method without its trailing `fetchImpl` parameter. */
+type WithoutFetch<F> = F extends (...args: infer A) => infer R
+  ? Required<A> extends [...infer P, typeof fetch]
+    ? (...args: P) => R
+    : F
+  : F;
+
+/**
+ * The static API methods of each model in a generated client module `M`, sending their
+ * requests through a service binding instead of the public network.
+ */
+export type ServiceClient<M> = {
+  [Model in keyof M]: {
+    [Method in Exclude<keyof M[Model], "prototype" | "fromJson">]: WithoutFetch<M[Model][Method]>;
+  };
+};
+
+/**
+ * @internal
+ * Bind the static API methods of the given `models` of a generated client module to a
+ * service binding, passing the binding's `fetch` as each method's `fetchImpl`.
+ *
+ * Instance methods are left as is; pass `env.<binding>.fetch` to them explicitly.
+ */
+export function serviceHelpers(
+  fetcher: Fetcher,
+  client: Record<string, any>,
+  models: string[],
+): Record<string, Record<string, (...args: unknown[]) => unknown>> {
+  const fetchImpl = ((input: any, init?: any) => fetcher.fetch(input, init)) as typeof fetch;
+
+  const helpers: Record<string, Record<string, (...args: unknown[]) => unknown>> = {};
+  for (const model of models) {
+    const cls = client[model];
+    const methods: Record<string, (...args: unknown[]) => unknown> = {};
+    for (const name of Object.getOwnPropertyNames(cls)) {
+      const fn = cls[name];
+      if (typeof fn !== "function" || name === "fromJson") {
+        continue;
+      }
+
+      // `fetchImpl` is the first (and only) defaulted parameter, so `fn.length` counts
+      // the parameters before it.
+      methods[name] = (...args: unknown[]) =>
+        fn.apply(cls, [...args.slice(0, fn.length), fetchImpl]);
+    }
+    helpers[model] = methods;
+  }
+  return helpers;
+}
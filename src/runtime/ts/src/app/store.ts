@@ -1,7 +1,7 @@
 import { Cidl, Model, DataSource, IncludeTree } from "../cidl.js";
 import { Orm } from "../router/orm.js";
 import { RuntimeContainer } from "../router/router.js";
-import { HttpResult } from "../ui/backend.js";
+import { HttpResult, Page } from "../ui/backend.js";
 import { CloesceError, CloesceResult, InternalError } from "../common.js";
 
 /**
@@ -104,11 +104,13 @@ function routeParams(model: Model, value: any): Record<string, unknown> {
 function buildSourceVerbs(env: any, cidl: Cidl, model: Model, ds: DataSource, registry: Registry) {
   const meta = model as any;
 
-  const userVerb = (verb: "get" | "list" | "save") => registry.get(model.name)?.[ds.name]?.[verb];
+  const userVerb = (verb: "get" | "list" | "count" | "save") =>
+    registry.get(model.name)?.[ds.name]?.[verb];
 
   const getNames = ds.get.parameters.map((p) => p.parameter.name);
   const listNames = ds.list.parameters.map((p) => p.name);
   const saveNames = ds.save.parameters.map((p) => p.name);
+  const countNames = ds.count.parameters.map((p) => p.name);
 
   const get = async (...args: unknown[]): Promise<HttpResult<any>> => {
     const override = userVerb("get");
@@ -123,7 +125,7 @@ function buildSourceVerbs(env: any, cidl: Cidl, model: Model, ds: DataSource, re
     return toHttp(res);
   };
 
-  const list = async (...args: unknown[]): Promise<HttpResult<any[]>> => {
+  const list = async (...args: unknown[]): Promise<HttpResult<Page<any>>> => {
     const override = userVerb("list");
     if (override) {
       return await coerceHttp(override(env, ...args));
@@ -139,6 +141,22 @@ function buildSourceVerbs(env: any, cidl: Cidl, model: Model, ds: DataSource, re
     return HttpResult.ok(200, res.value!);
   };
 
+  const count = async (...args: unknown[]): Promise<HttpResult<number>> => {
+    const override = userVerb("count");
+    if (override) {
+      return await coerceHttp(override(env, ...args));
+    }
+
+    await RuntimeContainer.init(cidl);
+
+    const params = zip(countNames, args);
+    const res = await Orm.fromEnv(env).count(meta, params, ds.count_plan as any);
+    if (res.errors.length > 0) {
+      return HttpResult.fail(400, CloesceError.displayErrors(res));
+    }
+    return HttpResult.ok(200, res.value!);
+  };
+
   const save = async (...args: unknown[]): Promise<HttpResult<any>> => {
     const override = userVerb("save");
     if (override) {
@@ -164,6 +182,23 @@ function buildSourceVerbs(env: any, cidl: Cidl, model: Model, ds: DataSource, re
     return toHttp(res);
   };
 
+  // A delete resolves the instance through `get` (honoring any override), then deletes
+  // everything the source's include tree reached.
+  const del = async (...args: unknown[]): Promise<HttpResult<void>> => {
+    const found = await get(...args);
+    if (!found.ok) {
+      return found as HttpResult<never>;
+    }
+
+    await RuntimeContainer.init(cidl);
+
+    const res = await Orm.fromEnv(env).delete(meta, found.data, ds.tree);
+    if (res.errors.length > 0) {
+      return HttpResult.fail(400, CloesceError.displayErrors(res));
+    }
+    return HttpResult.ok(200);
+  };
+
   const hydrate = async (row: any, ...rest: unknown[]): Promise<HttpResult<any>> => {
     await RuntimeContainer.init(cidl);
 
@@ -208,7 +243,7 @@ function buildSourceVerbs(env: any, cidl: Cidl, model: Model, ds: DataSource, re
     return toHttp(res);
   };
 
-  return { tree: ds.tree, get, list, save, hydrate, hydrateAll, load };
+  return { tree: ds.tree, get, list, count, save, delete: del, hydrate, hydrateAll, load };
 }
 
 /**
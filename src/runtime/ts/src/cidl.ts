@@ -3,7 +3,7 @@
  * TypeScript mirror of the Cloesce IDL (see `src/compiler/idl`).
  */
 
-export type CrudKind = "Save" | "Get" | "List";
+export type CrudKind = "Save" | "Get" | "List" | "Delete";
 
 export type CidlType =
   | "Void"
@@ -17,8 +17,10 @@ export type CidlType =
   | "Json"
   | "R2Object"
   | { Object: { name: string } }
+  | { Enum: { name: string } }
   | { Partial: { object_name: string } }
   | { KvObject: CidlType }
+  | { Page: CidlType }
   | { Nullable: CidlType }
   | { Array: CidlType };
 
@@ -50,8 +52,15 @@ export interface Column {
   foreign_key_reference: ForeignKeyReference | null;
   unique_ids: number[];
   composite_id: number | null;
+  default?: ColumnDefault | null;
 }
 
+export type ColumnDefault =
+  | { Number: Number }
+  | { Boolean: boolean }
+  | { String: string }
+  | "Now";
+
 export type NavigationCardinality = "One" | "Many";
 
 export interface NavigationKeyMapping {
@@ -92,7 +101,7 @@ export interface DurableTarget {
   shard_args: string[];
 }
 
-export type ParamSource = "Body" | "Header";
+export type ParamSource = "Body" | "Header" | "Query" | "Path";
 
 export interface ApiMethodParam {
   field: ValidatedField;
@@ -103,6 +112,8 @@ export interface ApiMethod {
   name: string;
   is_static: boolean;
   http_verb: HttpVerb;
+  /** A custom route relative to the Worker URL, e.g. `users/{id}/posts`. */
+  route?: string | null;
   return_media: MediaType;
   return_type: CidlType;
   parameters_media: MediaType;
@@ -110,6 +121,8 @@ export interface ApiMethod {
   data_source: string | null;
   injected: string[];
   durable_target?: DurableTarget | null;
+  /** Guards run, in order, before the method's `self` is hydrated. */
+  guards?: string[];
 }
 
 export type BackingKind = "D1" | "DurableObject";
@@ -176,9 +189,21 @@ export interface DataSource {
   get: DataSourceGetMethod;
   list: DataSourceMethod;
   save: DataSourceMethod;
+  count: DataSourceMethod;
   is_internal: boolean;
   get_plan?: unknown;
   list_plan?: unknown;
+  count_plan?: unknown;
+}
+
+export interface QueueBinding {
+  name: string;
+  message: ValidatedField;
+}
+
+export interface ServiceBinding {
+  name: string;
+  cidl: Cidl;
 }
 
 export interface WranglerEnv {
@@ -186,14 +211,35 @@ export interface WranglerEnv {
   kv_bindings: unknown[];
   r2_bindings: unknown[];
   durable_bindings: unknown[];
+  queue_bindings?: QueueBinding[];
+  service_bindings?: ServiceBinding[];
   vars: Field[];
+  secrets?: Field[];
+}
+
+export interface Enum {
+  name: string;
+  members: string[];
+}
+
+export interface CronTrigger {
+  expression: string;
+  injected: string[];
+}
+
+export interface Guard {
+  name: string;
+  injected: string[];
 }
 
 export interface Cidl {
   wrangler_env?: WranglerEnv;
   models: Record<string, Model>;
   poos: Record<string, PlainOldObject>;
+  enums?: Record<string, Enum>;
   injects: string[];
+  crons?: CronTrigger[];
+  guards?: Guard[];
 }
 
 /** @internal */
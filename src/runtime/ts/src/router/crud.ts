@@ -34,7 +34,7 @@ export function crudRoute(meta: Model, method: ApiMethod, env: any): ApiImplemen
  */
 function parseCrudName(
   name: string,
-): { verb: "get" | "list" | "save"; dataSourceName: string } | null {
+): { verb: "get" | "list" | "save" | "delete" | "count"; dataSourceName: string } | null {
   if (!name.startsWith("$")) {
     return null;
   }
@@ -43,8 +43,16 @@ function parseCrudName(
   const verb = (underscoreIdx === -1 ? rest : rest.slice(0, underscoreIdx)) as
     | "get"
     | "list"
-    | "save";
-  if (verb !== "get" && verb !== "list" && verb !== "save") {
+    | "save"
+    | "delete"
+    | "count";
+  if (
+    verb !== "get" &&
+    verb !== "list" &&
+    verb !== "save" &&
+    verb !== "delete" &&
+    verb !== "count"
+  ) {
     return null;
   }
   const dataSourceName = underscoreIdx === -1 ? "Default" : rest.slice(underscoreIdx + 1);
@@ -0,0 +1,55 @@
+import { interpolate, sinkResult, stepError, StorageResolver, templateArgs } from ".";
+import { CloesceErrorKind, CloesceResult } from "../../common";
+import type { Database, DeletePlan, DeleteStep } from "./plan";
+
+/**
+ * Execute a delete plan.
+ *
+ * Every argument is a literal from the deleted instance, so no step reads another's output.
+ * A later stage only exists to run after the rows it references are gone, so the first stage
+ * with a failed step halts the plan.
+ */
+export async function execute(plan: DeletePlan, storage: StorageResolver): Promise<CloesceResult<null>> {
+  const errors = [] as CloesceErrorKind[];
+
+  for (const stage of plan.stages) {
+    const settled = await Promise.allSettled(stage.steps.map((s) => step(s, storage)));
+    settled.forEach((res, i) => {
+      if (res.status === "rejected") {
+        errors.push(stepError(database(stage.steps[i]), res.reason));
+      }
+    });
+
+    if (errors.length > 0) {
+      break;
+    }
+  }
+  return sinkResult(null, errors);
+}
+
+async function step(step: DeleteStep, storage: StorageResolver): Promise<void> {
+  if ("SqlBatch" in step) {
+    const q = step.SqlBatch;
+    const statements = q.statements.map((s) => ({ sql: s.sql, bindings: s.arguments }));
+    await storage
+      .sql(
+        q.database,
+        q.shard.map(([, v]) => v),
+      )
+      .batch(statements);
+    return;
+  }
+
+  const q = step.KeyDelete;
+  const key = interpolate(q.segments, templateArgs(q.segments));
+  const store = storage.key(
+    q.database,
+    q.shard.map(([, v]) => v),
+  );
+  await store.delete(key);
+}
+
+/** The storage a step targets, for error typing. */
+function database(step: DeleteStep): Database {
+  return "SqlBatch" in step ? step.SqlBatch.database : step.KeyDelete.database;
+}
@@ -1,12 +1,13 @@
 /**
  * @internal
- * Runtime executor for the Cloesce query plan IR ({@link SelectPlan} / {@link SavePlan}).
+ * Runtime executor for the Cloesce query plan IR ({@link SelectPlan} / {@link SavePlan} / {@link DeletePlan}).
  */
 
 import type { CloesceErrorKind, CloesceResult } from "../../common.js";
-import type { SelectPlan, SavePlan, Database, TemplateSegment } from "./plan.js";
+import type { SelectPlan, SavePlan, DeletePlan, Database, TemplateSegment } from "./plan.js";
 import * as select from "./select.js";
 import * as save from "./save.js";
+import * as del from "./delete.js";
 
 export { MAX_BOUND_PARAMETERS } from "./select.js";
 
@@ -40,6 +41,7 @@ export interface SqlStore {
 export interface KeyStore {
   get(key: string): unknown;
   put(key: string, value: unknown, metadata?: unknown): unknown;
+  delete(key: string): unknown;
   getMany?(keys: string[]): Promise<Map<string, unknown>>;
 }
 
@@ -89,6 +91,20 @@ export async function executeSave(
   return save.execute(plan, storage);
 }
 
+/**
+ * Execute a delete plan.
+ *
+ * @param plan The delete plan to execute, generated during runtime by the WASM module.
+ * @param storage The storage resolver to use for resolving plan databases.
+ * @returns A `CloesceResult` with a null value and any errors encountered during execution.
+ */
+export async function executeDelete(
+  plan: DeletePlan,
+  storage: StorageResolver,
+): Promise<CloesceResult<null>> {
+  return del.execute(plan, storage);
+}
+
 /** Type a failed step's error by the storage it was targeting. */
 export function stepError(database: Database | null, error: unknown): CloesceErrorKind {
   switch (database?.kind) {
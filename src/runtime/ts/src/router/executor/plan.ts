@@ -34,6 +34,8 @@ export type MapCardinality = "One" | "Many";
 export interface SelectPlan {
   tables: TableDef[];
   stages: SelectStage[];
+  /** The columns a list pages over, each bound from a param decoded from its `cursor`. */
+  cursor?: string[];
 }
 
 export interface TableDef {
@@ -150,3 +152,34 @@ export type SaveQuery =
       };
     };
 //#endregion: Save IR
+
+//#region: Delete IR
+export interface DeletePlan {
+  stages: DeleteStage[];
+}
+
+export interface DeleteStage {
+  steps: DeleteStep[];
+}
+
+export interface DeleteStatement {
+  sql: string;
+  arguments: unknown[];
+}
+
+export type DeleteStep =
+  | {
+      SqlBatch: {
+        database: Database;
+        statements: DeleteStatement[];
+        shard: [string, unknown][];
+      };
+    }
+  | {
+      KeyDelete: {
+        database: Database;
+        segments: TemplateSegment<unknown>[];
+        shard: [string, unknown][];
+      };
+    };
+//#endregion: Delete IR
@@ -4,9 +4,10 @@ import { RuntimeContainer } from "./router.js";
 import { WasmResource, invokeOrmWasm } from "./wasm.js";
 import { Model, CidlType, Cidl, getNavigationCidlType } from "../cidl.js";
 import { CloesceError, CloesceResult, Either, InternalError, u8ToB64 } from "../common.js";
-import { DeepPartial, IncludeTree } from "../ui/backend.js";
+import { DeepPartial, IncludeTree, Page } from "../ui/backend.js";
 import {
   chunk,
+  executeDelete,
   executeSave,
   executeSelect,
   MAX_BULK_READ_KEYS,
@@ -14,7 +15,7 @@ import {
   type SqlStore,
   type StorageResolver,
 } from "./executor/index.js";
-import type { Database, SavePlan, SelectPlan } from "./executor/plan.js";
+import type { Database, DeletePlan, SavePlan, SelectPlan } from "./executor/plan.js";
 
 type HydrateArgs = {
   idl: Cidl;
@@ -29,6 +30,7 @@ type DurableStorage = {
   kv: {
     get(key: string): any;
     put(key: string, value: any): void;
+    delete(key: string): boolean;
     list(options?: { prefix?: string }): Iterable<[string, any]>;
   };
   transactionSync?<T>(closure: () => T): T;
@@ -61,9 +63,11 @@ export class Orm {
   }
 
   /**
-   * Load all matching `{@link Model}` rows and included relations.
+   * Load a page of `{@link Model}` rows and included relations.
    *
-   * - `params` carries the `limit` (and any shard/route keys) the list plan requires.
+   * - `params` carries the `limit`, the `cursor` of a previous page's `nextCursor` (absent
+   *   for the first page), and any shard/route keys the list plan requires.
+   * - A full page carries the cursor of the next; a short page is the last.
    * - A precompiled `plan` skips the WASM planning call when supplied.
    */
   async list<T extends object>(
@@ -71,11 +75,50 @@ export class Orm {
     params: Record<string, unknown>,
     includeTree: IncludeTree<T>,
     plan?: SelectPlan,
-  ): Promise<CloesceResult<T[]>> {
+  ): Promise<CloesceResult<Page<T>>> {
     includeTree ??= {} as IncludeTree<T>;
-    return this.runSelect(meta, "list", params, includeTree, plan, (body) => {
+
+    let selectPlan: SelectPlan;
+    let seek: Either<string, Record<string, unknown>>;
+    try {
+      selectPlan = plan ?? this.planSelect(meta, "list", includeTree);
+      seek = this.decodeCursor(selectPlan, params.cursor ?? null);
+    } catch (e) {
+      return CloesceError.generic(e);
+    }
+    if (seek.isLeft()) {
+      return CloesceError.cloesce(seek.value);
+    }
+
+    const seekParams = { ...params, ...seek.unwrap() };
+    return this.runSelect(meta, "list", seekParams, includeTree, selectPlan, (body) => {
       const rows = Array.isArray(body) ? body : [];
-      return rows.map((row) => this.coerce(meta, row, includeTree) as T);
+
+      // Encoded from the raw row, before coercion replaces its values.
+      const full = rows.length > 0 && rows.length === Number(params.limit);
+      const nextCursor = full ? this.encodeCursor(selectPlan, rows[rows.length - 1]) : null;
+
+      return {
+        results: rows.map((row) => this.coerce(meta, row, includeTree) as T),
+        nextCursor,
+      };
+    });
+  }
+
+  /**
+   * Count the `{@link Model}` rows matching the count plan's filters, without loading them.
+   *
+   * - `params` carries each `where` filter and any shard keys the count plan requires.
+   * - A precompiled `plan` skips the WASM planning call when supplied.
+   */
+  async count(
+    meta: Model,
+    params: Record<string, unknown>,
+    plan?: SelectPlan,
+  ): Promise<CloesceResult<number>> {
+    return this.runSelect(meta, "count", params, {}, plan, (body) => {
+      const row = body as { count?: unknown } | null;
+      return Number(row?.count ?? 0);
     });
   }
 
@@ -126,7 +169,7 @@ export class Orm {
 
   private async runSelect<R>(
     meta: Model,
-    op: "get" | "list",
+    op: "get" | "list" | "count",
     params: Record<string, unknown>,
     includeTree: IncludeTree<any>,
     plan: SelectPlan | undefined,
@@ -169,6 +212,29 @@ export class Orm {
     }
   }
 
+  /**
+   * Delete a hydrated `{@link Model}` instance and every relation the include tree reaches.
+   *
+   * `instance` must be the value returned by a `get` against the same include tree, so every
+   * key the delete plan addresses is present.
+   */
+  async delete<T extends object>(
+    meta: Model,
+    instance: T,
+    includeTree: IncludeTree<T>,
+  ): Promise<CloesceResult<null>> {
+    includeTree ??= {} as IncludeTree<T>;
+    const planRes = this.planDelete(meta, includeTree, instance);
+    if (planRes.isLeft()) {
+      return CloesceError.cloesce(planRes.value);
+    }
+    try {
+      return await executeDelete(planRes.unwrap(), this.storageResolver());
+    } catch (e) {
+      return CloesceError.generic(e);
+    }
+  }
+
   private planSelect(meta: Model, op: string, includeTree: IncludeTree<any>): SelectPlan {
     const { wasm } = RuntimeContainer.get();
     const res = invokeOrmWasm(
@@ -186,6 +252,40 @@ export class Orm {
     return JSON.parse(res.unwrap()) as SelectPlan;
   }
 
+  /** Decode a list's opaque `cursor` into the seek params `plan` binds. */
+  private decodeCursor(
+    plan: SelectPlan,
+    cursor: unknown,
+  ): Either<string, Record<string, unknown>> {
+    const { wasm } = RuntimeContainer.get();
+    const res = invokeOrmWasm(
+      wasm.decode_cursor,
+      [
+        WasmResource.fromString(JSON.stringify(plan.cursor ?? []), wasm),
+        WasmResource.fromString(JSON.stringify(cursor), wasm),
+      ],
+      wasm,
+    );
+    return res.map((json) => JSON.parse(json) as Record<string, unknown>);
+  }
+
+  /** Encode the cursor of the page after `row`, the last row of a list. */
+  private encodeCursor(plan: SelectPlan, row: unknown): string {
+    const { wasm } = RuntimeContainer.get();
+    const res = invokeOrmWasm(
+      wasm.encode_cursor,
+      [
+        WasmResource.fromString(JSON.stringify(plan.cursor ?? []), wasm),
+        WasmResource.fromString(JSON.stringify(row), wasm),
+      ],
+      wasm,
+    );
+    if (res.isLeft()) {
+      throw new InternalError(`Cursor encoding failed: ${res.value}`);
+    }
+    return res.unwrap();
+  }
+
   private planSave(
     meta: Model,
     includeTree: IncludeTree<any>,
@@ -208,6 +308,27 @@ export class Orm {
     return res.map((json) => JSON.parse(json) as SavePlan);
   }
 
+  private planDelete(
+    meta: Model,
+    includeTree: IncludeTree<any>,
+    instance: unknown,
+  ): Either<string, DeletePlan> {
+    const { wasm } = RuntimeContainer.get();
+    const res = invokeOrmWasm(
+      wasm.plan_delete,
+      [
+        WasmResource.fromString(meta.name, wasm),
+        WasmResource.fromString(JSON.stringify(includeTree), wasm),
+        WasmResource.fromString(
+          JSON.stringify(instance, (_, v) => (v instanceof Uint8Array ? u8ToB64(v) : v)),
+          wasm,
+        ),
+      ],
+      wasm,
+    );
+    return res.map((json) => JSON.parse(json) as DeletePlan);
+  }
+
   private storageResolver(): StorageResolver {
     const env = this.env;
     return {
@@ -343,6 +464,10 @@ class WorkerKvStore implements KeyStore {
       metadata: metadata as any,
     });
   }
+
+  delete(key: string): Promise<void> {
+    return this.namespace.delete(key);
+  }
 }
 
 class R2KeyStore implements KeyStore {
@@ -362,6 +487,10 @@ class R2KeyStore implements KeyStore {
   async put(key: string, value: unknown): Promise<void> {
     await this.bucket.put(key, value as any);
   }
+
+  delete(key: string): Promise<void> {
+    return this.bucket.delete(key);
+  }
 }
 
 class DurableKeyStore implements KeyStore {
@@ -384,6 +513,10 @@ class DurableKeyStore implements KeyStore {
   async put(key: string, value: unknown): Promise<void> {
     await this.stub.__cloesceKvPut(key, value);
   }
+
+  async delete(key: string): Promise<void> {
+    await this.stub.__cloesceKvDelete(key);
+  }
 }
 
 /** Resolve the stub for a DO shard from the raw shard values, mirroring the router's naming. */
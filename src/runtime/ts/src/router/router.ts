@@ -1,5 +1,14 @@
 import { OrmWasmExports, WasmResource, loadOrmWasm, invokeOrmWasm } from "./wasm.js";
-import { Cidl, Model, ApiMethod, DataSource, Field, ENV_DURABLE_TARGET_KEY } from "../cidl.js";
+import {
+  Cidl,
+  Model,
+  ApiMethod,
+  ApiMethodParam,
+  DataSource,
+  Field,
+  ParamSource,
+  ENV_DURABLE_TARGET_KEY,
+} from "../cidl.js";
 import { Either, InternalError } from "../common.js";
 import { HttpResult } from "../ui/backend.js";
 import { hydrateType } from "./orm.js";
@@ -65,15 +74,17 @@ enum RouterError {
   InvalidDatabaseQuery,
   ModelNotFound,
   UncaughtException,
+  RequestParamWrongSource,
 }
 
 /**
  * @internal
  * Runs the Cloesce router pipeline for one request: route match, validation,
- * self hydration, and method dispatch.
+ * guards, self hydration, and method dispatch.
  *
  * @param registry Maps a model name to its registered implementation module
  *   (route consts + custom / override data-source consts).
+ * @param guards Maps a guard name to its registered implementation.
  */
 export async function router(
   request: Request,
@@ -82,11 +93,12 @@ export async function router(
   env: any,
   registry: Map<string, any>,
   durableContext: unknown,
+  guards: Map<string, GuardImplementation> = new Map(),
 ): Promise<Response> {
   await RuntimeContainer.init(idl);
 
   try {
-    const result = await route(request, idl, workerUrl, env, registry, durableContext);
+    const result = await route(request, idl, workerUrl, env, registry, durableContext, guards);
 
     if (result instanceof Response) {
       // A forwarded Durable Object response is passed through unchanged.
@@ -128,6 +140,7 @@ async function route(
   env: any,
   registry: Map<string, any>,
   durableContext: unknown,
+  guards: Map<string, GuardImplementation>,
 ): Promise<HttpResult<unknown> | Response> {
   const { wasm } = RuntimeContainer.get();
 
@@ -144,6 +157,16 @@ async function route(
 
   const forwardRequest = route.forward ? request.clone() : undefined;
 
+  // Guards run on the host that dispatches the method, and before the body is validated so
+  // an unauthorized caller can't probe the request shape. A forwarded route is guarded by
+  // the Durable Object it's forwarded to.
+  if (!forwardRequest) {
+    const rejected = await runGuards(route, request.clone(), idl, env, guards);
+    if (rejected) {
+      return rejected;
+    }
+  }
+
   const validation = await validateRequest(request, wasm, idl, env, route);
   if (validation.isLeft()) {
     return validation.value;
@@ -180,11 +203,15 @@ function resolveImpl(
 /** @internal */
 export type ApiImplementation = (...args: unknown[]) => Promise<unknown> | unknown;
 
+/** @internal */
+export type GuardImplementation = (...args: unknown[]) => Promise<unknown> | unknown;
+
 /** @internal */
 export type MatchedRoute = {
   namespace: string;
   method: ApiMethod;
   getParamValues: Record<string, unknown>;
+  pathParamValues: Record<string, string>;
   forward: boolean;
   impl?: ApiImplementation;
   dataSource?: DataSource;
@@ -211,6 +238,39 @@ function matchRoute(
     if (parts.shift() !== p) return notFound(RouterError.UnknownPrefix);
   }
 
+  // Cloesce will mark requests that were forwarded to this router
+  // with the `cloesce-forwarded` header.
+  //
+  // If this header is present, we know that the request cannot be forwarded again
+  // and the impl must be present on this router.
+  //
+  // If the header is not present, but the method has a durable target, we can mark
+  // this route as one that should be forwarded.
+  const forwardedHeader = request.headers.get("cloesce-forwarded");
+  const shouldForward = (method: ApiMethod) =>
+    forwardedHeader === null && method.durable_target != null;
+
+  // Methods with a custom route are matched by their route alone
+  for (const model of Object.values(idl.models)) {
+    for (const method of model.apis) {
+      if (!method.route || request.method.toLowerCase() !== method.http_verb.toLowerCase()) {
+        continue;
+      }
+
+      const pathParamValues = matchRouteTemplate(method.route, parts);
+      if (!pathParamValues) continue;
+
+      return Either.right({
+        namespace: model.name,
+        method,
+        forward: shouldForward(method),
+        getParamValues: {},
+        pathParamValues,
+        model,
+      });
+    }
+  }
+
   if (parts.length < 2) {
     return notFound(RouterError.UnknownPrefix);
   }
@@ -225,7 +285,7 @@ function matchRoute(
     return notFound(RouterError.UnknownRoute);
   }
 
-  const method = model.apis.find((a) => a.name === methodName);
+  const method = model.apis.find((a) => a.name === methodName && !a.route);
   if (!method) {
     return notFound(RouterError.UnknownRoute);
   }
@@ -247,17 +307,7 @@ function matchRoute(
     }
   }
 
-  // Cloesce will mark requests that were forwarded to this router
-  // with the `cloesce-forwarded` header.
-  //
-  // If this header is present, we know that the request cannot be forwarded again
-  // and the impl must be present on this router.
-  //
-  // If the header is not present, but the method has a durable target, we can mark
-  // this route as one that should be forwarded.
-  const forwardedHeader = request.headers.get("cloesce-forwarded");
-  const hasDurableTarget = method.durable_target != null;
-  const forward = forwardedHeader === null && hasDurableTarget;
+  const forward = shouldForward(method);
 
   if (method.is_static) {
     return Either.right({
@@ -265,6 +315,7 @@ function matchRoute(
       method,
       forward,
       getParamValues: {},
+      pathParamValues: {},
       model,
     });
   }
@@ -280,11 +331,37 @@ function matchRoute(
     method,
     forward,
     getParamValues,
+    pathParamValues: {},
     dataSource,
     model,
   });
 }
 
+/**
+ * Matches the path segments of a request against a route template like `users/{id}/posts`.
+ * @returns the decoded value of each `{param}`, or undefined if the path does not match
+ */
+function matchRouteTemplate(route: string, parts: string[]): Record<string, string> | undefined {
+  const segments = route.split("/");
+  if (segments.length !== parts.length) return undefined;
+
+  const values: Record<string, string> = {};
+  for (let i = 0; i < segments.length; i++) {
+    const segment = segments[i];
+    if (!segment.startsWith("{")) {
+      if (segment !== parts[i]) return undefined;
+      continue;
+    }
+
+    try {
+      values[segment.slice(1, -1)] = decodeURIComponent(parts[i]);
+    } catch {
+      return undefined;
+    }
+  }
+  return values;
+}
+
 /**
  * Validates the request's body/search params against a ModelMethod
  * @returns 400 or a `RequestParamMap` consisting of each parameters name mapped to its value, and
@@ -320,22 +397,26 @@ async function validateRequest(
 
   const requiredParams = route.method.parameters;
 
-  // Extract all parameters
+  // A GET has no body, so its Body-sourced parameters are sent in the query string.
+  const isGet = route.method.http_verb === "Get";
+  const sourceOf = (p: ApiMethodParam): ParamSource =>
+    isGet && p.source === "Body" ? "Query" : p.source;
+
   const url = new URL(request.url);
-  let params: RequestParams = Object.fromEntries(url.searchParams.entries());
+  const query: RequestParams = Object.fromEntries(url.searchParams.entries());
+  let body: RequestParams = {};
 
   // A JSON body is only present when at least one parameter is Body-sourced.
   const hasBodyParams = requiredParams.some((p) => p.source === "Body");
 
-  if (
-    route.method.http_verb !== "Get" &&
-    (hasBodyParams || route.method.parameters_media === "Octet")
-  ) {
+  if (!isGet && (hasBodyParams || route.method.parameters_media === "Octet")) {
     try {
       switch (route.method.parameters_media) {
         case "Json": {
-          const body = await request.json<RequestParams>();
-          params = { ...params, ...body };
+          body = (await request.json<RequestParams>()) ?? {};
+          if (typeof body !== "object" || Array.isArray(body)) {
+            throw new Error("body is not an object");
+          }
           break;
         }
         case "Octet": {
@@ -345,7 +426,7 @@ async function validateRequest(
             (p) => typeof p.field.cidl_type === "string" && p.field.cidl_type === "Stream",
           )!;
 
-          params[streamParam.field.name] = request.body;
+          body = { [streamParam.field.name]: request.body };
           break;
         }
         default: {
@@ -357,11 +438,31 @@ async function validateRequest(
     }
   }
 
+  // Each parameter is read only from the source it declares; the same name arriving
+  // anywhere else is rejected rather than silently overriding or standing in for it.
+  const params: RequestParams = {};
   for (const p of requiredParams) {
-    if (p.source !== "Header") continue;
-    const raw = readHeader(request, p.field.name);
-    if (raw !== null) {
-      params[p.field.name] = raw;
+    const name = p.field.name;
+    const source = sourceOf(p);
+    const misplaced = (source !== "Query" && name in query) || (source !== "Body" && name in body);
+    if (misplaced) {
+      return invalidRequest(
+        RouterError.RequestParamWrongSource,
+        `Parameter ${name} must be sent in the ${source.toLowerCase()}`,
+      );
+    }
+
+    if (source === "Header") {
+      const raw = readHeader(request, name);
+      if (raw !== null) {
+        params[name] = raw;
+      }
+      continue;
+    }
+
+    const values = source === "Query" ? query : source === "Body" ? body : route.pathParamValues;
+    if (name in values) {
+      params[name] = values[name];
     }
   }
 
@@ -443,6 +544,49 @@ async function forward(
   return await stub.fetch(forwarded);
 }
 
+/**
+ * Runs a route's guards in order, stopping at the first to reject the request by returning
+ * a failed {@link HttpResult}. A guard that injects bindings is called with `env` first,
+ * mirroring API routes; one that injects nothing receives only the `request`. Guards run
+ * before validation and receive a clone, so reading its body leaves the original intact.
+ * @returns 501 if a guard has no implementation, 500 if one throws, the rejecting guard's
+ * result, or undefined if every guard passed.
+ */
+async function runGuards(
+  route: MatchedRoute,
+  request: Request,
+  idl: Cidl,
+  env: any,
+  guards: Map<string, GuardImplementation>,
+): Promise<HttpResult | undefined> {
+  for (const name of route.method.guards ?? []) {
+    const guard = guards.get(name);
+    if (!guard) {
+      return exit(
+        501,
+        RouterError.NotImplemented,
+        `Guard ${name} is declared in the schema but no implementation was provided.`,
+      ).unwrapLeft();
+    }
+
+    const injects = (idl.guards?.find((g) => g.name === name)?.injected.length ?? 0) > 0;
+    try {
+      const res = injects ? await guard(env, request) : await guard(request);
+      if (res instanceof HttpResult && !res.ok) {
+        return res;
+      }
+    } catch (e) {
+      return exit(
+        500,
+        RouterError.UncaughtException,
+        `Uncaught exception in guard ${name}: ${e instanceof Error ? e.message : String(e)}`,
+      ).unwrapLeft();
+    }
+  }
+
+  return undefined;
+}
+
 /**
  * Loads `self` for an instance route from the model's env store (default or `[source X]`
  * source), returning the hydrated value for method dispatch.
@@ -560,6 +704,7 @@ function exit(
 export const _cloesceInternal = {
   matchRoute,
   validateRequest,
+  runGuards,
   methodDispatch,
   RuntimeContainer,
   RouterError,
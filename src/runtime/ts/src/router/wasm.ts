@@ -34,6 +34,29 @@ export interface OrmWasmExports {
     payload_len: number,
   ): boolean;
 
+  plan_delete(
+    model_name_ptr: number,
+    model_name_len: number,
+    include_tree_ptr: number,
+    include_tree_len: number,
+    payload_ptr: number,
+    payload_len: number,
+  ): boolean;
+
+  decode_cursor(
+    columns_ptr: number,
+    columns_len: number,
+    cursor_ptr: number,
+    cursor_len: number,
+  ): boolean;
+
+  encode_cursor(
+    columns_ptr: number,
+    columns_len: number,
+    row_ptr: number,
+    row_len: number,
+  ): boolean;
+
   validate_type(
     validated_field_ptr: number,
     validated_field_len: number,
@@ -31,6 +31,19 @@ export class KValue<V> {
   }
 }
 
+/**
+ * One page of a generated `list`.
+ *
+ * @remarks
+ * - `nextCursor` is opaque: pass it back unchanged as the `cursor` of the next `list` to fetch
+ *   the following page.
+ * - `nextCursor` is `null` once a page comes back short of the `limit`, i.e. on the last page.
+ */
+export interface Page<T> {
+  results: T[];
+  nextCursor: string | null;
+}
+
 export type Primitive = string | number | boolean | bigint | symbol | null | undefined;
 export type IncludeTree<T> = T extends Primitive
   ? never
@@ -28,6 +28,7 @@ export function createIdl(args?: { models?: Model[] }): Cidl {
       get: { parameters: [], injected: [], is_stub: false },
       list: { parameters: [], injected: [], is_stub: false },
       save: { parameters: [], injected: [], is_stub: false },
+      count: { parameters: [], injected: [], is_stub: false },
       is_internal: false,
     };
   }
@@ -189,10 +190,12 @@ export class ModelBuilder {
     parameters: (Field & { source?: ParamSource })[],
     return_type: CidlType,
     data_source: string | null = null,
+    route: string | null = null,
   ): this {
     this.apis.push({
       name,
       http_verb,
+      route,
       is_static: data_source === null,
       parameters: parameters.map(({ source, ...f }) => ({
         field: { ...f, validators: [] },
@@ -222,6 +225,7 @@ export class ModelBuilder {
       },
       list: { parameters: [], injected: [], is_stub: false },
       save: { parameters: [], injected: [], is_stub: false },
+      count: { parameters: [], injected: [], is_stub: false },
       is_internal,
     };
     return this;
@@ -30,6 +30,7 @@ export class MockSqlStore implements SqlStore {
 export class MockKeyStore implements KeyStore {
   gets: string[] = [];
   puts: { key: string; value: unknown; metadata: unknown }[] = [];
+  deletes: string[] = [];
 
   constructor(private store: Map<string, unknown> = new Map()) {}
 
@@ -42,6 +43,11 @@ export class MockKeyStore implements KeyStore {
     this.puts.push({ key, value, metadata });
     this.store.set(key, value);
   }
+
+  delete(key: string): void {
+    this.deletes.push(key);
+    this.store.delete(key);
+  }
 }
 
 export class MockResolver implements StorageResolver {
@@ -95,6 +95,7 @@ describe("Match Route", () => {
     // Assert
     expect(res.unwrap()).toEqual({
       getParamValues: {},
+      pathParamValues: {},
       method: idl.models["Model"].apis.find((m) => m.name === "method"),
       model: idl.models["Model"],
       namespace: "Model",
@@ -122,6 +123,7 @@ describe("Match Route", () => {
     expect(res.unwrap()).toEqual({
       dataSource: idl.models["Model"].data_sources["ds"],
       getParamValues: { id: "0" },
+      pathParamValues: {},
       forward: false,
       model: idl.models["Model"],
       method: idl.models["Model"].apis.find((m) => m.name === "method"),
@@ -154,11 +156,65 @@ describe("Match Route", () => {
       dataSource: idl.models["Model"].data_sources["ds"],
       forward: false,
       getParamValues: { orgId: "acme", userId: "user123" },
+      pathParamValues: {},
       model: idl.models["Model"],
       method: idl.models["Model"].apis.find((m) => m.name === "method"),
       namespace: "Model",
     });
   });
+
+  test("Matches custom route", () => {
+    // Arrange
+    const request = createRequest("http://foo.com/api/users/42/posts", "GET");
+    const idl = createIdl({
+      models: [
+        ModelBuilder.model("Model")
+          .idPk()
+          .method(
+            "posts",
+            "Get",
+            [{ name: "id", cidl_type: "Int", source: "Path" }],
+            "Void",
+            null,
+            "users/{id}/posts",
+          )
+          .build(),
+      ],
+    });
+
+    // Act
+    const res = _cloesceInternal.matchRoute(request, idl, api);
+
+    // Assert
+    expect(res.unwrap()).toEqual({
+      getParamValues: {},
+      pathParamValues: { id: "42" },
+      method: idl.models["Model"].apis.find((m) => m.name === "posts"),
+      model: idl.models["Model"],
+      namespace: "Model",
+      forward: false,
+    });
+  });
+
+  test("Custom route is not served at the derived route => 404", () => {
+    // Arrange
+    const request = createRequest("http://foo.com/api/Model/posts", "GET");
+    const idl = createIdl({
+      models: [
+        ModelBuilder.model("Model")
+          .idPk()
+          .method("posts", "Get", [], "Void", null, "posts")
+          .build(),
+      ],
+    });
+
+    // Act
+    const res = _cloesceInternal.matchRoute(request, idl, api);
+
+    // Assert
+    expect(res.isLeft()).toBe(true);
+    expect(extractErrorCode(res.unwrapLeft().message)).toEqual(RouterError.UnknownRoute);
+  });
 });
 
 describe("Request Validation", () => {
@@ -176,6 +232,7 @@ describe("Request Validation", () => {
       model,
       method: model.apis.find((m) => m.name === "method")!,
       getParamValues: {},
+      pathParamValues: {},
       dataSource: model.data_sources["ds"],
       impl: mockImpl,
       forward: false,
@@ -207,6 +264,7 @@ describe("Request Validation", () => {
       namespace: "Foo",
       method: model.apis.find((m) => m.name === "method")!,
       getParamValues: {},
+      pathParamValues: {},
       impl: mockImpl,
       model,
       forward: false,
@@ -241,6 +299,7 @@ describe("Request Validation", () => {
       namespace: "Foo",
       method: model.apis.find((m) => m.name === "method")!,
       getParamValues: {},
+      pathParamValues: {},
       impl: mockImpl,
       model,
       forward: false,
@@ -259,6 +318,47 @@ describe("Request Validation", () => {
       RouterError.RequestBodyMissingParameters,
     );
   });
+
+  test.each([
+    ["Body param sent in the query string", "?id=1&payload=x", {}],
+    ["Query param sent in the body", "?id=1", { id: 2, payload: "x" }],
+  ])("%s => 400", async (_, search, body) => {
+    // Arrange
+    const request = createRequest(`http://foo.com/api/Foo/method${search}`, "POST", body);
+    const model = ModelBuilder.model("Foo")
+      .idPk()
+      .method(
+        "method",
+        "Post",
+        [
+          { name: "id", cidl_type: "Int", source: "Query" },
+          { name: "payload", cidl_type: "String", source: "Body" },
+        ],
+        "Void",
+      )
+      .build();
+
+    const route: MatchedRoute = {
+      namespace: "Foo",
+      method: model.apis.find((m) => m.name === "method")!,
+      getParamValues: {},
+      pathParamValues: {},
+      impl: mockImpl,
+      model,
+      forward: false,
+    };
+
+    const wasmMock = {} as any;
+    const idlMock = {} as any;
+    const envMock = {} as any;
+
+    // Act
+    const res = await _cloesceInternal.validateRequest(request, wasmMock, idlMock, envMock, route);
+
+    // Assert
+    expect(res.isLeft()).toBe(true);
+    expect(extractErrorCode(res.unwrapLeft().message)).toEqual(RouterError.RequestParamWrongSource);
+  });
 });
 
 describe("Method Dispatch", () => {
@@ -271,6 +371,7 @@ describe("Method Dispatch", () => {
       method: model.apis.find((m) => m.name === "testMethod")!,
       impl: () => {},
       getParamValues: {},
+      pathParamValues: {},
       model,
       forward: false,
     };
@@ -292,6 +393,7 @@ describe("Method Dispatch", () => {
       method: model.apis.find((m) => m.name === "testMethod")!,
       impl: () => HttpResult.ok(123, "foo"),
       getParamValues: {},
+      pathParamValues: {},
       model,
       forward: false,
     };
@@ -315,6 +417,7 @@ describe("Method Dispatch", () => {
       method: model.apis.find((m) => m.name === "testMethod")!,
       impl: () => "neigh",
       getParamValues: {},
+      pathParamValues: {},
       model,
       forward: false,
     };
@@ -340,6 +443,7 @@ describe("Method Dispatch", () => {
         throw new Error("boom");
       },
       getParamValues: {},
+      pathParamValues: {},
       model,
       forward: false,
     };
@@ -370,6 +474,7 @@ describe("Method Dispatch", () => {
       },
       impl,
       getParamValues: {},
+      pathParamValues: {},
       model,
       forward: false,
     };
@@ -390,6 +495,91 @@ describe("Method Dispatch", () => {
   });
 });
 
+describe("Guards", () => {
+  function guardedRoute(guards: string[]): MatchedRoute {
+    const model = ModelBuilder.model("Foo").idPk().method("testMethod", "Get", [], "Void").build();
+    return {
+      namespace: "Foo",
+      method: { ...model.apis.find((m) => m.name === "testMethod")!, guards },
+      impl: () => {},
+      getParamValues: {},
+      pathParamValues: {},
+      model,
+      forward: false,
+    };
+  }
+
+  test("Every guard passes => undefined", async () => {
+    // Arrange
+    const idl = { ...createIdl(), guards: [{ name: "A", injected: [] }] };
+    const request = createRequest(`${api}/Foo/testMethod`);
+    const guard = vi.fn(() => undefined);
+
+    // Act
+    const res = await _cloesceInternal.runGuards(
+      guardedRoute(["A"]),
+      request,
+      idl,
+      {},
+      new Map([["A", guard]]),
+    );
+
+    // Assert
+    expect(res).toBeUndefined();
+    expect(guard).toHaveBeenCalledWith(request);
+  });
+
+  test("Guard rejects => its result, later guards skipped", async () => {
+    // Arrange
+    const idl = {
+      ...createIdl(),
+      guards: [
+        { name: "A", injected: ["Auth"] },
+        { name: "B", injected: [] },
+      ],
+    };
+    const env = { Auth: { role: "user" } };
+    const request = createRequest(`${api}/Foo/testMethod`);
+    const a = vi.fn(() => HttpResult.fail(403, "Forbidden"));
+    const b = vi.fn();
+
+    // Act
+    const res = await _cloesceInternal.runGuards(
+      guardedRoute(["A", "B"]),
+      request,
+      idl,
+      env,
+      new Map([
+        ["A", a],
+        ["B", b],
+      ]),
+    );
+
+    // Assert
+    expect(res?.status).toBe(403);
+    expect(a).toHaveBeenCalledWith(env, request);
+    expect(b).not.toHaveBeenCalled();
+  });
+
+  test("Guard not registered => 501", async () => {
+    // Arrange
+    const idl = { ...createIdl(), guards: [{ name: "A", injected: [] }] };
+
+    // Act
+    const res = await _cloesceInternal.runGuards(
+      guardedRoute(["A"]),
+      createRequest(`${api}/Foo/testMethod`),
+      idl,
+      {},
+      new Map(),
+    );
+
+    // Assert
+    expect(res?.status).toBe(501);
+    expect(extractErrorCode(res?.message)).toBe(RouterError.NotImplemented);
+  });
+});
+
 describe("Forwarding", () => {
   function createDurableIdl() {
     const method = {
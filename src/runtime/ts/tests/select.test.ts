@@ -907,17 +907,17 @@ describe("executeSelect buffer merge + assemble ordering", () => {
 });
 
 describe("executeSelect seek pagination", () => {
-  test("binds lastSeen_* and limit params into the SQL args", async () => {
+  test("binds cursor and limit params into the SQL args", async () => {
     const store = new MockSqlStore(() => [{ id: 6 }]);
     const resolver = new MockResolver(() => store);
     const plan = selectPlan([
       sqlStep([], 'SELECT * FROM M WHERE "id" > ?1 ORDER BY "id" LIMIT ?2', {
-        args: [param("lastSeen_id"), param("limit")],
+        args: [param("$cursor_id"), param("limit")],
         mapping: many,
       }),
     ]);
 
-    await executeSelectOk(plan, { lastSeen_id: 5, limit: 20 }, resolver);
+    await executeSelectOk(plan, { $cursor_id: 5, limit: 20 }, resolver);
 
     expect(store.queries).toEqual([
       {
@@ -1125,14 +1125,14 @@ describe("seeded select", () => {
     const resolver = new MockResolver(() => rootStore);
     const plan = selectPlan([
       sqlStep([], 'SELECT * FROM M WHERE "id" > ?1 ORDER BY "id" LIMIT ?2', {
-        args: [param("lastSeen_id"), param("limit")],
+        args: [param("$cursor_id"), param("limit")],
         mapping: many,
       }),
     ]);
 
     const body = await executeSelectOk(
       plan,
-      {}, // no lastSeen_id / limit supplied
+      {}, // no $cursor_id / limit supplied
       resolver,
       [
         { id: 1, name: "a" },
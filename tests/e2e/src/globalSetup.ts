@@ -84,7 +84,7 @@ function writeCloesceConfig(fixtureDir: string, port: number) {
  * clean slate.
  */
 function cleanFixture(fixtureDir: string) {
-  for (const name of ["cidl.json", "wrangler.toml", "backend.ts", "client.ts"]) {
+  for (const name of ["cidl.json", "openapi.json", "wrangler.toml", "backend.ts", "client.ts"]) {
     fs.rmSync(path.join(fixtureDir, name), { force: true });
   }
 